@@ -0,0 +1,27 @@
+-- Task dependency graph
+-- A task may depend on any number of upstream tasks in the same project.
+-- Cycles are rejected by the API before inserting an edge.
+
+CREATE TABLE task_dependencies (
+    task_id             BLOB NOT NULL,
+    depends_on_task_id  BLOB NOT NULL,
+    created_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (task_id, depends_on_task_id),
+    CHECK (task_id != depends_on_task_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (depends_on_task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_dependencies_depends_on_task_id
+    ON task_dependencies(depends_on_task_id);
+
+-- Pending automatic starts for blocked tasks.
+-- The executor profile is stored as JSON (ExecutorProfileId) and consumed once
+-- the scheduler starts an attempt.
+CREATE TABLE task_auto_starts (
+    task_id              BLOB PRIMARY KEY,
+    executor_profile_id  TEXT NOT NULL,
+    base_branch          TEXT NOT NULL,
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
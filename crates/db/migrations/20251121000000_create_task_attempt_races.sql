@@ -0,0 +1,24 @@
+-- Race attempts: the same task run by several executors in parallel.
+-- Each race groups one attempt per executor profile; picking a winner
+-- archives the remaining attempts.
+
+CREATE TABLE task_attempt_races (
+    id                 BLOB PRIMARY KEY,
+    task_id            BLOB NOT NULL,
+    winner_attempt_id  BLOB,
+    created_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    decided_at         TEXT,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (winner_attempt_id) REFERENCES task_attempts(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_task_attempt_races_task_id ON task_attempt_races(task_id);
+
+CREATE TABLE task_attempt_race_entries (
+    race_id          BLOB NOT NULL,
+    task_attempt_id  BLOB NOT NULL UNIQUE,
+    archived         INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (race_id, task_attempt_id),
+    FOREIGN KEY (race_id) REFERENCES task_attempt_races(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,11 @@
+-- Per-project sandbox policy for execution processes.
+-- policy holds a JSON-encoded SandboxPolicy (backend, image, cpu, memory, network, mounts).
+
+CREATE TABLE project_sandbox_policies (
+    project_id  BLOB PRIMARY KEY,
+    enabled     INTEGER NOT NULL DEFAULT 0,
+    policy      TEXT NOT NULL DEFAULT '{}',
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
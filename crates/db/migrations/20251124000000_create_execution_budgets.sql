@@ -0,0 +1,26 @@
+-- Budget limits for coding agent runs, set per project and per executor profile.
+-- A NULL limit is unlimited; when both scopes set a limit the lower one applies.
+-- status_reason records why a process was stopped, e.g. 'budget_exceeded'.
+
+CREATE TABLE project_budgets (
+    project_id        BLOB PRIMARY KEY,
+    max_runtime_secs  INTEGER,
+    max_tool_calls    INTEGER,
+    max_tokens        INTEGER,
+    created_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- profile_key is "EXECUTOR" or "EXECUTOR:VARIANT"; a variant-less key applies to every variant
+CREATE TABLE executor_profile_budgets (
+    profile_key       TEXT PRIMARY KEY,
+    max_runtime_secs  INTEGER,
+    max_tool_calls    INTEGER,
+    max_tokens        INTEGER,
+    created_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at        TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+ALTER TABLE execution_processes ADD COLUMN status_reason TEXT
+    CHECK (status_reason IN ('budget_exceeded'));
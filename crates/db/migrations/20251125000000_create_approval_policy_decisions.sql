@@ -0,0 +1,19 @@
+-- Audit trail of tool call approvals decided by a project's approval policy.
+-- rule_index is the position of the matching rule in the policy file, NULL when
+-- no rule matched and the request was escalated to a human by default.
+
+CREATE TABLE approval_policy_decisions (
+    id                    BLOB PRIMARY KEY,
+    execution_process_id  BLOB NOT NULL,
+    tool_name             TEXT NOT NULL,
+    tool_call_id          TEXT NOT NULL,
+    action_type           TEXT,
+    decision              TEXT NOT NULL CHECK (decision IN ('approve', 'deny', 'escalate')),
+    rule_index            INTEGER,
+    reason                TEXT,
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_approval_policy_decisions_execution_process_id
+    ON approval_policy_decisions(execution_process_id);
@@ -0,0 +1,22 @@
+-- Durable record of every resolved tool call approval, whoever decided it.
+-- id is the approval id handed to executors; latency_ms is the time between the
+-- request and its resolution. Approvals decided by a project policy have
+-- decided_by = 'policy', unanswered requests have decided_by = 'timeout'.
+
+CREATE TABLE approvals (
+    id                    TEXT PRIMARY KEY,
+    execution_process_id  BLOB NOT NULL,
+    tool_name             TEXT NOT NULL,
+    tool_call_id          TEXT NOT NULL,
+    action_type           TEXT,
+    decided_by            TEXT NOT NULL CHECK (decided_by IN ('user', 'policy', 'timeout')),
+    status                TEXT NOT NULL CHECK (status IN ('approved', 'denied', 'timed_out')),
+    reason                TEXT,
+    requested_at          TEXT NOT NULL,
+    decided_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    latency_ms            INTEGER NOT NULL,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_approvals_execution_process_id ON approvals(execution_process_id);
+CREATE INDEX idx_approvals_decided_at ON approvals(decided_at);
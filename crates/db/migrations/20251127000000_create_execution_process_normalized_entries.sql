@@ -0,0 +1,34 @@
+-- The normalized conversation of a coding agent run, one row per entry, kept in
+-- conversation order by entry_index. Rows are appended while the agent runs so
+-- conversations can be served without re-normalizing raw logs. normalizer_version
+-- records the normalizer that produced the row; rows from an older normalizer are
+-- rewritten by the explicit re-normalization job.
+
+CREATE TABLE execution_process_normalized_entries (
+    execution_process_id  BLOB NOT NULL,
+    entry_index           INTEGER NOT NULL,
+    normalizer_version    INTEGER NOT NULL,
+    entry                 TEXT NOT NULL,
+    updated_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    PRIMARY KEY (execution_process_id, entry_index),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_execution_process_normalized_entries_version
+    ON execution_process_normalized_entries(normalizer_version);
+
+-- Marks a stored normalized conversation as complete: written once the normalizer for
+-- the run has finished, or by the re-normalization job. Without a row the stored
+-- entries may be partial and the conversation is re-normalized from raw logs, while a
+-- completed conversation is served as stored even when it has no entries at all.
+
+CREATE TABLE execution_process_normalizations (
+    execution_process_id  BLOB PRIMARY KEY NOT NULL,
+    normalizer_version    INTEGER NOT NULL,
+    entry_count           INTEGER NOT NULL,
+    completed_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_execution_process_normalizations_version
+    ON execution_process_normalizations(normalizer_version);
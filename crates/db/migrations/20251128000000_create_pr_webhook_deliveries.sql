@@ -0,0 +1,10 @@
+-- Repositories that deliver pull request webhooks. repo_key is the host and
+-- repository path, e.g. "github.com/owner/repo". The PR monitor keeps polling only
+-- the repositories without a recent delivery.
+
+CREATE TABLE pr_webhook_deliveries (
+    repo_key          TEXT PRIMARY KEY,
+    provider          TEXT NOT NULL,
+    last_event        TEXT NOT NULL,
+    last_delivery_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
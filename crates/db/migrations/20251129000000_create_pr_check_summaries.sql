@@ -0,0 +1,29 @@
+-- CI checks of tracked pull requests, aggregated by the PR monitor. checks holds a
+-- JSON-encoded list of the individual check runs and commit statuses for head_sha.
+-- auto_fix_head_sha is the last head commit a fix follow-up was queued for, so each
+-- failing commit is only handed back to the agent once.
+
+CREATE TABLE pr_check_summaries (
+    merge_id           BLOB PRIMARY KEY,
+    task_attempt_id    BLOB NOT NULL,
+    head_sha           TEXT,
+    state              TEXT NOT NULL,
+    checks             TEXT NOT NULL DEFAULT '[]',
+    auto_fix_head_sha  TEXT,
+    auto_fix_count     INTEGER NOT NULL DEFAULT 0,
+    updated_at         TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (merge_id) REFERENCES merges(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_pr_check_summaries_state ON pr_check_summaries(state);
+
+-- Per-project opt-in for queueing a follow-up when a PR's checks fail.
+CREATE TABLE project_ci_settings (
+    project_id              BLOB PRIMARY KEY,
+    auto_fix_failed_checks  INTEGER NOT NULL DEFAULT 0,
+    max_auto_fixes          INTEGER NOT NULL DEFAULT 3,
+    created_at              TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at              TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
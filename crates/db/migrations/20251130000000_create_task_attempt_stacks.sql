@@ -0,0 +1,18 @@
+-- Stacked attempts: an attempt based on another attempt's branch. base_commit is the
+-- parent branch commit the attempt currently sits on, so it can be rebased with
+-- `--onto` when the parent moves. A restack that fails records its error and the
+-- parent commit it failed for, which is not retried automatically.
+
+CREATE TABLE task_attempt_stacks (
+    task_attempt_id     BLOB PRIMARY KEY,
+    parent_attempt_id   BLOB NOT NULL,
+    base_commit         TEXT NOT NULL,
+    restack_error       TEXT,
+    failed_base_commit  TEXT,
+    created_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at          TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE,
+    FOREIGN KEY (parent_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_attempt_stacks_parent ON task_attempt_stacks(parent_attempt_id);
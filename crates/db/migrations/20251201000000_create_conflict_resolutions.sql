@@ -0,0 +1,18 @@
+-- Agent-assisted conflict resolution: a follow-up started on an attempt whose worktree
+-- has a rebase, merge, cherry-pick or revert stopped at conflicts. conflicted_files is
+-- the JSON list of files that were conflicted when it started; once the follow-up ends
+-- they are checked for leftover markers before the operation is continued. error holds
+-- why it could not be continued, with the operation left in progress.
+
+CREATE TABLE conflict_resolutions (
+    execution_process_id  BLOB PRIMARY KEY,
+    task_attempt_id       BLOB NOT NULL,
+    conflicted_files      TEXT NOT NULL,
+    error                 TEXT,
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    completed_at          TEXT,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_conflict_resolutions_task_attempt ON conflict_resolutions(task_attempt_id);
@@ -0,0 +1,42 @@
+-- Per-project merge queue. Queued attempts are merged into their target branch one at
+-- a time: each is rebased onto the current tip, verified with the project's verify
+-- script in its worktree, and merged only if that passes. Entries that finished are
+-- kept with their outcome; error and verify_log explain a failure.
+
+CREATE TABLE merge_queue_entries (
+    id               BLOB PRIMARY KEY,
+    project_id       BLOB NOT NULL,
+    task_attempt_id  BLOB NOT NULL,
+    target_branch    TEXT NOT NULL,
+    status           TEXT NOT NULL DEFAULT 'queued'
+                        CHECK (status IN ('queued', 'rebasing', 'verifying', 'merging',
+                                          'merged', 'failed', 'cancelled')),
+    error            TEXT,
+    verify_log       TEXT,
+    merge_commit     TEXT,
+    created_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at       TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_merge_queue_entries_project ON merge_queue_entries(project_id, status);
+
+-- An attempt is queued at most once at a time
+CREATE UNIQUE INDEX idx_merge_queue_entries_active_attempt
+    ON merge_queue_entries(task_attempt_id)
+    WHERE status IN ('queued', 'rebasing', 'verifying', 'merging');
+
+-- How a project's merge queue verifies and merges attempts. Without a verify script
+-- attempts are merged as soon as they rebase cleanly.
+
+CREATE TABLE project_merge_queue_settings (
+    project_id           BLOB PRIMARY KEY,
+    verify_script        TEXT,
+    verify_timeout_secs  INTEGER NOT NULL DEFAULT 1800,
+    merge_strategy       TEXT NOT NULL DEFAULT 'squash'
+                            CHECK (merge_strategy IN ('squash', 'fast_forward')),
+    created_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at           TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
@@ -0,0 +1,21 @@
+-- How attempts of a project are merged into their target branch, both directly and by
+-- the merge queue. Without a row attempts are squash merged with the default message.
+
+CREATE TABLE project_merge_settings (
+    project_id               BLOB PRIMARY KEY,
+    strategy                 TEXT NOT NULL DEFAULT 'squash'
+                                CHECK (strategy IN ('squash', 'merge_commit', 'rebase_fast_forward')),
+    -- Message for squash and merge commits, with {placeholders}; NULL uses the default
+    commit_message_template  TEXT,
+    created_at               TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at               TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- The merge queue follows the project's strategy from now on
+INSERT INTO project_merge_settings (project_id, strategy)
+SELECT project_id, 'rebase_fast_forward'
+FROM project_merge_queue_settings
+WHERE merge_strategy = 'fast_forward';
+
+ALTER TABLE project_merge_queue_settings DROP COLUMN merge_strategy;
@@ -0,0 +1,33 @@
+-- Additional repositories of a project, next to its primary git_repo_path. An attempt of
+-- a project with additional repositories gets one worktree per repository under a
+-- shared attempt directory; task_attempt_repositories records the additional ones with
+-- the repository they were created from, so cleanup works even after the repository
+-- is removed from the project.
+
+CREATE TABLE project_repositories (
+    id              BLOB PRIMARY KEY,
+    project_id      BLOB NOT NULL,
+    name            TEXT NOT NULL,
+    git_repo_path   TEXT NOT NULL,
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    UNIQUE (project_id, name),
+    UNIQUE (project_id, git_repo_path)
+);
+
+CREATE TABLE task_attempt_repositories (
+    id              BLOB PRIMARY KEY,
+    task_attempt_id BLOB NOT NULL,
+    name            TEXT NOT NULL,
+    git_repo_path   TEXT NOT NULL,
+    worktree_path   TEXT NOT NULL,
+    target_branch   TEXT NOT NULL,
+    merge_commit    TEXT,
+    pr_number       INTEGER,
+    pr_url          TEXT,
+    created_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at      TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE,
+    UNIQUE (task_attempt_id, name)
+);
@@ -0,0 +1,17 @@
+-- Forked attempts: an attempt started from another attempt's state after one of its
+-- processes. fork_commit is the HEAD that process left behind, session_id the agent
+-- session the fork's first follow-up resumes (NULL when the agent cannot fork sessions).
+
+CREATE TABLE task_attempt_forks (
+    task_attempt_id       BLOB PRIMARY KEY,
+    parent_attempt_id     BLOB NOT NULL,
+    execution_process_id  BLOB NOT NULL,
+    fork_commit           TEXT NOT NULL,
+    session_id            TEXT,
+    created_at            TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (task_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE,
+    FOREIGN KEY (parent_attempt_id) REFERENCES task_attempts(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_process_id) REFERENCES execution_processes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_attempt_forks_parent ON task_attempt_forks(parent_attempt_id);
@@ -0,0 +1,11 @@
+-- Per-project environment variables for execution processes.
+-- vars holds a JSON object of name -> value; values refer to encrypted secrets with
+-- {{secret:NAME}} instead of storing them here.
+
+CREATE TABLE project_env_vars (
+    project_id  BLOB PRIMARY KEY,
+    vars        TEXT NOT NULL DEFAULT '{}',
+    created_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
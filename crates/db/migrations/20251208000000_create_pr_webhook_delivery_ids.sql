@@ -0,0 +1,12 @@
+-- Delivery ids of processed pull request webhooks, so a redelivered or replayed
+-- delivery is acknowledged without being applied twice. Old ids are pruned by the
+-- PR monitor.
+
+CREATE TABLE pr_webhook_delivery_ids (
+    delivery_id  TEXT PRIMARY KEY,
+    provider     TEXT NOT NULL,
+    received_at  TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+);
+
+CREATE INDEX idx_pr_webhook_delivery_ids_received_at
+    ON pr_webhook_delivery_ids(received_at);
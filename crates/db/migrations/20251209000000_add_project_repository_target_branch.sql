@@ -0,0 +1,5 @@
+-- Branch of an additional repository that attempts are merged into. Recorded when the
+-- repository is added; NULL for repositories added before, which fall back to the
+-- attempt's own target branch.
+
+ALTER TABLE project_repositories ADD COLUMN target_branch TEXT;
@@ -0,0 +1,26 @@
+-- Approval policy verdicts are kept on the approval they decided instead of in a
+-- second audit table. policy_decision is NULL when no policy applied; 'escalate'
+-- marks approvals a policy left to a human, recorded once the human or the timeout
+-- resolved them.
+
+ALTER TABLE approvals ADD COLUMN policy_decision TEXT
+    CHECK (policy_decision IN ('approve', 'deny', 'escalate'));
+ALTER TABLE approvals ADD COLUMN rule_index INTEGER;
+
+UPDATE approvals
+SET (policy_decision, rule_index) = (
+    SELECT d.decision, d.rule_index
+    FROM approval_policy_decisions d
+    WHERE d.execution_process_id = approvals.execution_process_id
+      AND d.tool_call_id = approvals.tool_call_id
+    ORDER BY d.created_at DESC
+    LIMIT 1
+)
+WHERE EXISTS (
+    SELECT 1
+    FROM approval_policy_decisions d
+    WHERE d.execution_process_id = approvals.execution_process_id
+      AND d.tool_call_id = approvals.tool_call_id
+);
+
+DROP TABLE approval_policy_decisions;
@@ -0,0 +1,476 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Who or what resolved an approval
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecider {
+    User,
+    Policy,
+    Timeout,
+}
+
+/// What an approval policy rule does with a matching tool call. `Escalate` asks a
+/// human, as if no policy were configured.
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyDecision {
+    Approve,
+    Deny,
+    Escalate,
+}
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalOutcome {
+    Approved,
+    Denied,
+    TimedOut,
+}
+
+/// A resolved tool call approval
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct Approval {
+    pub id: String,
+    pub execution_process_id: Uuid,
+    pub tool_name: String,
+    pub tool_call_id: String,
+    /// The tool call's `ActionType`, as JSON, when its log entry was found
+    #[ts(type = "unknown | null")]
+    pub action_type: Option<sqlx::types::Json<serde_json::Value>>,
+    pub decided_by: ApprovalDecider,
+    pub status: ApprovalOutcome,
+    pub reason: Option<String>,
+    /// The project policy's verdict, `None` when no policy applied. `Escalate` means
+    /// the policy left the decision to `decided_by`.
+    pub policy_decision: Option<PolicyDecision>,
+    /// Index of the policy rule that matched, `None` when no rule matched
+    pub rule_index: Option<i64>,
+    pub requested_at: DateTime<Utc>,
+    pub decided_at: DateTime<Utc>,
+    pub latency_ms: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateApproval {
+    pub id: String,
+    pub execution_process_id: Uuid,
+    pub tool_name: String,
+    pub tool_call_id: String,
+    pub action_type: Option<serde_json::Value>,
+    pub decided_by: ApprovalDecider,
+    pub status: ApprovalOutcome,
+    pub reason: Option<String>,
+    pub policy_decision: Option<PolicyDecision>,
+    pub rule_index: Option<i64>,
+    pub requested_at: DateTime<Utc>,
+}
+
+/// Filters for the approval history; every field is optional
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+pub struct ApprovalFilter {
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub task_attempt_id: Option<Uuid>,
+    pub execution_process_id: Option<Uuid>,
+    pub tool_name: Option<String>,
+    pub decided_by: Option<ApprovalDecider>,
+    pub status: Option<ApprovalOutcome>,
+    pub policy_decision: Option<PolicyDecision>,
+    /// Only approvals decided at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only approvals decided before this time
+    pub until: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+}
+
+const DEFAULT_HISTORY_LIMIT: i64 = 100;
+const MAX_HISTORY_LIMIT: i64 = 1000;
+
+impl Approval {
+    pub async fn create(pool: &SqlitePool, data: &CreateApproval) -> Result<Self, sqlx::Error> {
+        let decided_at = Utc::now();
+        let latency_ms = (decided_at - data.requested_at).num_milliseconds().max(0);
+        sqlx::query_as::<_, Approval>(
+            r#"INSERT INTO approvals
+                   (id, execution_process_id, tool_name, tool_call_id, action_type,
+                    decided_by, status, reason, policy_decision, rule_index,
+                    requested_at, decided_at, latency_ms)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+               RETURNING id, execution_process_id, tool_name, tool_call_id, action_type,
+                         decided_by, status, reason, policy_decision, rule_index,
+                         requested_at, decided_at, latency_ms"#,
+        )
+        .bind(&data.id)
+        .bind(data.execution_process_id)
+        .bind(&data.tool_name)
+        .bind(&data.tool_call_id)
+        .bind(data.action_type.clone().map(sqlx::types::Json))
+        .bind(data.decided_by)
+        .bind(data.status)
+        .bind(data.reason.as_deref())
+        .bind(data.policy_decision)
+        .bind(data.rule_index)
+        .bind(data.requested_at)
+        .bind(decided_at)
+        .bind(latency_ms)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Most recently decided approvals first
+    pub async fn find_filtered(
+        pool: &SqlitePool,
+        filter: &ApprovalFilter,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            r#"SELECT a.id, a.execution_process_id, a.tool_name, a.tool_call_id, a.action_type,
+                      a.decided_by, a.status, a.reason, a.policy_decision, a.rule_index,
+                      a.requested_at, a.decided_at, a.latency_ms
+               FROM approvals a
+               JOIN execution_processes ep ON ep.id = a.execution_process_id
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE 1 = 1"#,
+        );
+        if let Some(id) = filter.project_id {
+            query.push(" AND t.project_id = ").push_bind(id);
+        }
+        if let Some(id) = filter.task_id {
+            query.push(" AND t.id = ").push_bind(id);
+        }
+        if let Some(id) = filter.task_attempt_id {
+            query.push(" AND ta.id = ").push_bind(id);
+        }
+        if let Some(id) = filter.execution_process_id {
+            query.push(" AND ep.id = ").push_bind(id);
+        }
+        if let Some(tool_name) = &filter.tool_name {
+            query
+                .push(" AND a.tool_name = ")
+                .push_bind(tool_name.clone());
+        }
+        if let Some(decided_by) = filter.decided_by {
+            query.push(" AND a.decided_by = ").push_bind(decided_by);
+        }
+        if let Some(status) = filter.status {
+            query.push(" AND a.status = ").push_bind(status);
+        }
+        if let Some(policy_decision) = filter.policy_decision {
+            query
+                .push(" AND a.policy_decision = ")
+                .push_bind(policy_decision);
+        }
+        if let Some(since) = filter.since {
+            query.push(" AND a.decided_at >= ").push_bind(since);
+        }
+        if let Some(until) = filter.until {
+            query.push(" AND a.decided_at < ").push_bind(until);
+        }
+        let limit = filter
+            .limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .clamp(1, MAX_HISTORY_LIMIT);
+        query
+            .push(" ORDER BY a.decided_at DESC LIMIT ")
+            .push_bind(limit);
+
+        query.build_query_as::<Approval>().fetch_all(pool).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use executors::{
+        actions::{
+            ExecutorAction, ExecutorActionType,
+            script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+        },
+        executors::BaseCodingAgent,
+    };
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::models::{
+        execution_process::{CreateExecutionProcess, ExecutionProcess, ExecutionProcessRunReason},
+        project::{CreateProject, Project},
+        task::{CreateTask, Task},
+        task_attempt::{CreateTaskAttempt, TaskAttempt},
+    };
+
+    /// The ids an approval can be filtered by
+    struct Scope {
+        project_id: Uuid,
+        task_id: Uuid,
+        task_attempt_id: Uuid,
+        execution_process_id: Uuid,
+    }
+
+    async fn setup() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    async fn create_scope(pool: &SqlitePool) -> Scope {
+        let project_id = Uuid::new_v4();
+        Project::create(
+            pool,
+            &CreateProject {
+                name: "project".to_string(),
+                git_repo_path: format!("/tmp/{project_id}"),
+                use_existing_repo: true,
+                setup_script: None,
+                dev_script: None,
+                cleanup_script: None,
+                copy_files: None,
+            },
+            project_id,
+        )
+        .await
+        .unwrap();
+
+        let task_id = Uuid::new_v4();
+        Task::create(
+            pool,
+            &CreateTask::from_title_description(project_id, "task".to_string(), None),
+            task_id,
+        )
+        .await
+        .unwrap();
+
+        let task_attempt_id = Uuid::new_v4();
+        TaskAttempt::create(
+            pool,
+            &CreateTaskAttempt {
+                executor: BaseCodingAgent::ClaudeCode,
+                base_branch: "main".to_string(),
+                branch: format!("attempt-{task_attempt_id}"),
+            },
+            task_attempt_id,
+            task_id,
+        )
+        .await
+        .unwrap();
+
+        let execution_process_id = Uuid::new_v4();
+        ExecutionProcess::create(
+            pool,
+            &CreateExecutionProcess {
+                task_attempt_id,
+                executor_action: ExecutorAction::new(
+                    ExecutorActionType::ScriptRequest(ScriptRequest {
+                        script: "true".to_string(),
+                        language: ScriptRequestLanguage::Bash,
+                        context: ScriptContext::SetupScript,
+                    }),
+                    None,
+                ),
+                run_reason: ExecutionProcessRunReason::CodingAgent,
+            },
+            execution_process_id,
+            None,
+        )
+        .await
+        .unwrap();
+
+        Scope {
+            project_id,
+            task_id,
+            task_attempt_id,
+            execution_process_id,
+        }
+    }
+
+    async fn record(
+        pool: &SqlitePool,
+        scope: &Scope,
+        tool_name: &str,
+        decided_by: ApprovalDecider,
+        status: ApprovalOutcome,
+        policy_decision: Option<PolicyDecision>,
+    ) -> Approval {
+        Approval::create(
+            pool,
+            &CreateApproval {
+                id: Uuid::new_v4().to_string(),
+                execution_process_id: scope.execution_process_id,
+                tool_name: tool_name.to_string(),
+                tool_call_id: Uuid::new_v4().to_string(),
+                action_type: Some(serde_json::json!({"action": "command_run", "command": "ls"})),
+                decided_by,
+                status,
+                reason: None,
+                policy_decision,
+                rule_index: policy_decision.map(|_| 0),
+                requested_at: Utc::now() - Duration::seconds(2),
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    fn ids(approvals: &[Approval]) -> Vec<&str> {
+        approvals.iter().map(|a| a.id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn create_records_latency_and_policy_verdict() {
+        let pool = setup().await;
+        let scope = create_scope(&pool).await;
+
+        let approval = record(
+            &pool,
+            &scope,
+            "Bash",
+            ApprovalDecider::User,
+            ApprovalOutcome::Approved,
+            Some(PolicyDecision::Escalate),
+        )
+        .await;
+
+        assert!(approval.latency_ms >= 2000);
+        assert_eq!(approval.policy_decision, Some(PolicyDecision::Escalate));
+        assert_eq!(approval.rule_index, Some(0));
+        assert_eq!(
+            approval.action_type.unwrap().0["command"],
+            serde_json::json!("ls")
+        );
+    }
+
+    #[tokio::test]
+    async fn find_filtered_applies_every_filter() {
+        let pool = setup().await;
+        let scope = create_scope(&pool).await;
+        let other = create_scope(&pool).await;
+
+        let by_user = record(
+            &pool,
+            &scope,
+            "Bash",
+            ApprovalDecider::User,
+            ApprovalOutcome::Approved,
+            None,
+        )
+        .await;
+        let by_policy = record(
+            &pool,
+            &scope,
+            "Edit",
+            ApprovalDecider::Policy,
+            ApprovalOutcome::Denied,
+            Some(PolicyDecision::Deny),
+        )
+        .await;
+        let timed_out = record(
+            &pool,
+            &other,
+            "Bash",
+            ApprovalDecider::Timeout,
+            ApprovalOutcome::TimedOut,
+            Some(PolicyDecision::Escalate),
+        )
+        .await;
+
+        let find = |filter: ApprovalFilter| {
+            let pool = pool.clone();
+            async move { Approval::find_filtered(&pool, &filter).await.unwrap() }
+        };
+
+        // Newest first
+        assert_eq!(
+            ids(&find(ApprovalFilter::default()).await),
+            [&timed_out.id, &by_policy.id, &by_user.id]
+        );
+        for filter in [
+            ApprovalFilter {
+                project_id: Some(scope.project_id),
+                ..Default::default()
+            },
+            ApprovalFilter {
+                task_id: Some(scope.task_id),
+                ..Default::default()
+            },
+            ApprovalFilter {
+                task_attempt_id: Some(scope.task_attempt_id),
+                ..Default::default()
+            },
+            ApprovalFilter {
+                execution_process_id: Some(scope.execution_process_id),
+                ..Default::default()
+            },
+        ] {
+            assert_eq!(ids(&find(filter).await), [&by_policy.id, &by_user.id]);
+        }
+        assert_eq!(
+            ids(&find(ApprovalFilter {
+                tool_name: Some("Bash".to_string()),
+                ..Default::default()
+            })
+            .await),
+            [&timed_out.id, &by_user.id]
+        );
+        assert_eq!(
+            ids(&find(ApprovalFilter {
+                decided_by: Some(ApprovalDecider::Policy),
+                ..Default::default()
+            })
+            .await),
+            [&by_policy.id]
+        );
+        assert_eq!(
+            ids(&find(ApprovalFilter {
+                status: Some(ApprovalOutcome::TimedOut),
+                ..Default::default()
+            })
+            .await),
+            [&timed_out.id]
+        );
+        assert_eq!(
+            ids(&find(ApprovalFilter {
+                policy_decision: Some(PolicyDecision::Escalate),
+                ..Default::default()
+            })
+            .await),
+            [&timed_out.id]
+        );
+        assert_eq!(
+            ids(&find(ApprovalFilter {
+                since: Some(by_policy.decided_at),
+                until: Some(timed_out.decided_at),
+                ..Default::default()
+            })
+            .await),
+            [&by_policy.id]
+        );
+        assert_eq!(
+            ids(&find(ApprovalFilter {
+                limit: Some(1),
+                ..Default::default()
+            })
+            .await),
+            [&timed_out.id]
+        );
+        // Out-of-range limits are clamped rather than rejected
+        assert_eq!(
+            find(ApprovalFilter {
+                limit: Some(0),
+                ..Default::default()
+            })
+            .await
+            .len(),
+            1
+        );
+    }
+}
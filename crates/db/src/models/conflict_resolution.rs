@@ -0,0 +1,117 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A follow-up asked to resolve the conflicts of an attempt's in-progress rebase, merge,
+/// cherry-pick or revert, which is continued once the follow-up leaves it conflict-free
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ConflictResolution {
+    pub execution_process_id: Uuid,
+    pub task_attempt_id: Uuid,
+    /// Files that were conflicted when the follow-up started
+    pub conflicted_files: Vec<String>,
+    /// Why the operation could not be continued; it is left in progress
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+struct ConflictResolutionRow {
+    execution_process_id: Uuid,
+    task_attempt_id: Uuid,
+    conflicted_files: Json<Vec<String>>,
+    error: Option<String>,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<ConflictResolutionRow> for ConflictResolution {
+    fn from(r: ConflictResolutionRow) -> Self {
+        ConflictResolution {
+            execution_process_id: r.execution_process_id,
+            task_attempt_id: r.task_attempt_id,
+            conflicted_files: r.conflicted_files.0,
+            error: r.error,
+            created_at: r.created_at,
+            completed_at: r.completed_at,
+        }
+    }
+}
+
+impl ConflictResolution {
+    pub async fn create(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        task_attempt_id: Uuid,
+        conflicted_files: &[String],
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, ConflictResolutionRow>(
+            r#"INSERT INTO conflict_resolutions (execution_process_id, task_attempt_id, conflicted_files)
+               VALUES (?, ?, ?)
+               RETURNING execution_process_id, task_attempt_id, conflicted_files, error,
+                         created_at, completed_at"#,
+        )
+        .bind(execution_process_id)
+        .bind(task_attempt_id)
+        .bind(Json(conflicted_files))
+        .fetch_one(pool)
+        .await
+        .map(ConflictResolution::from)
+    }
+
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ConflictResolutionRow>(
+            r#"SELECT execution_process_id, task_attempt_id, conflicted_files, error,
+                      created_at, completed_at
+               FROM conflict_resolutions
+               WHERE execution_process_id = ?"#,
+        )
+        .bind(execution_process_id)
+        .fetch_optional(pool)
+        .await
+        .map(|opt| opt.map(ConflictResolution::from))
+    }
+
+    pub async fn find_latest_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ConflictResolutionRow>(
+            r#"SELECT execution_process_id, task_attempt_id, conflicted_files, error,
+                      created_at, completed_at
+               FROM conflict_resolutions
+               WHERE task_attempt_id = ?
+               ORDER BY created_at DESC
+               LIMIT 1"#,
+        )
+        .bind(task_attempt_id)
+        .fetch_optional(pool)
+        .await
+        .map(|opt| opt.map(ConflictResolution::from))
+    }
+
+    /// Mark the resolution finished, with the reason the operation was left in progress
+    /// if it could not be continued
+    pub async fn complete(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"UPDATE conflict_resolutions
+               SET error = ?, completed_at = datetime('now', 'subsec')
+               WHERE execution_process_id = ?"#,
+        )
+        .bind(error)
+        .bind(execution_process_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
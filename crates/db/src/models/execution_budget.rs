@@ -0,0 +1,190 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Limits applied to a single coding agent run. `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromRow, Serialize, Deserialize, TS)]
+pub struct BudgetLimits {
+    /// Maximum wall-clock runtime in seconds
+    pub max_runtime_secs: Option<i64>,
+    /// Maximum number of tool calls made by the agent
+    pub max_tool_calls: Option<i64>,
+    /// Maximum input + output tokens, for agents that report usage. ACP agents (Gemini,
+    /// Qwen) report none, so only their runtime and tool calls are limited.
+    pub max_tokens: Option<i64>,
+}
+
+impl BudgetLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_runtime_secs.is_none()
+            && self.max_tool_calls.is_none()
+            && self.max_tokens.is_none()
+    }
+
+    /// Combine two sets of limits, keeping the stricter value of each
+    pub fn min(self, other: Self) -> Self {
+        fn min_opt(a: Option<i64>, b: Option<i64>) -> Option<i64> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            }
+        }
+        Self {
+            max_runtime_secs: min_opt(self.max_runtime_secs, other.max_runtime_secs),
+            max_tool_calls: min_opt(self.max_tool_calls, other.max_tool_calls),
+            max_tokens: min_opt(self.max_tokens, other.max_tokens),
+        }
+    }
+
+    /// Limits for a run of `executor_profile_id` in `project_id`: the stricter of the
+    /// project budget and the profile budget. A variant without its own budget falls
+    /// back to the budget of its executor.
+    pub async fn effective(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        executor_profile_id: &ExecutorProfileId,
+    ) -> Result<Self, sqlx::Error> {
+        let project = ProjectBudget::find_by_project_id(pool, project_id)
+            .await?
+            .map(|b| b.limits)
+            .unwrap_or_default();
+
+        let mut profile =
+            ExecutorProfileBudget::find_by_profile_key(pool, &executor_profile_id.cache_key())
+                .await?;
+        if profile.is_none() && executor_profile_id.variant.is_some() {
+            profile = ExecutorProfileBudget::find_by_profile_key(
+                pool,
+                &executor_profile_id.executor.to_string(),
+            )
+            .await?;
+        }
+
+        Ok(project.min(profile.map(|b| b.limits).unwrap_or_default()))
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectBudget {
+    pub project_id: Uuid,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub limits: BudgetLimits,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ExecutorProfileBudget {
+    /// "EXECUTOR" or "EXECUTOR:VARIANT"
+    pub profile_key: String,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub limits: BudgetLimits,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProjectBudget {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectBudget>(
+            r#"SELECT project_id, max_runtime_secs, max_tool_calls, max_tokens, created_at, updated_at
+               FROM project_budgets
+               WHERE project_id = ?"#,
+        )
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        limits: &BudgetLimits,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, ProjectBudget>(
+            r#"INSERT INTO project_budgets (project_id, max_runtime_secs, max_tool_calls, max_tokens)
+               VALUES (?, ?, ?, ?)
+               ON CONFLICT(project_id) DO UPDATE SET
+                 max_runtime_secs = excluded.max_runtime_secs,
+                 max_tool_calls = excluded.max_tool_calls,
+                 max_tokens = excluded.max_tokens,
+                 updated_at = datetime('now', 'subsec')
+               RETURNING project_id, max_runtime_secs, max_tool_calls, max_tokens, created_at, updated_at"#,
+        )
+        .bind(project_id)
+        .bind(limits.max_runtime_secs)
+        .bind(limits.max_tool_calls)
+        .bind(limits.max_tokens)
+        .fetch_one(pool)
+        .await
+    }
+}
+
+impl ExecutorProfileBudget {
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ExecutorProfileBudget>(
+            r#"SELECT profile_key, max_runtime_secs, max_tool_calls, max_tokens, created_at, updated_at
+               FROM executor_profile_budgets
+               ORDER BY profile_key ASC"#,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_by_profile_key(
+        pool: &SqlitePool,
+        profile_key: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ExecutorProfileBudget>(
+            r#"SELECT profile_key, max_runtime_secs, max_tool_calls, max_tokens, created_at, updated_at
+               FROM executor_profile_budgets
+               WHERE profile_key = ?"#,
+        )
+        .bind(profile_key)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        executor_profile_id: &ExecutorProfileId,
+        limits: &BudgetLimits,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, ExecutorProfileBudget>(
+            r#"INSERT INTO executor_profile_budgets (profile_key, max_runtime_secs, max_tool_calls, max_tokens)
+               VALUES (?, ?, ?, ?)
+               ON CONFLICT(profile_key) DO UPDATE SET
+                 max_runtime_secs = excluded.max_runtime_secs,
+                 max_tool_calls = excluded.max_tool_calls,
+                 max_tokens = excluded.max_tokens,
+                 updated_at = datetime('now', 'subsec')
+               RETURNING profile_key, max_runtime_secs, max_tool_calls, max_tokens, created_at, updated_at"#,
+        )
+        .bind(executor_profile_id.cache_key())
+        .bind(limits.max_runtime_secs)
+        .bind(limits.max_tool_calls)
+        .bind(limits.max_tokens)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(
+        pool: &SqlitePool,
+        executor_profile_id: &ExecutorProfileId,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(r#"DELETE FROM executor_profile_budgets WHERE profile_key = ?"#)
+            .bind(executor_profile_id.cache_key())
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -10,7 +10,7 @@ use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
 
-use super::{task::Task, task_attempt::TaskAttempt};
+use super::{task::Task, task_attempt::TaskAttempt, task_attempt_fork::TaskAttemptFork};
 
 #[derive(Debug, Error)]
 pub enum ExecutionProcessError {
@@ -49,6 +49,18 @@ pub enum ExecutionProcessRunReason {
     DevServer,
 }
 
+/// Why a process ended the way it did, when its status alone doesn't say
+#[derive(Debug, Clone, Type, Serialize, Deserialize, PartialEq, TS)]
+#[sqlx(
+    type_name = "execution_process_status_reason",
+    rename_all = "snake_case"
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionProcessStatusReason {
+    /// Stopped after exceeding a project or executor profile budget
+    BudgetExceeded,
+}
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct ExecutionProcess {
     pub id: Uuid,
@@ -62,6 +74,7 @@ pub struct ExecutionProcess {
     pub after_head_commit: Option<String>,
     pub status: ExecutionProcessStatus,
     pub exit_code: Option<i64>,
+    pub status_reason: Option<ExecutionProcessStatusReason>,
     /// dropped: true if this process is excluded from the current
     /// history view (due to restore/trimming). Hidden from logs/timeline;
     /// still listed in the Processes tab.
@@ -116,7 +129,7 @@ impl ExecutionProcess {
         sqlx::query_as!(
             ExecutionProcess,
             r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
+                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, status_reason as "status_reason: ExecutionProcessStatusReason", dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes WHERE id = ?"#,
             id
@@ -192,7 +205,7 @@ impl ExecutionProcess {
         sqlx::query_as!(
             ExecutionProcess,
             r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
+                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, status_reason as "status_reason: ExecutionProcessStatusReason", dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes WHERE rowid = ?"#,
             rowid
@@ -217,6 +230,7 @@ impl ExecutionProcess {
                       after_head_commit,
                       status          as "status!: ExecutionProcessStatus",
                       exit_code,
+                      status_reason   as "status_reason: ExecutionProcessStatusReason",
                       dropped,
                       started_at      as "started_at!: DateTime<Utc>",
                       completed_at    as "completed_at?: DateTime<Utc>",
@@ -238,7 +252,7 @@ impl ExecutionProcess {
         sqlx::query_as!(
             ExecutionProcess,
             r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
+                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, status_reason as "status_reason: ExecutionProcessStatusReason", dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes WHERE status = 'running' ORDER BY created_at ASC"#,
         )
@@ -255,6 +269,7 @@ impl ExecutionProcess {
             ExecutionProcess,
             r#"SELECT ep.id as "id!: Uuid", ep.task_attempt_id as "task_attempt_id!: Uuid", ep.run_reason as "run_reason!: ExecutionProcessRunReason", ep.executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>",
                       ep.before_head_commit, ep.after_head_commit, ep.status as "status!: ExecutionProcessStatus", ep.exit_code,
+                      ep.status_reason as "status_reason: ExecutionProcessStatusReason",
                       ep.dropped, ep.started_at as "started_at!: DateTime<Utc>", ep.completed_at as "completed_at?: DateTime<Utc>", ep.created_at as "created_at!: DateTime<Utc>", ep.updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes ep
                JOIN task_attempts ta ON ep.task_attempt_id = ta.id
@@ -293,7 +308,13 @@ impl ExecutionProcess {
 
         tracing::info!("Latest session id: {:?}", row);
 
-        Ok(row.and_then(|r| r.session_id))
+        match row.and_then(|r| r.session_id) {
+            Some(session_id) => Ok(Some(session_id)),
+            // A forked attempt resumes the session it was forked from until it has its own
+            None => Ok(TaskAttemptFork::find_by_attempt_id(pool, task_attempt_id)
+                .await?
+                .and_then(|fork| fork.session_id)),
+        }
     }
 
     /// Find latest execution process by task attempt and run reason
@@ -305,7 +326,7 @@ impl ExecutionProcess {
         sqlx::query_as!(
             ExecutionProcess,
             r#"SELECT id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
+                      after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, status_reason as "status_reason: ExecutionProcessStatusReason", dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>",
                       created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>"
                FROM execution_processes
                WHERE task_attempt_id = ? AND run_reason = ? AND dropped = FALSE
@@ -334,7 +355,7 @@ impl ExecutionProcess {
                     after_head_commit, status, exit_code, started_at, completed_at, created_at, updated_at
                 ) VALUES (?, ?, ?, ?, ?, NULL, ?, ?, ?, ?, ?, ?) RETURNING
                     id as "id!: Uuid", task_attempt_id as "task_attempt_id!: Uuid", run_reason as "run_reason!: ExecutionProcessRunReason", executor_action as "executor_action!: sqlx::types::Json<ExecutorActionField>", before_head_commit,
-                    after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
+                    after_head_commit, status as "status!: ExecutionProcessStatus", exit_code, status_reason as "status_reason: ExecutionProcessStatusReason", dropped, started_at as "started_at!: DateTime<Utc>", completed_at as "completed_at?: DateTime<Utc>", created_at as "created_at!: DateTime<Utc>", updated_at as "updated_at!: DateTime<Utc>""#,
             process_id,
             data.task_attempt_id,
             data.run_reason,
@@ -391,6 +412,26 @@ impl ExecutionProcess {
         Ok(())
     }
 
+    /// Mark a running process as killed for the given reason. Returns false if the
+    /// process had already finished or been stopped.
+    pub async fn mark_stopped_with_reason(
+        pool: &SqlitePool,
+        id: Uuid,
+        reason: ExecutionProcessStatusReason,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"UPDATE execution_processes
+               SET status = 'killed', status_reason = ?, completed_at = ?
+               WHERE id = ? AND status = 'running'"#,
+        )
+        .bind(reason)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Update the "after" commit oid for the process
     pub async fn update_after_head_commit(
         pool: &SqlitePool,
@@ -548,12 +589,20 @@ impl ExecutionProcess {
         pool: &SqlitePool,
         attempt_id: Uuid,
     ) -> Result<ExecutorProfileId, ExecutionProcessError> {
-        let latest_execution_process = Self::find_latest_by_task_attempt_and_run_reason(
+        let latest_execution_process = match Self::find_latest_by_task_attempt_and_run_reason(
             pool,
             attempt_id,
             &ExecutionProcessRunReason::CodingAgent,
         )
         .await?
+        {
+            Some(process) => Some(process),
+            // A forked attempt keeps the profile of the process it was forked after
+            None => match TaskAttemptFork::find_by_attempt_id(pool, attempt_id).await? {
+                Some(fork) => Self::find_by_id(pool, fork.execution_process_id).await?,
+                None => None,
+            },
+        }
         .ok_or_else(|| {
             ExecutionProcessError::ValidationError(
                 "Couldn't find initial coding agent process, has it run yet?".to_string(),
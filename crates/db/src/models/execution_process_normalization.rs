@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
+use uuid::Uuid;
+
+/// Completion marker for a stored normalized conversation. Present only once the
+/// normalizer finished, so stored entries without one may be partial.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ExecutionProcessNormalization {
+    pub execution_process_id: Uuid,
+    pub normalizer_version: i64,
+    pub entry_count: i64,
+    pub completed_at: DateTime<Utc>,
+}
+
+impl ExecutionProcessNormalization {
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ExecutionProcessNormalization>(
+            r#"SELECT execution_process_id, normalizer_version, entry_count, completed_at
+               FROM execution_process_normalizations
+               WHERE execution_process_id = ?"#,
+        )
+        .bind(execution_process_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn mark_complete<'e, E>(
+        executor: E,
+        execution_process_id: Uuid,
+        entry_count: usize,
+        normalizer_version: i64,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query(
+            r#"INSERT INTO execution_process_normalizations
+                 (execution_process_id, normalizer_version, entry_count)
+               VALUES (?, ?, ?)
+               ON CONFLICT(execution_process_id) DO UPDATE SET
+                 normalizer_version = excluded.normalizer_version,
+                 entry_count = excluded.entry_count,
+                 completed_at = datetime('now', 'subsec')"#,
+        )
+        .bind(execution_process_id)
+        .bind(normalizer_version)
+        .bind(entry_count as i64)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete<'e, E>(executor: E, execution_process_id: Uuid) -> Result<(), sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query(
+            r#"DELETE FROM execution_process_normalizations WHERE execution_process_id = ?"#,
+        )
+        .bind(execution_process_id)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use chrono::{DateTime, Utc};
+use executors::logs::{NormalizedEntry, utils::patch::ConversationFlush};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Sqlite, SqlitePool, Transaction, types::Json};
+use uuid::Uuid;
+
+use super::execution_process_normalization::ExecutionProcessNormalization;
+
+/// One entry of a coding agent run's normalized conversation
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ExecutionProcessNormalizedEntry {
+    pub execution_process_id: Uuid,
+    pub entry_index: i64,
+    pub normalizer_version: i64,
+    pub entry: Json<NormalizedEntry>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ExecutionProcessNormalizedEntry {
+    /// All entries of an execution process, in conversation order
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ExecutionProcessNormalizedEntry>(
+            r#"SELECT execution_process_id, entry_index, normalizer_version, entry, updated_at
+               FROM execution_process_normalized_entries
+               WHERE execution_process_id = ?
+               ORDER BY entry_index ASC"#,
+        )
+        .bind(execution_process_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Write one flushed batch of conversation changes in a single transaction
+    pub async fn write_flush(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        flush: &ConversationFlush,
+        normalizer_version: i64,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        for (index, entry) in &flush.upserts {
+            Self::upsert(
+                &mut tx,
+                execution_process_id,
+                *index as i64,
+                entry,
+                normalizer_version,
+            )
+            .await?;
+        }
+        if let Some(from) = flush.truncate_from {
+            sqlx::query(
+                r#"DELETE FROM execution_process_normalized_entries
+                   WHERE execution_process_id = ? AND entry_index >= ?"#,
+            )
+            .bind(execution_process_id)
+            .bind(from as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Replace the whole conversation of an execution process and mark it complete
+    pub async fn replace_all(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        entries: &[NormalizedEntry],
+        normalizer_version: i64,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            r#"DELETE FROM execution_process_normalized_entries WHERE execution_process_id = ?"#,
+        )
+        .bind(execution_process_id)
+        .execute(&mut *tx)
+        .await?;
+        for (index, entry) in entries.iter().enumerate() {
+            Self::upsert(
+                &mut tx,
+                execution_process_id,
+                index as i64,
+                entry,
+                normalizer_version,
+            )
+            .await?;
+        }
+        ExecutionProcessNormalization::mark_complete(
+            &mut *tx,
+            execution_process_id,
+            entries.len(),
+            normalizer_version,
+        )
+        .await?;
+        tx.commit().await
+    }
+
+    /// Drop the stored conversation and its completion marker
+    pub async fn delete_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        ExecutionProcessNormalization::delete(&mut *tx, execution_process_id).await?;
+        let result = sqlx::query(
+            r#"DELETE FROM execution_process_normalized_entries WHERE execution_process_id = ?"#,
+        )
+        .bind(execution_process_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Finished coding agent runs with raw logs whose conversation was never completely
+    /// stored or was produced by a normalizer older than `normalizer_version`
+    pub async fn find_outdated_execution_process_ids(
+        pool: &SqlitePool,
+        normalizer_version: i64,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar::<_, Uuid>(
+            r#"SELECT ep.id
+               FROM execution_processes ep
+               JOIN execution_process_logs epl ON epl.execution_id = ep.id
+               LEFT JOIN execution_process_normalizations n
+                 ON n.execution_process_id = ep.id
+               WHERE ep.run_reason = 'codingagent'
+                 AND ep.status != 'running'
+                 AND COALESCE(n.normalizer_version, -1) < ?
+               ORDER BY ep.created_at ASC"#,
+        )
+        .bind(normalizer_version)
+        .fetch_all(pool)
+        .await
+    }
+
+    async fn upsert(
+        tx: &mut Transaction<'_, Sqlite>,
+        execution_process_id: Uuid,
+        entry_index: i64,
+        entry: &NormalizedEntry,
+        normalizer_version: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"INSERT INTO execution_process_normalized_entries
+                 (execution_process_id, entry_index, normalizer_version, entry)
+               VALUES (?, ?, ?, ?)
+               ON CONFLICT(execution_process_id, entry_index) DO UPDATE SET
+                 normalizer_version = excluded.normalizer_version,
+                 entry = excluded.entry,
+                 updated_at = datetime('now', 'subsec')"#,
+        )
+        .bind(execution_process_id)
+        .bind(entry_index)
+        .bind(normalizer_version)
+        .bind(Json(entry))
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use utils::log_msg::TokenUsage;
+use uuid::Uuid;
+
+/// Token usage for one model turn of an execution process
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ExecutionProcessUsage {
+    pub id: Uuid,
+    pub execution_process_id: Uuid,
+    pub model: Option<String>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Usage summed over every turn recorded for one model
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ModelUsage {
+    pub model: Option<String>,
+    pub turns: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+}
+
+/// Aggregate usage for an execution process, attempt, task or project
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct UsageSummary {
+    pub turns: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub by_model: Vec<ModelUsage>,
+}
+
+impl From<Vec<ModelUsage>> for UsageSummary {
+    fn from(by_model: Vec<ModelUsage>) -> Self {
+        let mut summary = UsageSummary::default();
+        for m in &by_model {
+            summary.turns += m.turns;
+            summary.input_tokens += m.input_tokens;
+            summary.output_tokens += m.output_tokens;
+            summary.cache_read_tokens += m.cache_read_tokens;
+            summary.cache_write_tokens += m.cache_write_tokens;
+        }
+        summary.by_model = by_model;
+        summary
+    }
+}
+
+impl ExecutionProcessUsage {
+    pub async fn create(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+        usage: &TokenUsage,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, ExecutionProcessUsage>(
+            r#"INSERT INTO execution_process_usage
+                   (id, execution_process_id, model, input_tokens, output_tokens,
+                    cache_read_tokens, cache_write_tokens)
+               VALUES (?, ?, ?, ?, ?, ?, ?)
+               RETURNING id, execution_process_id, model, input_tokens, output_tokens,
+                         cache_read_tokens, cache_write_tokens, created_at"#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(execution_process_id)
+        .bind(usage.model.as_deref())
+        .bind(usage.input_tokens as i64)
+        .bind(usage.output_tokens as i64)
+        .bind(usage.cache_read_tokens as i64)
+        .bind(usage.cache_write_tokens as i64)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_execution_process_id(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ExecutionProcessUsage>(
+            r#"SELECT id, execution_process_id, model, input_tokens, output_tokens,
+                      cache_read_tokens, cache_write_tokens, created_at
+               FROM execution_process_usage
+               WHERE execution_process_id = ?
+               ORDER BY created_at ASC"#,
+        )
+        .bind(execution_process_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn summary_for_execution_process(
+        pool: &SqlitePool,
+        execution_process_id: Uuid,
+    ) -> Result<UsageSummary, sqlx::Error> {
+        Self::summary(pool, "ep.id", execution_process_id).await
+    }
+
+    pub async fn summary_for_task_attempt(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<UsageSummary, sqlx::Error> {
+        Self::summary(pool, "ta.id", task_attempt_id).await
+    }
+
+    pub async fn summary_for_task(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<UsageSummary, sqlx::Error> {
+        Self::summary(pool, "t.id", task_id).await
+    }
+
+    pub async fn summary_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<UsageSummary, sqlx::Error> {
+        Self::summary(pool, "t.project_id", project_id).await
+    }
+
+    /// Per-model totals for every turn whose execution process matches `scope_column = id`
+    async fn summary(
+        pool: &SqlitePool,
+        scope_column: &'static str,
+        id: Uuid,
+    ) -> Result<UsageSummary, sqlx::Error> {
+        let query = format!(
+            r#"SELECT u.model,
+                      COUNT(*)                  AS turns,
+                      SUM(u.input_tokens)       AS input_tokens,
+                      SUM(u.output_tokens)      AS output_tokens,
+                      SUM(u.cache_read_tokens)  AS cache_read_tokens,
+                      SUM(u.cache_write_tokens) AS cache_write_tokens
+               FROM execution_process_usage u
+               JOIN execution_processes ep ON ep.id = u.execution_process_id
+               JOIN task_attempts ta ON ta.id = ep.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               WHERE {scope_column} = ?
+               GROUP BY u.model
+               ORDER BY output_tokens DESC"#
+        );
+        sqlx::query_as::<_, ModelUsage>(&query)
+            .bind(id)
+            .fetch_all(pool)
+            .await
+            .map(UsageSummary::from)
+    }
+}
@@ -0,0 +1,216 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MergeQueueStatus {
+    Queued,
+    Rebasing,
+    Verifying,
+    Merging,
+    Merged,
+    Failed,
+    Cancelled,
+}
+
+impl MergeQueueStatus {
+    /// Whether the entry still holds its attempt's place in the queue
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            MergeQueueStatus::Queued
+                | MergeQueueStatus::Rebasing
+                | MergeQueueStatus::Verifying
+                | MergeQueueStatus::Merging
+        )
+    }
+}
+
+/// An attempt waiting in, or processed by, its project's merge queue
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct MergeQueueEntry {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub target_branch: String,
+    pub status: MergeQueueStatus,
+    /// Why the attempt was not merged
+    pub error: Option<String>,
+    /// Tail of the verify script's output when it failed
+    pub verify_log: Option<String>,
+    pub merge_commit: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MergeQueueEntry {
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        task_attempt_id: Uuid,
+        target_branch: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, MergeQueueEntry>(
+            r#"INSERT INTO merge_queue_entries (id, project_id, task_attempt_id, target_branch)
+               VALUES (?, ?, ?, ?)
+               RETURNING id, project_id, task_attempt_id, target_branch, status, error,
+                         verify_log, merge_commit, created_at, updated_at"#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(project_id)
+        .bind(task_attempt_id)
+        .bind(target_branch)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, MergeQueueEntry>(
+            r#"SELECT id, project_id, task_attempt_id, target_branch, status, error,
+                      verify_log, merge_commit, created_at, updated_at
+               FROM merge_queue_entries
+               WHERE id = ?"#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, MergeQueueEntry>(
+            r#"SELECT id, project_id, task_attempt_id, target_branch, status, error,
+                      verify_log, merge_commit, created_at, updated_at
+               FROM merge_queue_entries
+               WHERE rowid = ?"#,
+        )
+        .bind(rowid)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_active_by_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, MergeQueueEntry>(
+            r#"SELECT id, project_id, task_attempt_id, target_branch, status, error,
+                      verify_log, merge_commit, created_at, updated_at
+               FROM merge_queue_entries
+               WHERE task_attempt_id = ?
+                 AND status IN ('queued', 'rebasing', 'verifying', 'merging')"#,
+        )
+        .bind(task_attempt_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// The project's queue in order, with entries that finished during the last day
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, MergeQueueEntry>(
+            r#"SELECT id, project_id, task_attempt_id, target_branch, status, error,
+                      verify_log, merge_commit, created_at, updated_at
+               FROM merge_queue_entries
+               WHERE project_id = ?
+                 AND (status IN ('queued', 'rebasing', 'verifying', 'merging')
+                      OR updated_at > datetime('now', '-1 day'))
+               ORDER BY created_at ASC"#,
+        )
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Queued entries of every project, oldest first
+    pub async fn find_queued(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, MergeQueueEntry>(
+            r#"SELECT id, project_id, task_attempt_id, target_branch, status, error,
+                      verify_log, merge_commit, created_at, updated_at
+               FROM merge_queue_entries
+               WHERE status = 'queued'
+               ORDER BY created_at ASC"#,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Move an active entry to `status`. Returns false if it is no longer active, e.g.
+    /// because it was cancelled meanwhile.
+    pub async fn set_status(
+        pool: &SqlitePool,
+        id: Uuid,
+        status: MergeQueueStatus,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"UPDATE merge_queue_entries
+               SET status = ?, updated_at = datetime('now', 'subsec')
+               WHERE id = ? AND status IN ('queued', 'rebasing', 'verifying', 'merging')"#,
+        )
+        .bind(status)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn mark_merged(
+        pool: &SqlitePool,
+        id: Uuid,
+        merge_commit: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"UPDATE merge_queue_entries
+               SET status = 'merged', merge_commit = ?, updated_at = datetime('now', 'subsec')
+               WHERE id = ?"#,
+        )
+        .bind(merge_commit)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn mark_failed(
+        pool: &SqlitePool,
+        id: Uuid,
+        error: &str,
+        verify_log: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"UPDATE merge_queue_entries
+               SET status = 'failed', error = ?, verify_log = ?,
+                   updated_at = datetime('now', 'subsec')
+               WHERE id = ? AND status IN ('queued', 'rebasing', 'verifying', 'merging')"#,
+        )
+        .bind(error)
+        .bind(verify_log)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Take an active entry out of the queue. Returns false if it already finished.
+    pub async fn cancel(pool: &SqlitePool, id: Uuid) -> Result<bool, sqlx::Error> {
+        Self::set_status(pool, id, MergeQueueStatus::Cancelled).await
+    }
+
+    /// Fail entries left mid-way by a previous run, which never resumes them blindly
+    pub async fn fail_interrupted(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"UPDATE merge_queue_entries
+               SET status = 'failed', error = 'Interrupted by a restart; queue the attempt again',
+                   updated_at = datetime('now', 'subsec')
+               WHERE status IN ('rebasing', 'verifying', 'merging')"#,
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -1,11 +1,31 @@
+pub mod approval;
+pub mod conflict_resolution;
 pub mod draft;
+pub mod execution_budget;
 pub mod execution_process;
 pub mod execution_process_logs;
+pub mod execution_process_normalization;
+pub mod execution_process_normalized_entry;
+pub mod execution_process_usage;
 pub mod executor_session;
 pub mod image;
 pub mod merge;
+pub mod merge_queue_entry;
+pub mod pr_check_summary;
+pub mod pr_webhook_delivery;
 pub mod project;
+pub mod project_ci_settings;
+pub mod project_env;
+pub mod project_merge_queue_settings;
+pub mod project_merge_settings;
+pub mod project_repository;
+pub mod project_sandbox_policy;
 pub mod shared_task;
 pub mod tag;
 pub mod task;
 pub mod task_attempt;
+pub mod task_attempt_fork;
+pub mod task_attempt_race;
+pub mod task_attempt_repository;
+pub mod task_attempt_stack;
+pub mod task_dependency;
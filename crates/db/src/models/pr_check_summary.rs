@@ -0,0 +1,218 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Outcome of a single CI check, or of all checks on a commit combined
+#[derive(Debug, Clone, Copy, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum CheckState {
+    Pending,
+    Success,
+    Failure,
+    /// Skipped, cancelled or neutral checks, and commits without any checks
+    Neutral,
+}
+
+/// A check run or commit status reported for a pull request's head commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct CheckRun {
+    pub name: String,
+    pub state: CheckState,
+    /// Link to the check's details, e.g. the CI job page
+    pub url: Option<String>,
+}
+
+/// All checks reported for a pull request's head commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct PrChecks {
+    pub head_sha: Option<String>,
+    pub state: CheckState,
+    pub checks: Vec<CheckRun>,
+}
+
+impl PrChecks {
+    /// Aggregate `checks`: any failure fails the commit, otherwise anything still
+    /// running keeps it pending
+    pub fn new(head_sha: Option<String>, checks: Vec<CheckRun>) -> Self {
+        let has = |state| checks.iter().any(|c| c.state == state);
+        let state = if has(CheckState::Failure) {
+            CheckState::Failure
+        } else if has(CheckState::Pending) {
+            CheckState::Pending
+        } else if has(CheckState::Success) {
+            CheckState::Success
+        } else {
+            CheckState::Neutral
+        };
+        Self {
+            head_sha,
+            state,
+            checks,
+        }
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &CheckRun> {
+        self.checks
+            .iter()
+            .filter(|c| c.state == CheckState::Failure)
+    }
+}
+
+/// The latest checks the PR monitor saw for a tracked pull request
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct PrCheckSummary {
+    pub merge_id: Uuid,
+    pub task_attempt_id: Uuid,
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub checks: PrChecks,
+    /// Head commit a fix follow-up was last queued for
+    pub auto_fix_head_sha: Option<String>,
+    pub auto_fix_count: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+struct PrCheckSummaryRow {
+    merge_id: Uuid,
+    task_attempt_id: Uuid,
+    head_sha: Option<String>,
+    state: CheckState,
+    checks: Json<Vec<CheckRun>>,
+    auto_fix_head_sha: Option<String>,
+    auto_fix_count: i64,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<PrCheckSummaryRow> for PrCheckSummary {
+    fn from(r: PrCheckSummaryRow) -> Self {
+        PrCheckSummary {
+            merge_id: r.merge_id,
+            task_attempt_id: r.task_attempt_id,
+            checks: PrChecks {
+                head_sha: r.head_sha,
+                state: r.state,
+                checks: r.checks.0,
+            },
+            auto_fix_head_sha: r.auto_fix_head_sha,
+            auto_fix_count: r.auto_fix_count,
+            updated_at: r.updated_at,
+        }
+    }
+}
+
+impl PrCheckSummary {
+    pub async fn find_by_merge_id(
+        pool: &SqlitePool,
+        merge_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, PrCheckSummaryRow>(
+            r#"SELECT merge_id, task_attempt_id, head_sha, state, checks,
+                      auto_fix_head_sha, auto_fix_count, updated_at
+               FROM pr_check_summaries
+               WHERE merge_id = ?"#,
+        )
+        .bind(merge_id)
+        .fetch_optional(pool)
+        .await
+        .map(|opt| opt.map(PrCheckSummary::from))
+    }
+
+    /// Record the latest checks of a PR, keeping its auto-fix bookkeeping
+    pub async fn upsert(
+        pool: &SqlitePool,
+        merge_id: Uuid,
+        task_attempt_id: Uuid,
+        checks: &PrChecks,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, PrCheckSummaryRow>(
+            r#"INSERT INTO pr_check_summaries (merge_id, task_attempt_id, head_sha, state, checks)
+               VALUES (?, ?, ?, ?, ?)
+               ON CONFLICT(merge_id) DO UPDATE SET
+                 head_sha = excluded.head_sha,
+                 state = excluded.state,
+                 checks = excluded.checks,
+                 updated_at = datetime('now', 'subsec')
+               RETURNING merge_id, task_attempt_id, head_sha, state, checks,
+                         auto_fix_head_sha, auto_fix_count, updated_at"#,
+        )
+        .bind(merge_id)
+        .bind(task_attempt_id)
+        .bind(&checks.head_sha)
+        .bind(checks.state)
+        .bind(Json(&checks.checks))
+        .fetch_one(pool)
+        .await
+        .map(PrCheckSummary::from)
+    }
+
+    /// Failing open PRs in projects with auto-fix enabled whose head commit has not
+    /// been handed back to the agent yet
+    pub async fn find_pending_auto_fixes(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, PrCheckSummaryRow>(
+            r#"SELECT s.merge_id, s.task_attempt_id, s.head_sha, s.state, s.checks,
+                      s.auto_fix_head_sha, s.auto_fix_count, s.updated_at
+               FROM pr_check_summaries s
+               JOIN merges m ON m.id = s.merge_id
+               JOIN task_attempts ta ON ta.id = s.task_attempt_id
+               JOIN tasks t ON t.id = ta.task_id
+               JOIN project_ci_settings ci ON ci.project_id = t.project_id
+               WHERE s.state = 'failure'
+                 AND m.pr_status = 'open'
+                 AND ci.auto_fix_failed_checks = 1
+                 AND s.auto_fix_count < ci.max_auto_fixes
+                 AND s.head_sha IS NOT NULL
+                 AND s.auto_fix_head_sha IS NOT s.head_sha
+               ORDER BY s.updated_at ASC"#,
+        )
+        .fetch_all(pool)
+        .await
+        .map(|rows| rows.into_iter().map(PrCheckSummary::from).collect())
+    }
+
+    /// Claim the auto-fix for `head_sha`. Returns false if it was already claimed or
+    /// the PR has moved to another commit.
+    pub async fn claim_auto_fix(
+        pool: &SqlitePool,
+        merge_id: Uuid,
+        head_sha: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"UPDATE pr_check_summaries
+               SET auto_fix_head_sha = head_sha,
+                   auto_fix_count = auto_fix_count + 1
+               WHERE merge_id = ?
+                 AND head_sha = ?
+                 AND auto_fix_head_sha IS NOT head_sha"#,
+        )
+        .bind(merge_id)
+        .bind(head_sha)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Undo [`Self::claim_auto_fix`] when the fix could not be queued, so a later check
+    /// retries it
+    pub async fn release_auto_fix(
+        pool: &SqlitePool,
+        merge_id: Uuid,
+        head_sha: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"UPDATE pr_check_summaries
+               SET auto_fix_head_sha = NULL,
+                   auto_fix_count = auto_fix_count - 1
+               WHERE merge_id = ?
+                 AND auto_fix_head_sha = ?"#,
+        )
+        .bind(merge_id)
+        .bind(head_sha)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+/// The latest verified pull request status webhook received for a repository
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PrWebhookDelivery {
+    /// Host and repository path, e.g. "github.com/owner/repo"
+    pub repo_key: String,
+    /// "github", "gitlab" or "gitea"
+    pub provider: String,
+    pub last_event: String,
+    pub last_delivery_at: DateTime<Utc>,
+}
+
+impl PrWebhookDelivery {
+    pub async fn record(
+        pool: &SqlitePool,
+        repo_key: &str,
+        provider: &str,
+        event: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"INSERT INTO pr_webhook_deliveries (repo_key, provider, last_event, last_delivery_at)
+               VALUES (?, ?, ?, ?)
+               ON CONFLICT(repo_key) DO UPDATE SET
+                 provider = excluded.provider,
+                 last_event = excluded.last_event,
+                 last_delivery_at = excluded.last_delivery_at"#,
+        )
+        .bind(repo_key)
+        .bind(provider)
+        .bind(event)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Keys of repositories that delivered a webhook at or after `since`
+    pub async fn find_repo_keys_delivered_since(
+        pool: &SqlitePool,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar::<_, String>(
+            r#"SELECT repo_key FROM pr_webhook_deliveries WHERE last_delivery_at >= ?"#,
+        )
+        .bind(since)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Claim a delivery id. Returns false when the delivery was already seen, i.e. it is
+    /// a redelivery or a replay.
+    pub async fn claim_delivery_id(
+        pool: &SqlitePool,
+        provider: &str,
+        delivery_id: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"INSERT INTO pr_webhook_delivery_ids (delivery_id, provider, received_at)
+               VALUES (?, ?, ?)
+               ON CONFLICT(delivery_id) DO NOTHING"#,
+        )
+        .bind(format!("{provider}:{delivery_id}"))
+        .bind(provider)
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Give up a claimed delivery id, so a redelivery of a delivery that could not be
+    /// applied is applied after all
+    pub async fn release_delivery_id(
+        pool: &SqlitePool,
+        provider: &str,
+        delivery_id: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(r#"DELETE FROM pr_webhook_delivery_ids WHERE delivery_id = ?"#)
+            .bind(format!("{provider}:{delivery_id}"))
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Forget delivery ids received before `before`
+    pub async fn delete_delivery_ids_before(
+        pool: &SqlitePool,
+        before: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(r#"DELETE FROM pr_webhook_delivery_ids WHERE received_at < ?"#)
+            .bind(before)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn released_delivery_ids_can_be_claimed_again() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        assert!(
+            PrWebhookDelivery::claim_delivery_id(&pool, "github", "d1")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !PrWebhookDelivery::claim_delivery_id(&pool, "github", "d1")
+                .await
+                .unwrap()
+        );
+        // Ids are scoped to their provider
+        assert!(
+            PrWebhookDelivery::claim_delivery_id(&pool, "gitea", "d1")
+                .await
+                .unwrap()
+        );
+
+        PrWebhookDelivery::release_delivery_id(&pool, "github", "d1")
+            .await
+            .unwrap();
+        assert!(
+            PrWebhookDelivery::claim_delivery_id(&pool, "github", "d1")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !PrWebhookDelivery::claim_delivery_id(&pool, "gitea", "d1")
+                .await
+                .unwrap()
+        );
+    }
+}
@@ -73,6 +73,15 @@ pub enum SearchMatchType {
 }
 
 impl Project {
+    /// Directory of the primary repository's worktree inside the attempt directory of a
+    /// project with additional repositories
+    pub fn repo_dir_name(&self) -> String {
+        self.git_repo_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "repo".to_string())
+    }
+
     pub async fn count(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar!(r#"SELECT COUNT(*) as "count!: i64" FROM projects"#)
             .fetch_one(pool)
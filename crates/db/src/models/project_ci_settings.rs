@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// How a project reacts to failing CI checks on its attempts' pull requests
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectCiSettings {
+    pub project_id: Uuid,
+    /// Queue a follow-up with the failing job logs when a PR's checks fail
+    pub auto_fix_failed_checks: bool,
+    /// Stop queueing fixes for a PR after this many
+    pub max_auto_fixes: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpsertProjectCiSettings {
+    pub auto_fix_failed_checks: bool,
+    pub max_auto_fixes: i64,
+}
+
+impl ProjectCiSettings {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectCiSettings>(
+            r#"SELECT project_id, auto_fix_failed_checks, max_auto_fixes, created_at, updated_at
+               FROM project_ci_settings
+               WHERE project_id = ?"#,
+        )
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertProjectCiSettings,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, ProjectCiSettings>(
+            r#"INSERT INTO project_ci_settings (project_id, auto_fix_failed_checks, max_auto_fixes)
+               VALUES (?, ?, ?)
+               ON CONFLICT(project_id) DO UPDATE SET
+                 auto_fix_failed_checks = excluded.auto_fix_failed_checks,
+                 max_auto_fixes = excluded.max_auto_fixes,
+                 updated_at = datetime('now', 'subsec')
+               RETURNING project_id, auto_fix_failed_checks, max_auto_fixes, created_at, updated_at"#,
+        )
+        .bind(project_id)
+        .bind(data.auto_fix_failed_checks)
+        .bind(data.max_auto_fixes)
+        .fetch_one(pool)
+        .await
+    }
+}
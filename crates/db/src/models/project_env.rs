@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Environment variables set on every execution process of a project
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ProjectEnv {
+    pub project_id: Uuid,
+    /// Values may refer to secrets with `{{secret:NAME}}`
+    pub vars: HashMap<String, String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+struct ProjectEnvRow {
+    project_id: Uuid,
+    vars: Json<HashMap<String, String>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<ProjectEnvRow> for ProjectEnv {
+    fn from(r: ProjectEnvRow) -> Self {
+        ProjectEnv {
+            project_id: r.project_id,
+            vars: r.vars.0,
+            created_at: r.created_at,
+            updated_at: r.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpsertProjectEnv {
+    pub vars: HashMap<String, String>,
+}
+
+impl ProjectEnv {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectEnvRow>(
+            r#"SELECT project_id, vars, created_at, updated_at
+               FROM project_env_vars
+               WHERE project_id = ?"#,
+        )
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+        .map(|opt| opt.map(ProjectEnv::from))
+    }
+
+    /// The project's variables, empty when none are set
+    pub async fn vars_for_project(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<HashMap<String, String>, sqlx::Error> {
+        Ok(Self::find_by_project_id(pool, project_id)
+            .await?
+            .map(|env| env.vars)
+            .unwrap_or_default())
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertProjectEnv,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, ProjectEnvRow>(
+            r#"INSERT INTO project_env_vars (project_id, vars)
+               VALUES (?, ?)
+               ON CONFLICT(project_id) DO UPDATE SET
+                 vars = excluded.vars,
+                 updated_at = datetime('now', 'subsec')
+               RETURNING project_id, vars, created_at, updated_at"#,
+        )
+        .bind(project_id)
+        .bind(Json(&data.vars))
+        .fetch_one(pool)
+        .await
+        .map(ProjectEnv::from)
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// How a project's merge queue verifies attempts; they are merged with the project's
+/// merge settings
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectMergeQueueSettings {
+    pub project_id: Uuid,
+    /// Run in the attempt's worktree after it is rebased; it is merged only if this exits 0
+    pub verify_script: Option<String>,
+    /// The verify script is stopped and the attempt fails after this many seconds
+    pub verify_timeout_secs: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpsertProjectMergeQueueSettings {
+    pub verify_script: Option<String>,
+    pub verify_timeout_secs: i64,
+}
+
+impl ProjectMergeQueueSettings {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectMergeQueueSettings>(
+            r#"SELECT project_id, verify_script, verify_timeout_secs, created_at, updated_at
+               FROM project_merge_queue_settings
+               WHERE project_id = ?"#,
+        )
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertProjectMergeQueueSettings,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, ProjectMergeQueueSettings>(
+            r#"INSERT INTO project_merge_queue_settings
+                 (project_id, verify_script, verify_timeout_secs)
+               VALUES (?, ?, ?)
+               ON CONFLICT(project_id) DO UPDATE SET
+                 verify_script = excluded.verify_script,
+                 verify_timeout_secs = excluded.verify_timeout_secs,
+                 updated_at = datetime('now', 'subsec')
+               RETURNING project_id, verify_script, verify_timeout_secs, created_at, updated_at"#,
+        )
+        .bind(project_id)
+        .bind(&data.verify_script)
+        .bind(data.verify_timeout_secs)
+        .fetch_one(pool)
+        .await
+    }
+}
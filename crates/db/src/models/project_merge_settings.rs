@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, Type};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// How an attempt's branch lands on its target branch
+#[derive(Debug, Clone, Copy, Default, Type, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Squash the attempt's commits into a single commit on the target branch
+    #[default]
+    Squash,
+    /// Merge the attempt's branch with a merge commit, keeping its commits
+    MergeCommit,
+    /// Rebase the attempt's branch onto the target branch and fast-forward the target
+    RebaseFastForward,
+}
+
+/// How a project's attempts are merged, directly or by the merge queue
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectMergeSettings {
+    pub project_id: Uuid,
+    pub strategy: MergeStrategy,
+    /// Message of squash and merge commits. Supports `{task_title}`, `{task_description}`,
+    /// `{task_id}`, `{task_id_short}`, `{attempt_id}`, `{executor}`, `{agent_summary}`
+    /// and `{co_authored_by}`; the default message is used when unset.
+    pub commit_message_template: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpsertProjectMergeSettings {
+    pub strategy: MergeStrategy,
+    pub commit_message_template: Option<String>,
+}
+
+impl ProjectMergeSettings {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectMergeSettings>(
+            r#"SELECT project_id, strategy, commit_message_template, created_at, updated_at
+               FROM project_merge_settings
+               WHERE project_id = ?"#,
+        )
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertProjectMergeSettings,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, ProjectMergeSettings>(
+            r#"INSERT INTO project_merge_settings (project_id, strategy, commit_message_template)
+               VALUES (?, ?, ?)
+               ON CONFLICT(project_id) DO UPDATE SET
+                 strategy = excluded.strategy,
+                 commit_message_template = excluded.commit_message_template,
+                 updated_at = datetime('now', 'subsec')
+               RETURNING project_id, strategy, commit_message_template, created_at, updated_at"#,
+        )
+        .bind(project_id)
+        .bind(data.strategy)
+        .bind(&data.commit_message_template)
+        .fetch_one(pool)
+        .await
+    }
+}
@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// A repository of a project besides its primary `git_repo_path`
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct ProjectRepository {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    /// Directory of the repository's worktree inside an attempt's directory
+    pub name: String,
+    #[sqlx(try_from = "String")]
+    pub git_repo_path: PathBuf,
+    /// Branch attempts are merged into. Unset for repositories added before it was
+    /// recorded, which use the attempt's target branch.
+    pub target_branch: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct CreateProjectRepository {
+    pub name: String,
+    pub git_repo_path: String,
+    /// Defaults to the branch checked out in the repository when it is added
+    #[serde(default)]
+    #[ts(optional)]
+    pub target_branch: Option<String>,
+}
+
+impl ProjectRepository {
+    pub async fn create(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        name: &str,
+        git_repo_path: &str,
+        target_branch: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, ProjectRepository>(
+            r#"INSERT INTO project_repositories (id, project_id, name, git_repo_path, target_branch)
+               VALUES (?, ?, ?, ?, ?)
+               RETURNING id, project_id, name, git_repo_path, target_branch, created_at,
+                         updated_at"#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(project_id)
+        .bind(name)
+        .bind(git_repo_path)
+        .bind(target_branch)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectRepository>(
+            r#"SELECT id, project_id, name, git_repo_path, target_branch, created_at, updated_at
+               FROM project_repositories
+               WHERE id = ?"#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectRepository>(
+            r#"SELECT id, project_id, name, git_repo_path, target_branch, created_at, updated_at
+               FROM project_repositories
+               WHERE project_id = ?
+               ORDER BY name ASC"#,
+        )
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM project_repositories WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
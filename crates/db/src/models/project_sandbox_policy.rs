@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use executors::sandbox::SandboxPolicy;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Whether, and how, a project's execution processes run inside a sandbox
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ProjectSandboxPolicy {
+    pub project_id: Uuid,
+    pub enabled: bool,
+    pub policy: SandboxPolicy,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+struct ProjectSandboxPolicyRow {
+    project_id: Uuid,
+    enabled: bool,
+    policy: Json<SandboxPolicy>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<ProjectSandboxPolicyRow> for ProjectSandboxPolicy {
+    fn from(r: ProjectSandboxPolicyRow) -> Self {
+        ProjectSandboxPolicy {
+            project_id: r.project_id,
+            enabled: r.enabled,
+            policy: r.policy.0,
+            created_at: r.created_at,
+            updated_at: r.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+pub struct UpsertProjectSandboxPolicy {
+    pub enabled: bool,
+    pub policy: SandboxPolicy,
+}
+
+impl ProjectSandboxPolicy {
+    pub async fn find_by_project_id(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ProjectSandboxPolicyRow>(
+            r#"SELECT project_id, enabled, policy, created_at, updated_at
+               FROM project_sandbox_policies
+               WHERE project_id = ?"#,
+        )
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+        .map(|opt| opt.map(ProjectSandboxPolicy::from))
+    }
+
+    /// The policy to enforce for a project, or `None` when sandboxing is off
+    pub async fn find_enabled(
+        pool: &SqlitePool,
+        project_id: Uuid,
+    ) -> Result<Option<SandboxPolicy>, sqlx::Error> {
+        Ok(Self::find_by_project_id(pool, project_id)
+            .await?
+            .filter(|p| p.enabled)
+            .map(|p| p.policy))
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        project_id: Uuid,
+        data: &UpsertProjectSandboxPolicy,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, ProjectSandboxPolicyRow>(
+            r#"INSERT INTO project_sandbox_policies (project_id, enabled, policy)
+               VALUES (?, ?, ?)
+               ON CONFLICT(project_id) DO UPDATE SET
+                 enabled = excluded.enabled,
+                 policy = excluded.policy,
+                 updated_at = datetime('now', 'subsec')
+               RETURNING project_id, enabled, policy, created_at, updated_at"#,
+        )
+        .bind(project_id)
+        .bind(data.enabled)
+        .bind(Json(&data.policy))
+        .fetch_one(pool)
+        .await
+        .map(ProjectSandboxPolicy::from)
+    }
+}
@@ -234,6 +234,15 @@ ORDER BY t.created_at DESC"#,
         .await
     }
 
+    /// The current status of every task
+    pub async fn find_all_statuses(
+        pool: &SqlitePool,
+    ) -> Result<Vec<(Uuid, TaskStatus)>, sqlx::Error> {
+        sqlx::query_as::<_, (Uuid, TaskStatus)>(r#"SELECT id, status FROM tasks"#)
+            .fetch_all(pool)
+            .await
+    }
+
     pub async fn find_by_rowid(pool: &SqlitePool, rowid: i64) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as!(
             Task,
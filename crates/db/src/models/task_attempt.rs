@@ -388,6 +388,15 @@ impl TaskAttempt {
         .await?)
     }
 
+    /// Delete an attempt that never got going; its processes and other rows go with it
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM task_attempts WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
     pub async fn update_target_branch(
         pool: &SqlitePool,
         attempt_id: Uuid,
@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Links a forked attempt to the attempt and process it was forked from
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskAttemptFork {
+    pub task_attempt_id: Uuid,
+    pub parent_attempt_id: Uuid,
+    /// Process of the parent attempt the fork starts after
+    pub execution_process_id: Uuid,
+    /// HEAD the process left behind, which the fork's branch starts at
+    pub fork_commit: String,
+    /// Agent session the fork's first follow-up resumes; none when the agent cannot fork
+    /// its sessions
+    pub session_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaskAttemptFork {
+    pub async fn create(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        parent_attempt_id: Uuid,
+        execution_process_id: Uuid,
+        fork_commit: &str,
+        session_id: Option<&str>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, TaskAttemptFork>(
+            r#"INSERT INTO task_attempt_forks
+                   (task_attempt_id, parent_attempt_id, execution_process_id, fork_commit,
+                    session_id)
+               VALUES (?, ?, ?, ?, ?)
+               RETURNING task_attempt_id, parent_attempt_id, execution_process_id, fork_commit,
+                         session_id, created_at"#,
+        )
+        .bind(task_attempt_id)
+        .bind(parent_attempt_id)
+        .bind(execution_process_id)
+        .bind(fork_commit)
+        .bind(session_id)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, TaskAttemptFork>(
+            r#"SELECT task_attempt_id, parent_attempt_id, execution_process_id, fork_commit,
+                      session_id, created_at
+               FROM task_attempt_forks
+               WHERE task_attempt_id = ?"#,
+        )
+        .bind(task_attempt_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Forks among the attempts of a task, oldest first
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, TaskAttemptFork>(
+            r#"SELECT f.task_attempt_id, f.parent_attempt_id, f.execution_process_id,
+                      f.fork_commit, f.session_id, f.created_at
+               FROM task_attempt_forks f
+               JOIN task_attempts ta ON ta.id = f.task_attempt_id
+               WHERE ta.task_id = ?
+               ORDER BY f.created_at ASC"#,
+        )
+        .bind(task_id)
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,167 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task_attempt::TaskAttempt;
+
+/// A group of attempts for the same task, one per executor profile, run in parallel.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskAttemptRace {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub winner_attempt_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskAttemptRaceEntry {
+    pub race_id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub archived: bool,
+}
+
+impl TaskAttemptRace {
+    pub async fn create(
+        pool: &SqlitePool,
+        id: Uuid,
+        task_id: Uuid,
+        attempt_ids: &[Uuid],
+    ) -> Result<Self, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let race = sqlx::query_as::<_, TaskAttemptRace>(
+            r#"INSERT INTO task_attempt_races (id, task_id)
+               VALUES (?, ?)
+               RETURNING id, task_id, winner_attempt_id, created_at, decided_at"#,
+        )
+        .bind(id)
+        .bind(task_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for attempt_id in attempt_ids {
+            sqlx::query(
+                r#"INSERT INTO task_attempt_race_entries (race_id, task_attempt_id) VALUES (?, ?)"#,
+            )
+            .bind(id)
+            .bind(attempt_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(race)
+    }
+
+    pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, TaskAttemptRace>(
+            r#"SELECT id, task_id, winner_attempt_id, created_at, decided_at
+               FROM task_attempt_races
+               WHERE id = ?"#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, TaskAttemptRace>(
+            r#"SELECT id, task_id, winner_attempt_id, created_at, decided_at
+               FROM task_attempt_races
+               WHERE task_id = ?
+               ORDER BY created_at DESC"#,
+        )
+        .bind(task_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Find the race a given attempt belongs to, if any
+    pub async fn find_by_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, TaskAttemptRace>(
+            r#"SELECT r.id, r.task_id, r.winner_attempt_id, r.created_at, r.decided_at
+               FROM task_attempt_races r
+               JOIN task_attempt_race_entries e ON e.race_id = r.id
+               WHERE e.task_attempt_id = ?"#,
+        )
+        .bind(task_attempt_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn entries(
+        &self,
+        pool: &SqlitePool,
+    ) -> Result<Vec<TaskAttemptRaceEntry>, sqlx::Error> {
+        sqlx::query_as::<_, TaskAttemptRaceEntry>(
+            r#"SELECT race_id, task_attempt_id, archived
+               FROM task_attempt_race_entries
+               WHERE race_id = ?"#,
+        )
+        .bind(self.id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn attempts(&self, pool: &SqlitePool) -> Result<Vec<TaskAttempt>, sqlx::Error> {
+        sqlx::query_as::<_, TaskAttempt>(
+            r#"SELECT ta.id, ta.task_id, ta.container_ref, ta.branch, ta.target_branch, ta.executor,
+                      ta.worktree_deleted, ta.setup_completed_at, ta.created_at, ta.updated_at
+               FROM task_attempts ta
+               JOIN task_attempt_race_entries e ON e.task_attempt_id = ta.id
+               WHERE e.race_id = ?
+               ORDER BY ta.created_at ASC"#,
+        )
+        .bind(self.id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query(r#"DELETE FROM task_attempt_races WHERE id = ?"#)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Record the winning attempt and archive every other attempt in the race
+    pub async fn set_winner(
+        pool: &SqlitePool,
+        race_id: Uuid,
+        winner_attempt_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            r#"UPDATE task_attempt_race_entries
+               SET archived = (task_attempt_id != ?)
+               WHERE race_id = ?"#,
+        )
+        .bind(winner_attempt_id)
+        .bind(race_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let race = sqlx::query_as::<_, TaskAttemptRace>(
+            r#"UPDATE task_attempt_races
+               SET winner_attempt_id = ?, decided_at = datetime('now', 'subsec')
+               WHERE id = ?
+               RETURNING id, task_id, winner_attempt_id, created_at, decided_at"#,
+        )
+        .bind(winner_attempt_id)
+        .bind(race_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(race)
+    }
+}
@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Worktree of one of the project's additional repositories, created for an attempt
+/// next to the worktree of the primary repository
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskAttemptRepository {
+    pub id: Uuid,
+    pub task_attempt_id: Uuid,
+    pub name: String,
+    #[sqlx(try_from = "String")]
+    pub git_repo_path: PathBuf,
+    #[sqlx(try_from = "String")]
+    pub worktree_path: PathBuf,
+    /// Branch of this repository the attempt's branch is merged into
+    pub target_branch: String,
+    pub merge_commit: Option<String>,
+    pub pr_number: Option<i64>,
+    pub pr_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TaskAttemptRepository {
+    pub async fn create(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        name: &str,
+        git_repo_path: &str,
+        worktree_path: &str,
+        target_branch: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, TaskAttemptRepository>(
+            r#"INSERT INTO task_attempt_repositories
+                 (id, task_attempt_id, name, git_repo_path, worktree_path, target_branch)
+               VALUES (?, ?, ?, ?, ?, ?)
+               RETURNING id, task_attempt_id, name, git_repo_path, worktree_path, target_branch,
+                         merge_commit, pr_number, pr_url, created_at, updated_at"#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(task_attempt_id)
+        .bind(name)
+        .bind(git_repo_path)
+        .bind(worktree_path)
+        .bind(target_branch)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_task_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, TaskAttemptRepository>(
+            r#"SELECT id, task_attempt_id, name, git_repo_path, worktree_path, target_branch,
+                      merge_commit, pr_number, pr_url, created_at, updated_at
+               FROM task_attempt_repositories
+               WHERE task_attempt_id = ?
+               ORDER BY name ASC"#,
+        )
+        .bind(task_attempt_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Whether any attempt has a worktree at `worktree_path`
+    pub async fn worktree_path_exists(
+        pool: &SqlitePool,
+        worktree_path: &str,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar::<_, bool>(
+            r#"SELECT EXISTS(SELECT 1 FROM task_attempt_repositories WHERE worktree_path = ?)"#,
+        )
+        .bind(worktree_path)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn set_merge_commit(
+        pool: &SqlitePool,
+        id: Uuid,
+        merge_commit: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"UPDATE task_attempt_repositories
+               SET merge_commit = ?, updated_at = datetime('now', 'subsec')
+               WHERE id = ?"#,
+        )
+        .bind(merge_commit)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_pr(
+        pool: &SqlitePool,
+        id: Uuid,
+        pr_number: i64,
+        pr_url: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"UPDATE task_attempt_repositories
+               SET pr_number = ?, pr_url = ?, updated_at = datetime('now', 'subsec')
+               WHERE id = ?"#,
+        )
+        .bind(pr_number)
+        .bind(pr_url)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
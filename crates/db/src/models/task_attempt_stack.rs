@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Links a stacked attempt to the attempt whose branch it is based on
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskAttemptStack {
+    pub task_attempt_id: Uuid,
+    pub parent_attempt_id: Uuid,
+    /// Commit of the parent's branch the attempt is currently based on
+    pub base_commit: String,
+    /// Why the last restack failed, e.g. merge conflicts
+    pub restack_error: Option<String>,
+    /// Parent commit the last restack failed for; it is not retried automatically
+    pub failed_base_commit: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TaskAttemptStack {
+    pub async fn create(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        parent_attempt_id: Uuid,
+        base_commit: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, TaskAttemptStack>(
+            r#"INSERT INTO task_attempt_stacks (task_attempt_id, parent_attempt_id, base_commit)
+               VALUES (?, ?, ?)
+               RETURNING task_attempt_id, parent_attempt_id, base_commit, restack_error,
+                         failed_base_commit, created_at, updated_at"#,
+        )
+        .bind(task_attempt_id)
+        .bind(parent_attempt_id)
+        .bind(base_commit)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn find_by_attempt_id(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, TaskAttemptStack>(
+            r#"SELECT task_attempt_id, parent_attempt_id, base_commit, restack_error,
+                      failed_base_commit, created_at, updated_at
+               FROM task_attempt_stacks
+               WHERE task_attempt_id = ?"#,
+        )
+        .bind(task_attempt_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Attempts stacked directly on `parent_attempt_id`
+    pub async fn find_children(
+        pool: &SqlitePool,
+        parent_attempt_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, TaskAttemptStack>(
+            r#"SELECT task_attempt_id, parent_attempt_id, base_commit, restack_error,
+                      failed_base_commit, created_at, updated_at
+               FROM task_attempt_stacks
+               WHERE parent_attempt_id = ?
+               ORDER BY created_at ASC"#,
+        )
+        .bind(parent_attempt_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Every stacked attempt, oldest first so parents are visited before their children
+    pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, TaskAttemptStack>(
+            r#"SELECT s.task_attempt_id, s.parent_attempt_id, s.base_commit, s.restack_error,
+                      s.failed_base_commit, s.created_at, s.updated_at
+               FROM task_attempt_stacks s
+               JOIN task_attempts ta ON ta.id = s.task_attempt_id
+               ORDER BY ta.created_at ASC"#,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Record a successful restack onto `base_commit` of `parent_attempt_id`
+    pub async fn set_base(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        parent_attempt_id: Uuid,
+        base_commit: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"UPDATE task_attempt_stacks
+               SET parent_attempt_id = ?, base_commit = ?, restack_error = NULL,
+                   failed_base_commit = NULL, updated_at = datetime('now', 'subsec')
+               WHERE task_attempt_id = ?"#,
+        )
+        .bind(parent_attempt_id)
+        .bind(base_commit)
+        .bind(task_attempt_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_error(
+        pool: &SqlitePool,
+        task_attempt_id: Uuid,
+        failed_base_commit: &str,
+        error: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"UPDATE task_attempt_stacks
+               SET restack_error = ?, failed_base_commit = ?,
+                   updated_at = datetime('now', 'subsec')
+               WHERE task_attempt_id = ?"#,
+        )
+        .bind(error)
+        .bind(failed_base_commit)
+        .bind(task_attempt_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Unstack an attempt, e.g. once the bottom of its stack has been merged
+    pub async fn delete(pool: &SqlitePool, task_attempt_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM task_attempt_stacks WHERE task_attempt_id = ?")
+            .bind(task_attempt_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,476 @@
+use chrono::{DateTime, Utc};
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool, types::Json};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use super::task::Task;
+
+/// Edge in the task dependency graph: `task_id` is blocked until
+/// `depends_on_task_id` is done or has been merged.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+pub struct TaskDependency {
+    pub task_id: Uuid,
+    pub depends_on_task_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Attempt configuration used to start a blocked task once all of its
+/// upstream tasks are satisfied.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TaskAutoStart {
+    pub task_id: Uuid,
+    pub executor_profile_id: ExecutorProfileId,
+    pub base_branch: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+struct TaskAutoStartRow {
+    task_id: Uuid,
+    executor_profile_id: Json<ExecutorProfileId>,
+    base_branch: String,
+    created_at: DateTime<Utc>,
+}
+
+impl From<TaskAutoStartRow> for TaskAutoStart {
+    fn from(r: TaskAutoStartRow) -> Self {
+        TaskAutoStart {
+            task_id: r.task_id,
+            executor_profile_id: r.executor_profile_id.0,
+            base_branch: r.base_branch,
+            created_at: r.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct TaskDependencies {
+    pub depends_on: Vec<Task>, // Upstream tasks that block this task
+    pub dependents: Vec<Task>, // Downstream tasks blocked by this task
+    pub auto_start: Option<TaskAutoStart>,
+    pub is_blocked: bool,
+}
+
+/// SQL predicate (over an upstream task aliased `up`) that is true when the
+/// upstream task no longer blocks its dependents.
+const UPSTREAM_SATISFIED: &str = r#"(
+    up.status = 'done'
+    OR EXISTS (
+        SELECT 1 FROM merges m
+        JOIN task_attempts ta ON ta.id = m.task_attempt_id
+        WHERE ta.task_id = up.id
+          AND (m.merge_type = 'direct' OR m.pr_status = 'merged')
+    )
+)"#;
+
+impl TaskDependency {
+    pub async fn find_depends_on(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Task>, sqlx::Error> {
+        sqlx::query_as::<_, Task>(
+            r#"SELECT t.id, t.project_id, t.title, t.description, t.status, t.parent_task_attempt,
+                      t.shared_task_id, t.created_at, t.updated_at
+               FROM task_dependencies d
+               JOIN tasks t ON t.id = d.depends_on_task_id
+               WHERE d.task_id = ?
+               ORDER BY t.created_at ASC"#,
+        )
+        .bind(task_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find_dependents(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Vec<Task>, sqlx::Error> {
+        sqlx::query_as::<_, Task>(
+            r#"SELECT t.id, t.project_id, t.title, t.description, t.status, t.parent_task_attempt,
+                      t.shared_task_id, t.created_at, t.updated_at
+               FROM task_dependencies d
+               JOIN tasks t ON t.id = d.task_id
+               WHERE d.depends_on_task_id = ?
+               ORDER BY t.created_at ASC"#,
+        )
+        .bind(task_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Returns true if adding the edge `task_id -> depends_on_task_id` would
+    /// close a cycle, i.e. `task_id` is already reachable upstream of
+    /// `depends_on_task_id`.
+    pub async fn would_create_cycle(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        if task_id == depends_on_task_id {
+            return Ok(true);
+        }
+        let (exists,): (bool,) = sqlx::query_as(
+            r#"WITH RECURSIVE upstream(id) AS (
+                   SELECT depends_on_task_id FROM task_dependencies WHERE task_id = $1
+                   UNION
+                   SELECT d.depends_on_task_id
+                   FROM task_dependencies d
+                   JOIN upstream u ON d.task_id = u.id
+               )
+               SELECT EXISTS(SELECT 1 FROM upstream WHERE id = $2)"#,
+        )
+        .bind(depends_on_task_id)
+        .bind(task_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(exists)
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, TaskDependency>(
+            r#"INSERT INTO task_dependencies (task_id, depends_on_task_id)
+               VALUES (?, ?)
+               ON CONFLICT(task_id, depends_on_task_id) DO UPDATE SET task_id = excluded.task_id
+               RETURNING task_id, depends_on_task_id, created_at"#,
+        )
+        .bind(task_id)
+        .bind(depends_on_task_id)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn delete(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        depends_on_task_id: Uuid,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"DELETE FROM task_dependencies WHERE task_id = ? AND depends_on_task_id = ?"#,
+        )
+        .bind(task_id)
+        .bind(depends_on_task_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// A task is blocked while any of its upstream tasks is neither done nor merged.
+    pub async fn is_blocked(pool: &SqlitePool, task_id: Uuid) -> Result<bool, sqlx::Error> {
+        let query = format!(
+            r#"SELECT EXISTS(
+                   SELECT 1 FROM task_dependencies d
+                   JOIN tasks up ON up.id = d.depends_on_task_id
+                   WHERE d.task_id = ? AND NOT {UPSTREAM_SATISFIED}
+               )"#
+        );
+        let (blocked,): (bool,) = sqlx::query_as(&query).bind(task_id).fetch_one(pool).await?;
+        Ok(blocked)
+    }
+
+    pub async fn find_for_task(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<TaskDependencies, sqlx::Error> {
+        Ok(TaskDependencies {
+            depends_on: Self::find_depends_on(pool, task_id).await?,
+            dependents: Self::find_dependents(pool, task_id).await?,
+            auto_start: TaskAutoStart::find_by_task_id(pool, task_id).await?,
+            is_blocked: Self::is_blocked(pool, task_id).await?,
+        })
+    }
+}
+
+impl TaskAutoStart {
+    pub async fn find_by_task_id(
+        pool: &SqlitePool,
+        task_id: Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, TaskAutoStartRow>(
+            r#"SELECT task_id, executor_profile_id, base_branch, created_at
+               FROM task_auto_starts
+               WHERE task_id = ?"#,
+        )
+        .bind(task_id)
+        .fetch_optional(pool)
+        .await
+        .map(|opt| opt.map(TaskAutoStart::from))
+    }
+
+    pub async fn upsert(
+        pool: &SqlitePool,
+        task_id: Uuid,
+        executor_profile_id: &ExecutorProfileId,
+        base_branch: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, TaskAutoStartRow>(
+            r#"INSERT INTO task_auto_starts (task_id, executor_profile_id, base_branch)
+               VALUES (?, ?, ?)
+               ON CONFLICT(task_id) DO UPDATE SET
+                 executor_profile_id = excluded.executor_profile_id,
+                 base_branch = excluded.base_branch
+               RETURNING task_id, executor_profile_id, base_branch, created_at"#,
+        )
+        .bind(task_id)
+        .bind(Json(executor_profile_id))
+        .bind(base_branch)
+        .fetch_one(pool)
+        .await
+        .map(TaskAutoStart::from)
+    }
+
+    /// Deletes the pending auto start. Returns true if a row was removed, which
+    /// lets the scheduler claim a start exactly once.
+    pub async fn delete(pool: &SqlitePool, task_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(r#"DELETE FROM task_auto_starts WHERE task_id = ?"#)
+            .bind(task_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Pending auto starts whose task is still in todo, has no attempts yet and
+    /// whose upstream tasks are all done or merged.
+    pub async fn find_ready(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        let query = format!(
+            r#"SELECT s.task_id, s.executor_profile_id, s.base_branch, s.created_at
+               FROM task_auto_starts s
+               JOIN tasks t ON t.id = s.task_id
+               WHERE t.status = 'todo'
+                 AND NOT EXISTS (SELECT 1 FROM task_attempts ta WHERE ta.task_id = t.id)
+                 AND NOT EXISTS (
+                     SELECT 1 FROM task_dependencies d
+                     JOIN tasks up ON up.id = d.depends_on_task_id
+                     WHERE d.task_id = s.task_id AND NOT {UPSTREAM_SATISFIED}
+                 )
+               ORDER BY s.created_at ASC"#
+        );
+        sqlx::query_as::<_, TaskAutoStartRow>(&query)
+            .fetch_all(pool)
+            .await
+            .map(|rows| rows.into_iter().map(TaskAutoStart::from).collect())
+    }
+
+    /// Auto starts that can never fire because the task already moved on
+    /// (it was started manually or its status changed).
+    pub async fn delete_stale(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"DELETE FROM task_auto_starts
+               WHERE task_id IN (
+                   SELECT t.id FROM tasks t
+                   WHERE t.status != 'todo'
+                      OR EXISTS (SELECT 1 FROM task_attempts ta WHERE ta.task_id = t.id)
+               )"#,
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::executors::BaseCodingAgent;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::models::{
+        merge::{Merge, MergeStatus},
+        project::{CreateProject, Project},
+        task::{CreateTask, TaskStatus},
+        task_attempt::{CreateTaskAttempt, TaskAttempt},
+    };
+
+    async fn setup() -> (SqlitePool, Uuid) {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let project_id = Uuid::new_v4();
+        Project::create(
+            &pool,
+            &CreateProject {
+                name: "project".to_string(),
+                git_repo_path: format!("/tmp/{project_id}"),
+                use_existing_repo: true,
+                setup_script: None,
+                dev_script: None,
+                cleanup_script: None,
+                copy_files: None,
+            },
+            project_id,
+        )
+        .await
+        .unwrap();
+        (pool, project_id)
+    }
+
+    async fn create_task(pool: &SqlitePool, project_id: Uuid, title: &str) -> Uuid {
+        let id = Uuid::new_v4();
+        Task::create(
+            pool,
+            &CreateTask::from_title_description(project_id, title.to_string(), None),
+            id,
+        )
+        .await
+        .unwrap();
+        id
+    }
+
+    async fn create_attempt(pool: &SqlitePool, task_id: Uuid) -> Uuid {
+        let id = Uuid::new_v4();
+        TaskAttempt::create(
+            pool,
+            &CreateTaskAttempt {
+                executor: BaseCodingAgent::ClaudeCode,
+                base_branch: "main".to_string(),
+                branch: format!("attempt-{id}"),
+            },
+            id,
+            task_id,
+        )
+        .await
+        .unwrap();
+        id
+    }
+
+    async fn ready_task_ids(pool: &SqlitePool) -> Vec<Uuid> {
+        TaskAutoStart::find_ready(pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|auto_start| auto_start.task_id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn cycles_are_detected_through_the_whole_upstream_chain() {
+        let (pool, project_id) = setup().await;
+        let a = create_task(&pool, project_id, "a").await;
+        let b = create_task(&pool, project_id, "b").await;
+        let c = create_task(&pool, project_id, "c").await;
+
+        assert!(
+            TaskDependency::would_create_cycle(&pool, a, a)
+                .await
+                .unwrap()
+        );
+
+        // c depends on b, b depends on a
+        TaskDependency::create(&pool, b, a).await.unwrap();
+        TaskDependency::create(&pool, c, b).await.unwrap();
+
+        assert!(
+            TaskDependency::would_create_cycle(&pool, a, b)
+                .await
+                .unwrap()
+        );
+        assert!(
+            TaskDependency::would_create_cycle(&pool, a, c)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !TaskDependency::would_create_cycle(&pool, c, a)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn upstream_is_satisfied_when_done_or_merged() {
+        let (pool, project_id) = setup().await;
+        let upstream = create_task(&pool, project_id, "upstream").await;
+        let downstream = create_task(&pool, project_id, "downstream").await;
+        TaskDependency::create(&pool, downstream, upstream)
+            .await
+            .unwrap();
+        assert!(TaskDependency::is_blocked(&pool, downstream).await.unwrap());
+
+        Task::update_status(&pool, upstream, TaskStatus::Done)
+            .await
+            .unwrap();
+        assert!(!TaskDependency::is_blocked(&pool, downstream).await.unwrap());
+
+        // A direct merge satisfies the upstream regardless of its status
+        Task::update_status(&pool, upstream, TaskStatus::InReview)
+            .await
+            .unwrap();
+        assert!(TaskDependency::is_blocked(&pool, downstream).await.unwrap());
+        let attempt = create_attempt(&pool, upstream).await;
+        Merge::create_direct(&pool, attempt, "main", "abc123")
+            .await
+            .unwrap();
+        assert!(!TaskDependency::is_blocked(&pool, downstream).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn open_pull_requests_block_until_merged() {
+        let (pool, project_id) = setup().await;
+        let upstream = create_task(&pool, project_id, "upstream").await;
+        let downstream = create_task(&pool, project_id, "downstream").await;
+        TaskDependency::create(&pool, downstream, upstream)
+            .await
+            .unwrap();
+
+        let attempt = create_attempt(&pool, upstream).await;
+        let pr = Merge::create_pr(&pool, attempt, "main", 1, "https://example.com/pr/1")
+            .await
+            .unwrap();
+        assert!(TaskDependency::is_blocked(&pool, downstream).await.unwrap());
+
+        Merge::update_status(
+            &pool,
+            pr.id,
+            MergeStatus::Merged,
+            Some("abc123".to_string()),
+        )
+        .await
+        .unwrap();
+        assert!(!TaskDependency::is_blocked(&pool, downstream).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn auto_starts_become_ready_and_then_stale() {
+        let (pool, project_id) = setup().await;
+        let upstream = create_task(&pool, project_id, "upstream").await;
+        let downstream = create_task(&pool, project_id, "downstream").await;
+        TaskDependency::create(&pool, downstream, upstream)
+            .await
+            .unwrap();
+        TaskAutoStart::upsert(
+            &pool,
+            downstream,
+            &ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+            "main",
+        )
+        .await
+        .unwrap();
+
+        assert!(ready_task_ids(&pool).await.is_empty());
+        assert_eq!(TaskAutoStart::delete_stale(&pool).await.unwrap(), 0);
+
+        Task::update_status(&pool, upstream, TaskStatus::Done)
+            .await
+            .unwrap();
+        assert_eq!(ready_task_ids(&pool).await, vec![downstream]);
+
+        // Once the task was started some other way the auto start can never fire
+        create_attempt(&pool, downstream).await;
+        assert!(ready_task_ids(&pool).await.is_empty());
+        assert_eq!(TaskAutoStart::delete_stale(&pool).await.unwrap(), 1);
+        assert!(
+            TaskAutoStart::find_by_task_id(&pool, downstream)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -30,6 +30,7 @@ use services::services::{
     git::{GitService, GitServiceError},
     image::{ImageError, ImageService},
     pr_monitor::PrMonitorService,
+    secrets::SecretStoreError,
     share::{RemoteSync, RemoteSyncHandle, ShareConfig, SharePublisher},
     worktree_manager::WorktreeError,
 };
@@ -70,6 +71,8 @@ pub enum DeploymentError {
     Event(#[from] EventError),
     #[error(transparent)]
     Config(#[from] ConfigError),
+    #[error(transparent)]
+    SecretStore(#[from] SecretStoreError),
     #[error("Remote client not configured")]
     RemoteClientNotConfigured,
     #[error(transparent)]
@@ -142,7 +145,7 @@ pub trait Deployment: Clone + Send + Sync + 'static {
         Ok(())
     }
 
-    async fn spawn_pr_monitor_service(&self) -> tokio::task::JoinHandle<()> {
+    fn pr_monitor(&self) -> PrMonitorService {
         let db = self.db().clone();
         let analytics = self
             .analytics()
@@ -152,7 +155,18 @@ pub trait Deployment: Clone + Send + Sync + 'static {
                 analytics_service: analytics_service.clone(),
             });
         let publisher = self.share_publisher().ok();
-        PrMonitorService::spawn(db, analytics, publisher).await
+        PrMonitorService::new(
+            db,
+            self.config().clone(),
+            self.container().secrets().clone(),
+            analytics,
+            publisher,
+            self.events().msg_store().clone(),
+        )
+    }
+
+    async fn spawn_pr_monitor_service(&self) -> tokio::task::JoinHandle<()> {
+        self.pr_monitor().spawn().await
     }
 
     async fn track_if_analytics_allowed(&self, event_name: &str, properties: Value) {
@@ -10,7 +10,9 @@ use workspace_utils::shell::get_shell_command;
 use crate::{
     actions::Executable,
     approvals::ExecutorApprovalService,
+    env::resolve_env,
     executors::{ExecutorError, SpawnedChild},
+    sandbox::Sandbox,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
@@ -24,6 +26,7 @@ pub enum ScriptContext {
     CleanupScript,
     DevServer,
     GithubCliSetupScript,
+    VerifyScript,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
@@ -41,14 +44,20 @@ impl Executable for ScriptRequest {
         _approvals: Arc<dyn ExecutorApprovalService>,
     ) -> Result<SpawnedChild, ExecutorError> {
         let (shell_cmd, shell_arg) = get_shell_command();
-        let mut command = Command::new(shell_cmd);
+        let shell_args = vec![shell_arg.to_string(), self.script.clone()];
+        let env = resolve_env(None)?;
+        let (program, args) = match Sandbox::current() {
+            Some(sandbox) => sandbox.wrap(&shell_cmd, shell_args, &env).await?,
+            None => (shell_cmd.into(), shell_args),
+        };
+        let mut command = Command::new(program);
         command
             .kill_on_drop(true)
             .stdin(std::process::Stdio::null())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
-            .arg(shell_arg)
-            .arg(&self.script)
+            .args(&args)
+            .envs(env)
             .current_dir(current_dir);
 
         let child = command.group_spawn()?;
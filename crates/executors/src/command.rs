@@ -1,12 +1,13 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tokio::process::Command;
 use ts_rs::TS;
 use workspace_utils::shell::resolve_executable_path;
 
-use crate::executors::ExecutorError;
+use crate::{env::resolve_env, executors::ExecutorError, sandbox::Sandbox};
 
 #[derive(Debug, Error)]
 pub enum CommandBuildError {
@@ -22,19 +23,52 @@ pub enum CommandBuildError {
 pub struct CommandParts {
     program: String,
     args: Vec<String>,
+    env: Option<HashMap<String, String>>,
 }
 
 impl CommandParts {
     pub fn new(program: String, args: Vec<String>) -> Self {
-        Self { program, args }
+        Self {
+            program,
+            args,
+            env: None,
+        }
+    }
+
+    pub fn with_env(mut self, env: Option<HashMap<String, String>>) -> Self {
+        self.env = env;
+        self
     }
 
-    pub async fn into_resolved(self) -> Result<(PathBuf, Vec<String>), ExecutorError> {
-        let CommandParts { program, args } = self;
-        let executable = resolve_executable_path(&program)
-            .await
-            .ok_or(ExecutorError::ExecutableNotFound { program })?;
-        Ok((executable, args))
+    pub async fn into_resolved(self) -> Result<ResolvedCommand, ExecutorError> {
+        let CommandParts { program, args, env } = self;
+        let env = resolve_env(env.as_ref())?;
+        let (program, args) = match Sandbox::current() {
+            Some(sandbox) => sandbox.wrap(&program, args, &env).await?,
+            None => {
+                let executable = resolve_executable_path(&program)
+                    .await
+                    .ok_or(ExecutorError::ExecutableNotFound { program })?;
+                (executable, args)
+            }
+        };
+        Ok(ResolvedCommand { program, args, env })
+    }
+}
+
+/// A command ready to spawn, with the project and profile environment it runs with
+#[derive(Debug, Clone)]
+pub struct ResolvedCommand {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+}
+
+impl ResolvedCommand {
+    pub fn into_command(self) -> Command {
+        let mut command = Command::new(self.program);
+        command.args(self.args).envs(self.env);
+        command
     }
 }
 
@@ -52,6 +86,12 @@ pub struct CmdOverrides {
     )]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub additional_params: Option<Vec<String>>,
+    #[schemars(
+        title = "Environment Variables",
+        description = "Environment variables set for the agent. Use {{secret:NAME}} to insert a value from the secret store"
+    )]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
@@ -60,6 +100,8 @@ pub struct CommandBuilder {
     pub base: String,
     /// Optional parameters to append to the base command
     pub params: Option<Vec<String>>,
+    /// Environment variables set for the command, possibly referring to secrets
+    pub env: Option<HashMap<String, String>>,
 }
 
 impl CommandBuilder {
@@ -67,6 +109,7 @@ impl CommandBuilder {
         Self {
             base: base.into(),
             params: None,
+            env: None,
         }
     }
 
@@ -97,6 +140,18 @@ impl CommandBuilder {
         self
     }
 
+    pub fn envs<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.env
+            .get_or_insert_with(HashMap::new)
+            .extend(vars.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
     pub fn build_initial(&self) -> Result<CommandParts, CommandBuildError> {
         self.build(&[])
     }
@@ -112,7 +167,7 @@ impl CommandBuilder {
         let mut parts = split_command_line(&self.simple_join(additional_args))?;
 
         let program = parts.remove(0);
-        Ok(CommandParts::new(program, parts))
+        Ok(CommandParts::new(program, parts).with_env(self.env.clone()))
     }
 
     fn simple_join(&self, additional_args: &[String]) -> String {
@@ -148,9 +203,14 @@ pub fn apply_overrides(builder: CommandBuilder, overrides: &CmdOverrides) -> Com
     } else {
         builder
     };
-    if let Some(ref extra) = overrides.additional_params {
+    let builder = if let Some(ref extra) = overrides.additional_params {
         builder.extend_params(extra.clone())
     } else {
         builder
+    };
+    if let Some(ref env) = overrides.env {
+        builder.envs(env.clone())
+    } else {
+        builder
     }
 }
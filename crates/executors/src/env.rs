@@ -0,0 +1,165 @@
+//! Environment variables injected into executor child processes.
+//!
+//! Profiles set variables through [`crate::command::CmdOverrides::env`]; projects set
+//! theirs through an [`ExecutionEnv`] installed for the duration of a spawn with
+//! [`ExecutionEnv::scope`], which also carries the secrets values may refer to.
+//! A value refers to a secret with `{{secret:NAME}}`, so secret values never have to be
+//! written to `profiles.json` or the database.
+
+use std::{collections::HashMap, future::Future, sync::LazyLock};
+
+use regex::{Captures, Regex};
+
+use crate::executors::ExecutorError;
+
+static SECRET_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*secret:([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap());
+
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionEnv {
+    /// Project variables, possibly referring to secrets
+    pub vars: HashMap<String, String>,
+    /// Secret values by name
+    pub secrets: HashMap<String, String>,
+}
+
+tokio::task_local! {
+    static CURRENT_ENV: ExecutionEnv;
+}
+
+impl ExecutionEnv {
+    pub fn new(vars: HashMap<String, String>, secrets: HashMap<String, String>) -> Self {
+        Self { vars, secrets }
+    }
+
+    /// Run `f` with `env` available to every process it spawns. `None` runs `f` unchanged.
+    pub async fn scope<F: Future>(env: Option<ExecutionEnv>, f: F) -> F::Output {
+        match env {
+            Some(env) => CURRENT_ENV.scope(env, f).await,
+            None => f.await,
+        }
+    }
+
+    /// The environment active for the current spawn, if any
+    pub fn current() -> Option<ExecutionEnv> {
+        CURRENT_ENV.try_with(Clone::clone).ok()
+    }
+
+    /// Variables to set on a process: the project's, overridden by the profile's, with
+    /// secret references replaced by their values
+    pub fn resolve(
+        &self,
+        profile_env: Option<&HashMap<String, String>>,
+    ) -> Result<Vec<(String, String)>, ExecutorError> {
+        let mut vars: Vec<(&String, &String)> = self
+            .vars
+            .iter()
+            .filter(|(name, _)| !profile_env.is_some_and(|env| env.contains_key(*name)))
+            .chain(profile_env.into_iter().flatten())
+            .collect();
+        vars.sort();
+        vars.into_iter()
+            .map(|(name, value)| Ok((name.clone(), expand_secrets(value, &self.secrets)?)))
+            .collect()
+    }
+}
+
+/// Resolve `profile_env` against the environment of the current spawn
+pub fn resolve_env(
+    profile_env: Option<&HashMap<String, String>>,
+) -> Result<Vec<(String, String)>, ExecutorError> {
+    ExecutionEnv::current()
+        .unwrap_or_default()
+        .resolve(profile_env)
+}
+
+/// Whether `value` is nothing but a single `{{secret:NAME}}` reference
+pub fn is_secret_reference(value: &str) -> bool {
+    SECRET_REFERENCE
+        .find(value.trim())
+        .is_some_and(|m| m.len() == value.trim().len())
+}
+
+/// Replace every `{{secret:NAME}}` in `value` with the secret's value
+pub fn expand_secrets(
+    value: &str,
+    secrets: &HashMap<String, String>,
+) -> Result<String, ExecutorError> {
+    let mut missing = None;
+    let expanded =
+        SECRET_REFERENCE.replace_all(value, |caps: &Captures| match secrets.get(&caps[1]) {
+            Some(secret) => secret.clone(),
+            None => {
+                missing.get_or_insert_with(|| caps[1].to_string());
+                String::new()
+            }
+        });
+    match missing {
+        Some(name) => Err(ExecutorError::MissingSecret(name)),
+        None => Ok(expanded.into_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn secret_references_are_expanded() {
+        let secrets = map(&[("API_TOKEN", "s3cr3t")]);
+        assert_eq!(
+            expand_secrets("Bearer {{secret:API_TOKEN}}", &secrets).unwrap(),
+            "Bearer s3cr3t"
+        );
+        assert_eq!(
+            expand_secrets("{{ secret:API_TOKEN }}", &secrets).unwrap(),
+            "s3cr3t"
+        );
+        assert_eq!(
+            expand_secrets("plain value", &secrets).unwrap(),
+            "plain value"
+        );
+        assert!(matches!(
+            expand_secrets("{{secret:OTHER}}", &secrets),
+            Err(ExecutorError::MissingSecret(name)) if name == "OTHER"
+        ));
+
+        assert!(is_secret_reference(" {{secret:API_TOKEN}} "));
+        assert!(!is_secret_reference("Bearer {{secret:API_TOKEN}}"));
+        assert!(!is_secret_reference("glpat-plaintext"));
+    }
+
+    #[test]
+    fn profile_variables_override_project_variables() {
+        let env = ExecutionEnv::new(
+            map(&[("HTTPS_PROXY", "http://proxy:3128"), ("MODEL", "small")]),
+            map(&[("KEY", "s3cr3t")]),
+        );
+        let profile = map(&[("MODEL", "large"), ("API_KEY", "{{secret:KEY}}")]);
+        assert_eq!(
+            env.resolve(Some(&profile)).unwrap(),
+            [
+                ("API_KEY".to_string(), "s3cr3t".to_string()),
+                ("HTTPS_PROXY".to_string(), "http://proxy:3128".to_string()),
+                ("MODEL".to_string(), "large".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn scope_provides_project_variables() {
+        assert!(resolve_env(None).unwrap().is_empty());
+        let env = ExecutionEnv::new(map(&[("A", "1")]), HashMap::new());
+        let vars = ExecutionEnv::scope(Some(env), async { resolve_env(None) })
+            .await
+            .unwrap();
+        assert_eq!(vars, [("A".to_string(), "1".to_string())]);
+    }
+}
@@ -1,19 +1,31 @@
+use std::sync::Arc;
+
 use agent_client_protocol as acp;
 use async_trait::async_trait;
-use tokio::sync::mpsc;
+use tokio::{runtime::Handle, sync::mpsc};
 use tracing::{debug, warn};
+use workspace_utils::approvals::ApprovalStatus;
 
-use crate::executors::acp::AcpEvent;
+use crate::{approvals::ExecutorApprovalService, executors::acp::AcpEvent};
 
 /// ACP client that handles agent-client protocol communication
 pub struct AcpClient {
     event_tx: mpsc::UnboundedSender<AcpEvent>,
+    /// Service that decides permission requests, with the runtime to call it on.
+    /// Permission requests are auto-approved without one.
+    approvals: Option<(Arc<dyn ExecutorApprovalService>, Handle)>,
 }
 
 impl AcpClient {
     /// Create a new ACP client
-    pub fn new(event_tx: mpsc::UnboundedSender<AcpEvent>) -> Self {
-        Self { event_tx }
+    pub fn new(
+        event_tx: mpsc::UnboundedSender<AcpEvent>,
+        approvals: Option<(Arc<dyn ExecutorApprovalService>, Handle)>,
+    ) -> Self {
+        Self {
+            event_tx,
+            approvals,
+        }
     }
 
     pub fn record_user_prompt_event(&self, prompt: &str) {
@@ -26,30 +38,20 @@ impl AcpClient {
             warn!("Failed to send ACP event: {}", e);
         }
     }
-}
 
-#[async_trait(?Send)]
-impl acp::Client for AcpClient {
-    async fn request_permission(
-        &self,
-        args: acp::RequestPermissionRequest,
-    ) -> Result<acp::RequestPermissionResponse, acp::Error> {
-        // Forward the request as an event
-        self.send_event(AcpEvent::RequestPermission(args.clone()));
-
-        // Auto-approve with best available option
-        let chosen_option = args
-            .options
+    /// Approve with the best available option
+    fn auto_approve(options: &[acp::PermissionOption]) -> acp::RequestPermissionOutcome {
+        let chosen_option = options
             .iter()
             .find(|o| matches!(o.kind, acp::PermissionOptionKind::AllowAlways))
             .or_else(|| {
-                args.options
+                options
                     .iter()
                     .find(|o| matches!(o.kind, acp::PermissionOptionKind::AllowOnce))
             })
-            .or_else(|| args.options.first());
+            .or_else(|| options.first());
 
-        let outcome = if let Some(opt) = chosen_option {
+        if let Some(opt) = chosen_option {
             debug!("Auto-approving permission with option: {}", opt.id);
             acp::RequestPermissionOutcome::Selected {
                 option_id: opt.id.clone(),
@@ -57,6 +59,94 @@ impl acp::Client for AcpClient {
         } else {
             warn!("No permission options available, cancelling");
             acp::RequestPermissionOutcome::Cancelled
+        }
+    }
+
+    /// Select the agent's allow or reject option, preferring the one-off kind. Cancels when the
+    /// agent offers neither.
+    fn select_option(
+        options: &[acp::PermissionOption],
+        approved: bool,
+    ) -> acp::RequestPermissionOutcome {
+        let is_once = |o: &&acp::PermissionOption| {
+            if approved {
+                matches!(o.kind, acp::PermissionOptionKind::AllowOnce)
+            } else {
+                matches!(o.kind, acp::PermissionOptionKind::RejectOnce)
+            }
+        };
+        let is_always = |o: &&acp::PermissionOption| {
+            if approved {
+                matches!(o.kind, acp::PermissionOptionKind::AllowAlways)
+            } else {
+                matches!(o.kind, acp::PermissionOptionKind::RejectAlways)
+            }
+        };
+        match options
+            .iter()
+            .find(is_once)
+            .or_else(|| options.iter().find(is_always))
+        {
+            Some(opt) => acp::RequestPermissionOutcome::Selected {
+                option_id: opt.id.clone(),
+            },
+            None => {
+                warn!("No matching permission option available, cancelling");
+                acp::RequestPermissionOutcome::Cancelled
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl acp::Client for AcpClient {
+    async fn request_permission(
+        &self,
+        args: acp::RequestPermissionRequest,
+    ) -> Result<acp::RequestPermissionResponse, acp::Error> {
+        // Forward the request as an event
+        self.send_event(AcpEvent::RequestPermission(args.clone()));
+
+        let Some((approvals, runtime)) = &self.approvals else {
+            return Ok(acp::RequestPermissionResponse {
+                outcome: Self::auto_approve(&args.options),
+                meta: None,
+            });
+        };
+
+        let tool_call_id = args.tool_call.id.0.to_string();
+        let tool_name = args
+            .tool_call
+            .fields
+            .title
+            .clone()
+            .unwrap_or_else(|| "tool".to_string());
+        let tool_input = serde_json::to_value(&args.tool_call).unwrap_or_default();
+        let approvals = approvals.clone();
+        // The ACP connection runs on its own single-threaded runtime, the approval service
+        // belongs to the server's runtime.
+        let status = runtime
+            .spawn(async move {
+                approvals
+                    .request_tool_approval(&tool_name, tool_input, &tool_call_id)
+                    .await
+            })
+            .await;
+
+        let outcome = match status {
+            Ok(Ok(ApprovalStatus::Approved)) => Self::select_option(&args.options, true),
+            Ok(Ok(ApprovalStatus::Denied { .. } | ApprovalStatus::TimedOut)) => {
+                Self::select_option(&args.options, false)
+            }
+            Ok(Ok(ApprovalStatus::Pending)) => acp::RequestPermissionOutcome::Cancelled,
+            Ok(Err(e)) => {
+                warn!("Approval request failed, cancelling: {}", e);
+                acp::RequestPermissionOutcome::Cancelled
+            }
+            Err(e) => {
+                warn!("Approval task failed, cancelling: {}", e);
+                acp::RequestPermissionOutcome::Cancelled
+            }
         };
 
         Ok(acp::RequestPermissionResponse {
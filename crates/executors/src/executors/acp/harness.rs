@@ -8,7 +8,7 @@ use agent_client_protocol as proto;
 use agent_client_protocol::Agent as _;
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use futures::StreamExt;
-use tokio::{io::AsyncWriteExt, process::Command, sync::mpsc};
+use tokio::{io::AsyncWriteExt, sync::mpsc};
 use tokio_util::{
     compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt},
     io::ReaderStream,
@@ -18,6 +18,7 @@ use workspace_utils::stream_lines::LinesStreamExt;
 
 use super::{AcpClient, SessionManager};
 use crate::{
+    approvals::ExecutorApprovalService,
     command::CommandParts,
     executors::{ExecutorError, SpawnedChild, acp::AcpEvent},
 };
@@ -25,6 +26,7 @@ use crate::{
 /// Reusable harness for ACP-based conns (Gemini, Qwen, etc.)
 pub struct AcpAgentHarness {
     session_namespace: String,
+    approvals: Option<Arc<dyn ExecutorApprovalService>>,
 }
 
 impl Default for AcpAgentHarness {
@@ -39,6 +41,7 @@ impl AcpAgentHarness {
     pub fn new() -> Self {
         Self {
             session_namespace: "gemini_sessions".to_string(),
+            approvals: None,
         }
     }
 
@@ -46,24 +49,30 @@ impl AcpAgentHarness {
     pub fn with_session_namespace(namespace: impl Into<String>) -> Self {
         Self {
             session_namespace: namespace.into(),
+            approvals: None,
         }
     }
 
+    /// Route the agent's permission requests through the approval service instead of
+    /// approving them automatically
+    pub fn with_approvals(mut self, approvals: Arc<dyn ExecutorApprovalService>) -> Self {
+        self.approvals = Some(approvals);
+        self
+    }
+
     pub async fn spawn_with_command(
         &self,
         current_dir: &Path,
         prompt: String,
         command_parts: CommandParts,
     ) -> Result<SpawnedChild, ExecutorError> {
-        let (program_path, args) = command_parts.into_resolved().await?;
-        let mut command = Command::new(program_path);
+        let mut command = command_parts.into_resolved().await?.into_command();
         command
             .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
-            .args(&args)
             .env("NODE_NO_WARNINGS", "1");
 
         let mut child = command.group_spawn()?;
@@ -76,6 +85,7 @@ impl AcpAgentHarness {
             prompt,
             Some(exit_tx),
             self.session_namespace.clone(),
+            self.approvals.clone(),
         )
         .await?;
 
@@ -92,15 +102,13 @@ impl AcpAgentHarness {
         session_id: &str,
         command_parts: CommandParts,
     ) -> Result<SpawnedChild, ExecutorError> {
-        let (program_path, args) = command_parts.into_resolved().await?;
-        let mut command = Command::new(program_path);
+        let mut command = command_parts.into_resolved().await?.into_command();
         command
             .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(current_dir)
-            .args(&args)
             .env("NODE_NO_WARNINGS", "1");
 
         let mut child = command.group_spawn()?;
@@ -113,6 +121,7 @@ impl AcpAgentHarness {
             prompt,
             Some(exit_tx),
             self.session_namespace.clone(),
+            self.approvals.clone(),
         )
         .await?;
 
@@ -129,6 +138,7 @@ impl AcpAgentHarness {
         prompt: String,
         exit_signal: Option<tokio::sync::oneshot::Sender<()>>,
         session_namespace: String,
+        approvals: Option<Arc<dyn ExecutorApprovalService>>,
     ) -> Result<(), ExecutorError> {
         // Take child's stdio for ACP wiring
         let orig_stdout = child.inner().stdout.take().ok_or_else(|| {
@@ -216,6 +226,7 @@ impl AcpAgentHarness {
         });
 
         let mut exit_signal_tx = exit_signal;
+        let approvals = approvals.map(|approvals| (approvals, tokio::runtime::Handle::current()));
 
         // Run ACP client in a LocalSet
         tokio::task::spawn_blocking(move || {
@@ -244,7 +255,7 @@ impl AcpAgentHarness {
                         let session_manager = std::sync::Arc::new(session_manager);
 
                         // Create ACP client
-                        let client = AcpClient::new(event_tx.clone());
+                        let client = AcpClient::new(event_tx.clone(), approvals);
 
                         client.record_user_prompt_event(&prompt);
 
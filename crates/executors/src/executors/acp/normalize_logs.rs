@@ -14,13 +14,21 @@ use workspace_utils::msg_store::MsgStore;
 
 pub use super::AcpAgentHarness;
 use super::AcpEvent;
-use crate::logs::{
-    ActionType, FileChange, NormalizedEntry, NormalizedEntryError, NormalizedEntryType, ToolResult,
-    ToolResultValueType, ToolStatus as LogToolStatus,
-    stderr_processor::normalize_stderr_logs,
-    utils::{ConversationPatch, EntryIndexProvider},
+use crate::{
+    approvals::ToolCallMetadata,
+    logs::{
+        ActionType, FileChange, NormalizedEntry, NormalizedEntryError, NormalizedEntryType,
+        ToolResult, ToolResultValueType, ToolStatus as LogToolStatus,
+        stderr_processor::normalize_stderr_logs,
+        utils::{ConversationPatch, EntryIndexProvider},
+    },
 };
 
+/// Normalize the event log of an ACP agent such as Gemini or Qwen.
+///
+/// No token usage is pushed, unlike for Claude Code and Codex: ACP 0.4 carries no usage
+/// in its session updates or prompt responses, so these agents have no usage rows and
+/// token budgets do not apply to them.
 pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
     // stderr normalization
     let entry_index = EntryIndexProvider::start_from(&msg_store);
@@ -232,7 +240,11 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                     status: convert_tool_status(&tool_data.status),
                 },
                 content: get_tool_content(tool_data),
-                metadata: None,
+                // Lets approval requests find the entry of their tool call
+                metadata: serde_json::to_value(ToolCallMetadata {
+                    tool_call_id: tool_data.id.0.to_string(),
+                })
+                .ok(),
             };
             let patch = if is_new {
                 ConversationPatch::add_normalized_entry(tool_data.index, entry)
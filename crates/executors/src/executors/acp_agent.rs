@@ -0,0 +1,233 @@
+use std::{path::Path, sync::Arc};
+
+use async_trait::async_trait;
+use derivative::Derivative;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use workspace_utils::{msg_store::MsgStore, shell::resolve_executable_path};
+
+use crate::{
+    approvals::ExecutorApprovalService,
+    command::{CmdOverrides, CommandBuilder, apply_overrides},
+    executors::{
+        AppendPrompt, ExecutorError, SpawnedChild, StandardCodingAgentExecutor,
+        acp::AcpAgentHarness,
+    },
+};
+
+/// Any agent that speaks the Agent Client Protocol over stdio
+#[derive(Derivative, Clone, Serialize, Deserialize, TS, JsonSchema)]
+#[derivative(Debug, PartialEq)]
+pub struct AcpAgent {
+    #[serde(default)]
+    pub append_prompt: AppendPrompt,
+    /// Command that starts the agent in ACP mode, e.g. "my-agent --acp"
+    pub command: String,
+    /// Ask the user to decide the agent's permission requests instead of approving them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approvals: Option<bool>,
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+
+    #[serde(skip)]
+    #[ts(skip)]
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    approvals_service: Option<Arc<dyn ExecutorApprovalService>>,
+}
+
+impl AcpAgent {
+    fn build_command_builder(&self) -> CommandBuilder {
+        apply_overrides(CommandBuilder::new(self.command.clone()), &self.cmd)
+    }
+
+    fn harness(&self) -> AcpAgentHarness {
+        let harness = AcpAgentHarness::with_session_namespace("acp_sessions");
+        match &self.approvals_service {
+            Some(approvals) if self.approvals.unwrap_or(false) => {
+                harness.with_approvals(approvals.clone())
+            }
+            _ => harness,
+        }
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for AcpAgent {
+    fn use_approvals(&mut self, approvals: Arc<dyn ExecutorApprovalService>) {
+        self.approvals_service = Some(approvals);
+    }
+
+    async fn spawn(&self, current_dir: &Path, prompt: &str) -> Result<SpawnedChild, ExecutorError> {
+        let command = self.build_command_builder().build_initial()?;
+        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        self.harness()
+            .spawn_with_command(current_dir, combined_prompt, command)
+            .await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let command = self.build_command_builder().build_follow_up(&[])?;
+        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        self.harness()
+            .spawn_follow_up_with_command(current_dir, combined_prompt, session_id, command)
+            .await
+    }
+
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, worktree_path: &Path) {
+        super::acp::normalize_logs(msg_store, worktree_path);
+    }
+
+    fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+
+    async fn check_availability(&self) -> bool {
+        let base = self
+            .cmd
+            .base_command_override
+            .as_deref()
+            .unwrap_or(&self.command);
+        match shlex::split(base).and_then(|parts| parts.into_iter().next()) {
+            Some(program) => resolve_executable_path(&program).await.is_some(),
+            None => false,
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{os::unix::fs::PermissionsExt, path::PathBuf, sync::Mutex, time::Duration};
+
+    use serde_json::Value;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use workspace_utils::approvals::ApprovalStatus;
+
+    use super::*;
+    use crate::{approvals::ExecutorApprovalError, executors::acp::AcpEvent};
+
+    /// Answers the handshake, asks permission to edit a file, then reports whether it was
+    /// allowed before ending the turn
+    const FAKE_AGENT: &str = r#"#!/bin/sh
+respond() {
+  printf '{"jsonrpc":"2.0","id":%s,"result":%s}\n' "$1" "$2"
+}
+message() {
+  printf '{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"fake-session","update":{"sessionUpdate":"agent_message_chunk","content":{"type":"text","text":"%s"}}}}\n' "$1"
+}
+while IFS= read -r line; do
+  id=$(printf '%s\n' "$line" | sed -n 's/.*"id":\([0-9][0-9]*\).*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      respond "$id" '{"protocolVersion":1,"agentCapabilities":{},"authMethods":[]}' ;;
+    *'"method":"session/new"'*)
+      respond "$id" '{"sessionId":"fake-session"}' ;;
+    *'"method":"session/prompt"'*)
+      printf '{"jsonrpc":"2.0","id":1000,"method":"session/request_permission","params":{"sessionId":"fake-session","toolCall":{"toolCallId":"call-1","title":"Edit notes.txt","kind":"edit","status":"pending"},"options":[{"optionId":"allow-once","name":"Allow","kind":"allow_once"},{"optionId":"reject-once","name":"Reject","kind":"reject_once"}]}}\n'
+      IFS= read -r reply
+      case "$reply" in
+        *'"optionId":"allow-once"'*) message "allowed" ;;
+        *) message "rejected" ;;
+      esac
+      respond "$id" '{"stopReason":"end_turn"}' ;;
+  esac
+done
+"#;
+
+    struct FakeApprovals {
+        status: ApprovalStatus,
+        requests: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl ExecutorApprovalService for FakeApprovals {
+        async fn request_tool_approval(
+            &self,
+            tool_name: &str,
+            _tool_input: Value,
+            tool_call_id: &str,
+        ) -> Result<ApprovalStatus, ExecutorApprovalError> {
+            self.requests
+                .lock()
+                .unwrap()
+                .push((tool_name.to_string(), tool_call_id.to_string()));
+            Ok(self.status.clone())
+        }
+    }
+
+    fn write_fake_agent() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fake-acp-agent-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("fake-agent.sh");
+        std::fs::write(&script, FAKE_AGENT).unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        script
+    }
+
+    /// Run one turn of the fake agent and return its messages
+    async fn run_turn(status: ApprovalStatus) -> (Vec<String>, Vec<(String, String)>) {
+        let script = write_fake_agent();
+        let approvals = Arc::new(FakeApprovals {
+            status,
+            requests: Mutex::new(Vec::new()),
+        });
+        let mut agent: AcpAgent = serde_json::from_value(serde_json::json!({
+            "command": script.to_string_lossy(),
+            "approvals": true,
+        }))
+        .unwrap();
+        agent.use_approvals(approvals.clone());
+
+        let mut spawned = agent
+            .spawn(script.parent().unwrap(), "Edit the notes")
+            .await
+            .unwrap();
+        let stdout = spawned.child.inner().stdout.take().unwrap();
+        let mut lines = BufReader::new(stdout).lines();
+
+        let mut messages = Vec::new();
+        tokio::time::timeout(Duration::from_secs(30), async {
+            while let Ok(Some(line)) = lines.next_line().await {
+                match line.parse::<AcpEvent>() {
+                    Ok(AcpEvent::Message(agent_client_protocol::ContentBlock::Text(text))) => {
+                        messages.push(text.text)
+                    }
+                    Ok(AcpEvent::Done(_)) => break,
+                    _ => {}
+                }
+            }
+        })
+        .await
+        .expect("fake agent did not finish its turn");
+        let _ = spawned.child.kill().await;
+        let _ = std::fs::remove_dir_all(script.parent().unwrap());
+
+        let requests = approvals.requests.lock().unwrap().clone();
+        (messages, requests)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn approved_permission_request_selects_allow_option() {
+        let (messages, requests) = run_turn(ApprovalStatus::Approved).await;
+        assert_eq!(
+            requests,
+            [("Edit notes.txt".to_string(), "call-1".to_string())]
+        );
+        assert_eq!(messages, ["allowed"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn denied_permission_request_selects_reject_option() {
+        let (messages, requests) = run_turn(ApprovalStatus::Denied {
+            reason: Some("not now".to_string()),
+        })
+        .await;
+        assert_eq!(requests.len(), 1);
+        assert_eq!(messages, ["rejected"]);
+    }
+}
@@ -10,12 +10,11 @@ use command_group::AsyncCommandGroup;
 use futures::StreamExt;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tokio::process::Command;
 use ts_rs::TS;
 use workspace_utils::{
     approvals::ApprovalStatus,
     diff::{concatenate_diff_hunks, create_unified_diff, create_unified_diff_hunk},
-    log_msg::LogMsg,
+    log_msg::{LogMsg, TokenUsage},
     msg_store::MsgStore,
     path::make_path_relative,
 };
@@ -205,17 +204,15 @@ impl ClaudeCode {
         prompt: &str,
         command_parts: CommandParts,
     ) -> Result<SpawnedChild, ExecutorError> {
-        let (program_path, args) = command_parts.into_resolved().await?;
         let combined_prompt = self.append_prompt.combine_prompt(prompt);
 
-        let mut command = Command::new(program_path);
+        let mut command = command_parts.into_resolved().await?.into_command();
         command
             .kill_on_drop(true)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .current_dir(current_dir)
-            .args(&args);
+            .current_dir(current_dir);
 
         let mut child = command.group_spawn()?;
         let child_stdout = child.inner().stdout.take().ok_or_else(|| {
@@ -320,7 +317,10 @@ impl ClaudeLogProcessor {
             while let Some(Ok(msg)) = stream.next().await {
                 let chunk = match msg {
                     LogMsg::Stdout(x) => x,
-                    LogMsg::JsonPatch(_) | LogMsg::SessionId(_) | LogMsg::Stderr(_) => continue,
+                    LogMsg::JsonPatch(_)
+                    | LogMsg::SessionId(_)
+                    | LogMsg::Usage(_)
+                    | LogMsg::Stderr(_) => continue,
                     LogMsg::Finished => break,
                 };
 
@@ -364,6 +364,10 @@ impl ClaudeLogProcessor {
                             for patch in patches {
                                 msg_store.push_patch(patch);
                             }
+
+                            if let Some(usage) = processor.extract_usage(&claude_json) {
+                                msg_store.push_usage(usage);
+                            }
                         }
                         Err(_) => {
                             // Handle non-JSON output as raw system message
@@ -692,6 +696,31 @@ impl ClaudeLogProcessor {
         }
     }
 
+    /// Token usage for a completed assistant message. Claude reports final counts
+    /// for each message in its `message_delta` stream event.
+    fn extract_usage(&self, claude_json: &ClaudeJson) -> Option<TokenUsage> {
+        let ClaudeJson::StreamEvent {
+            event:
+                ClaudeStreamEvent::MessageDelta {
+                    usage: Some(usage), ..
+                },
+            ..
+        } = claude_json
+        else {
+            return None;
+        };
+        if usage.input_tokens.is_none() && usage.output_tokens.is_none() {
+            return None;
+        }
+        Some(TokenUsage {
+            model: self.model_name.clone(),
+            input_tokens: usage.input_tokens.unwrap_or(0),
+            output_tokens: usage.output_tokens.unwrap_or(0),
+            cache_read_tokens: usage.cache_read_input_tokens.unwrap_or(0),
+            cache_write_tokens: usage.cache_creation_input_tokens.unwrap_or(0),
+        })
+    }
+
     /// Convert Claude JSON to normalized patches
     fn normalize_entries(
         &mut self,
@@ -1838,6 +1867,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_message_delta_usage_extraction() {
+        let mut processor = ClaudeLogProcessor::new();
+        let start_json = r#"{"type":"stream_event","event":{"type":"message_start","message":{"id":"msg_1","type":"message","role":"assistant","model":"claude-sonnet-4-20250514","content":[]}}}"#;
+        let parsed: ClaudeJson = serde_json::from_str(start_json).unwrap();
+        normalize_helper(&mut processor, &parsed, "");
+        assert_eq!(processor.extract_usage(&parsed), None);
+
+        let delta_json = r#"{"type":"stream_event","event":{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"input_tokens":12,"output_tokens":345,"cache_creation_input_tokens":100,"cache_read_input_tokens":2000}}}"#;
+        let parsed: ClaudeJson = serde_json::from_str(delta_json).unwrap();
+        assert_eq!(
+            processor.extract_usage(&parsed),
+            Some(TokenUsage {
+                model: Some("claude-sonnet-4-20250514".to_string()),
+                input_tokens: 12,
+                output_tokens: 345,
+                cache_read_tokens: 2000,
+                cache_write_tokens: 100,
+            })
+        );
+    }
+
     #[test]
     fn test_assistant_message_parsing() {
         let assistant_json = r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Hello world"}]},"session_id":"abc123"}"#;
@@ -29,6 +29,7 @@ use serde_json::Value;
 use workspace_utils::{
     approvals::ApprovalStatus,
     diff::{concatenate_diff_hunks, extract_unified_diff_hunks},
+    log_msg::TokenUsage,
     msg_store::MsgStore,
     path::make_path_relative,
 };
@@ -216,6 +217,7 @@ struct LogState {
     patches: HashMap<String, PatchState>,
     web_searches: HashMap<String, WebSearchState>,
     token_usage_info: Option<TokenUsageInfo>,
+    model: Option<String>,
 }
 
 enum StreamingTextKind {
@@ -234,9 +236,29 @@ impl LogState {
             patches: HashMap::new(),
             web_searches: HashMap::new(),
             token_usage_info: None,
+            model: None,
         }
     }
 
+    /// Usage of the turn a token count reports, or None when the count repeats the
+    /// previous one. Token counts are re-sent alongside rate limit updates; only a change
+    /// in the running total marks a new turn.
+    fn record_token_count(&mut self, info: TokenUsageInfo) -> Option<TokenUsage> {
+        let is_new_turn = self.token_usage_info.as_ref().is_none_or(|prev| {
+            prev.total_token_usage.total_tokens != info.total_token_usage.total_tokens
+        });
+        let last = &info.last_token_usage;
+        let usage = is_new_turn.then(|| TokenUsage {
+            model: self.model.clone(),
+            input_tokens: last.input_tokens.saturating_sub(last.cached_input_tokens),
+            output_tokens: last.output_tokens,
+            cache_read_tokens: last.cached_input_tokens,
+            cache_write_tokens: 0,
+        });
+        self.token_usage_info = Some(info);
+        usage
+    }
+
     fn streaming_text_update(
         &mut self,
         content: String,
@@ -415,7 +437,9 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
             }
 
             if let Ok(response) = serde_json::from_str::<JSONRPCResponse>(&line) {
-                handle_jsonrpc_response(response, &msg_store, &entry_index);
+                if let Some(model) = handle_jsonrpc_response(response, &msg_store, &entry_index) {
+                    state.model = Some(model);
+                }
                 continue;
             }
 
@@ -424,6 +448,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                     server_notification
                 {
                     msg_store.push_session_id(session_configured.session_id.to_string());
+                    state.model = Some(session_configured.model.clone());
                     handle_model_params(
                         session_configured.model,
                         session_configured.reasoning_effort,
@@ -462,6 +487,7 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
             match event {
                 EventMsg::SessionConfigured(payload) => {
                     msg_store.push_session_id(payload.session_id.to_string());
+                    state.model = Some(payload.model.clone());
                     handle_model_params(
                         payload.model,
                         payload.reasoning_effort,
@@ -964,8 +990,10 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
                     );
                 }
                 EventMsg::TokenCount(payload) => {
-                    if let Some(info) = payload.info {
-                        state.token_usage_info = Some(info);
+                    if let Some(info) = payload.info
+                        && let Some(usage) = state.record_token_count(info)
+                    {
+                        msg_store.push_usage(usage);
                     }
                 }
                 EventMsg::AgentReasoningRawContent(..)
@@ -987,14 +1015,15 @@ pub fn normalize_logs(msg_store: Arc<MsgStore>, worktree_path: &Path) {
     });
 }
 
+/// Handles the `newConversation` response, returning the model the session runs with
 fn handle_jsonrpc_response(
     response: JSONRPCResponse,
     msg_store: &Arc<MsgStore>,
     entry_index: &EntryIndexProvider,
-) {
+) -> Option<String> {
     let Ok(response) = serde_json::from_value::<NewConversationResponse>(response.result.clone())
     else {
-        return;
+        return None;
     };
 
     match SessionHandler::extract_session_id_from_rollout_path(response.rollout_path) {
@@ -1003,11 +1032,12 @@ fn handle_jsonrpc_response(
     }
 
     handle_model_params(
-        response.model,
+        response.model.clone(),
         response.reasoning_effort,
         msg_store,
         entry_index,
     );
+    Some(response.model)
 }
 
 fn handle_model_params(
@@ -1164,3 +1194,57 @@ impl ToNormalizedEntryOpt for Approval {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_count(total_tokens: u64, input: u64, cached: u64, output: u64) -> TokenUsageInfo {
+        serde_json::from_value(serde_json::json!({
+            "total_token_usage": {
+                "input_tokens": total_tokens,
+                "cached_input_tokens": 0,
+                "output_tokens": 0,
+                "reasoning_output_tokens": 0,
+                "total_tokens": total_tokens,
+            },
+            "last_token_usage": {
+                "input_tokens": input,
+                "cached_input_tokens": cached,
+                "output_tokens": output,
+                "reasoning_output_tokens": 0,
+                "total_tokens": input + output,
+            },
+            "model_context_window": null,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn token_count_usage_is_recorded_once_per_turn() {
+        let mut state = LogState::new(EntryIndexProvider::test_new());
+        state.model = Some("gpt-5-codex".to_string());
+
+        assert_eq!(
+            state.record_token_count(token_count(1500, 1200, 1000, 300)),
+            Some(TokenUsage {
+                model: Some("gpt-5-codex".to_string()),
+                input_tokens: 200,
+                output_tokens: 300,
+                cache_read_tokens: 1000,
+                cache_write_tokens: 0,
+            })
+        );
+        // Re-sent with a rate limit update: same running total, same turn
+        assert_eq!(
+            state.record_token_count(token_count(1500, 1200, 1000, 300)),
+            None
+        );
+        assert_eq!(
+            state
+                .record_token_count(token_count(1900, 350, 0, 50))
+                .map(|usage| (usage.input_tokens, usage.output_tokens)),
+            Some((350, 50))
+        );
+    }
+}
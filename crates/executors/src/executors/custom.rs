@@ -0,0 +1,631 @@
+use std::{path::Path, process::Stdio, sync::Arc};
+
+use async_trait::async_trait;
+use command_group::AsyncCommandGroup;
+use futures::StreamExt;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use ts_rs::TS;
+use workspace_utils::{msg_store::MsgStore, shell::resolve_executable_path};
+
+use crate::{
+    command::{CmdOverrides, CommandBuildError, CommandBuilder, apply_overrides},
+    executors::{AppendPrompt, ExecutorError, SpawnedChild, StandardCodingAgentExecutor},
+    logs::{
+        ActionType, NormalizedEntry, NormalizedEntryError, NormalizedEntryType, ToolStatus,
+        plain_text_processor::PlainTextLogProcessor,
+        stderr_processor::normalize_stderr_logs,
+        utils::{ConversationPatch, EntryIndexProvider},
+    },
+};
+
+/// Executor for agents that have no dedicated integration. The adapter spec describes how to
+/// invoke the agent and how to turn its output into conversation entries.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct Custom {
+    #[serde(default)]
+    pub append_prompt: AppendPrompt,
+    pub adapter: AdapterSpec,
+    #[serde(flatten)]
+    pub cmd: CmdOverrides,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct AdapterSpec {
+    /// Base command, e.g. "my-agent --headless"
+    pub command: String,
+    /// Parameters of the initial run. `{prompt}` is replaced with the prompt; without it the
+    /// prompt is written to stdin.
+    #[serde(default)]
+    pub params: Vec<String>,
+    /// Parameters of follow-up runs, used in place of `params`. `{session_id}` is replaced with
+    /// the session to resume. Follow-ups are not supported when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow_up_params: Option<Vec<String>>,
+    /// Where the session id appears in stdout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<SessionIdSpec>,
+    #[serde(default)]
+    pub output: OutputSpec,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct SessionIdSpec {
+    pub matches: LineMatcher,
+    /// Template of the session id, e.g. "{1}" or "{/session_id}"
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema, Default)]
+pub struct OutputSpec {
+    #[serde(default)]
+    pub format: OutputFormat,
+    /// Rules tried in order on each stdout line. Lines no rule matches are shown as assistant
+    /// messages, except JSON lines in `json_lines` format, which are dropped.
+    #[serde(default)]
+    pub rules: Vec<OutputRule>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    #[default]
+    PlainText,
+    JsonLines,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+pub struct OutputRule {
+    pub matches: LineMatcher,
+    pub entry: EntryTemplate,
+}
+
+/// Matches a stdout line. Templates of a regex match can use `{1}` or `{name}` for its
+/// groups, those of a JSON match `{/json/pointer}`. `{line}` is the whole line.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LineMatcher {
+    Regex {
+        pattern: String,
+    },
+    /// A JSON line that has a value at `pointer`, equal to `equals` when given
+    Json {
+        pointer: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        equals: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EntryTemplate {
+    AssistantMessage {
+        content: String,
+    },
+    Thinking {
+        content: String,
+    },
+    SystemMessage {
+        content: String,
+    },
+    ErrorMessage {
+        content: String,
+    },
+    ToolUse {
+        tool_name: String,
+        action_type: ActionTemplate,
+        content: String,
+    },
+    /// Drop the line
+    Ignore,
+}
+
+/// Action of a tool use, shaped like `ActionType` with the fields that can be templated
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ActionTemplate {
+    FileRead { path: String },
+    FileEdit { path: String },
+    CommandRun { command: String },
+    Search { query: String },
+    WebFetch { url: String },
+    Tool,
+    Other { description: String },
+}
+
+impl Custom {
+    fn build_command_builder(
+        &self,
+        params: &[String],
+        prompt: &str,
+        session_id: Option<&str>,
+    ) -> Result<CommandBuilder, CommandBuildError> {
+        let prompt = shlex::try_quote(prompt)?;
+        let session_id = session_id.map(shlex::try_quote).transpose()?;
+        let params = params.iter().map(|param| {
+            let param = match &session_id {
+                Some(session_id) => param.replace("{session_id}", session_id),
+                None => param.clone(),
+            };
+            param.replace("{prompt}", &prompt)
+        });
+        let builder = CommandBuilder::new(self.adapter.command.clone()).params(params);
+        Ok(apply_overrides(builder, &self.cmd))
+    }
+
+    async fn spawn_with_params(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        params: &[String],
+        session_id: Option<&str>,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        // Reject a broken spec before anything runs
+        AdapterRuntime::compile(&self.adapter)?;
+
+        let combined_prompt = self.append_prompt.combine_prompt(prompt);
+        let prompt_in_params = params.iter().any(|param| param.contains("{prompt}"));
+        let command_parts = self
+            .build_command_builder(params, &combined_prompt, session_id)?
+            .build_initial()?;
+        let mut command = command_parts.into_resolved().await?.into_command();
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .current_dir(current_dir);
+
+        let mut child = command.group_spawn()?;
+
+        if let Some(mut stdin) = child.inner().stdin.take() {
+            if !prompt_in_params {
+                stdin.write_all(combined_prompt.as_bytes()).await?;
+            }
+            stdin.shutdown().await?;
+        }
+
+        Ok(child.into())
+    }
+}
+
+#[async_trait]
+impl StandardCodingAgentExecutor for Custom {
+    async fn spawn(&self, current_dir: &Path, prompt: &str) -> Result<SpawnedChild, ExecutorError> {
+        self.spawn_with_params(current_dir, prompt, &self.adapter.params, None)
+            .await
+    }
+
+    async fn spawn_follow_up(
+        &self,
+        current_dir: &Path,
+        prompt: &str,
+        session_id: &str,
+    ) -> Result<SpawnedChild, ExecutorError> {
+        let Some(follow_up_params) = &self.adapter.follow_up_params else {
+            return Err(ExecutorError::FollowUpNotSupported(
+                "the adapter spec has no follow_up_params".to_string(),
+            ));
+        };
+        self.spawn_with_params(current_dir, prompt, follow_up_params, Some(session_id))
+            .await
+    }
+
+    /// Matches each stdout line against the adapter's rules. Matched lines become their own
+    /// entries, the rest is clustered into assistant messages by a `PlainTextLogProcessor`.
+    fn normalize_logs(&self, msg_store: Arc<MsgStore>, _worktree_path: &Path) {
+        let entry_index_counter = EntryIndexProvider::start_from(&msg_store);
+        normalize_stderr_logs(msg_store.clone(), entry_index_counter.clone());
+
+        let runtime = match AdapterRuntime::compile(&self.adapter) {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                tracing::error!("Failed to compile adapter spec: {}", e);
+                return;
+            }
+        };
+
+        tokio::spawn(async move {
+            let mut stdout_lines = msg_store.stdout_lines_stream();
+            let mut processor = Self::create_plain_text_processor(entry_index_counter.clone());
+            let mut session_id_found = false;
+
+            while let Some(Ok(line)) = stdout_lines.next().await {
+                let line = strip_ansi_escapes::strip_str(&line);
+                let json = runtime.parse_json(&line);
+
+                if !session_id_found
+                    && let Some(session_id) = runtime.session_id(&line, json.as_ref())
+                {
+                    msg_store.push_session_id(session_id);
+                    session_id_found = true;
+                }
+
+                match runtime.match_line(&line, json.as_ref()) {
+                    Some(RuleMatch::Entry(entry)) => {
+                        msg_store.push_patch(ConversationPatch::add_normalized_entry(
+                            entry_index_counter.next(),
+                            entry,
+                        ));
+                        // Text after this entry starts a new message
+                        processor = Self::create_plain_text_processor(entry_index_counter.clone());
+                    }
+                    Some(RuleMatch::Ignore) => {}
+                    None if json.is_some() => {}
+                    None => {
+                        for patch in processor.process(line + "\n") {
+                            msg_store.push_patch(patch);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    fn default_mcp_config_path(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+
+    async fn check_availability(&self) -> bool {
+        let base = self
+            .cmd
+            .base_command_override
+            .as_deref()
+            .unwrap_or(&self.adapter.command);
+        match shlex::split(base).and_then(|parts| parts.into_iter().next()) {
+            Some(program) => resolve_executable_path(&program).await.is_some(),
+            None => false,
+        }
+    }
+}
+
+impl Custom {
+    fn create_plain_text_processor(index_provider: EntryIndexProvider) -> PlainTextLogProcessor {
+        PlainTextLogProcessor::builder()
+            .normalized_entry_producer(Box::new(|content: String| NormalizedEntry {
+                timestamp: None,
+                entry_type: NormalizedEntryType::AssistantMessage,
+                content,
+                metadata: None,
+            }))
+            .index_provider(index_provider)
+            .build()
+    }
+}
+
+enum RuleMatch {
+    Entry(NormalizedEntry),
+    Ignore,
+}
+
+enum Matcher {
+    Regex(Regex),
+    Json {
+        pointer: String,
+        equals: Option<String>,
+    },
+}
+
+/// What a matcher captured from a line, used to fill in templates
+enum Captures<'a> {
+    Regex(regex::Captures<'a>),
+    Json(&'a Value),
+}
+
+/// Adapter spec with its regexes compiled
+struct AdapterRuntime {
+    format: OutputFormat,
+    session_id: Option<(Matcher, String)>,
+    rules: Vec<(Matcher, EntryTemplate)>,
+}
+
+impl AdapterRuntime {
+    fn compile(spec: &AdapterSpec) -> Result<Self, ExecutorError> {
+        let session_id = spec
+            .session_id
+            .as_ref()
+            .map(|session_id| {
+                Ok::<_, ExecutorError>((
+                    Matcher::compile(&session_id.matches)?,
+                    session_id.value.clone(),
+                ))
+            })
+            .transpose()?;
+        let rules = spec
+            .output
+            .rules
+            .iter()
+            .map(|rule| Ok((Matcher::compile(&rule.matches)?, rule.entry.clone())))
+            .collect::<Result<_, ExecutorError>>()?;
+        Ok(Self {
+            format: spec.output.format,
+            session_id,
+            rules,
+        })
+    }
+
+    fn parse_json(&self, line: &str) -> Option<Value> {
+        match self.format {
+            OutputFormat::PlainText => None,
+            OutputFormat::JsonLines => serde_json::from_str::<Value>(line.trim())
+                .ok()
+                .filter(Value::is_object),
+        }
+    }
+
+    fn session_id(&self, line: &str, json: Option<&Value>) -> Option<String> {
+        let (matcher, value) = self.session_id.as_ref()?;
+        let captures = matcher.captures(line, json)?;
+        let session_id = render(value, line, &captures);
+        let session_id = session_id.trim();
+        (!session_id.is_empty()).then(|| session_id.to_string())
+    }
+
+    fn match_line(&self, line: &str, json: Option<&Value>) -> Option<RuleMatch> {
+        self.rules.iter().find_map(|(matcher, template)| {
+            let captures = matcher.captures(line, json)?;
+            Some(entry_from_template(template, line, &captures))
+        })
+    }
+}
+
+impl Matcher {
+    fn compile(matcher: &LineMatcher) -> Result<Self, ExecutorError> {
+        match matcher {
+            LineMatcher::Regex { pattern } => Regex::new(pattern)
+                .map(Self::Regex)
+                .map_err(|e| ExecutorError::InvalidAdapter(format!("regex `{pattern}`: {e}"))),
+            LineMatcher::Json { pointer, equals } => Ok(Self::Json {
+                pointer: pointer.clone(),
+                equals: equals.clone(),
+            }),
+        }
+    }
+
+    fn captures<'a>(&self, line: &'a str, json: Option<&'a Value>) -> Option<Captures<'a>> {
+        match self {
+            Self::Regex(regex) => regex.captures(line).map(Captures::Regex),
+            Self::Json { pointer, equals } => {
+                let json = json?;
+                let value = json.pointer(pointer)?;
+                if equals
+                    .as_ref()
+                    .is_some_and(|equals| json_value_text(value) != *equals)
+                {
+                    return None;
+                }
+                Some(Captures::Json(json))
+            }
+        }
+    }
+}
+
+impl Captures<'_> {
+    fn get(&self, key: &str) -> Option<String> {
+        match self {
+            Self::Regex(captures) => {
+                let group = match key.parse::<usize>() {
+                    Ok(index) => captures.get(index),
+                    Err(_) => captures.name(key),
+                };
+                group.map(|group| group.as_str().to_string())
+            }
+            Self::Json(json) => json.pointer(key).map(json_value_text),
+        }
+    }
+}
+
+fn json_value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Replace each `{key}` of the template with what was captured under that key. Unknown keys
+/// render as empty, text without a closing brace is kept as is.
+fn render(template: &str, line: &str, captures: &Captures) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let key = &rest[start + 1..start + len];
+        match captures.get(key) {
+            Some(value) => rendered.push_str(&value),
+            None if key == "line" => rendered.push_str(line.trim_end()),
+            None => {}
+        }
+        rest = &rest[start + len + 1..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+fn entry_from_template(template: &EntryTemplate, line: &str, captures: &Captures) -> RuleMatch {
+    let fill = |template: &str| render(template, line, captures);
+    let (entry_type, content) = match template {
+        EntryTemplate::AssistantMessage { content } => {
+            (NormalizedEntryType::AssistantMessage, fill(content))
+        }
+        EntryTemplate::Thinking { content } => (NormalizedEntryType::Thinking, fill(content)),
+        EntryTemplate::SystemMessage { content } => {
+            (NormalizedEntryType::SystemMessage, fill(content))
+        }
+        EntryTemplate::ErrorMessage { content } => (
+            NormalizedEntryType::ErrorMessage {
+                error_type: NormalizedEntryError::Other,
+            },
+            fill(content),
+        ),
+        EntryTemplate::ToolUse {
+            tool_name,
+            action_type,
+            content,
+        } => {
+            let tool_name = fill(tool_name);
+            let action_type = match action_type {
+                ActionTemplate::FileRead { path } => ActionType::FileRead { path: fill(path) },
+                ActionTemplate::FileEdit { path } => ActionType::FileEdit {
+                    path: fill(path),
+                    changes: vec![],
+                },
+                ActionTemplate::CommandRun { command } => ActionType::CommandRun {
+                    command: fill(command),
+                    result: None,
+                },
+                ActionTemplate::Search { query } => ActionType::Search { query: fill(query) },
+                ActionTemplate::WebFetch { url } => ActionType::WebFetch { url: fill(url) },
+                ActionTemplate::Tool => ActionType::Tool {
+                    tool_name: tool_name.clone(),
+                    arguments: None,
+                    result: None,
+                },
+                ActionTemplate::Other { description } => ActionType::Other {
+                    description: fill(description),
+                },
+            };
+            (
+                NormalizedEntryType::ToolUse {
+                    tool_name,
+                    action_type,
+                    status: ToolStatus::Success,
+                },
+                fill(content),
+            )
+        }
+        EntryTemplate::Ignore => return RuleMatch::Ignore,
+    };
+    RuleMatch::Entry(NormalizedEntry {
+        timestamp: None,
+        entry_type,
+        content,
+        metadata: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adapter(value: serde_json::Value) -> AdapterSpec {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn regex_rules_map_lines_to_entries() {
+        let runtime = AdapterRuntime::compile(&adapter(serde_json::json!({
+            "command": "my-agent",
+            "output": {
+                "rules": [
+                    {
+                        "matches": { "type": "regex", "pattern": r"^\$ (?P<cmd>.+)$" },
+                        "entry": {
+                            "type": "tool_use",
+                            "tool_name": "bash",
+                            "action_type": { "action": "command_run", "command": "{cmd}" },
+                            "content": "{cmd}"
+                        }
+                    },
+                    {
+                        "matches": { "type": "regex", "pattern": r"^\[debug\]" },
+                        "entry": { "type": "ignore" }
+                    }
+                ]
+            }
+        })))
+        .unwrap();
+
+        let Some(RuleMatch::Entry(entry)) = runtime.match_line("$ cargo test", None) else {
+            panic!("expected a tool use entry");
+        };
+        assert_eq!(entry.content, "cargo test");
+        assert!(matches!(
+            entry.entry_type,
+            NormalizedEntryType::ToolUse {
+                action_type: ActionType::CommandRun { ref command, .. },
+                ..
+            } if command == "cargo test"
+        ));
+        assert!(matches!(
+            runtime.match_line("[debug] tick", None),
+            Some(RuleMatch::Ignore)
+        ));
+        assert!(runtime.match_line("Hello there", None).is_none());
+    }
+
+    #[test]
+    fn json_lines_provide_session_id_and_entries() {
+        let runtime = AdapterRuntime::compile(&adapter(serde_json::json!({
+            "command": "my-agent",
+            "session_id": {
+                "matches": { "type": "json", "pointer": "/session" },
+                "value": "{/session}"
+            },
+            "output": {
+                "format": "json_lines",
+                "rules": [{
+                    "matches": { "type": "json", "pointer": "/type", "equals": "read" },
+                    "entry": {
+                        "type": "tool_use",
+                        "tool_name": "read",
+                        "action_type": { "action": "file_read", "path": "{/file}" },
+                        "content": "Read {/file}"
+                    }
+                }]
+            }
+        })))
+        .unwrap();
+
+        let line = r#"{"session":"abc-123","type":"init"}"#;
+        let json = runtime.parse_json(line);
+        assert_eq!(
+            runtime.session_id(line, json.as_ref()).as_deref(),
+            Some("abc-123")
+        );
+        assert!(runtime.match_line(line, json.as_ref()).is_none());
+
+        let line = r#"{"type":"read","file":"src/main.rs"}"#;
+        let json = runtime.parse_json(line);
+        let Some(RuleMatch::Entry(entry)) = runtime.match_line(line, json.as_ref()) else {
+            panic!("expected a file read entry");
+        };
+        assert_eq!(entry.content, "Read src/main.rs");
+        assert!(runtime.parse_json("plain output").is_none());
+    }
+
+    #[test]
+    fn prompt_and_session_id_are_quoted_into_params() {
+        let custom: Custom = serde_json::from_value(serde_json::json!({
+            "adapter": {
+                "command": "my-agent run",
+                "params": ["--prompt", "{prompt}"],
+                "follow_up_params": ["--resume", "{session_id}", "--prompt", "{prompt}"]
+            }
+        }))
+        .unwrap();
+        let params = custom.adapter.follow_up_params.clone().unwrap();
+        let builder = custom
+            .build_command_builder(&params, "fix the 'bug'", Some("s 1"))
+            .unwrap();
+        let args = shlex::split(&builder.params.unwrap().join(" ")).unwrap();
+        assert_eq!(args, ["--resume", "s 1", "--prompt", "fix the 'bug'"]);
+    }
+
+    #[test]
+    fn invalid_regex_is_rejected() {
+        let result = AdapterRuntime::compile(&adapter(serde_json::json!({
+            "command": "my-agent",
+            "output": { "rules": [{
+                "matches": { "type": "regex", "pattern": "(" },
+                "entry": { "type": "ignore" }
+            }]}
+        })));
+        assert!(matches!(result, Err(ExecutorError::InvalidAdapter(_))));
+    }
+}
@@ -17,18 +17,20 @@ use crate::{
     approvals::ExecutorApprovalService,
     command::CommandBuildError,
     executors::{
-        amp::Amp, claude::ClaudeCode, codex::Codex, copilot::Copilot, cursor::CursorAgent,
-        gemini::Gemini, opencode::Opencode, qwen::QwenCode,
+        acp_agent::AcpAgent, amp::Amp, claude::ClaudeCode, codex::Codex, copilot::Copilot,
+        cursor::CursorAgent, custom::Custom, gemini::Gemini, opencode::Opencode, qwen::QwenCode,
     },
     mcp_config::McpConfig,
 };
 
 pub mod acp;
+pub mod acp_agent;
 pub mod amp;
 pub mod claude;
 pub mod codex;
 pub mod copilot;
 pub mod cursor;
+pub mod custom;
 pub mod gemini;
 pub mod opencode;
 pub mod qwen;
@@ -66,6 +68,12 @@ pub enum ExecutorError {
     ExecutableNotFound { program: String },
     #[error("Setup helper not supported")]
     SetupHelperNotSupported,
+    #[error("Sandbox error: {0}")]
+    Sandbox(String),
+    #[error("Invalid adapter spec: {0}")]
+    InvalidAdapter(String),
+    #[error("Secret `{0}` is not set")]
+    MissingSecret(String),
 }
 
 #[enum_dispatch]
@@ -95,6 +103,8 @@ pub enum CodingAgent {
     CursorAgent,
     QwenCode,
     Copilot,
+    Custom,
+    AcpAgent,
 }
 
 impl CodingAgent {
@@ -147,8 +157,9 @@ impl CodingAgent {
             Self::Codex(_) => vec![BaseAgentCapability::SessionFork],
             Self::Gemini(_) => vec![BaseAgentCapability::SessionFork],
             Self::QwenCode(_) => vec![BaseAgentCapability::SessionFork],
+            Self::AcpAgent(_) => vec![BaseAgentCapability::SessionFork],
             Self::CursorAgent(_) => vec![BaseAgentCapability::SetupHelper],
-            Self::Opencode(_) | Self::Copilot(_) => vec![],
+            Self::Opencode(_) | Self::Copilot(_) | Self::Custom(_) => vec![],
         }
     }
 }
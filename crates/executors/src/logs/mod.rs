@@ -7,6 +7,11 @@ pub mod plain_text_processor;
 pub mod stderr_processor;
 pub mod utils;
 
+/// Version of the normalized conversation format produced by the executors' log
+/// normalizers. Bump this whenever a normalizer changes what it produces for the same
+/// raw logs, so stored conversations can be found and re-normalized.
+pub const NORMALIZER_VERSION: i64 = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[ts(export)]
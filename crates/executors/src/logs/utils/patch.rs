@@ -1,3 +1,5 @@
+use std::collections::BTreeSet;
+
 use json_patch::Patch;
 use serde::{Deserialize, Serialize};
 use serde_json::{from_value, json, to_value};
@@ -139,3 +141,235 @@ pub fn extract_normalized_entry_from_patch(patch: &Patch) -> Option<(usize, Norm
             .map(|entry| (entry_index, entry))
     })
 }
+
+/// A change to the normalized conversation described by one JsonPatch operation
+#[derive(Debug, Clone)]
+pub enum EntryChange {
+    Add(usize, NormalizedEntry),
+    Replace(usize, NormalizedEntry),
+    Remove(usize),
+}
+
+impl EntryChange {
+    /// Apply this change to an in-memory conversation with JSON Patch array semantics
+    pub fn apply_to(self, entries: &mut Vec<NormalizedEntry>) {
+        match self {
+            EntryChange::Add(index, entry) => entries.insert(index.min(entries.len()), entry),
+            EntryChange::Replace(index, entry) => match entries.get_mut(index) {
+                Some(existing) => *existing = entry,
+                None => entries.push(entry),
+            },
+            EntryChange::Remove(index) => {
+                if index < entries.len() {
+                    entries.remove(index);
+                }
+            }
+        }
+    }
+}
+
+/// In-memory copy of a stored conversation that tracks which entries changed since the
+/// last [`ConversationMirror::take_flush`], so a batch of changes is written as one set
+/// of row updates however many patches it spanned
+#[derive(Debug, Default)]
+pub struct ConversationMirror {
+    entries: Vec<NormalizedEntry>,
+    /// Number of entries in the store as of the last flush
+    stored_len: usize,
+    dirty: BTreeSet<usize>,
+}
+
+/// The rows that bring a store in line with its [`ConversationMirror`]
+#[derive(Debug, Default)]
+pub struct ConversationFlush {
+    /// Entries to insert or overwrite, by index
+    pub upserts: Vec<(usize, NormalizedEntry)>,
+    /// Stored entries at or after this index no longer exist
+    pub truncate_from: Option<usize>,
+}
+
+impl ConversationFlush {
+    pub fn is_empty(&self) -> bool {
+        self.upserts.is_empty() && self.truncate_from.is_none()
+    }
+}
+
+impl ConversationMirror {
+    pub fn entries(&self) -> &[NormalizedEntry] {
+        &self.entries
+    }
+
+    /// Apply a change; inserts and removes dirty every entry they shift
+    pub fn apply(&mut self, change: EntryChange) {
+        let len = self.entries.len();
+        match &change {
+            EntryChange::Add(index, _) => self.dirty.extend((*index).min(len)..=len),
+            EntryChange::Replace(index, _) => {
+                self.dirty.insert((*index).min(len));
+            }
+            EntryChange::Remove(index) => self.dirty.extend(*index..len),
+        }
+        change.apply_to(&mut self.entries);
+    }
+
+    /// Take the rows changed since the last flush
+    pub fn take_flush(&mut self) -> ConversationFlush {
+        let len = self.entries.len();
+        let upserts = std::mem::take(&mut self.dirty)
+            .into_iter()
+            .take_while(|index| *index < len)
+            .map(|index| (index, self.entries[index].clone()))
+            .collect();
+        let truncate_from = (len < self.stored_len).then_some(len);
+        self.stored_len = len;
+        ConversationFlush {
+            upserts,
+            truncate_from,
+        }
+    }
+}
+
+/// Extract every change to normalized entries from a JsonPatch, in order.
+/// Operations on stdout, stderr and diff entries are skipped.
+pub fn normalized_entry_changes(patch: &Patch) -> Vec<EntryChange> {
+    let Some(value) = to_value(patch).ok() else {
+        return vec![];
+    };
+    let Some(ops) = value.as_array() else {
+        return vec![];
+    };
+    ops.iter()
+        .filter_map(|op| {
+            let entry_index = op
+                .get("path")?
+                .as_str()?
+                .strip_prefix("/entries/")?
+                .parse::<usize>()
+                .ok()?;
+            let operation = from_value::<PatchOperation>(op.get("op")?.clone()).ok()?;
+            if operation == PatchOperation::Remove {
+                return Some(EntryChange::Remove(entry_index));
+            }
+
+            let value = op.get("value")?;
+            if value.get("type")?.as_str()? != "NORMALIZED_ENTRY" {
+                return None;
+            }
+            let entry = from_value::<NormalizedEntry>(value.get("content")?.clone()).ok()?;
+            Some(match operation {
+                PatchOperation::Add => EntryChange::Add(entry_index, entry),
+                _ => EntryChange::Replace(entry_index, entry),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::NormalizedEntryType;
+
+    fn message(content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::AssistantMessage,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn replays_conversation_patches() {
+        let patches = [
+            ConversationPatch::add_normalized_entry(0, message("a")),
+            ConversationPatch::add_normalized_entry(1, message("b")),
+            ConversationPatch::add_stdout(2, "raw".to_string()),
+            ConversationPatch::replace(1, message("b2")),
+            ConversationPatch::add_normalized_entry(2, message("c")),
+            ConversationPatch::remove(0),
+        ];
+
+        let mut entries = Vec::new();
+        for patch in &patches {
+            for change in normalized_entry_changes(patch) {
+                change.apply_to(&mut entries);
+            }
+        }
+
+        let contents: Vec<_> = entries.iter().map(|e| e.content.as_str()).collect();
+        assert_eq!(contents, ["b2", "c"]);
+        assert!(matches!(
+            normalized_entry_changes(&ConversationPatch::remove(3))[..],
+            [EntryChange::Remove(3)]
+        ));
+    }
+
+    fn flushed(mirror: &mut ConversationMirror) -> (Vec<(usize, String)>, Option<usize>) {
+        let flush = mirror.take_flush();
+        let upserts = flush
+            .upserts
+            .into_iter()
+            .map(|(index, entry)| (index, entry.content))
+            .collect();
+        (upserts, flush.truncate_from)
+    }
+
+    #[test]
+    fn mirror_flushes_only_changed_entries() {
+        let mut mirror = ConversationMirror::default();
+        mirror.apply(EntryChange::Add(0, message("a")));
+        mirror.apply(EntryChange::Add(1, message("b")));
+        mirror.apply(EntryChange::Replace(1, message("b2")));
+        assert_eq!(
+            flushed(&mut mirror),
+            (vec![(0, "a".into()), (1, "b2".into())], None)
+        );
+        assert!(mirror.take_flush().is_empty());
+
+        // Appending and replacing leave earlier entries alone
+        mirror.apply(EntryChange::Add(2, message("c")));
+        mirror.apply(EntryChange::Replace(0, message("a2")));
+        assert_eq!(
+            flushed(&mut mirror),
+            (vec![(0, "a2".into()), (2, "c".into())], None)
+        );
+    }
+
+    #[test]
+    fn mirror_rewrites_shifted_entries() {
+        let mut mirror = ConversationMirror::default();
+        for (index, content) in ["a", "b", "c"].into_iter().enumerate() {
+            mirror.apply(EntryChange::Add(index, message(content)));
+        }
+        mirror.take_flush();
+
+        // An insert shifts everything after it up by one
+        mirror.apply(EntryChange::Add(1, message("x")));
+        assert_eq!(
+            flushed(&mut mirror),
+            (
+                vec![(1, "x".into()), (2, "b".into()), (3, "c".into())],
+                None
+            )
+        );
+
+        // Removes shift the rest down and drop the stored tail
+        mirror.apply(EntryChange::Remove(0));
+        mirror.apply(EntryChange::Remove(2));
+        assert_eq!(
+            flushed(&mut mirror),
+            (vec![(0, "x".into()), (1, "b".into())], Some(2))
+        );
+
+        // Out of range changes clamp to the end, like JSON Patch on an array
+        mirror.apply(EntryChange::Remove(9));
+        mirror.apply(EntryChange::Replace(7, message("d")));
+        assert_eq!(flushed(&mut mirror), (vec![(2, "d".into())], None));
+        let contents: Vec<_> = mirror
+            .entries()
+            .iter()
+            .map(|e| e.content.as_str())
+            .collect();
+        assert_eq!(contents, ["x", "b", "d"]);
+    }
+}
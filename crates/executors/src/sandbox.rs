@@ -0,0 +1,463 @@
+//! Rootless process sandboxing for executor child processes.
+//!
+//! A [`Sandbox`] is installed for the duration of an executor spawn with
+//! [`Sandbox::scope`]. While it is active, every command resolved through
+//! [`crate::command::CommandParts::into_resolved`] (and every script request) is
+//! rewritten to run inside podman or bubblewrap, with only the worktree mounted
+//! read-write. The wrapped process keeps the same stdio, so log processing is
+//! identical to an unsandboxed run.
+//!
+//! Git on the host runs hooks and config commands from the repository, so a worktree's
+//! git metadata is mounted with only what a commit writes (objects, refs, logs and the
+//! worktree's own admin directory) writable. Hooks, config and info stay read-only.
+
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use workspace_utils::shell::resolve_executable_path;
+
+use crate::executors::ExecutorError;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum SandboxBackend {
+    #[default]
+    Bubblewrap,
+    Podman,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+#[ts(rename_all = "snake_case")]
+pub enum SandboxNetwork {
+    /// No network access at all
+    #[default]
+    None,
+    /// Share the host network namespace
+    Host,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema, PartialEq, Default)]
+pub struct SandboxPolicy {
+    pub backend: SandboxBackend,
+    /// OCI image to run processes in (podman backend only)
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Maximum number of CPUs, e.g. 1.5
+    #[serde(default)]
+    pub cpus: Option<f64>,
+    /// Memory limit in megabytes
+    #[serde(default)]
+    pub memory_mb: Option<u32>,
+    #[serde(default)]
+    pub network: SandboxNetwork,
+    /// Additional host paths mounted read-only (e.g. agent install directories)
+    #[serde(default)]
+    pub read_only_paths: Vec<PathBuf>,
+    /// Additional host paths mounted read-write (e.g. agent credential directories)
+    #[serde(default)]
+    pub writable_paths: Vec<PathBuf>,
+}
+
+/// Longest a podman image pull may take. Pulls run before the process is spawned, so
+/// they do not count against the spawn timeout.
+pub const IMAGE_PULL_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Host directories needed to run ordinary binaries under bubblewrap
+const BWRAP_SYSTEM_DIRS: &[&str] = &["/usr", "/bin", "/sbin", "/lib", "/lib64", "/etc", "/opt"];
+
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    pub policy: SandboxPolicy,
+    /// Worktree the process runs in
+    pub worktree: PathBuf,
+    /// Other worktrees the process edits, e.g. of a project's additional repositories
+    pub extra_worktrees: Vec<PathBuf>,
+    /// Name given to the podman container, so it can be stopped with the process
+    pub container_name: Option<String>,
+}
+
+tokio::task_local! {
+    static CURRENT_SANDBOX: Sandbox;
+}
+
+impl Sandbox {
+    pub fn new(policy: SandboxPolicy, worktree: PathBuf, extra_worktrees: Vec<PathBuf>) -> Self {
+        Self {
+            policy,
+            worktree,
+            extra_worktrees,
+            container_name: None,
+        }
+    }
+
+    /// Name the podman container; other backends have no container to name
+    pub fn with_container_name(mut self, name: String) -> Self {
+        if self.policy.backend == SandboxBackend::Podman {
+            self.container_name = Some(name);
+        }
+        self
+    }
+
+    /// Stop and remove a podman container started by [`Sandbox::wrap`]. Killing the
+    /// `podman run` client does not stop the container itself.
+    pub async fn stop_container(name: &str) -> Result<(), ExecutorError> {
+        let podman = resolve_launcher("podman").await?;
+        let status = tokio::process::Command::new(podman)
+            .args(["rm", "--force", "--ignore", name])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .map_err(ExecutorError::Io)?;
+        if !status.success() {
+            return Err(ExecutorError::Sandbox(format!(
+                "podman could not remove container {name}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Get ready to run processes: pull the podman image unless it is present already.
+    /// Bubblewrap needs no preparation.
+    pub async fn prepare(&self) -> Result<(), ExecutorError> {
+        if self.policy.backend != SandboxBackend::Podman {
+            return Ok(());
+        }
+        let image = self.podman_image()?;
+        let podman = resolve_launcher("podman").await?;
+        let exists = tokio::process::Command::new(&podman)
+            .args(["image", "exists", image])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .map_err(ExecutorError::Io)?;
+        if exists.success() {
+            return Ok(());
+        }
+
+        tracing::info!("Pulling sandbox image {}", image);
+        let pull = tokio::process::Command::new(&podman)
+            .args(["pull", "--quiet", image])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .output();
+        let output = tokio::time::timeout(IMAGE_PULL_TIMEOUT, pull)
+            .await
+            .map_err(|_| {
+                ExecutorError::Sandbox(format!(
+                    "pulling image {image} took more than {} seconds",
+                    IMAGE_PULL_TIMEOUT.as_secs()
+                ))
+            })?
+            .map_err(ExecutorError::Io)?;
+        if !output.status.success() {
+            return Err(ExecutorError::Sandbox(format!(
+                "podman could not pull image {image}: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Run `f` with `sandbox` applied to every process it spawns. `None` runs `f` unchanged.
+    pub async fn scope<F: Future>(sandbox: Option<Sandbox>, f: F) -> F::Output {
+        match sandbox {
+            Some(sandbox) => CURRENT_SANDBOX.scope(sandbox, f).await,
+            None => f.await,
+        }
+    }
+
+    /// The sandbox active for the current spawn, if any
+    pub fn current() -> Option<Sandbox> {
+        CURRENT_SANDBOX.try_with(Clone::clone).ok()
+    }
+
+    /// Rewrite `program args..` into a sandbox launcher invocation. `env` is set on the
+    /// launcher and must reach the sandboxed process.
+    pub async fn wrap(
+        &self,
+        program: &str,
+        args: Vec<String>,
+        env: &[(String, String)],
+    ) -> Result<(PathBuf, Vec<String>), ExecutorError> {
+        match self.policy.backend {
+            SandboxBackend::Podman => self.wrap_podman(program, args, env).await,
+            SandboxBackend::Bubblewrap => self.wrap_bubblewrap(program, args).await,
+        }
+    }
+
+    async fn wrap_podman(
+        &self,
+        program: &str,
+        args: Vec<String>,
+        env: &[(String, String)],
+    ) -> Result<(PathBuf, Vec<String>), ExecutorError> {
+        let image = self.podman_image()?;
+        let podman = resolve_launcher("podman").await?;
+
+        let mut wrapped: Vec<String> = vec![
+            "run".into(),
+            "--rm".into(),
+            "-i".into(),
+            "--init".into(),
+            "--userns=keep-id".into(),
+            "--security-opt=no-new-privileges".into(),
+        ];
+        if let Some(name) = &self.container_name {
+            wrapped.push(format!("--name={name}"));
+        }
+        wrapped.push(match self.policy.network {
+            SandboxNetwork::None => "--network=none".into(),
+            SandboxNetwork::Host => "--network=host".into(),
+        });
+        if let Some(cpus) = self.policy.cpus {
+            wrapped.push(format!("--cpus={cpus}"));
+        }
+        if let Some(memory_mb) = self.policy.memory_mb {
+            wrapped.push(format!("--memory={memory_mb}m"));
+        }
+        for (path, writable) in self.mounts() {
+            let mode = if writable { "rw" } else { "ro" };
+            let path = path.display();
+            wrapped.push(format!("--volume={path}:{path}:{mode}"));
+        }
+        // Pass variables by name only; podman copies the values from its own environment,
+        // so they never show up in the process list
+        for (name, _) in env {
+            wrapped.push(format!("--env={name}"));
+        }
+        wrapped.push(format!("--workdir={}", self.worktree.display()));
+        wrapped.push(image.to_string());
+        // The program is resolved inside the image, not on the host
+        wrapped.push(program.to_string());
+        wrapped.extend(args);
+
+        Ok((podman, wrapped))
+    }
+
+    fn podman_image(&self) -> Result<&str, ExecutorError> {
+        self.policy
+            .image
+            .as_deref()
+            .ok_or_else(|| ExecutorError::Sandbox("podman sandbox requires an image".to_string()))
+    }
+
+    async fn wrap_bubblewrap(
+        &self,
+        program: &str,
+        args: Vec<String>,
+    ) -> Result<(PathBuf, Vec<String>), ExecutorError> {
+        let bwrap = resolve_launcher("bwrap").await?;
+        let executable = resolve_executable_path(program).await.ok_or_else(|| {
+            ExecutorError::ExecutableNotFound {
+                program: program.to_string(),
+            }
+        })?;
+
+        let mut wrapped: Vec<String> = vec![
+            "--die-with-parent".into(),
+            "--new-session".into(),
+            "--unshare-all".into(),
+        ];
+        if self.policy.network == SandboxNetwork::Host {
+            wrapped.push("--share-net".into());
+        }
+        for dir in BWRAP_SYSTEM_DIRS {
+            wrapped.extend(["--ro-bind-try".into(), dir.to_string(), dir.to_string()]);
+        }
+        wrapped.extend([
+            "--proc".into(),
+            "/proc".into(),
+            "--dev".into(),
+            "/dev".into(),
+            "--tmpfs".into(),
+            "/tmp".into(),
+        ]);
+        if let Some(home) = dirs::home_dir() {
+            wrapped.extend(["--tmpfs".into(), home.display().to_string()]);
+        }
+        // Make the executable's installation (e.g. a node version manager prefix) visible
+        if let Some(install_root) = install_root(&executable) {
+            let root = install_root.display().to_string();
+            wrapped.extend(["--ro-bind-try".into(), root.clone(), root]);
+        }
+        for (path, writable) in self.mounts() {
+            let flag = if writable {
+                "--bind-try"
+            } else {
+                "--ro-bind-try"
+            };
+            let path = path.display().to_string();
+            wrapped.extend([flag.into(), path.clone(), path]);
+        }
+        wrapped.extend(["--chdir".into(), self.worktree.display().to_string()]);
+        wrapped.push("--".into());
+        wrapped.push(executable.display().to_string());
+        wrapped.extend(args);
+
+        // bubblewrap has no resource controls of its own; use a transient systemd scope
+        if self.policy.cpus.is_none() && self.policy.memory_mb.is_none() {
+            return Ok((bwrap, wrapped));
+        }
+        let systemd_run = resolve_launcher("systemd-run").await?;
+        let mut scoped: Vec<String> = vec!["--user".into(), "--scope".into(), "--quiet".into()];
+        if let Some(cpus) = self.policy.cpus {
+            scoped.push(format!(
+                "--property=CPUQuota={}%",
+                (cpus * 100.0).round() as u64
+            ));
+        }
+        if let Some(memory_mb) = self.policy.memory_mb {
+            scoped.push(format!("--property=MemoryMax={memory_mb}M"));
+        }
+        scoped.push("--".into());
+        scoped.push(bwrap.display().to_string());
+        scoped.extend(wrapped);
+
+        Ok((systemd_run, scoped))
+    }
+
+    /// Host paths mounted into the sandbox, with whether each one is writable. Later
+    /// mounts are nested inside earlier ones.
+    fn mounts(&self) -> Vec<(PathBuf, bool)> {
+        let mut mounts = Vec::new();
+        for worktree in std::iter::once(&self.worktree).chain(&self.extra_worktrees) {
+            mounts.push((worktree.clone(), true));
+            mounts.extend(git_mounts(worktree));
+        }
+        mounts.extend(
+            self.policy
+                .read_only_paths
+                .iter()
+                .map(|p| (p.clone(), false)),
+        );
+        mounts.extend(self.policy.writable_paths.iter().map(|p| (p.clone(), true)));
+        mounts
+    }
+}
+
+/// Git metadata of `worktree`, mounted so git works inside the sandbox without letting
+/// it change hooks or config that git on the host would run
+fn git_mounts(worktree: &Path) -> Vec<(PathBuf, bool)> {
+    let dot_git = worktree.join(".git");
+    if dot_git.is_dir() {
+        // The repository itself: everything is inside the writable worktree already
+        return ["hooks", "config", "info"]
+            .into_iter()
+            .map(|name| dot_git.join(name))
+            .filter(|path| path.exists())
+            .map(|path| (path, false))
+            .collect();
+    }
+
+    // A linked worktree: `.git` is a file pointing at `<common dir>/worktrees/<name>`
+    let Some(admin_dir) = std::fs::read_to_string(&dot_git)
+        .ok()
+        .and_then(|contents| {
+            contents
+                .strip_prefix("gitdir:")
+                .map(|dir| worktree.join(dir.trim()))
+        })
+        .and_then(|dir| std::fs::canonicalize(dir).ok())
+    else {
+        return Vec::new();
+    };
+    let Some(common_dir) = std::fs::read_to_string(admin_dir.join("commondir"))
+        .ok()
+        .and_then(|dir| std::fs::canonicalize(admin_dir.join(dir.trim())).ok())
+    else {
+        return Vec::new();
+    };
+
+    let mut mounts = vec![(common_dir.clone(), false)];
+    for name in ["objects", "refs", "logs"] {
+        let path = common_dir.join(name);
+        // Committing appends to the branch's reflog, which cannot be created read-only
+        if std::fs::create_dir_all(&path).is_ok() {
+            mounts.push((path, true));
+        }
+    }
+    mounts.push((admin_dir, true));
+    // The agent must not point the worktree at a git dir of its own making
+    mounts.push((dot_git, false));
+    mounts
+}
+
+async fn resolve_launcher(program: &str) -> Result<PathBuf, ExecutorError> {
+    resolve_executable_path(program)
+        .await
+        .ok_or_else(|| ExecutorError::ExecutableNotFound {
+            program: program.to_string(),
+        })
+}
+
+/// `<prefix>/bin/<exe>` -> `<prefix>`, following symlinks first
+fn install_root(executable: &Path) -> Option<PathBuf> {
+    let canonical = std::fs::canonicalize(executable).ok()?;
+    let root = canonical.parent()?.parent()?;
+    // System prefixes are already mounted
+    if BWRAP_SYSTEM_DIRS.iter().any(|d| root.starts_with(d)) || root == Path::new("/") {
+        return None;
+    }
+    Some(root.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linked_worktree_git_metadata_is_mounted_without_hooks_or_config() {
+        let root = std::env::temp_dir().join(format!("sandbox-git-{}", uuid::Uuid::new_v4()));
+        let common_dir = root.join("repo/.git");
+        let admin_dir = common_dir.join("worktrees/attempt");
+        let worktree = root.join("attempt");
+        for dir in [&admin_dir, &common_dir.join("hooks"), &worktree] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        std::fs::write(common_dir.join("config"), "[core]\n").unwrap();
+        std::fs::write(admin_dir.join("commondir"), "../..\n").unwrap();
+        std::fs::write(
+            worktree.join(".git"),
+            format!("gitdir: {}\n", admin_dir.display()),
+        )
+        .unwrap();
+
+        let mounts = git_mounts(&worktree);
+        let common_dir = std::fs::canonicalize(&common_dir).unwrap();
+        let writable: Vec<PathBuf> = mounts
+            .iter()
+            .filter(|(_, writable)| *writable)
+            .map(|(path, _)| path.clone())
+            .collect();
+        assert_eq!(
+            writable,
+            [
+                common_dir.join("objects"),
+                common_dir.join("refs"),
+                common_dir.join("logs"),
+                common_dir.join("worktrees/attempt"),
+            ]
+        );
+        // The common dir, with its hooks and config, and the worktree's pointer to it
+        // are read-only
+        assert!(mounts.contains(&(common_dir, false)));
+        assert!(mounts.contains(&(worktree.join(".git"), false)));
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,149 @@
+use std::{collections::HashSet, fmt};
+
+use db::models::execution_budget::BudgetLimits;
+use executors::logs::{NormalizedEntryType, utils::patch::extract_normalized_entry_from_patch};
+use utils::log_msg::LogMsg;
+
+/// The budget limit that stopped a run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BudgetExceeded {
+    Runtime { limit_secs: i64 },
+    ToolCalls { used: i64, limit: i64 },
+    Tokens { used: i64, limit: i64 },
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetExceeded::Runtime { limit_secs } => write!(
+                f,
+                "Budget exceeded: stopped after reaching the wall-clock limit of {limit_secs}s"
+            ),
+            BudgetExceeded::ToolCalls { used, limit } => write!(
+                f,
+                "Budget exceeded: stopped after {used} tool calls (limit {limit})"
+            ),
+            BudgetExceeded::Tokens { used, limit } => write!(
+                f,
+                "Budget exceeded: stopped after using {used} tokens (limit {limit})"
+            ),
+        }
+    }
+}
+
+/// Tallies tool calls and token usage from an execution's log messages
+#[derive(Debug)]
+pub struct BudgetTracker {
+    limits: BudgetLimits,
+    tool_call_entries: HashSet<usize>,
+    tokens: i64,
+}
+
+impl BudgetTracker {
+    pub fn new(limits: BudgetLimits) -> Self {
+        Self {
+            limits,
+            tool_call_entries: HashSet::new(),
+            tokens: 0,
+        }
+    }
+
+    /// Account for one log message, returning the limit it pushed the run over, if any
+    pub fn observe(&mut self, msg: &LogMsg) -> Option<BudgetExceeded> {
+        match msg {
+            LogMsg::JsonPatch(patch) => {
+                // Tool entries are added once and replaced as their status changes
+                if let Some((index, entry)) = extract_normalized_entry_from_patch(patch)
+                    && matches!(entry.entry_type, NormalizedEntryType::ToolUse { .. })
+                    && self.tool_call_entries.insert(index)
+                    && let Some(limit) = self.limits.max_tool_calls
+                {
+                    let used = self.tool_call_entries.len() as i64;
+                    if used > limit {
+                        return Some(BudgetExceeded::ToolCalls { used, limit });
+                    }
+                }
+            }
+            LogMsg::Usage(usage) => {
+                self.tokens += (usage.input_tokens + usage.output_tokens) as i64;
+                if let Some(limit) = self.limits.max_tokens
+                    && self.tokens > limit
+                {
+                    return Some(BudgetExceeded::Tokens {
+                        used: self.tokens,
+                        limit,
+                    });
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::logs::{ActionType, NormalizedEntry, ToolStatus, utils::ConversationPatch};
+    use utils::log_msg::TokenUsage;
+
+    use super::*;
+
+    fn tool_entry() -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type: NormalizedEntryType::ToolUse {
+                tool_name: "bash".to_string(),
+                action_type: ActionType::Other {
+                    description: "ls".to_string(),
+                },
+                status: ToolStatus::Created,
+            },
+            content: "ls".to_string(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn counts_each_tool_entry_once() {
+        let mut tracker = BudgetTracker::new(BudgetLimits {
+            max_tool_calls: Some(1),
+            ..Default::default()
+        });
+
+        let add = LogMsg::JsonPatch(ConversationPatch::add_normalized_entry(0, tool_entry()));
+        let replace = LogMsg::JsonPatch(ConversationPatch::replace(0, tool_entry()));
+        assert_eq!(tracker.observe(&add), None);
+        assert_eq!(tracker.observe(&replace), None);
+
+        let second = LogMsg::JsonPatch(ConversationPatch::add_normalized_entry(1, tool_entry()));
+        assert_eq!(
+            tracker.observe(&second),
+            Some(BudgetExceeded::ToolCalls { used: 2, limit: 1 })
+        );
+    }
+
+    #[test]
+    fn sums_input_and_output_tokens() {
+        let mut tracker = BudgetTracker::new(BudgetLimits {
+            max_tokens: Some(100),
+            ..Default::default()
+        });
+        let usage = |input_tokens, output_tokens| {
+            LogMsg::Usage(TokenUsage {
+                input_tokens,
+                output_tokens,
+                cache_read_tokens: 1000,
+                ..Default::default()
+            })
+        };
+
+        assert_eq!(tracker.observe(&usage(40, 20)), None);
+        assert_eq!(
+            tracker.observe(&usage(30, 20)),
+            Some(BudgetExceeded::Tokens {
+                used: 110,
+                limit: 100
+            })
+        );
+    }
+}
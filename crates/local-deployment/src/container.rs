@@ -13,21 +13,28 @@ use db::{
     DBService,
     models::{
         draft::{Draft, DraftType},
+        execution_budget::BudgetLimits,
         execution_process::{
             ExecutionContext, ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus,
+            ExecutionProcessStatusReason,
         },
         executor_session::ExecutorSession,
         image::TaskImage,
         merge::Merge,
         project::Project,
+        project_env::ProjectEnv,
+        project_repository::ProjectRepository,
+        project_sandbox_policy::ProjectSandboxPolicy,
         task::{Task, TaskStatus},
         task_attempt::TaskAttempt,
+        task_attempt_repository::TaskAttemptRepository,
     },
 };
 use deployment::{DeploymentError, RemoteClientNotConfigured};
 use executors::{
-    actions::{Executable, ExecutorAction},
+    actions::{Executable, ExecutorAction, ExecutorActionType, script::ScriptRequest},
     approvals::{ExecutorApprovalService, NoopExecutorApprovalService},
+    env::ExecutionEnv,
     executors::BaseCodingAgent,
     logs::{
         NormalizedEntryType,
@@ -36,6 +43,7 @@ use executors::{
             patch::{escape_json_pointer_segment, extract_normalized_entry_from_patch},
         },
     },
+    sandbox::Sandbox,
 };
 use futures::{FutureExt, StreamExt, TryStreamExt, stream::select};
 use serde_json::json;
@@ -43,29 +51,46 @@ use services::services::{
     analytics::AnalyticsContext,
     approvals::{Approvals, executor_approvals::ExecutorApprovalBridge},
     config::Config,
-    container::{ContainerError, ContainerRef, ContainerService},
+    conflict_resolution,
+    container::{ContainerError, ContainerRef, ContainerService, SpawnedScript},
     diff_stream::{self, DiffStreamHandle},
     git::{Commit, DiffTarget, GitService},
     image::ImageService,
     notification::NotificationService,
+    secrets::SecretStore,
     share::SharePublisher,
     worktree_manager::WorktreeManager,
 };
 use tokio::{sync::RwLock, task::JoinHandle};
 use tokio_util::io::ReaderStream;
 use utils::{
+    diff::Diff,
     log_msg::LogMsg,
     msg_store::MsgStore,
+    redaction::{Redactor, compile_patterns, redact_logs},
     text::{git_branch_id, short_uuid},
 };
 use uuid::Uuid;
 
-use crate::command;
+use crate::{
+    budget::{BudgetExceeded, BudgetTracker},
+    command,
+};
+
+/// Longest an executor may take to spawn its process. Sandbox images are pulled before
+/// this starts counting, under their own timeout.
+const SPAWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// When set, every execution process runs in its project's sandbox, and attempts of
+/// projects without an enabled sandbox policy cannot run at all
+const REQUIRE_SANDBOX_ENV: &str = "REQUIRE_EXECUTION_SANDBOX";
 
 #[derive(Clone)]
 pub struct LocalContainerService {
     db: DBService,
     child_store: Arc<RwLock<HashMap<Uuid, Arc<RwLock<AsyncGroupChild>>>>>,
+    /// Podman containers of running sandboxed executions, by execution id
+    sandbox_containers: Arc<RwLock<HashMap<Uuid, String>>>,
     msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
     config: Arc<RwLock<Config>>,
     git: GitService,
@@ -73,6 +98,7 @@ pub struct LocalContainerService {
     analytics: Option<AnalyticsContext>,
     approvals: Approvals,
     publisher: Result<SharePublisher, RemoteClientNotConfigured>,
+    secrets: Arc<SecretStore>,
 }
 
 impl LocalContainerService {
@@ -86,12 +112,14 @@ impl LocalContainerService {
         analytics: Option<AnalyticsContext>,
         approvals: Approvals,
         publisher: Result<SharePublisher, RemoteClientNotConfigured>,
+        secrets: Arc<SecretStore>,
     ) -> Self {
         let child_store = Arc::new(RwLock::new(HashMap::new()));
 
         LocalContainerService {
             db,
             child_store,
+            sandbox_containers: Arc::new(RwLock::new(HashMap::new())),
             msg_stores,
             config,
             git,
@@ -99,6 +127,7 @@ impl LocalContainerService {
             analytics,
             approvals,
             publisher,
+            secrets,
         }
     }
 
@@ -117,6 +146,22 @@ impl LocalContainerService {
         map.remove(id);
     }
 
+    /// Kill an execution's process group and, when it runs in a podman sandbox, its
+    /// container
+    async fn kill_execution(
+        &self,
+        exec_id: &Uuid,
+        child: &mut AsyncGroupChild,
+    ) -> Result<(), ContainerError> {
+        let result = command::kill_process_group(child).await;
+        if let Some(name) = self.sandbox_containers.write().await.remove(exec_id)
+            && let Err(e) = Sandbox::stop_container(&name).await
+        {
+            tracing::warn!("Failed to stop sandbox container {}: {}", name, e);
+        }
+        result
+    }
+
     /// A context is finalized when
     /// - The next action is None (no follow-up actions)
     /// - The run reason is not DevServer
@@ -188,6 +233,31 @@ impl LocalContainerService {
         Ok(())
     }
 
+    /// Whether a directory under the worktree base directory belongs to a task attempt,
+    /// either as its worktree or as the attempt directory of a multi-repository project
+    async fn worktree_dir_in_use(&self, path: &Path) -> Result<bool, sqlx::Error> {
+        let pool = &self.db().pool;
+        if TaskAttempt::container_ref_exists(pool, &path.to_string_lossy()).await? {
+            return Ok(true);
+        }
+        // Worktrees have a .git file; an attempt directory only holds worktrees
+        if path.join(".git").exists() {
+            return Ok(false);
+        }
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return Ok(false);
+        };
+        for entry in entries.flatten() {
+            let child = entry.path().to_string_lossy().to_string();
+            if TaskAttempt::container_ref_exists(pool, &child).await?
+                || TaskAttemptRepository::worktree_path_exists(pool, &child).await?
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     /// Find and delete orphaned worktrees that don't correspond to any task attempts
     async fn cleanup_orphaned_worktrees(&self) {
         // Check if orphan cleanup is disabled via environment variable
@@ -231,9 +301,7 @@ impl LocalContainerService {
             }
 
             let worktree_path_str = path.to_string_lossy().to_string();
-            if let Ok(false) =
-                TaskAttempt::container_ref_exists(&self.db().pool, &worktree_path_str).await
-            {
+            if let Ok(false) = self.worktree_dir_in_use(&path).await {
                 // This is an orphaned worktree - delete it
                 tracing::info!("Found orphaned worktree: {}", worktree_path_str);
                 if let Err(e) = WorktreeManager::cleanup_worktree(&path, None).await {
@@ -252,6 +320,40 @@ impl LocalContainerService {
         }
     }
 
+    /// Remove the worktrees of the attempt's additional repositories, then the attempt
+    /// directory holding them and the primary worktree at `worktree_path`
+    async fn cleanup_attempt_repositories(
+        db: &DBService,
+        attempt_id: Uuid,
+        worktree_path: &Path,
+    ) -> Result<(), sqlx::Error> {
+        let repositories =
+            TaskAttemptRepository::find_by_task_attempt_id(&db.pool, attempt_id).await?;
+        if repositories.is_empty() {
+            return Ok(());
+        }
+        for repository in &repositories {
+            if let Err(e) = WorktreeManager::cleanup_worktree(
+                &repository.worktree_path,
+                Some(&repository.git_repo_path),
+            )
+            .await
+            {
+                tracing::warn!(
+                    "Failed to clean up worktree of repository '{}' for task attempt {}: {}",
+                    repository.name,
+                    attempt_id,
+                    e
+                );
+            }
+        }
+        // Only removed once empty, i.e. when every worktree in it is gone
+        if let Some(attempt_dir) = worktree_path.parent() {
+            let _ = std::fs::remove_dir(attempt_dir);
+        }
+        Ok(())
+    }
+
     pub async fn cleanup_expired_attempt(
         db: &DBService,
         attempt_id: Uuid,
@@ -259,6 +361,7 @@ impl LocalContainerService {
         git_repo_path: PathBuf,
     ) -> Result<(), DeploymentError> {
         WorktreeManager::cleanup_worktree(&worktree_path, Some(&git_repo_path)).await?;
+        Self::cleanup_attempt_repositories(db, attempt_id, &worktree_path).await?;
         // Mark worktree as deleted in database after successful cleanup
         TaskAttempt::mark_worktree_deleted(&db.pool, attempt_id).await?;
         tracing::info!("Successfully marked worktree as deleted for attempt {attempt_id}",);
@@ -346,7 +449,7 @@ impl LocalContainerService {
                     // Executor signaled completion: kill group and remember to force Completed(0)
                     if let Some(child_lock) = child_store.read().await.get(&exec_id).cloned() {
                         let mut child = child_lock.write().await ;
-                        if let Err(err) = command::kill_process_group(&mut child).await {
+                        if let Err(err) = container.kill_execution(&exec_id, &mut child).await {
                             tracing::error!("Failed to kill process group after exit signal: {} {}", exec_id, err);
                         }
                     }
@@ -445,6 +548,10 @@ impl LocalContainerService {
                     }
                 }
 
+                // Continue the rebase or merge a conflict resolution follow-up was started for
+                conflict_resolution::finish(&container, &ctx.execution_process, &ctx.task_attempt)
+                    .await;
+
                 // Fire analytics event when CodingAgent execution has finished
                 if config.read().await.analytics_enabled
                     && matches!(
@@ -492,6 +599,8 @@ impl LocalContainerService {
 
             // Cleanup child handle
             child_store.write().await.remove(&exec_id);
+            // `--rm` removes a container whose process exited on its own
+            container.sandbox_containers.write().await.remove(&exec_id);
         })
     }
 
@@ -556,12 +665,24 @@ impl LocalContainerService {
         // Merge and forward into the store
         let merged = select(out, err); // Stream<Item = Result<LogMsg, io::Error>>
         let debounced = utils::stream_ext::debounce_logs(merged);
-        store.clone().spawn_forwarder(debounced);
+        // Mask secrets before anything can persist, stream or share the output
+        let redacted = redact_logs(debounced, self.log_redactor().await);
+        store.clone().spawn_forwarder(redacted);
 
         let mut map = self.msg_stores().write().await;
         map.insert(id, store);
     }
 
+    /// Redactor for a new process's output, with the secrets stored when it starts
+    async fn log_redactor(&self) -> Redactor {
+        let patterns = compile_patterns(&self.config.read().await.redaction_patterns)
+            .unwrap_or_else(|e| {
+                tracing::warn!("Ignoring invalid log redaction patterns: {}", e);
+                Vec::new()
+            });
+        Redactor::new(patterns, self.secrets.values().await.into_values())
+    }
+
     /// Get the worktree path for a task attempt
     #[allow(dead_code)]
     async fn get_worktree_path(
@@ -596,6 +717,161 @@ impl LocalContainerService {
         Ok(project_repo_path)
     }
 
+    /// The project's environment variables and the secrets they, or the executor profile,
+    /// may refer to
+    async fn execution_env_for_attempt(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<ExecutionEnv, ContainerError> {
+        let task = task_attempt
+            .parent_task(&self.db.pool)
+            .await?
+            .ok_or(ContainerError::Other(anyhow!("Parent task not found")))?;
+        let vars = ProjectEnv::vars_for_project(&self.db.pool, task.project_id).await?;
+        Ok(ExecutionEnv::new(vars, self.secrets.values().await))
+    }
+
+    /// Build the sandbox for an attempt's processes when its project has sandboxing enabled.
+    /// Fails instead of running on the host when sandboxing is required.
+    async fn sandbox_for_attempt(
+        &self,
+        task_attempt: &TaskAttempt,
+        worktree_path: &Path,
+    ) -> Result<Option<Sandbox>, ContainerError> {
+        let task = task_attempt
+            .parent_task(&self.db.pool)
+            .await?
+            .ok_or(ContainerError::Other(anyhow!("Parent task not found")))?;
+        let Some(policy) =
+            ProjectSandboxPolicy::find_enabled(&self.db.pool, task.project_id).await?
+        else {
+            if std::env::var(REQUIRE_SANDBOX_ENV).is_ok() {
+                return Err(ContainerError::Other(anyhow!(
+                    "{REQUIRE_SANDBOX_ENV} is set but the project has no sandbox policy enabled"
+                )));
+            }
+            return Ok(None);
+        };
+
+        // The worktrees of additional repositories are edited alongside the primary one
+        let extra_worktrees =
+            TaskAttemptRepository::find_by_task_attempt_id(&self.db.pool, task_attempt.id)
+                .await?
+                .into_iter()
+                .map(|repository| repository.worktree_path)
+                .collect();
+
+        Ok(Some(Sandbox::new(
+            policy,
+            worktree_path.to_path_buf(),
+            extra_worktrees,
+        )))
+    }
+
+    /// Budget limits for a coding agent run, or None when neither the project nor the
+    /// executor profile sets any
+    async fn budget_for_execution(
+        &self,
+        task_attempt: &TaskAttempt,
+        executor_action: &ExecutorAction,
+    ) -> Result<Option<BudgetLimits>, ContainerError> {
+        let executor_profile_id = match executor_action.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => &request.executor_profile_id,
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => &request.executor_profile_id,
+            _ => return Ok(None),
+        };
+        let task = task_attempt
+            .parent_task(&self.db.pool)
+            .await?
+            .ok_or(ContainerError::Other(anyhow!("Parent task not found")))?;
+
+        let limits =
+            BudgetLimits::effective(&self.db.pool, task.project_id, executor_profile_id).await?;
+        Ok((!limits.is_unlimited()).then_some(limits))
+    }
+
+    /// Watch a running process and stop it once it goes over its wall-clock, tool call
+    /// or token budget
+    fn spawn_budget_monitor(
+        &self,
+        exec_id: Uuid,
+        limits: BudgetLimits,
+        msg_store: Arc<MsgStore>,
+    ) -> JoinHandle<()> {
+        let container = self.clone();
+        tokio::spawn(async move {
+            let mut runtime_limit = match limits.max_runtime_secs {
+                Some(secs) => tokio::time::sleep(Duration::from_secs(secs.max(0) as u64))
+                    .map(move |_| BudgetExceeded::Runtime { limit_secs: secs })
+                    .boxed(),
+                None => std::future::pending::<BudgetExceeded>().boxed(),
+            };
+            let mut stream = msg_store.history_plus_stream();
+            let mut tracker = BudgetTracker::new(limits);
+
+            let exceeded = loop {
+                tokio::select! {
+                    exceeded = &mut runtime_limit => break exceeded,
+                    msg = stream.next() => match msg {
+                        Some(Ok(LogMsg::Finished)) | None => return,
+                        Some(Ok(msg)) => {
+                            if let Some(exceeded) = tracker.observe(&msg) {
+                                break exceeded;
+                            }
+                        }
+                        Some(Err(_)) => continue,
+                    }
+                }
+            };
+
+            container
+                .stop_over_budget(exec_id, &msg_store, exceeded)
+                .await;
+        })
+    }
+
+    async fn stop_over_budget(
+        &self,
+        exec_id: Uuid,
+        msg_store: &MsgStore,
+        exceeded: BudgetExceeded,
+    ) {
+        // Claim the stop first so a process that already finished is left untouched
+        match ExecutionProcess::mark_stopped_with_reason(
+            &self.db.pool,
+            exec_id,
+            ExecutionProcessStatusReason::BudgetExceeded,
+        )
+        .await
+        {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to mark execution process {} as over budget: {}",
+                    exec_id,
+                    e
+                );
+                return;
+            }
+        }
+
+        tracing::info!("Stopping execution process {}: {}", exec_id, exceeded);
+        // Stderr is persisted with the raw logs and normalized into an error entry
+        msg_store.push_stderr(exceeded.to_string());
+
+        if let Some(child_lock) = self.get_child_from_store(&exec_id).await {
+            let mut child = child_lock.write().await;
+            if let Err(e) = self.kill_execution(&exec_id, &mut child).await {
+                tracing::error!(
+                    "Failed to kill process group of over-budget execution {}: {}",
+                    exec_id,
+                    e
+                );
+            }
+        }
+    }
+
     /// Create a diff log stream for merged attempts (never changes) for WebSocket
     fn create_merged_diff_stream(
         &self,
@@ -634,6 +910,46 @@ impl LocalContainerService {
         Ok(diff_stream::DiffStreamHandle::new(stream, None))
     }
 
+    /// Where the changes of an attempt are read from: the merge commit once the attempt
+    /// is merged with nothing left over, otherwise its worktree
+    async fn diff_source(&self, task_attempt: &TaskAttempt) -> Result<DiffSource, ContainerError> {
+        let project_repo_path = self.get_project_repo_path(task_attempt).await?;
+        let latest_merge =
+            Merge::find_latest_by_task_attempt_id(&self.db.pool, task_attempt.id).await?;
+
+        let is_ahead = if let Ok((ahead, _)) = self.git().get_branch_status(
+            &project_repo_path,
+            &task_attempt.branch,
+            &task_attempt.target_branch,
+        ) {
+            ahead > 0
+        } else {
+            false
+        };
+
+        if let Some(merge) = &latest_merge
+            && let Some(merge_commit) = merge.merge_commit()
+            && self.is_container_clean(task_attempt).await?
+            && !is_ahead
+        {
+            return Ok(DiffSource::Merged {
+                repo_path: project_repo_path,
+                merge_commit,
+            });
+        }
+
+        let container_ref = self.ensure_container_exists(task_attempt).await?;
+        let base_commit = self.git().get_base_commit(
+            &project_repo_path,
+            &task_attempt.branch,
+            &task_attempt.target_branch,
+        )?;
+        Ok(DiffSource::Worktree {
+            worktree_path: PathBuf::from(container_ref),
+            base_commit,
+        })
+    }
+
     /// Create a live diff log stream for ongoing attempts for WebSocket
     /// Returns a stream that owns the filesystem watcher - when dropped, watcher is cleaned up
     async fn create_live_diff_stream(
@@ -653,6 +969,17 @@ impl LocalContainerService {
     }
 }
 
+enum DiffSource {
+    Merged {
+        repo_path: PathBuf,
+        merge_commit: String,
+    },
+    Worktree {
+        worktree_path: PathBuf,
+        base_commit: Commit,
+    },
+}
+
 fn success_exit_status() -> std::process::ExitStatus {
     #[cfg(unix)]
     {
@@ -684,6 +1011,10 @@ impl ContainerService for LocalContainerService {
         self.publisher.as_ref().ok()
     }
 
+    fn secrets(&self) -> &Arc<SecretStore> {
+        &self.secrets
+    }
+
     async fn git_branch_prefix(&self) -> String {
         self.config.read().await.git_branch_prefix.clone()
     }
@@ -700,22 +1031,76 @@ impl ContainerService for LocalContainerService {
 
         let worktree_dir_name =
             LocalContainerService::dir_name_from_task_attempt(&task_attempt.id, &task.title);
-        let worktree_path = WorktreeManager::get_worktree_base_dir().join(&worktree_dir_name);
+        let attempt_dir = WorktreeManager::get_worktree_base_dir().join(&worktree_dir_name);
 
         let project = task
             .parent_project(&self.db.pool)
             .await?
             .ok_or(sqlx::Error::RowNotFound)?;
+        let repositories = ProjectRepository::find_by_project_id(&self.db.pool, project.id).await?;
+
+        // With additional repositories the attempt directory holds one worktree per
+        // repository, the primary one being where the agent runs
+        let worktree_path = if repositories.is_empty() {
+            attempt_dir.clone()
+        } else {
+            attempt_dir.join(project.repo_dir_name())
+        };
 
+        // A forked attempt's branch already exists, starting at the commit it forks from
+        let create_branch = !self
+            .git()
+            .local_branch_exists(&project.git_repo_path, &task_attempt.branch)?;
         WorktreeManager::create_worktree(
             &project.git_repo_path,
             &task_attempt.branch,
             &worktree_path,
             &task_attempt.target_branch,
-            true, // create new branch
+            create_branch,
         )
         .await?;
 
+        for repository in &repositories {
+            let target_branch = match &repository.target_branch {
+                Some(target_branch) => target_branch.clone(),
+                None if self.git().local_branch_exists(
+                    &repository.git_repo_path,
+                    &task_attempt.target_branch,
+                )? =>
+                {
+                    task_attempt.target_branch.clone()
+                }
+                None => {
+                    return Err(ContainerError::Other(anyhow!(
+                        "Repository '{}' has no target branch configured and no branch '{}'",
+                        repository.name,
+                        task_attempt.target_branch
+                    )));
+                }
+            };
+            let repository_worktree_path = attempt_dir.join(&repository.name);
+            let create_branch = !self
+                .git()
+                .local_branch_exists(&repository.git_repo_path, &task_attempt.branch)?;
+            WorktreeManager::create_worktree(
+                &repository.git_repo_path,
+                &task_attempt.branch,
+                &repository_worktree_path,
+                &target_branch,
+                create_branch,
+            )
+            .await?;
+            TaskAttemptRepository::create(
+                &self.db.pool,
+                task_attempt.id,
+                &repository.name,
+                &repository.git_repo_path.to_string_lossy(),
+                &repository_worktree_path.to_string_lossy(),
+                &target_branch,
+            )
+            .await?;
+        }
+
         // Copy files specified in the project's copy_files field
         if let Some(copy_files) = &project.copy_files
             && !copy_files.trim().is_empty()
@@ -761,18 +1146,17 @@ impl ContainerService for LocalContainerService {
                 None
             }
         };
-        WorktreeManager::cleanup_worktree(
-            &PathBuf::from(task_attempt.container_ref.clone().unwrap_or_default()),
-            git_repo_path.as_deref(),
-        )
-        .await
-        .unwrap_or_else(|e| {
-            tracing::warn!(
-                "Failed to clean up worktree for task attempt {}: {}",
-                task_attempt.id,
-                e
-            );
-        });
+        let worktree_path = PathBuf::from(task_attempt.container_ref.clone().unwrap_or_default());
+        WorktreeManager::cleanup_worktree(&worktree_path, git_repo_path.as_deref())
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(
+                    "Failed to clean up worktree for task attempt {}: {}",
+                    task_attempt.id,
+                    e
+                );
+            });
+        Self::cleanup_attempt_repositories(&self.db, task_attempt.id, &worktree_path).await?;
         Ok(())
     }
 
@@ -802,6 +1186,16 @@ impl ContainerService for LocalContainerService {
             &worktree_path,
         )
         .await?;
+        for repository in
+            TaskAttemptRepository::find_by_task_attempt_id(&self.db.pool, task_attempt.id).await?
+        {
+            WorktreeManager::ensure_worktree_exists(
+                &repository.git_repo_path,
+                &task_attempt.branch,
+                &repository.worktree_path,
+            )
+            .await?;
+        }
 
         Ok(container_ref.to_string())
     }
@@ -837,25 +1231,56 @@ impl ContainerService for LocalContainerService {
 
         let approvals_service: Arc<dyn ExecutorApprovalService> =
             match executor_action.base_executor() {
-                Some(BaseCodingAgent::Codex) | Some(BaseCodingAgent::ClaudeCode) => {
-                    ExecutorApprovalBridge::new(
-                        self.approvals.clone(),
-                        self.db.clone(),
-                        execution_process.id,
-                    )
-                }
+                Some(
+                    executor @ (BaseCodingAgent::Codex
+                    | BaseCodingAgent::ClaudeCode
+                    | BaseCodingAgent::AcpAgent),
+                ) => ExecutorApprovalBridge::new(
+                    self.approvals.clone(),
+                    self.db.clone(),
+                    execution_process.id,
+                    executor == BaseCodingAgent::AcpAgent,
+                ),
                 _ => Arc::new(NoopExecutorApprovalService {}),
             };
 
+        let sandbox = self
+            .sandbox_for_attempt(task_attempt, &current_dir)
+            .await?
+            .map(|sandbox| {
+                sandbox.with_container_name(format!("sandbox-{}", execution_process.id))
+            });
+        let env = self.execution_env_for_attempt(task_attempt).await?;
+        if let Some(sandbox) = &sandbox {
+            tracing::debug!(
+                "Running execution process {} in sandbox",
+                execution_process.id
+            );
+            sandbox.prepare().await?;
+            if let Some(name) = &sandbox.container_name {
+                self.sandbox_containers
+                    .write()
+                    .await
+                    .insert(execution_process.id, name.clone());
+            }
+        }
+
         // Create the child and stream, add to execution tracker with timeout
         let mut spawned = tokio::time::timeout(
-            Duration::from_secs(30),
-            executor_action.spawn(&current_dir, approvals_service),
+            SPAWN_TIMEOUT,
+            ExecutionEnv::scope(
+                Some(env),
+                Sandbox::scope(
+                    sandbox,
+                    executor_action.spawn(&current_dir, approvals_service),
+                ),
+            ),
         )
         .await
         .map_err(|_| {
             ContainerError::Other(anyhow!(
-                "Timeout: process took more than 30 seconds to start"
+                "Timeout: process took more than {} seconds to start",
+                SPAWN_TIMEOUT.as_secs()
             ))
         })??;
 
@@ -868,6 +1293,14 @@ impl ContainerService for LocalContainerService {
         // Spawn unified exit monitor: watches OS exit and optional executor signal
         let _hn = self.spawn_exit_monitor(&execution_process.id, spawned.exit_signal);
 
+        if let Some(limits) = self
+            .budget_for_execution(task_attempt, executor_action)
+            .await?
+            && let Some(msg_store) = self.get_msg_store_by_id(&execution_process.id).await
+        {
+            self.spawn_budget_monitor(execution_process.id, limits, msg_store);
+        }
+
         Ok(())
     }
 
@@ -894,7 +1327,10 @@ impl ContainerService for LocalContainerService {
         // Kill the child process and remove from the store
         {
             let mut child_guard = child.write().await;
-            if let Err(e) = command::kill_process_group(&mut child_guard).await {
+            if let Err(e) = self
+                .kill_execution(&execution_process.id, &mut child_guard)
+                .await
+            {
                 tracing::error!(
                     "Failed to stop execution process {}: {}",
                     execution_process.id,
@@ -962,42 +1398,77 @@ impl ContainerService for LocalContainerService {
         stats_only: bool,
     ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, ContainerError>
     {
-        let project_repo_path = self.get_project_repo_path(task_attempt).await?;
-        let latest_merge =
-            Merge::find_latest_by_task_attempt_id(&self.db.pool, task_attempt.id).await?;
+        let wrapper = match self.diff_source(task_attempt).await? {
+            DiffSource::Merged {
+                repo_path,
+                merge_commit,
+            } => self.create_merged_diff_stream(&repo_path, &merge_commit, stats_only)?,
+            DiffSource::Worktree {
+                worktree_path,
+                base_commit,
+            } => {
+                self.create_live_diff_stream(&worktree_path, &base_commit, stats_only)
+                    .await?
+            }
+        };
+        Ok(Box::pin(wrapper))
+    }
 
-        let is_ahead = if let Ok((ahead, _)) = self.git().get_branch_status(
-            &project_repo_path,
-            &task_attempt.branch,
-            &task_attempt.target_branch,
-        ) {
-            ahead > 0
-        } else {
-            false
+    async fn diff_stats(&self, task_attempt: &TaskAttempt) -> Result<Vec<Diff>, ContainerError> {
+        let source = self.diff_source(task_attempt).await?;
+        let target = match &source {
+            DiffSource::Merged {
+                repo_path,
+                merge_commit,
+            } => DiffTarget::Commit {
+                repo_path,
+                commit_sha: merge_commit,
+            },
+            DiffSource::Worktree {
+                worktree_path,
+                base_commit,
+            } => DiffTarget::Worktree {
+                worktree_path,
+                base_commit,
+            },
         };
 
-        if let Some(merge) = &latest_merge
-            && let Some(commit) = merge.merge_commit()
-            && self.is_container_clean(task_attempt).await?
-            && !is_ahead
-        {
-            let wrapper =
-                self.create_merged_diff_stream(&project_repo_path, &commit, stats_only)?;
-            return Ok(Box::pin(wrapper));
+        let cum = Arc::new(AtomicUsize::new(0));
+        let mut diffs = self.git().get_diffs(target, None)?;
+        for diff in &mut diffs {
+            diff_stream::apply_stream_omit_policy(diff, &cum, true);
         }
+        Ok(diffs)
+    }
 
-        let container_ref = self.ensure_container_exists(task_attempt).await?;
-        let worktree_path = PathBuf::from(container_ref);
-        let base_commit = self.git().get_base_commit(
-            &project_repo_path,
-            &task_attempt.branch,
-            &task_attempt.target_branch,
-        )?;
-
-        let wrapper = self
-            .create_live_diff_stream(&worktree_path, &base_commit, stats_only)
-            .await?;
-        Ok(Box::pin(wrapper))
+    async fn spawn_script(
+        &self,
+        task_attempt: &TaskAttempt,
+        current_dir: &Path,
+        request: &ScriptRequest,
+    ) -> Result<SpawnedScript, ContainerError> {
+        let sandbox = self
+            .sandbox_for_attempt(task_attempt, current_dir)
+            .await?
+            .map(|sandbox| sandbox.with_container_name(format!("script-{}", Uuid::new_v4())));
+        let env = self.execution_env_for_attempt(task_attempt).await?;
+        if let Some(sandbox) = &sandbox {
+            sandbox.prepare().await?;
+        }
+        let sandbox_container = sandbox.as_ref().and_then(|s| s.container_name.clone());
+
+        let spawned = ExecutionEnv::scope(
+            Some(env),
+            Sandbox::scope(
+                sandbox,
+                request.spawn(current_dir, Arc::new(NoopExecutorApprovalService {})),
+            ),
+        )
+        .await?;
+        Ok(SpawnedScript {
+            child: spawned.child,
+            sandbox_container,
+        })
     }
 
     async fn try_commit_changes(&self, ctx: &ExecutionContext) -> Result<bool, ContainerError> {
@@ -1064,7 +1535,34 @@ impl ContainerService for LocalContainerService {
             message
         );
 
-        let changes_committed = self.git().commit(Path::new(container_ref), &message)?;
+        // Committing now would conclude an in-progress rebase, merge, cherry-pick or revert
+        // with whatever the worktree holds; the changes are staged when it is continued
+        if self
+            .git()
+            .detect_conflict_op(Path::new(container_ref))?
+            .is_some()
+        {
+            tracing::debug!(
+                "Not committing changes for task attempt {}: a conflicted operation is in progress",
+                ctx.task_attempt.id
+            );
+            return Ok(false);
+        }
+
+        let mut changes_committed = self.git().commit(Path::new(container_ref), &message)?;
+        for repository in
+            TaskAttemptRepository::find_by_task_attempt_id(&self.db.pool, ctx.task_attempt.id)
+                .await?
+        {
+            if self
+                .git()
+                .detect_conflict_op(&repository.worktree_path)?
+                .is_some()
+            {
+                continue;
+            }
+            changes_committed |= self.git().commit(&repository.worktree_path, &message)?;
+        }
         Ok(changes_committed)
     }
 
@@ -1244,7 +1742,6 @@ impl LocalContainerService {
             return Ok(());
         };
 
-        use executors::actions::ExecutorActionType;
         let initial_executor_profile_id = match &latest.executor_action()?.typ {
             ExecutorActionType::CodingAgentInitialRequest(req) => req.executor_profile_id.clone(),
             ExecutorActionType::CodingAgentFollowUpRequest(req) => req.executor_profile_id.clone(),
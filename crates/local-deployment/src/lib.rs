@@ -7,7 +7,9 @@ use executors::profile::ExecutorConfigs;
 use services::services::{
     analytics::{AnalyticsConfig, AnalyticsContext, AnalyticsService, generate_user_id},
     approvals::Approvals,
+    attempt_stack::AttemptStackService,
     auth::AuthContext,
+    ci_auto_fix::CiAutoFixService,
     config::{Config, load_config_from_file, save_config_to_file},
     container::ContainerService,
     drafts::DraftsService,
@@ -16,19 +18,24 @@ use services::services::{
     filesystem::FilesystemService,
     git::GitService,
     image::ImageService,
+    merge_queue::MergeQueueService,
+    notifier::NotifierService,
     oauth_credentials::OAuthCredentials,
     remote_client::{RemoteClient, RemoteClientError},
+    secrets::SecretStore,
     share::{RemoteSyncHandle, ShareConfig, SharePublisher},
+    task_scheduler::TaskSchedulerService,
 };
 use tokio::sync::{Mutex, RwLock};
 use utils::{
     api::oauth::LoginStatus,
-    assets::{config_path, credentials_path},
+    assets::{config_path, credentials_path, secrets_key_path, secrets_path},
     msg_store::MsgStore,
 };
 use uuid::Uuid;
 
 use crate::container::LocalContainerService;
+mod budget;
 mod command;
 pub mod container;
 
@@ -120,7 +127,7 @@ impl Deployment for LocalDeployment {
             });
         }
 
-        let approvals = Approvals::new(msg_stores.clone());
+        let approvals = Approvals::new(msg_stores.clone(), events_msg_store.clone());
 
         let share_config = ShareConfig::from_env();
 
@@ -129,6 +136,11 @@ impl Deployment for LocalDeployment {
             tracing::warn!(?e, "failed to load OAuth credentials");
         }
 
+        let secrets = Arc::new(SecretStore::open_or_reset(
+            secrets_path(),
+            &secrets_key_path(),
+        )?);
+
         let profile_cache = Arc::new(RwLock::new(None));
         let auth_context = AuthContext::new(oauth_credentials.clone(), profile_cache.clone());
 
@@ -183,12 +195,30 @@ impl Deployment for LocalDeployment {
             analytics_ctx,
             approvals.clone(),
             share_publisher.clone(),
+            secrets.clone(),
         );
         container.spawn_worktree_cleanup().await;
+        TaskSchedulerService::spawn(db.clone(), container.clone()).await;
+        AttemptStackService::spawn(container.clone(), config.clone()).await;
 
         let events = EventService::new(db.clone(), events_msg_store, events_entry_count);
+        NotifierService::spawn(
+            db.clone(),
+            config.clone(),
+            secrets,
+            events.msg_store().clone(),
+        )
+        .await;
 
         let drafts = DraftsService::new(db.clone(), image.clone());
+        CiAutoFixService::spawn(
+            db.clone(),
+            config.clone(),
+            drafts.clone(),
+            container.clone(),
+        )
+        .await;
+        MergeQueueService::spawn(drafts.clone(), container.clone(), config.clone()).await;
         let file_search_cache = Arc::new(FileSearchCache::new());
 
         let deployment = Self {
@@ -0,0 +1,664 @@
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
+use anyhow::{Context, anyhow, bail};
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    project::Project,
+    task::{CreateTask, Task, TaskStatus, TaskWithAttemptStatus},
+    task_attempt::TaskAttempt,
+};
+use executors::{
+    executors::BaseCodingAgent,
+    logs::{
+        NormalizedEntry, NormalizedEntryType, ToolStatus,
+        utils::patch::extract_normalized_entry_from_patch,
+    },
+    profile::{ExecutorProfileId, canonical_variant_key},
+};
+use futures_util::StreamExt;
+use serde::{Deserialize, de::DeserializeOwned};
+use server::routes::task_attempts::{CreateGitHubPrRequest, CreateTaskAttemptBody};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use utils::{
+    approvals::{ApprovalResponse, ApprovalStatus},
+    log_msg::LogMsg,
+    port_file::read_port_file,
+};
+use uuid::Uuid;
+
+const USAGE: &str = "\
+Usage: cli <command> [args]
+
+Commands:
+  projects                                   List projects
+  tasks <project-id> [--status <status>]     List tasks in a project
+  create <project-id> <title> [--description <text>]
+                                             Create a task
+  attempts <task-id>                         List attempts for a task
+  start <task-id> --executor <EXECUTOR[:VARIANT]> --base-branch <branch> [--follow]
+                                             Start an attempt, optionally tailing its logs
+  logs <attempt-id> [--process <execution-process-id>]
+                                             Tail the normalized logs of the latest coding agent run
+  approve <approval-id> --process <execution-process-id>
+                                             Approve a pending tool call
+  deny <approval-id> --process <execution-process-id> [--reason <text>]
+                                             Deny a pending tool call
+  merge <attempt-id>                         Merge an attempt into its target branch
+  pr <attempt-id> --title <title> [--body <text>] [--target-branch <branch>]
+                                             Open a pull request for an attempt
+
+The server URL is taken from VIBE_BACKEND_URL, or HOST and BACKEND_PORT/PORT,
+falling back to the port file written by the running server.";
+
+/// How often `start --follow` checks whether the coding agent run has started
+const PROCESS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Polls after a successful setup script before giving up on the coding agent run,
+/// which normally starts right away
+const MAX_POLLS_AFTER_SETUP: u32 = 30;
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse(std::env::args().skip(1))?;
+    if args.positional.is_empty() {
+        println!("{USAGE}");
+        std::process::exit(if args.switch("help") { 0 } else { 2 });
+    }
+    let code = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(async {
+            let client = Client::new(resolve_base_url().await?);
+            run(&client, args).await
+        })?;
+    std::process::exit(code);
+}
+
+/// Positional arguments plus `--flag value` / `--flag=value` options
+struct Args {
+    positional: Vec<String>,
+    flags: HashMap<String, String>,
+}
+
+impl Args {
+    /// Options that take no value
+    const SWITCHES: &'static [&'static str] = &["follow", "help"];
+
+    fn parse(mut raw: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut positional = Vec::new();
+        let mut flags = HashMap::new();
+        while let Some(arg) = raw.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                positional.push(arg);
+                continue;
+            };
+            if let Some((name, value)) = name.split_once('=') {
+                flags.insert(name.to_string(), value.to_string());
+            } else if Self::SWITCHES.contains(&name) {
+                flags.insert(name.to_string(), String::new());
+            } else {
+                let value = raw
+                    .next()
+                    .ok_or_else(|| anyhow!("Missing value for --{name}"))?;
+                flags.insert(name.to_string(), value);
+            }
+        }
+        Ok(Self { positional, flags })
+    }
+
+    fn positional(&self, index: usize, name: &str) -> anyhow::Result<&str> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| anyhow!("Missing <{name}>\n\n{USAGE}"))
+    }
+
+    fn uuid(&self, index: usize, name: &str) -> anyhow::Result<Uuid> {
+        let raw = self.positional(index, name)?;
+        Uuid::parse_str(raw).with_context(|| format!("Invalid {name} '{raw}'"))
+    }
+
+    fn flag(&self, name: &str) -> Option<&str> {
+        self.flags
+            .get(name)
+            .map(String::as_str)
+            .filter(|v| !v.trim().is_empty())
+    }
+
+    fn required_flag(&self, name: &str) -> anyhow::Result<&str> {
+        self.flag(name)
+            .ok_or_else(|| anyhow!("Missing --{name}\n\n{USAGE}"))
+    }
+
+    fn uuid_flag(&self, name: &str) -> anyhow::Result<Option<Uuid>> {
+        self.flag(name)
+            .map(|raw| Uuid::parse_str(raw).with_context(|| format!("Invalid --{name} '{raw}'")))
+            .transpose()
+    }
+
+    fn switch(&self, name: &str) -> bool {
+        self.flags.contains_key(name)
+    }
+}
+
+/// Same resolution order as the MCP task server
+async fn resolve_base_url() -> anyhow::Result<String> {
+    if let Ok(url) = std::env::var("VIBE_BACKEND_URL") {
+        return Ok(url);
+    }
+    let host = std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+    let port = match std::env::var("BACKEND_PORT").or_else(|_| std::env::var("PORT")) {
+        Ok(port_str) => port_str
+            .parse::<u16>()
+            .map_err(|e| anyhow!("Invalid port value '{}': {}", port_str, e))?,
+        Err(_) => read_port_file("vibe-kanban")
+            .await
+            .context("Could not find a running vibe-kanban server")?,
+    };
+    Ok(format!("http://{}:{}", host, port))
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiResponseEnvelope {
+    success: bool,
+    #[serde(default)]
+    data: serde_json::Value,
+    error_data: Option<serde_json::Value>,
+    message: Option<String>,
+}
+
+struct Client {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Client {
+    fn new(base_url: String) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url, path.trim_start_matches('/'))
+    }
+
+    fn ws_url(&self, path: &str) -> String {
+        let url = self.url(path);
+        match url.strip_prefix("http") {
+            Some(rest) => format!("ws{rest}"),
+            None => url,
+        }
+    }
+
+    async fn send_json<T: DeserializeOwned>(
+        &self,
+        rb: reqwest::RequestBuilder,
+    ) -> anyhow::Result<T> {
+        let resp = rb.send().await.context("Failed to connect to VK API")?;
+        let status = resp.status();
+        let body = resp
+            .text()
+            .await
+            .context("Failed to read VK API response")?;
+
+        let envelope = match serde_json::from_str::<ApiResponseEnvelope>(&body) {
+            Ok(envelope) => envelope,
+            Err(_) if !status.is_success() => bail!("VK API returned error status: {status}"),
+            Err(e) => bail!("Failed to parse VK API response: {e}"),
+        };
+        if !envelope.success {
+            let msg = envelope
+                .message
+                .or_else(|| envelope.error_data.map(|d| d.to_string()))
+                .unwrap_or_else(|| format!("VK API returned error status: {status}"));
+            bail!(msg);
+        }
+        // Routes without a payload answer with `data: null`, which is valid for `()`
+        serde_json::from_value(envelope.data).context("Failed to parse VK API response data")
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> anyhow::Result<T> {
+        self.send_json(self.http.get(self.url(path))).await
+    }
+
+    async fn post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &impl serde::Serialize,
+    ) -> anyhow::Result<T> {
+        self.send_json(self.http.post(self.url(path)).json(body))
+            .await
+    }
+}
+
+/// Run a command, returning the process exit code
+async fn run(client: &Client, args: Args) -> anyhow::Result<i32> {
+    let command = args.positional[0].as_str();
+    match command {
+        "projects" => {
+            let projects: Vec<Project> = client.get("/api/projects").await?;
+            for project in projects {
+                println!(
+                    "{}\t{}\t{}",
+                    project.id,
+                    project.name,
+                    project.git_repo_path.display()
+                );
+            }
+        }
+        "tasks" => {
+            let project_id = args.uuid(1, "project-id")?;
+            let status = args
+                .flag("status")
+                .map(|s| {
+                    TaskStatus::from_str(s).map_err(|_| {
+                        anyhow!(
+                            "Invalid status '{s}'. Valid values: 'todo', 'inprogress', 'inreview', 'done', 'cancelled'"
+                        )
+                    })
+                })
+                .transpose()?;
+            let tasks: Vec<TaskWithAttemptStatus> = client
+                .get(&format!("/api/tasks?project_id={project_id}"))
+                .await?;
+            for task in tasks
+                .iter()
+                .filter(|t| status.as_ref().is_none_or(|s| &t.status == s))
+            {
+                let activity = if task.has_in_progress_attempt {
+                    "running"
+                } else if task.last_attempt_failed {
+                    "failed"
+                } else {
+                    ""
+                };
+                println!("{}\t{}\t{}\t{}", task.id, task.status, activity, task.title);
+            }
+        }
+        "create" => {
+            let project_id = args.uuid(1, "project-id")?;
+            let title = args.positional(2, "title")?.to_string();
+            let description = args.flag("description").map(str::to_string);
+            let task: Task = client
+                .post(
+                    "/api/tasks",
+                    &CreateTask::from_title_description(project_id, title, description),
+                )
+                .await?;
+            println!("{}", task.id);
+        }
+        "attempts" => {
+            let task_id = args.uuid(1, "task-id")?;
+            let attempts: Vec<TaskAttempt> = client
+                .get(&format!("/api/task-attempts?task_id={task_id}"))
+                .await?;
+            for attempt in attempts {
+                println!(
+                    "{}\t{}\t{}\t{} -> {}",
+                    attempt.id,
+                    attempt.created_at.to_rfc3339(),
+                    attempt.executor,
+                    attempt.branch,
+                    attempt.target_branch
+                );
+            }
+        }
+        "start" => {
+            let task_id = args.uuid(1, "task-id")?;
+            let executor_profile_id = parse_executor_profile_id(args.required_flag("executor")?)?;
+            let base_branch = args.required_flag("base-branch")?.trim().to_string();
+            let attempt: TaskAttempt = client
+                .post(
+                    "/api/task-attempts",
+                    &CreateTaskAttemptBody {
+                        task_id,
+                        executor_profile_id,
+                        base_branch,
+                        parent_attempt_id: None,
+                    },
+                )
+                .await?;
+            println!("{}", attempt.id);
+            if args.switch("follow") {
+                let process = wait_for_coding_agent_process(client, attempt.id).await?;
+                return follow_logs(client, process.id).await;
+            }
+        }
+        "logs" => {
+            let attempt_id = args.uuid(1, "attempt-id")?;
+            let process_id = match args.uuid_flag("process")? {
+                Some(id) => id,
+                None => latest_coding_agent_process(client, attempt_id).await?.id,
+            };
+            return follow_logs(client, process_id).await;
+        }
+        "approve" | "deny" => {
+            let approval_id = args.positional(1, "approval-id")?;
+            let execution_process_id = args
+                .uuid_flag("process")?
+                .ok_or_else(|| anyhow!("Missing --process\n\n{USAGE}"))?;
+            let status = if command == "approve" {
+                ApprovalStatus::Approved
+            } else {
+                ApprovalStatus::Denied {
+                    reason: args.flag("reason").map(str::to_string),
+                }
+            };
+            // This route answers with the bare status rather than an ApiResponse
+            let resp = client
+                .http
+                .post(client.url(&format!("/api/approvals/{approval_id}/respond")))
+                .json(&ApprovalResponse {
+                    execution_process_id,
+                    status,
+                })
+                .send()
+                .await
+                .context("Failed to connect to VK API")?;
+            if !resp.status().is_success() {
+                bail!("VK API returned error status: {}", resp.status());
+            }
+            let status: ApprovalStatus = resp.json().await?;
+            println!("{}", approval_status_label(&status));
+        }
+        "merge" => {
+            let attempt_id = args.uuid(1, "attempt-id")?;
+            let url = client.url(&format!("/api/task-attempts/{attempt_id}/merge"));
+            client.send_json::<()>(client.http.post(url)).await?;
+            println!("Merged {attempt_id}");
+        }
+        "pr" => {
+            let attempt_id = args.uuid(1, "attempt-id")?;
+            let pr_url: String = client
+                .post(
+                    &format!("/api/task-attempts/{attempt_id}/pr"),
+                    &CreateGitHubPrRequest {
+                        title: args.required_flag("title")?.to_string(),
+                        body: args.flag("body").map(str::to_string),
+                        target_branch: args.flag("target-branch").map(str::to_string),
+                    },
+                )
+                .await?;
+            println!("{pr_url}");
+        }
+        other => {
+            eprintln!("Unknown command '{other}'\n\n{USAGE}");
+            return Ok(2);
+        }
+    }
+    Ok(0)
+}
+
+/// Accepts `EXECUTOR` or `EXECUTOR:VARIANT`, e.g. `claude-code:plan`
+fn parse_executor_profile_id(raw: &str) -> anyhow::Result<ExecutorProfileId> {
+    let (executor, variant) = match raw.trim().split_once(':') {
+        Some((executor, variant)) => (executor.trim(), Some(variant.trim())),
+        None => (raw.trim(), None),
+    };
+    let normalized = executor.replace('-', "_").to_ascii_uppercase();
+    let executor = BaseCodingAgent::from_str(&normalized)
+        .map_err(|_| anyhow!("Unknown executor '{executor}'"))?;
+    Ok(match variant.filter(|v| !v.is_empty()) {
+        Some(variant) => ExecutorProfileId::with_variant(executor, canonical_variant_key(variant)),
+        None => ExecutorProfileId::new(executor),
+    })
+}
+
+async fn latest_coding_agent_process(
+    client: &Client,
+    attempt_id: Uuid,
+) -> anyhow::Result<ExecutionProcess> {
+    let processes: Vec<ExecutionProcess> = client
+        .get(&format!(
+            "/api/execution-processes?task_attempt_id={attempt_id}"
+        ))
+        .await?;
+    processes
+        .into_iter()
+        .rev()
+        .find(|p| p.run_reason == ExecutionProcessRunReason::CodingAgent)
+        .ok_or_else(|| anyhow!("Attempt {attempt_id} has no coding agent runs"))
+}
+
+/// The attempt's first coding agent process, waiting for a setup script to run first
+async fn wait_for_coding_agent_process(
+    client: &Client,
+    attempt_id: Uuid,
+) -> anyhow::Result<ExecutionProcess> {
+    let mut announced = false;
+    let mut polls_after_setup = 0;
+    loop {
+        let processes: Vec<ExecutionProcess> = client
+            .get(&format!(
+                "/api/execution-processes?task_attempt_id={attempt_id}"
+            ))
+            .await?;
+        if let Some(process) = processes
+            .iter()
+            .find(|p| p.run_reason == ExecutionProcessRunReason::CodingAgent)
+        {
+            return Ok(process.clone());
+        }
+        let setup = processes
+            .iter()
+            .find(|p| p.run_reason == ExecutionProcessRunReason::SetupScript)
+            .ok_or_else(|| anyhow!("Attempt {attempt_id} has no coding agent runs"))?;
+        match setup.status {
+            ExecutionProcessStatus::Failed | ExecutionProcessStatus::Killed => {
+                bail!("Setup script of attempt {attempt_id} did not succeed")
+            }
+            // The coding agent starts as soon as the setup script succeeds
+            ExecutionProcessStatus::Completed => {
+                polls_after_setup += 1;
+                if polls_after_setup > MAX_POLLS_AFTER_SETUP {
+                    bail!("Attempt {attempt_id} has no coding agent runs");
+                }
+            }
+            _ if !announced => {
+                eprintln!("Waiting for the setup script to finish...");
+                announced = true;
+            }
+            _ => {}
+        }
+        tokio::time::sleep(PROCESS_POLL_INTERVAL).await;
+    }
+}
+
+/// Stream an execution's normalized conversation to stdout until it finishes. The
+/// exit code is 0 only if the process completed successfully.
+async fn follow_logs(client: &Client, execution_process_id: Uuid) -> anyhow::Result<i32> {
+    let url = client.ws_url(&format!(
+        "/api/execution-processes/{execution_process_id}/normalized-logs/ws"
+    ));
+    let (mut socket, _) = connect_async(url.as_str())
+        .await
+        .with_context(|| format!("Failed to connect to {url}"))?;
+
+    let mut printer = LogPrinter::new(execution_process_id);
+    while let Some(msg) = socket.next().await {
+        let text = match msg? {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        if serde_json::from_str::<serde_json::Value>(&text)
+            .is_ok_and(|v| v.get("finished").is_some())
+        {
+            break;
+        }
+        if let Ok(LogMsg::JsonPatch(patch)) = serde_json::from_str::<LogMsg>(&text)
+            && let Some((index, entry)) = extract_normalized_entry_from_patch(&patch)
+        {
+            printer.on_entry(index, entry);
+        }
+    }
+    printer.flush();
+
+    let process: ExecutionProcess = client
+        .get(&format!("/api/execution-processes/{execution_process_id}"))
+        .await?;
+    println!("--- {:?}", process.status);
+    Ok(match process.status {
+        ExecutionProcessStatus::Completed if process.exit_code.unwrap_or(0) == 0 => 0,
+        _ => 1,
+    })
+}
+
+/// Prints conversation entries as they settle. Messages are streamed by replacing
+/// the same entry, so an entry is only printed once a later one arrives; tool calls
+/// are printed immediately and again whenever their status changes.
+struct LogPrinter {
+    execution_process_id: Uuid,
+    held: Option<(usize, NormalizedEntry)>,
+    printed_tools: HashMap<usize, String>,
+}
+
+impl LogPrinter {
+    fn new(execution_process_id: Uuid) -> Self {
+        Self {
+            execution_process_id,
+            held: None,
+            printed_tools: HashMap::new(),
+        }
+    }
+
+    fn on_entry(&mut self, index: usize, entry: NormalizedEntry) {
+        if self.held.as_ref().is_some_and(|(i, _)| *i != index) {
+            self.flush();
+        }
+
+        let NormalizedEntryType::ToolUse {
+            tool_name, status, ..
+        } = &entry.entry_type
+        else {
+            self.held = Some((index, entry));
+            return;
+        };
+
+        let line = format!(
+            "[{tool_name}] {}{}",
+            entry.content,
+            tool_status_suffix(status)
+        );
+        if self.printed_tools.get(&index) == Some(&line) {
+            return;
+        }
+        println!("{line}");
+        if let ToolStatus::PendingApproval { approval_id, .. } = status {
+            println!(
+                "    respond with: cli approve|deny {approval_id} --process {}",
+                self.execution_process_id
+            );
+        }
+        self.printed_tools.insert(index, line);
+    }
+
+    fn flush(&mut self) {
+        let Some((_, entry)) = self.held.take() else {
+            return;
+        };
+        let content = entry.content.trim_end();
+        match &entry.entry_type {
+            NormalizedEntryType::UserMessage => println!("> {content}"),
+            NormalizedEntryType::UserFeedback { denied_tool } => {
+                println!("> ({denied_tool} denied) {content}")
+            }
+            NormalizedEntryType::AssistantMessage => println!("{content}"),
+            NormalizedEntryType::Thinking => println!("(thinking) {content}"),
+            NormalizedEntryType::SystemMessage => println!("[system] {content}"),
+            NormalizedEntryType::ErrorMessage { .. } => println!("[error] {content}"),
+            NormalizedEntryType::ToolUse { .. }
+            | NormalizedEntryType::Loading
+            | NormalizedEntryType::NextAction { .. } => {}
+        }
+    }
+}
+
+fn tool_status_suffix(status: &ToolStatus) -> String {
+    match status {
+        ToolStatus::Created => String::new(),
+        ToolStatus::Success => " (ok)".to_string(),
+        ToolStatus::Failed => " (failed)".to_string(),
+        ToolStatus::Denied {
+            reason: Some(reason),
+        } => format!(" (denied: {reason})"),
+        ToolStatus::Denied { reason: None } => " (denied)".to_string(),
+        ToolStatus::PendingApproval { approval_id, .. } => {
+            format!(" (awaiting approval {approval_id})")
+        }
+        ToolStatus::TimedOut => " (approval timed out)".to_string(),
+    }
+}
+
+fn approval_status_label(status: &ApprovalStatus) -> String {
+    match status {
+        ApprovalStatus::Pending => "pending".to_string(),
+        ApprovalStatus::Approved => "approved".to_string(),
+        ApprovalStatus::Denied {
+            reason: Some(reason),
+        } => format!("denied: {reason}"),
+        ApprovalStatus::Denied { reason: None } => "denied".to_string(),
+        ApprovalStatus::TimedOut => "timed out".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &[&str]) -> anyhow::Result<Args> {
+        Args::parse(raw.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn args_separate_positionals_flags_and_switches() {
+        let args = parse(&[
+            "start",
+            "--executor",
+            "codex",
+            "--follow",
+            "--base-branch=main",
+            "task",
+        ])
+        .unwrap();
+        assert_eq!(args.positional, ["start", "task"]);
+        assert_eq!(args.flag("executor"), Some("codex"));
+        assert_eq!(args.flag("base-branch"), Some("main"));
+        assert!(args.switch("follow"));
+        assert!(!args.switch("help"));
+        // A switch has no value of its own
+        assert_eq!(args.flag("follow"), None);
+    }
+
+    #[test]
+    fn args_reject_missing_and_invalid_values() {
+        assert!(parse(&["pr", "--title"]).is_err());
+
+        let args = parse(&["logs", "not-a-uuid", "--process", " ", "--reason="]).unwrap();
+        assert!(args.uuid(1, "attempt-id").is_err());
+        assert!(args.positional(2, "title").is_err());
+        // Blank values count as missing
+        assert!(args.required_flag("process").is_err());
+        assert_eq!(args.uuid_flag("process").unwrap(), None);
+        assert_eq!(args.flag("reason"), None);
+    }
+
+    #[test]
+    fn executor_profile_ids_accept_cli_spellings_and_variants() {
+        assert_eq!(
+            parse_executor_profile_id("claude-code").unwrap(),
+            ExecutorProfileId::new(BaseCodingAgent::ClaudeCode)
+        );
+        assert_eq!(
+            parse_executor_profile_id(" CODEX : high ").unwrap(),
+            ExecutorProfileId::with_variant(BaseCodingAgent::Codex, "HIGH".to_string())
+        );
+        // An empty variant is the default profile
+        assert_eq!(
+            parse_executor_profile_id("codex:").unwrap(),
+            ExecutorProfileId::new(BaseCodingAgent::Codex)
+        );
+        assert!(parse_executor_profile_id("cobol").is_err());
+    }
+}
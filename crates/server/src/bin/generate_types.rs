@@ -17,6 +17,24 @@ fn generate_types_content() -> String {
         db::models::project::UpdateProject::decl(),
         db::models::project::SearchResult::decl(),
         db::models::project::SearchMatchType::decl(),
+        db::models::project_sandbox_policy::ProjectSandboxPolicy::decl(),
+        db::models::project_sandbox_policy::UpsertProjectSandboxPolicy::decl(),
+        db::models::project_env::ProjectEnv::decl(),
+        db::models::project_env::UpsertProjectEnv::decl(),
+        server::routes::secrets::SetSecretRequest::decl(),
+        db::models::project_ci_settings::ProjectCiSettings::decl(),
+        db::models::project_ci_settings::UpsertProjectCiSettings::decl(),
+        db::models::project_merge_queue_settings::ProjectMergeQueueSettings::decl(),
+        db::models::project_merge_queue_settings::UpsertProjectMergeQueueSettings::decl(),
+        db::models::project_merge_settings::ProjectMergeSettings::decl(),
+        db::models::project_merge_settings::UpsertProjectMergeSettings::decl(),
+        db::models::project_merge_settings::MergeStrategy::decl(),
+        db::models::project_repository::ProjectRepository::decl(),
+        db::models::project_repository::CreateProjectRepository::decl(),
+        db::models::task_attempt_repository::TaskAttemptRepository::decl(),
+        executors::sandbox::SandboxPolicy::decl(),
+        executors::sandbox::SandboxBackend::decl(),
+        executors::sandbox::SandboxNetwork::decl(),
         server::routes::projects::CreateRemoteProjectRequest::decl(),
         server::routes::projects::LinkToExistingRequest::decl(),
         executors::actions::ExecutorAction::decl(),
@@ -37,6 +55,9 @@ fn generate_types_content() -> String {
         db::models::task::TaskRelationships::decl(),
         db::models::task::CreateTask::decl(),
         db::models::task::UpdateTask::decl(),
+        db::models::task_dependency::TaskDependency::decl(),
+        db::models::task_dependency::TaskAutoStart::decl(),
+        db::models::task_dependency::TaskDependencies::decl(),
         db::models::shared_task::SharedTask::decl(),
         db::models::image::Image::decl(),
         db::models::image::CreateImage::decl(),
@@ -86,6 +107,8 @@ fn generate_types_content() -> String {
         server::routes::shared_tasks::AssignSharedTaskResponse::decl(),
         server::routes::tasks::ShareTaskResponse::decl(),
         server::routes::tasks::CreateAndStartTaskRequest::decl(),
+        server::routes::tasks::CreateTaskDependencyRequest::decl(),
+        server::routes::tasks::SetTaskAutoStartRequest::decl(),
         server::routes::task_attempts::CreateGitHubPrRequest::decl(),
         server::routes::images::ImageResponse::decl(),
         services::services::github_service::GitHubServiceError::decl(),
@@ -95,6 +118,12 @@ fn generate_types_content() -> String {
         services::services::config::EditorConfig::decl(),
         services::services::config::EditorType::decl(),
         services::services::config::GitHubConfig::decl(),
+        services::services::config::GitHostConfig::decl(),
+        services::services::git_host::GitHostKind::decl(),
+        services::services::config::NotifierConfig::decl(),
+        services::services::config::NotifierTarget::decl(),
+        services::services::config::NotificationEventKind::decl(),
+        services::services::config::SmtpSecurity::decl(),
         services::services::config::SoundFile::decl(),
         services::services::config::UiLanguage::decl(),
         services::services::config::ShowcaseState::decl(),
@@ -102,6 +131,8 @@ fn generate_types_content() -> String {
         utils::diff::Diff::decl(),
         utils::diff::DiffChangeKind::decl(),
         services::services::github_service::RepositoryInfo::decl(),
+        services::services::github_service::PrReviewThread::decl(),
+        services::services::github_service::PrReviewComment::decl(),
         executors::command::CommandBuilder::decl(),
         executors::profile::ExecutorProfileId::decl(),
         executors::profile::ExecutorConfig::decl(),
@@ -118,6 +149,16 @@ fn generate_types_content() -> String {
         executors::executors::codex::ReasoningSummaryFormat::decl(),
         executors::executors::cursor::CursorAgent::decl(),
         executors::executors::copilot::Copilot::decl(),
+        executors::executors::custom::Custom::decl(),
+        executors::executors::custom::AdapterSpec::decl(),
+        executors::executors::custom::SessionIdSpec::decl(),
+        executors::executors::custom::OutputSpec::decl(),
+        executors::executors::custom::OutputFormat::decl(),
+        executors::executors::custom::OutputRule::decl(),
+        executors::executors::custom::LineMatcher::decl(),
+        executors::executors::custom::EntryTemplate::decl(),
+        executors::executors::custom::ActionTemplate::decl(),
+        executors::executors::acp_agent::AcpAgent::decl(),
         executors::executors::opencode::Opencode::decl(),
         executors::executors::qwen::QwenCode::decl(),
         executors::executors::AppendPrompt::decl(),
@@ -132,16 +173,49 @@ fn generate_types_content() -> String {
         server::routes::task_attempts::ReplaceProcessRequest::decl(),
         server::routes::task_attempts::CommitInfo::decl(),
         server::routes::task_attempts::BranchStatus::decl(),
+        server::routes::task_attempts::MergePreview::decl(),
+        server::routes::task_attempts::repositories::AttemptRepositoryDiff::decl(),
+        server::routes::task_attempts::fork::ForkTaskAttemptRequest::decl(),
+        services::services::attempt_fork::AttemptForkTree::decl(),
         services::services::git::ConflictOp::decl(),
+        db::models::conflict_resolution::ConflictResolution::decl(),
         db::models::task_attempt::TaskAttempt::decl(),
+        db::models::task_attempt_stack::TaskAttemptStack::decl(),
+        db::models::task_attempt_fork::TaskAttemptFork::decl(),
+        db::models::merge_queue_entry::MergeQueueEntry::decl(),
+        db::models::merge_queue_entry::MergeQueueStatus::decl(),
+        db::models::task_attempt_race::TaskAttemptRace::decl(),
+        db::models::task_attempt_race::TaskAttemptRaceEntry::decl(),
+        server::routes::task_attempts::race::CreateRaceAttemptsBody::decl(),
+        server::routes::task_attempts::race::RaceAttemptsResponse::decl(),
+        server::routes::task_attempts::race::RaceAttemptComparison::decl(),
+        server::routes::task_attempts::race::RaceComparisonResponse::decl(),
+        server::routes::task_attempts::race::PickRaceWinnerRequest::decl(),
+        server::routes::task_attempts::pr_review::CreateReviewDraftRequest::decl(),
         db::models::execution_process::ExecutionProcess::decl(),
         db::models::execution_process::ExecutionProcessStatus::decl(),
         db::models::execution_process::ExecutionProcessRunReason::decl(),
+        db::models::execution_process::ExecutionProcessStatusReason::decl(),
+        db::models::execution_budget::BudgetLimits::decl(),
+        db::models::execution_budget::ProjectBudget::decl(),
+        db::models::execution_budget::ExecutorProfileBudget::decl(),
+        server::routes::config::UpdateExecutorProfileBudget::decl(),
+        db::models::execution_process_usage::ExecutionProcessUsage::decl(),
+        db::models::execution_process_usage::ModelUsage::decl(),
+        db::models::execution_process_usage::UsageSummary::decl(),
+        db::models::approval::PolicyDecision::decl(),
+        db::models::approval::ApprovalDecider::decl(),
+        db::models::approval::ApprovalOutcome::decl(),
+        db::models::approval::Approval::decl(),
+        db::models::approval::ApprovalFilter::decl(),
         db::models::merge::Merge::decl(),
         db::models::merge::DirectMerge::decl(),
         db::models::merge::PrMerge::decl(),
         db::models::merge::MergeStatus::decl(),
         db::models::merge::PullRequestInfo::decl(),
+        db::models::pr_check_summary::CheckState::decl(),
+        db::models::pr_check_summary::CheckRun::decl(),
+        db::models::pr_check_summary::PrChecks::decl(),
         db::models::draft::Draft::decl(),
         db::models::draft::DraftType::decl(),
         executors::logs::CommandExitStatus::decl(),
@@ -232,6 +306,14 @@ fn generate_schemas() -> Result<HashMap<&'static str, String>, serde_json::Error
             "copilot",
             generate_json_schema::<executors::executors::copilot::Copilot>()?,
         ),
+        (
+            "custom",
+            generate_json_schema::<executors::executors::custom::Custom>()?,
+        ),
+        (
+            "acp_agent",
+            generate_json_schema::<executors::executors::acp_agent::AcpAgent>()?,
+        ),
     ]);
     println!(
         "✅ JSON schemas generated. {} schemas created.",
@@ -11,9 +11,14 @@ use deployment::{DeploymentError, RemoteClientNotConfigured};
 use executors::executors::ExecutorError;
 use git2::Error as Git2Error;
 use services::services::{
-    config::ConfigError, container::ContainerError, drafts::DraftsServiceError,
-    git::GitServiceError, github_service::GitHubServiceError, image::ImageError,
-    remote_client::RemoteClientError, share::ShareError, worktree_manager::WorktreeError,
+    attempt_fork::AttemptForkError, attempt_race::AttemptRaceError,
+    attempt_stack::AttemptStackError, config::ConfigError,
+    conflict_resolution::ConflictResolutionError, container::ContainerError,
+    drafts::DraftsServiceError, git::GitServiceError, git_host::GitHostError,
+    github_service::GitHubServiceError, image::ImageError, merge_queue::MergeQueueError,
+    merge_strategy::MergeStrategyError, pr_monitor::PrMonitorError,
+    remote_client::RemoteClientError, secrets::SecretStoreError, share::ShareError,
+    worktree_manager::WorktreeError,
 };
 use thiserror::Error;
 use utils::response::ApiResponse;
@@ -32,6 +37,8 @@ pub enum ApiError {
     #[error(transparent)]
     GitHubService(#[from] GitHubServiceError),
     #[error(transparent)]
+    GitHost(#[from] GitHostError),
+    #[error(transparent)]
     Deployment(#[from] DeploymentError),
     #[error(transparent)]
     Container(#[from] ContainerError),
@@ -103,6 +110,13 @@ impl IntoResponse for ApiError {
                 _ => (StatusCode::INTERNAL_SERVER_ERROR, "GitServiceError"),
             },
             ApiError::GitHubService(_) => (StatusCode::INTERNAL_SERVER_ERROR, "GitHubServiceError"),
+            ApiError::GitHost(host_err) => match host_err {
+                GitHostError::GitHub(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "GitHubServiceError")
+                }
+                GitHostError::UnsupportedRemote(_) => (StatusCode::BAD_REQUEST, "GitHostError"),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "GitHostError"),
+            },
             ApiError::Deployment(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DeploymentError"),
             ApiError::Container(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ContainerError"),
             ApiError::Executor(_) => (StatusCode::INTERNAL_SERVER_ERROR, "ExecutorError"),
@@ -304,3 +318,102 @@ impl From<ShareError> for ApiError {
         }
     }
 }
+
+impl From<PrMonitorError> for ApiError {
+    fn from(err: PrMonitorError) -> Self {
+        match err {
+            PrMonitorError::GitHost(err) => ApiError::GitHost(err),
+            PrMonitorError::TaskAttemptError(err) => ApiError::TaskAttempt(err),
+            PrMonitorError::Sqlx(err) => ApiError::Database(err),
+        }
+    }
+}
+
+impl From<AttemptStackError> for ApiError {
+    fn from(err: AttemptStackError) -> Self {
+        match err {
+            AttemptStackError::GitService(err) => ApiError::GitService(err),
+            AttemptStackError::Container(err) => ApiError::Container(err),
+            AttemptStackError::GitHost(err) => ApiError::GitHost(err),
+            AttemptStackError::TaskAttemptError(err) => ApiError::TaskAttempt(err),
+            AttemptStackError::Sqlx(err) => ApiError::Database(err),
+        }
+    }
+}
+
+impl From<AttemptRaceError> for ApiError {
+    fn from(err: AttemptRaceError) -> Self {
+        match err {
+            AttemptRaceError::Container(err) => ApiError::Container(err),
+            AttemptRaceError::TaskAttemptError(err) => ApiError::TaskAttempt(err),
+            AttemptRaceError::Sqlx(err) => ApiError::Database(err),
+            AttemptRaceError::TooFewExecutors | AttemptRaceError::NotInRace => {
+                ApiError::BadRequest(err.to_string())
+            }
+            AttemptRaceError::WinnerAlreadyPicked => ApiError::Conflict(err.to_string()),
+        }
+    }
+}
+
+impl From<AttemptForkError> for ApiError {
+    fn from(err: AttemptForkError) -> Self {
+        match err {
+            AttemptForkError::GitService(err) => ApiError::GitService(err),
+            AttemptForkError::Container(err) => ApiError::Container(err),
+            AttemptForkError::TaskAttemptError(err) => ApiError::TaskAttempt(err),
+            AttemptForkError::Sqlx(err) => ApiError::Database(err),
+            AttemptForkError::ProcessNotFound | AttemptForkError::NotForkable(_) => {
+                ApiError::BadRequest(err.to_string())
+            }
+        }
+    }
+}
+
+impl From<ConflictResolutionError> for ApiError {
+    fn from(err: ConflictResolutionError) -> Self {
+        match err {
+            ConflictResolutionError::GitService(err) => ApiError::GitService(err),
+            ConflictResolutionError::Container(err) => ApiError::Container(err),
+            ConflictResolutionError::ExecutionProcess(err) => ApiError::ExecutionProcess(err),
+            ConflictResolutionError::Sqlx(err) => ApiError::Database(err),
+            ConflictResolutionError::NoConflicts => ApiError::BadRequest(err.to_string()),
+            ConflictResolutionError::ProcessRunning => ApiError::Conflict(err.to_string()),
+        }
+    }
+}
+
+impl From<MergeQueueError> for ApiError {
+    fn from(err: MergeQueueError) -> Self {
+        match err {
+            MergeQueueError::GitService(err) => ApiError::GitService(err),
+            MergeQueueError::Container(err) => ApiError::Container(err),
+            MergeQueueError::TaskAttemptError(err) => ApiError::TaskAttempt(err),
+            MergeQueueError::Drafts(err) => ApiError::Drafts(err),
+            MergeQueueError::Sqlx(err) => ApiError::Database(err),
+            MergeQueueError::MergeStrategy(err) => err.into(),
+            MergeQueueError::AlreadyQueued => ApiError::Conflict(err.to_string()),
+            MergeQueueError::NotQueued => ApiError::BadRequest(err.to_string()),
+        }
+    }
+}
+
+impl From<SecretStoreError> for ApiError {
+    fn from(err: SecretStoreError) -> Self {
+        match err {
+            SecretStoreError::Io(err) => ApiError::Io(err),
+            SecretStoreError::InvalidName(_) => ApiError::BadRequest(err.to_string()),
+            _ => ApiError::Io(std::io::Error::other(err)),
+        }
+    }
+}
+
+impl From<MergeStrategyError> for ApiError {
+    fn from(err: MergeStrategyError) -> Self {
+        match err {
+            MergeStrategyError::GitService(err) => ApiError::GitService(err),
+            MergeStrategyError::Sqlx(err) => ApiError::Database(err),
+            MergeStrategyError::RepositoryConflicts(_)
+            | MergeStrategyError::RepositoryFailed { .. } => ApiError::Conflict(err.to_string()),
+        }
+    }
+}
@@ -1,11 +1,16 @@
-use std::{future::Future, path::PathBuf, str::FromStr};
+use std::{collections::BTreeMap, future::Future, path::PathBuf, str::FromStr};
 
 use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason},
     project::Project,
     task::{CreateTask, Task, TaskStatus, TaskWithAttemptStatus, UpdateTask},
     task_attempt::TaskAttempt,
 };
-use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
+use executors::{
+    executors::BaseCodingAgent,
+    logs::{NormalizedEntry, NormalizedEntryType, ToolStatus},
+    profile::ExecutorProfileId,
+};
 use rmcp::{
     ErrorData, ServerHandler,
     handler::server::tool::{Parameters, ToolRouter},
@@ -16,9 +21,29 @@ use rmcp::{
 };
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json;
+use services::services::git::GitService;
+use utils::{
+    approvals::{ApprovalResponse, ApprovalStatus},
+    diff::Diff,
+};
 use uuid::Uuid;
 
-use crate::routes::task_attempts::CreateTaskAttemptBody;
+use crate::routes::task_attempts::{
+    BranchStatus, CreateFollowUpAttempt, CreateGitHubPrRequest, CreateTaskAttemptBody,
+};
+
+/// The serialized name of a unit variant, or the `tag` field of an internally tagged one
+fn enum_label<T: Serialize>(value: &T, tag: &str) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(label)) => label,
+        Ok(v) => v
+            .get(tag)
+            .and_then(|t| t.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        Err(_) => String::new(),
+    }
+}
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct CreateTaskRequest {
@@ -234,6 +259,226 @@ pub struct GetTaskResponse {
     pub task: TaskDetails,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ListTaskAttemptsRequest {
+    #[schemars(description = "The ID of the task whose attempts to list")]
+    pub task_id: Uuid,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct TaskAttemptSummary {
+    #[schemars(description = "The unique identifier of the attempt")]
+    pub id: String,
+    #[schemars(description = "The coding agent executor used by the attempt")]
+    pub executor: String,
+    #[schemars(description = "The git branch the attempt works on")]
+    pub branch: String,
+    #[schemars(description = "The branch the attempt will be merged into")]
+    pub target_branch: String,
+    #[schemars(description = "When the attempt was created")]
+    pub created_at: String,
+    #[schemars(description = "When the attempt was last updated")]
+    pub updated_at: String,
+}
+
+impl TaskAttemptSummary {
+    fn from_attempt(attempt: TaskAttempt) -> Self {
+        Self {
+            id: attempt.id.to_string(),
+            executor: attempt.executor,
+            branch: attempt.branch,
+            target_branch: attempt.target_branch,
+            created_at: attempt.created_at.to_rfc3339(),
+            updated_at: attempt.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ListTaskAttemptsResponse {
+    pub attempts: Vec<TaskAttemptSummary>,
+    pub count: usize,
+    pub task_id: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetAttemptConversationRequest {
+    #[schemars(description = "The ID of the task attempt")]
+    pub attempt_id: Uuid,
+    #[schemars(description = "Maximum number of most recent entries to return (default: 50)")]
+    pub limit: Option<i32>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct ConversationEntry {
+    #[schemars(description = "Position of the entry in the conversation")]
+    pub index: usize,
+    #[schemars(
+        description = "Entry type, e.g. 'user_message', 'assistant_message', 'tool_use', 'error_message'"
+    )]
+    pub entry_type: String,
+    pub content: String,
+    #[schemars(description = "Tool name, for tool_use entries")]
+    pub tool_name: Option<String>,
+    #[schemars(
+        description = "Tool status, for tool_use entries: 'created', 'success', 'failed', 'denied', 'pending_approval', 'timed_out'"
+    )]
+    pub tool_status: Option<String>,
+    #[schemars(description = "Approval ID to pass to `respond_to_approval`, if pending")]
+    pub approval_id: Option<String>,
+}
+
+impl ConversationEntry {
+    fn from_entry(index: usize, entry: NormalizedEntry) -> Self {
+        let entry_type = enum_label(&entry.entry_type, "type");
+        let (tool_name, tool_status, approval_id) = match entry.entry_type {
+            NormalizedEntryType::ToolUse {
+                tool_name, status, ..
+            } => {
+                let approval_id = match &status {
+                    ToolStatus::PendingApproval { approval_id, .. } => Some(approval_id.clone()),
+                    _ => None,
+                };
+                (
+                    Some(tool_name),
+                    Some(enum_label(&status, "status")),
+                    approval_id,
+                )
+            }
+            _ => (None, None, None),
+        };
+        Self {
+            index,
+            entry_type,
+            content: entry.content,
+            tool_name,
+            tool_status,
+            approval_id,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct GetAttemptConversationResponse {
+    pub attempt_id: String,
+    pub execution_process_id: String,
+    #[schemars(description = "Process status: 'running', 'completed', 'failed', 'killed'")]
+    pub status: String,
+    pub entries: Vec<ConversationEntry>,
+    #[schemars(description = "Total number of entries in the conversation")]
+    pub total_entries: usize,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetAttemptDiffRequest {
+    #[schemars(description = "The ID of the task attempt")]
+    pub attempt_id: Uuid,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct DiffFileSummary {
+    pub path: String,
+    #[schemars(
+        description = "'added', 'deleted', 'modified', 'renamed', 'copied' or 'permissionChange'"
+    )]
+    pub change: String,
+    pub additions: Option<usize>,
+    pub deletions: Option<usize>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct GetAttemptDiffResponse {
+    pub attempt_id: String,
+    pub target_branch: String,
+    pub commits_ahead: Option<usize>,
+    pub commits_behind: Option<usize>,
+    pub has_uncommitted_changes: Option<bool>,
+    pub conflicted_files: Vec<String>,
+    pub files: Vec<DiffFileSummary>,
+    pub total_additions: usize,
+    pub total_deletions: usize,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SendFollowUpRequest {
+    #[schemars(description = "The ID of the task attempt to continue")]
+    pub attempt_id: Uuid,
+    #[schemars(description = "The follow-up instructions for the coding agent")]
+    pub prompt: String,
+    #[schemars(description = "Optional executor variant to use for the follow-up")]
+    pub variant: Option<String>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct SendFollowUpResponse {
+    pub attempt_id: String,
+    pub execution_process_id: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct StopExecutionRequest {
+    #[schemars(
+        description = "The ID of the execution process to stop. Either this or `attempt_id` is required"
+    )]
+    pub execution_process_id: Option<Uuid>,
+    #[schemars(description = "Stop every running process of this task attempt instead")]
+    pub attempt_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct StopExecutionResponse {
+    pub stopped: bool,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct RespondToApprovalRequest {
+    #[schemars(description = "The approval ID reported on the pending tool call")]
+    pub approval_id: String,
+    #[schemars(description = "The ID of the execution process that requested approval")]
+    pub execution_process_id: Uuid,
+    #[schemars(description = "True to allow the tool call, false to deny it")]
+    pub approve: bool,
+    #[schemars(description = "Optional reason passed back to the agent when denying")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct RespondToApprovalResponse {
+    pub approval_id: String,
+    #[schemars(description = "Resulting status: 'approved', 'denied', 'timed_out' or 'pending'")]
+    pub status: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MergeTaskAttemptRequest {
+    #[schemars(description = "The ID of the task attempt to merge into its target branch")]
+    pub attempt_id: Uuid,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct MergeTaskAttemptResponse {
+    pub attempt_id: String,
+    pub merged: bool,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CreatePullRequestRequest {
+    #[schemars(description = "The ID of the task attempt to open a pull request for")]
+    pub attempt_id: Uuid,
+    #[schemars(description = "The pull request title")]
+    pub title: String,
+    #[schemars(description = "Optional pull request body")]
+    pub body: Option<String>,
+    #[schemars(description = "Optional base branch; defaults to the attempt's target branch")]
+    pub target_branch: Option<String>,
+}
+
+#[derive(Debug, Serialize, schemars::JsonSchema)]
+pub struct CreatePullRequestResponse {
+    pub attempt_id: String,
+    pub pr_url: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct TaskServer {
     client: reqwest::Client,
@@ -252,9 +497,10 @@ impl TaskServer {
 }
 
 #[derive(Debug, Deserialize)]
-struct ApiResponseEnvelope<T> {
+struct ApiResponseEnvelope {
     success: bool,
-    data: Option<T>,
+    #[serde(default)]
+    data: serde_json::Value,
     message: Option<String>,
 }
 
@@ -297,7 +543,7 @@ impl TaskServer {
             );
         }
 
-        let api_response = resp.json::<ApiResponseEnvelope<T>>().await.map_err(|e| {
+        let api_response = resp.json::<ApiResponseEnvelope>().await.map_err(|e| {
             Self::err("Failed to parse VK API response", Some(&e.to_string())).unwrap()
         })?;
 
@@ -306,9 +552,10 @@ impl TaskServer {
             return Err(Self::err("VK API returned error", Some(msg)).unwrap());
         }
 
-        api_response
-            .data
-            .ok_or_else(|| Self::err("VK API response missing data field", None).unwrap())
+        // Routes without a payload answer with `data: null`, which is valid for `()`
+        serde_json::from_value(api_response.data).map_err(|e| {
+            Self::err("VK API response has unexpected data", Some(&e.to_string())).unwrap()
+        })
     }
 
     fn url(&self, path: &str) -> String {
@@ -318,6 +565,28 @@ impl TaskServer {
             path.trim_start_matches('/')
         )
     }
+
+    async fn latest_coding_agent_process(
+        &self,
+        attempt_id: Uuid,
+    ) -> Result<ExecutionProcess, CallToolResult> {
+        let url = self.url(&format!(
+            "/api/execution-processes?task_attempt_id={}",
+            attempt_id
+        ));
+        let processes: Vec<ExecutionProcess> = self.send_json(self.client.get(&url)).await?;
+        processes
+            .into_iter()
+            .rev()
+            .find(|p| p.run_reason == ExecutionProcessRunReason::CodingAgent)
+            .ok_or_else(|| {
+                Self::err(
+                    "Task attempt has no coding agent executions".to_string(),
+                    Some(attempt_id.to_string()),
+                )
+                .unwrap()
+            })
+    }
 }
 
 #[tool_router]
@@ -485,6 +754,7 @@ impl TaskServer {
             task_id,
             executor_profile_id,
             base_branch,
+            parent_attempt_id: None,
         };
 
         let url = self.url("/api/task-attempts");
@@ -586,6 +856,272 @@ impl TaskServer {
 
         TaskServer::success(&response)
     }
+
+    #[tool(
+        description = "List the attempts (agent runs on their own branch) of a task/ticket, oldest first. `task_id` is required!"
+    )]
+    async fn list_task_attempts(
+        &self,
+        Parameters(ListTaskAttemptsRequest { task_id }): Parameters<ListTaskAttemptsRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let url = self.url(&format!("/api/task-attempts?task_id={}", task_id));
+        let attempts: Vec<TaskAttempt> = match self.send_json(self.client.get(&url)).await {
+            Ok(a) => a,
+            Err(e) => return Ok(e),
+        };
+
+        let attempts: Vec<TaskAttemptSummary> = attempts
+            .into_iter()
+            .map(TaskAttemptSummary::from_attempt)
+            .collect();
+
+        TaskServer::success(&ListTaskAttemptsResponse {
+            count: attempts.len(),
+            attempts,
+            task_id: task_id.to_string(),
+        })
+    }
+
+    #[tool(
+        description = "Read the conversation (messages, tool calls, errors) of the latest coding agent execution of a task attempt. Tool calls waiting for approval carry an `approval_id`. `attempt_id` is required!"
+    )]
+    async fn get_attempt_conversation(
+        &self,
+        Parameters(GetAttemptConversationRequest { attempt_id, limit }): Parameters<
+            GetAttemptConversationRequest,
+        >,
+    ) -> Result<CallToolResult, ErrorData> {
+        let process = match self.latest_coding_agent_process(attempt_id).await {
+            Ok(p) => p,
+            Err(e) => return Ok(e),
+        };
+
+        let url = self.url(&format!(
+            "/api/execution-processes/{}/normalized-entries",
+            process.id
+        ));
+        let entries: Vec<NormalizedEntry> = match self.send_json(self.client.get(&url)).await {
+            Ok(e) => e,
+            Err(e) => return Ok(e),
+        };
+
+        let total_entries = entries.len();
+        let limit = limit.unwrap_or(50).max(0) as usize;
+        let entries: Vec<ConversationEntry> = entries
+            .into_iter()
+            .enumerate()
+            .skip(total_entries.saturating_sub(limit))
+            .map(|(index, entry)| ConversationEntry::from_entry(index, entry))
+            .collect();
+
+        TaskServer::success(&GetAttemptConversationResponse {
+            attempt_id: attempt_id.to_string(),
+            execution_process_id: process.id.to_string(),
+            status: enum_label(&process.status, ""),
+            entries,
+            total_entries,
+        })
+    }
+
+    #[tool(
+        description = "Summarize the changes made by a task attempt: changed files with line counts, commits ahead/behind the target branch and any conflicts. `attempt_id` is required!"
+    )]
+    async fn get_attempt_diff(
+        &self,
+        Parameters(GetAttemptDiffRequest { attempt_id }): Parameters<GetAttemptDiffRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let url = self.url(&format!("/api/task-attempts/{}/branch-status", attempt_id));
+        let branch_status: BranchStatus = match self.send_json(self.client.get(&url)).await {
+            Ok(s) => s,
+            Err(e) => return Ok(e),
+        };
+
+        let url = self.url(&format!("/api/task-attempts/{}/diff/stats", attempt_id));
+        let diffs: Vec<Diff> = match self.send_json(self.client.get(&url)).await {
+            Ok(d) => d,
+            Err(e) => return Ok(e),
+        };
+        let diffs: BTreeMap<String, Diff> = diffs
+            .into_iter()
+            .map(|diff| (GitService::diff_path(&diff), diff))
+            .collect();
+
+        let files: Vec<DiffFileSummary> = diffs
+            .into_iter()
+            .map(|(path, diff)| DiffFileSummary {
+                path,
+                change: enum_label(&diff.change, ""),
+                additions: diff.additions,
+                deletions: diff.deletions,
+            })
+            .collect();
+
+        TaskServer::success(&GetAttemptDiffResponse {
+            attempt_id: attempt_id.to_string(),
+            target_branch: branch_status.target_branch_name,
+            commits_ahead: branch_status.commits_ahead,
+            commits_behind: branch_status.commits_behind,
+            has_uncommitted_changes: branch_status.has_uncommitted_changes,
+            conflicted_files: branch_status.conflicted_files,
+            total_additions: files.iter().filter_map(|f| f.additions).sum(),
+            total_deletions: files.iter().filter_map(|f| f.deletions).sum(),
+            files,
+        })
+    }
+
+    #[tool(
+        description = "Send follow-up instructions to the coding agent of a task attempt, continuing its previous session. `attempt_id` and `prompt` are required!"
+    )]
+    async fn send_follow_up(
+        &self,
+        Parameters(SendFollowUpRequest {
+            attempt_id,
+            prompt,
+            variant,
+        }): Parameters<SendFollowUpRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if prompt.trim().is_empty() {
+            return Self::err("Prompt must not be empty.".to_string(), None::<String>);
+        }
+
+        let payload = CreateFollowUpAttempt {
+            prompt,
+            variant: variant.filter(|v| !v.trim().is_empty()),
+            image_ids: None,
+            retry_process_id: None,
+            force_when_dirty: None,
+            perform_git_reset: None,
+        };
+        let url = self.url(&format!("/api/task-attempts/{}/follow-up", attempt_id));
+        let process: ExecutionProcess =
+            match self.send_json(self.client.post(&url).json(&payload)).await {
+                Ok(p) => p,
+                Err(e) => return Ok(e),
+            };
+
+        TaskServer::success(&SendFollowUpResponse {
+            attempt_id: process.task_attempt_id.to_string(),
+            execution_process_id: process.id.to_string(),
+        })
+    }
+
+    #[tool(
+        description = "Stop a running execution. Pass either `execution_process_id` to stop one process or `attempt_id` to stop everything running for a task attempt."
+    )]
+    async fn stop_execution(
+        &self,
+        Parameters(StopExecutionRequest {
+            execution_process_id,
+            attempt_id,
+        }): Parameters<StopExecutionRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let url = match (execution_process_id, attempt_id) {
+            (Some(id), None) => self.url(&format!("/api/execution-processes/{}/stop", id)),
+            (None, Some(id)) => self.url(&format!("/api/task-attempts/{}/stop", id)),
+            _ => {
+                return Self::err(
+                    "Pass exactly one of `execution_process_id` or `attempt_id`.".to_string(),
+                    None::<String>,
+                );
+            }
+        };
+        if let Err(e) = self.send_json::<()>(self.client.post(&url)).await {
+            return Ok(e);
+        }
+
+        TaskServer::success(&StopExecutionResponse { stopped: true })
+    }
+
+    #[tool(
+        description = "Approve or deny a tool call that a coding agent is waiting on. The `approval_id` and `execution_process_id` come from `get_attempt_conversation`."
+    )]
+    async fn respond_to_approval(
+        &self,
+        Parameters(RespondToApprovalRequest {
+            approval_id,
+            execution_process_id,
+            approve,
+            reason,
+        }): Parameters<RespondToApprovalRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let payload = ApprovalResponse {
+            execution_process_id,
+            status: if approve {
+                ApprovalStatus::Approved
+            } else {
+                ApprovalStatus::Denied { reason }
+            },
+        };
+
+        // This route answers with the bare status rather than an ApiResponse envelope
+        let url = self.url(&format!("/api/approvals/{}/respond", approval_id));
+        let resp = match self.client.post(&url).json(&payload).send().await {
+            Ok(r) => r,
+            Err(e) => return Self::err("Failed to connect to VK API", Some(&e.to_string())),
+        };
+        if !resp.status().is_success() {
+            return Self::err(
+                format!("VK API returned error status: {}", resp.status()),
+                None,
+            );
+        }
+        let status: ApprovalStatus = match resp.json().await {
+            Ok(s) => s,
+            Err(e) => return Self::err("Failed to parse VK API response", Some(&e.to_string())),
+        };
+
+        TaskServer::success(&RespondToApprovalResponse {
+            approval_id,
+            status: enum_label(&status, "status"),
+        })
+    }
+
+    #[tool(
+        description = "Merge a task attempt's branch into its target branch. `attempt_id` is required!"
+    )]
+    async fn merge_task_attempt(
+        &self,
+        Parameters(MergeTaskAttemptRequest { attempt_id }): Parameters<MergeTaskAttemptRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let url = self.url(&format!("/api/task-attempts/{}/merge", attempt_id));
+        if let Err(e) = self.send_json::<()>(self.client.post(&url)).await {
+            return Ok(e);
+        }
+
+        TaskServer::success(&MergeTaskAttemptResponse {
+            attempt_id: attempt_id.to_string(),
+            merged: true,
+        })
+    }
+
+    #[tool(
+        description = "Push a task attempt's branch and open a GitHub pull request for it. `attempt_id` and `title` are required!"
+    )]
+    async fn create_pull_request(
+        &self,
+        Parameters(CreatePullRequestRequest {
+            attempt_id,
+            title,
+            body,
+            target_branch,
+        }): Parameters<CreatePullRequestRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let payload = CreateGitHubPrRequest {
+            title,
+            body,
+            target_branch: target_branch.filter(|b| !b.trim().is_empty()),
+        };
+        let url = self.url(&format!("/api/task-attempts/{}/pr", attempt_id));
+        let pr_url: String = match self.send_json(self.client.post(&url).json(&payload)).await {
+            Ok(u) => u,
+            Err(e) => return Ok(e),
+        };
+
+        TaskServer::success(&CreatePullRequestResponse {
+            attempt_id: attempt_id.to_string(),
+            pr_url,
+        })
+    }
 }
 
 #[tool_handler]
@@ -600,7 +1136,440 @@ impl ServerHandler for TaskServer {
                 name: "vibe-kanban".to_string(),
                 version: "1.0.0".to_string(),
             },
-            instructions: Some("A task and project management server. If you need to create or update tickets or tasks then use these tools. Most of them absolutely require that you pass the `project_id` of the project that you are currently working on. This should be provided to you. Call `list_tasks` to fetch the `task_ids` of all the tasks in a project`. TOOLS: 'list_projects', 'list_tasks', 'create_task', 'start_task_attempt', 'get_task', 'update_task', 'delete_task'. Make sure to pass `project_id` or `task_id` where required. You can use list tools to get the available ids.".to_string()),
+            instructions: Some("A task and project management server. If you need to create or update tickets or tasks then use these tools. Most of them absolutely require that you pass the `project_id` of the project that you are currently working on. This should be provided to you. Call `list_tasks` to fetch the `task_ids` of all the tasks in a project`. TOOLS: 'list_projects', 'list_tasks', 'create_task', 'start_task_attempt', 'get_task', 'update_task', 'delete_task', 'list_task_attempts', 'get_attempt_conversation', 'get_attempt_diff', 'send_follow_up', 'stop_execution', 'respond_to_approval', 'merge_task_attempt', 'create_pull_request'. Make sure to pass `project_id` or `task_id` where required. You can use list tools to get the available ids.".to_string()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Json, Router,
+        extract::Path,
+        routing::{get, post},
+    };
+    use chrono::Utc;
+    use db::models::execution_process::{ExecutionProcessStatus, ExecutorActionField};
+    use executors::logs::ActionType;
+    use utils::{diff::DiffChangeKind, response::ApiResponse};
+
+    use super::*;
+
+    /// Serve `router` on a random local port and return its base URL
+    async fn serve(router: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    /// Whether the tool reported an error, and the JSON it answered with
+    fn payload(result: CallToolResult) -> (bool, serde_json::Value) {
+        let result = serde_json::to_value(&result).unwrap();
+        let text = result["content"][0]["text"].as_str().unwrap();
+        (
+            result["isError"] == serde_json::json!(true),
+            serde_json::from_str(text).unwrap(),
+        )
+    }
+
+    fn attempt(task_id: Uuid) -> TaskAttempt {
+        TaskAttempt {
+            id: Uuid::new_v4(),
+            task_id,
+            container_ref: None,
+            branch: "vk/1234-add-checkout".to_string(),
+            target_branch: "main".to_string(),
+            executor: "CLAUDE_CODE".to_string(),
+            worktree_deleted: false,
+            setup_completed_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn process(task_attempt_id: Uuid, run_reason: ExecutionProcessRunReason) -> ExecutionProcess {
+        ExecutionProcess {
+            id: Uuid::new_v4(),
+            task_attempt_id,
+            run_reason,
+            executor_action: sqlx::types::Json(ExecutorActionField::Other(serde_json::json!({}))),
+            before_head_commit: None,
+            after_head_commit: None,
+            status: ExecutionProcessStatus::Running,
+            exit_code: None,
+            status_reason: None,
+            dropped: false,
+            started_at: Utc::now(),
+            completed_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn entry(entry_type: NormalizedEntryType, content: &str) -> NormalizedEntry {
+        NormalizedEntry {
+            timestamp: None,
+            entry_type,
+            content: content.to_string(),
+            metadata: None,
+        }
+    }
+
+    fn diff(change: DiffChangeKind, old_path: Option<&str>, new_path: Option<&str>) -> Diff {
+        Diff {
+            change,
+            old_path: old_path.map(str::to_string),
+            new_path: new_path.map(str::to_string),
+            old_content: None,
+            new_content: None,
+            content_omitted: true,
+            additions: Some(3),
+            deletions: Some(1),
+        }
+    }
+
+    fn branch_status() -> BranchStatus {
+        BranchStatus {
+            commits_behind: Some(1),
+            commits_ahead: Some(2),
+            has_uncommitted_changes: Some(false),
+            head_oid: None,
+            uncommitted_count: None,
+            untracked_count: None,
+            target_branch_name: "main".to_string(),
+            remote_commits_behind: None,
+            remote_commits_ahead: None,
+            merges: vec![],
+            is_rebase_in_progress: false,
+            conflict_op: None,
+            conflicted_files: vec!["src/cart.rs".to_string()],
+            conflict_resolution: None,
+            pr_checks: None,
+        }
+    }
+
+    /// A VK API with one attempt whose latest coding agent run waits on an approval
+    struct FakeApi {
+        attempt: TaskAttempt,
+        coding_agent: ExecutionProcess,
+    }
+
+    impl FakeApi {
+        fn new() -> Self {
+            let attempt = attempt(Uuid::new_v4());
+            let coding_agent = process(attempt.id, ExecutionProcessRunReason::CodingAgent);
+            Self {
+                attempt,
+                coding_agent,
+            }
+        }
+
+        async fn serve(&self) -> TaskServer {
+            let attempts = vec![self.attempt.clone()];
+            let processes = vec![
+                process(self.attempt.id, ExecutionProcessRunReason::SetupScript),
+                self.coding_agent.clone(),
+                process(self.attempt.id, ExecutionProcessRunReason::DevServer),
+            ];
+            let entries = vec![
+                entry(NormalizedEntryType::UserMessage, "Add a checkout page"),
+                entry(NormalizedEntryType::AssistantMessage, "Running the tests"),
+                entry(
+                    NormalizedEntryType::ToolUse {
+                        tool_name: "Bash".to_string(),
+                        action_type: ActionType::CommandRun {
+                            command: "cargo test".to_string(),
+                            result: None,
+                        },
+                        status: ToolStatus::PendingApproval {
+                            approval_id: "approval-1".to_string(),
+                            requested_at: Utc::now(),
+                            timeout_at: Utc::now(),
+                        },
+                    },
+                    "cargo test",
+                ),
+            ];
+            let diffs = vec![
+                diff(
+                    DiffChangeKind::Modified,
+                    Some("src/lib.rs"),
+                    Some("src/lib.rs"),
+                ),
+                diff(DiffChangeKind::Deleted, Some("src/cart.rs"), None),
+                diff(DiffChangeKind::Added, None, Some("src/checkout.rs")),
+            ];
+            let follow_up = process(self.attempt.id, ExecutionProcessRunReason::CodingAgent);
+
+            let router = Router::new()
+                .route(
+                    "/api/task-attempts",
+                    get(move || async move { Json(ApiResponse::<_>::success(attempts)) }),
+                )
+                .route(
+                    "/api/execution-processes",
+                    get(move || async move { Json(ApiResponse::<_>::success(processes)) }),
+                )
+                .route(
+                    "/api/execution-processes/{id}/normalized-entries",
+                    get(move || async move { Json(ApiResponse::<_>::success(entries)) }),
+                )
+                .route(
+                    "/api/execution-processes/{id}/stop",
+                    post(|| async { Json(ApiResponse::<()>::success(())) }),
+                )
+                .route(
+                    "/api/task-attempts/{id}/branch-status",
+                    get(|| async { Json(ApiResponse::<_>::success(branch_status())) }),
+                )
+                .route(
+                    "/api/task-attempts/{id}/diff/stats",
+                    get(move || async move { Json(ApiResponse::<_>::success(diffs)) }),
+                )
+                .route(
+                    "/api/task-attempts/{id}/follow-up",
+                    post(move || async move { Json(ApiResponse::<_>::success(follow_up)) }),
+                )
+                .route(
+                    "/api/task-attempts/{id}/stop",
+                    post(|| async { Json(ApiResponse::<()>::success(())) }),
+                )
+                .route(
+                    "/api/task-attempts/{id}/merge",
+                    post(|| async { Json(ApiResponse::<()>::error("Merge conflicts")) }),
+                )
+                .route(
+                    "/api/task-attempts/{id}/pr",
+                    post(|Json(request): Json<CreateGitHubPrRequest>| async move {
+                        let url = format!(
+                            "https://github.com/owner/repo/pull/7?base={}",
+                            request.target_branch.unwrap_or_default()
+                        );
+                        Json(ApiResponse::<_>::success(url))
+                    }),
+                )
+                .route(
+                    "/api/approvals/{id}/respond",
+                    post(
+                        |Path(_id): Path<String>, Json(response): Json<ApprovalResponse>| async move {
+                            Json(response.status)
+                        },
+                    ),
+                );
+            TaskServer::new(&serve(router).await)
+        }
+    }
+
+    #[tokio::test]
+    async fn list_task_attempts_summarizes_each_attempt() {
+        let api = FakeApi::new();
+        let server = api.serve().await;
+
+        let result = server
+            .list_task_attempts(Parameters(ListTaskAttemptsRequest {
+                task_id: api.attempt.task_id,
+            }))
+            .await
+            .unwrap();
+        let (is_error, body) = payload(result);
+
+        assert!(!is_error);
+        assert_eq!(body["count"], 1);
+        assert_eq!(body["attempts"][0]["id"], api.attempt.id.to_string());
+        assert_eq!(body["attempts"][0]["branch"], "vk/1234-add-checkout");
+        assert_eq!(body["attempts"][0]["target_branch"], "main");
+    }
+
+    #[tokio::test]
+    async fn get_attempt_conversation_returns_the_latest_agent_run_tail() {
+        let api = FakeApi::new();
+        let server = api.serve().await;
+
+        let result = server
+            .get_attempt_conversation(Parameters(GetAttemptConversationRequest {
+                attempt_id: api.attempt.id,
+                limit: Some(2),
+            }))
+            .await
+            .unwrap();
+        let (is_error, body) = payload(result);
+
+        assert!(!is_error);
+        assert_eq!(
+            body["execution_process_id"],
+            api.coding_agent.id.to_string()
+        );
+        assert_eq!(body["status"], "running");
+        assert_eq!(body["total_entries"], 3);
+        let entries = body["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["index"], 1);
+        assert_eq!(entries[0]["entry_type"], "assistant_message");
+        assert_eq!(entries[1]["index"], 2);
+        assert_eq!(entries[1]["tool_name"], "Bash");
+        assert_eq!(entries[1]["tool_status"], "pending_approval");
+        assert_eq!(entries[1]["approval_id"], "approval-1");
+    }
+
+    #[tokio::test]
+    async fn get_attempt_diff_combines_branch_status_and_file_stats() {
+        let api = FakeApi::new();
+        let server = api.serve().await;
+
+        let result = server
+            .get_attempt_diff(Parameters(GetAttemptDiffRequest {
+                attempt_id: api.attempt.id,
+            }))
+            .await
+            .unwrap();
+        let (is_error, body) = payload(result);
+
+        assert!(!is_error);
+        assert_eq!(body["commits_ahead"], 2);
+        assert_eq!(body["commits_behind"], 1);
+        assert_eq!(body["conflicted_files"], serde_json::json!(["src/cart.rs"]));
+        let paths: Vec<&str> = body["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["path"].as_str().unwrap())
+            .collect();
+        // Deleted files are listed under their old path
+        assert_eq!(paths, ["src/cart.rs", "src/checkout.rs", "src/lib.rs"]);
+        assert_eq!(body["files"][0]["change"], "deleted");
+        assert_eq!(body["total_additions"], 9);
+        assert_eq!(body["total_deletions"], 3);
+    }
+
+    #[tokio::test]
+    async fn send_follow_up_requires_a_prompt() {
+        let api = FakeApi::new();
+        let server = api.serve().await;
+
+        let (is_error, _) = payload(
+            server
+                .send_follow_up(Parameters(SendFollowUpRequest {
+                    attempt_id: api.attempt.id,
+                    prompt: "  ".to_string(),
+                    variant: None,
+                }))
+                .await
+                .unwrap(),
+        );
+        assert!(is_error);
+
+        let (is_error, body) = payload(
+            server
+                .send_follow_up(Parameters(SendFollowUpRequest {
+                    attempt_id: api.attempt.id,
+                    prompt: "Also add tests".to_string(),
+                    variant: None,
+                }))
+                .await
+                .unwrap(),
+        );
+        assert!(!is_error);
+        assert_eq!(body["attempt_id"], api.attempt.id.to_string());
+        assert_ne!(
+            body["execution_process_id"],
+            api.coding_agent.id.to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn stop_execution_takes_exactly_one_target() {
+        let api = FakeApi::new();
+        let server = api.serve().await;
+
+        let (is_error, _) = payload(
+            server
+                .stop_execution(Parameters(StopExecutionRequest {
+                    execution_process_id: Some(api.coding_agent.id),
+                    attempt_id: Some(api.attempt.id),
+                }))
+                .await
+                .unwrap(),
+        );
+        assert!(is_error);
+
+        for (execution_process_id, attempt_id) in [
+            (Some(api.coding_agent.id), None),
+            (None, Some(api.attempt.id)),
+        ] {
+            let (is_error, body) = payload(
+                server
+                    .stop_execution(Parameters(StopExecutionRequest {
+                        execution_process_id,
+                        attempt_id,
+                    }))
+                    .await
+                    .unwrap(),
+            );
+            assert!(!is_error);
+            assert_eq!(body["stopped"], true);
+        }
+    }
+
+    #[tokio::test]
+    async fn respond_to_approval_reports_the_resulting_status() {
+        let api = FakeApi::new();
+        let server = api.serve().await;
+
+        for (approve, status) in [(true, "approved"), (false, "denied")] {
+            let (is_error, body) = payload(
+                server
+                    .respond_to_approval(Parameters(RespondToApprovalRequest {
+                        approval_id: "approval-1".to_string(),
+                        execution_process_id: api.coding_agent.id,
+                        approve,
+                        reason: Some("Not now".to_string()),
+                    }))
+                    .await
+                    .unwrap(),
+            );
+            assert!(!is_error);
+            assert_eq!(body["approval_id"], "approval-1");
+            assert_eq!(body["status"], status);
+        }
+    }
+
+    #[tokio::test]
+    async fn merge_task_attempt_surfaces_api_errors() {
+        let api = FakeApi::new();
+        let server = api.serve().await;
+
+        let (is_error, body) = payload(
+            server
+                .merge_task_attempt(Parameters(MergeTaskAttemptRequest {
+                    attempt_id: api.attempt.id,
+                }))
+                .await
+                .unwrap(),
+        );
+
+        assert!(is_error);
+        assert_eq!(body["details"], "Merge conflicts");
+    }
+
+    #[tokio::test]
+    async fn create_pull_request_ignores_a_blank_target_branch() {
+        let api = FakeApi::new();
+        let server = api.serve().await;
+
+        let (is_error, body) = payload(
+            server
+                .create_pull_request(Parameters(CreatePullRequestRequest {
+                    attempt_id: api.attempt.id,
+                    title: "Add checkout".to_string(),
+                    body: None,
+                    target_branch: Some(" ".to_string()),
+                }))
+                .await
+                .unwrap(),
+        );
+
+        assert!(!is_error);
+        assert_eq!(body["pr_url"], "https://github.com/owner/repo/pull/7?base=");
+    }
+}
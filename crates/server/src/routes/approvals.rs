@@ -1,13 +1,27 @@
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    routing::post,
+    response::Json as ResponseJson,
+    routing::{get, post},
 };
+use db::models::approval::{Approval, ApprovalFilter};
 use deployment::Deployment;
-use utils::approvals::{ApprovalResponse, ApprovalStatus};
+use utils::{
+    approvals::{ApprovalResponse, ApprovalStatus},
+    response::ApiResponse,
+};
+
+use crate::{DeploymentImpl, error::ApiError};
 
-use crate::DeploymentImpl;
+/// Resolved approvals, newest first, filtered by scope, tool, decider, status and time
+pub async fn get_approval_history(
+    State(deployment): State<DeploymentImpl>,
+    Query(filter): Query<ApprovalFilter>,
+) -> Result<ResponseJson<ApiResponse<Vec<Approval>>>, ApiError> {
+    let approvals = Approval::find_filtered(&deployment.db().pool, &filter).await?;
+    Ok(ResponseJson(ApiResponse::success(approvals)))
+}
 
 pub async fn respond_to_approval(
     State(deployment): State<DeploymentImpl>,
@@ -40,5 +54,7 @@ pub async fn respond_to_approval(
 }
 
 pub fn router() -> Router<DeploymentImpl> {
-    Router::new().route("/approvals/{id}/respond", post(respond_to_approval))
+    Router::new()
+        .route("/approvals", get(get_approval_history))
+        .route("/approvals/{id}/respond", post(respond_to_approval))
 }
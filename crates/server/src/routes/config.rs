@@ -8,15 +8,20 @@ use axum::{
     response::{Json as ResponseJson, Response},
     routing::{get, put},
 };
+use db::models::execution_budget::{BudgetLimits, ExecutorProfileBudget};
 use deployment::{Deployment, DeploymentError};
 use executors::{
+    env::{expand_secrets, is_secret_reference},
     executors::{BaseAgentCapability, BaseCodingAgent, StandardCodingAgentExecutor},
     mcp_config::{McpConfig, read_agent_config, write_agent_config},
     profile::{ExecutorConfigs, ExecutorProfileId},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use services::services::config::{Config, ConfigError, SoundFile, save_config_to_file};
+use services::services::{
+    config::{Config, ConfigError, SoundFile, save_config_to_file},
+    container::ContainerService,
+};
 use tokio::fs;
 use ts_rs::TS;
 use utils::{api::oauth::LoginStatus, assets::config_path, response::ApiResponse};
@@ -30,6 +35,10 @@ pub fn router() -> Router<DeploymentImpl> {
         .route("/sounds/{sound}", get(get_sound))
         .route("/mcp-config", get(get_mcp_servers).post(update_mcp_servers))
         .route("/profiles", get(get_profiles).put(update_profiles))
+        .route(
+            "/profiles/budgets",
+            get(get_profile_budgets).put(update_profile_budget),
+        )
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -112,6 +121,51 @@ async fn update_config(
         ));
     }
 
+    if let Err(e) = utils::redaction::compile_patterns(&new_config.redaction_patterns) {
+        return ResponseJson(ApiResponse::error(&format!(
+            "Invalid log redaction pattern: {}",
+            e
+        )));
+    }
+
+    // Git host tokens live in the secret store; config.json only refers to them
+    let secrets = deployment.container().secrets().values().await;
+    for host in &new_config.git_hosts {
+        let Some(token) = &host.token else {
+            continue;
+        };
+        if !is_secret_reference(token) {
+            return ResponseJson(ApiResponse::error(&format!(
+                "The token for git host '{}' must be a {{{{secret:NAME}}}} reference to the secret store",
+                host.host
+            )));
+        }
+        if let Err(e) = expand_secrets(token, &secrets) {
+            return ResponseJson(ApiResponse::error(&format!(
+                "Invalid token for git host '{}': {}",
+                host.host, e
+            )));
+        }
+    }
+
+    // So are the URLs and passwords notifiers authenticate with
+    for notifier in &new_config.notifiers {
+        for (field, value) in notifier.target.credentials() {
+            if !is_secret_reference(value) {
+                return ResponseJson(ApiResponse::error(&format!(
+                    "The {} of notifier '{}' must be a {{{{secret:NAME}}}} reference to the secret store",
+                    field, notifier.name
+                )));
+            }
+            if let Err(e) = expand_secrets(value, &secrets) {
+                return ResponseJson(ApiResponse::error(&format!(
+                    "Invalid {} for notifier '{}': {}",
+                    field, notifier.name, e
+                )));
+            }
+        }
+    }
+
     // Get old config state before updating
     let old_config = deployment.config().read().await.clone();
 
@@ -426,3 +480,52 @@ async fn update_profiles(
         ))),
     }
 }
+
+#[derive(Debug, Deserialize, TS)]
+pub struct UpdateExecutorProfileBudget {
+    pub executor_profile_id: ExecutorProfileId,
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub limits: BudgetLimits,
+}
+
+async fn get_profile_budgets(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutorProfileBudget>>>, ApiError> {
+    let budgets = ExecutorProfileBudget::find_all(&deployment.db().pool).await?;
+    Ok(ResponseJson(ApiResponse::success(budgets)))
+}
+
+/// Set the budget for an executor profile. Clearing every limit removes the budget.
+async fn update_profile_budget(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpdateExecutorProfileBudget>,
+) -> Result<ResponseJson<ApiResponse<Option<ExecutorProfileBudget>>>, ApiError> {
+    validate_budget_limits(&payload.limits)?;
+    let pool = &deployment.db().pool;
+
+    if payload.limits.is_unlimited() {
+        ExecutorProfileBudget::delete(pool, &payload.executor_profile_id).await?;
+        return Ok(ResponseJson(ApiResponse::success(None)));
+    }
+
+    let budget =
+        ExecutorProfileBudget::upsert(pool, &payload.executor_profile_id, &payload.limits).await?;
+    Ok(ResponseJson(ApiResponse::success(Some(budget))))
+}
+
+pub(crate) fn validate_budget_limits(limits: &BudgetLimits) -> Result<(), ApiError> {
+    let limits = [
+        ("Runtime", limits.max_runtime_secs),
+        ("Tool call", limits.max_tool_calls),
+        ("Token", limits.max_tokens),
+    ];
+    for (name, limit) in limits {
+        if limit.is_some_and(|l| l <= 0) {
+            return Err(ApiError::BadRequest(format!(
+                "{name} limit must be greater than zero"
+            )));
+        }
+    }
+    Ok(())
+}
@@ -9,10 +9,13 @@ use axum::{
     response::{IntoResponse, Json as ResponseJson},
     routing::{get, post},
 };
-use db::models::execution_process::{
-    ExecutionProcess, ExecutionProcessError, ExecutionProcessStatus,
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessError, ExecutionProcessStatus},
+    execution_process_normalized_entry::ExecutionProcessNormalizedEntry,
+    execution_process_usage::{ExecutionProcessUsage, UsageSummary},
 };
 use deployment::Deployment;
+use executors::logs::{NORMALIZER_VERSION, NormalizedEntry};
 use futures_util::{SinkExt, StreamExt, TryStreamExt};
 use serde::Deserialize;
 use services::services::container::ContainerService;
@@ -44,6 +47,45 @@ pub async fn get_execution_processes(
     Ok(ResponseJson(ApiResponse::success(execution_processes)))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    pub task_attempt_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+}
+
+/// Token usage summed over every execution process of an attempt or a task
+pub async fn get_usage(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<UsageQuery>,
+) -> Result<ResponseJson<ApiResponse<UsageSummary>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let summary = match (query.task_attempt_id, query.task_id) {
+        (Some(task_attempt_id), None) => {
+            ExecutionProcessUsage::summary_for_task_attempt(pool, task_attempt_id).await?
+        }
+        (None, Some(task_id)) => ExecutionProcessUsage::summary_for_task(pool, task_id).await?,
+        _ => {
+            return Err(ApiError::BadRequest(
+                "Specify exactly one of task_attempt_id or task_id".to_string(),
+            ));
+        }
+    };
+
+    Ok(ResponseJson(ApiResponse::success(summary)))
+}
+
+pub async fn get_execution_process_usage(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ExecutionProcessUsage>>>, ApiError> {
+    let usage = ExecutionProcessUsage::find_by_execution_process_id(
+        &deployment.db().pool,
+        execution_process.id,
+    )
+    .await?;
+    Ok(ResponseJson(ApiResponse::success(usage)))
+}
+
 pub async fn get_execution_process_by_id(
     Extension(execution_process): Extension<ExecutionProcess>,
     State(_deployment): State<DeploymentImpl>,
@@ -180,6 +222,20 @@ async fn handle_normalized_logs_ws(
     Ok(())
 }
 
+/// The conversation of a process as it stands now, without waiting for a running
+/// process to finish
+pub async fn get_normalized_entries(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<NormalizedEntry>>>, ApiError> {
+    let entries = deployment
+        .container()
+        .conversation_snapshot(&execution_process.id)
+        .await
+        .unwrap_or_default();
+    Ok(ResponseJson(ApiResponse::success(entries)))
+}
+
 pub async fn stop_execution_process(
     Extension(execution_process): Extension<ExecutionProcess>,
     State(deployment): State<DeploymentImpl>,
@@ -192,6 +248,51 @@ pub async fn stop_execution_process(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+/// Re-normalize a finished process's raw logs and replace its stored conversation.
+/// Returns the number of entries stored.
+pub async fn renormalize_execution_process(
+    Extension(execution_process): Extension<ExecutionProcess>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<usize>>, ApiError> {
+    if execution_process.status == ExecutionProcessStatus::Running {
+        return Err(ApiError::Conflict(
+            "Execution process is still running".to_string(),
+        ));
+    }
+    let entries = deployment
+        .container()
+        .renormalize_logs(&execution_process.id)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(entries)))
+}
+
+/// Queue re-normalization of every finished coding agent run whose stored conversation
+/// is missing or was produced by an older normalizer. Returns the number queued.
+pub async fn renormalize_outdated_execution_processes(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<usize>>, ApiError> {
+    let ids = ExecutionProcessNormalizedEntry::find_outdated_execution_process_ids(
+        &deployment.db().pool,
+        NORMALIZER_VERSION,
+    )
+    .await?;
+    let queued = ids.len();
+
+    tokio::spawn(async move {
+        for id in ids {
+            match deployment.container().renormalize_logs(&id).await {
+                Ok(entries) => {
+                    tracing::debug!("Re-normalized execution {} into {} entries", id, entries)
+                }
+                Err(e) => tracing::warn!("Failed to re-normalize execution {}: {}", id, e),
+            }
+        }
+        tracing::info!("Finished re-normalizing {} execution processes", queued);
+    });
+
+    Ok(ResponseJson(ApiResponse::success(queued)))
+}
+
 pub async fn stream_execution_processes_ws(
     ws: WebSocketUpgrade,
     State(deployment): State<DeploymentImpl>,
@@ -251,7 +352,10 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_attempt_id_router = Router::new()
         .route("/", get(get_execution_process_by_id))
         .route("/stop", post(stop_execution_process))
+        .route("/renormalize", post(renormalize_execution_process))
+        .route("/usage", get(get_execution_process_usage))
         .route("/raw-logs/ws", get(stream_raw_logs_ws))
+        .route("/normalized-entries", get(get_normalized_entries))
         .route("/normalized-logs/ws", get(stream_normalized_logs_ws))
         .layer(from_fn_with_state(
             deployment.clone(),
@@ -261,6 +365,11 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_attempts_router = Router::new()
         .route("/", get(get_execution_processes))
         .route("/stream/ws", get(stream_execution_processes_ws))
+        .route("/usage", get(get_usage))
+        .route(
+            "/renormalize",
+            post(renormalize_outdated_execution_processes),
+        )
         .nest("/{id}", task_attempt_id_router);
 
     Router::new().nest("/execution-processes", task_attempts_router)
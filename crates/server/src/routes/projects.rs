@@ -6,20 +6,31 @@ use axum::{
     http::StatusCode,
     middleware::from_fn_with_state,
     response::Json as ResponseJson,
-    routing::{get, post},
+    routing::{delete, get, post},
 };
 use db::models::{
+    execution_budget::{BudgetLimits, ProjectBudget},
+    execution_process_usage::{ExecutionProcessUsage, UsageSummary},
     project::{CreateProject, Project, ProjectError, SearchMatchType, SearchResult, UpdateProject},
+    project_ci_settings::{ProjectCiSettings, UpsertProjectCiSettings},
+    project_env::{ProjectEnv, UpsertProjectEnv},
+    project_merge_queue_settings::{ProjectMergeQueueSettings, UpsertProjectMergeQueueSettings},
+    project_merge_settings::{ProjectMergeSettings, UpsertProjectMergeSettings},
+    project_repository::{CreateProjectRepository, ProjectRepository},
+    project_sandbox_policy::{ProjectSandboxPolicy, UpsertProjectSandboxPolicy},
     task::Task,
 };
 use deployment::Deployment;
+use executors::env::expand_secrets;
 use ignore::WalkBuilder;
 use serde::Deserialize;
 use services::services::{
+    container::ContainerService,
     file_ranker::FileRanker,
     file_search_cache::{CacheError, SearchMode, SearchQuery},
     git::GitBranch,
     remote_client::CreateRemoteProjectPayload,
+    secrets::is_valid_env_name,
     share::link_shared_tasks_to_project,
 };
 use ts_rs::TS;
@@ -30,7 +41,10 @@ use utils::{
 };
 use uuid::Uuid;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_project_middleware};
+use crate::{
+    DeploymentImpl, error::ApiError, middleware::load_project_middleware,
+    routes::config::validate_budget_limits,
+};
 
 #[derive(Deserialize, TS)]
 pub struct LinkToExistingRequest {
@@ -645,6 +659,345 @@ async fn search_files_in_repo(
     Ok(results)
 }
 
+pub async fn get_project_sandbox_policy(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectSandboxPolicy>>>, ApiError> {
+    let policy =
+        ProjectSandboxPolicy::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(policy)))
+}
+
+pub async fn update_project_sandbox_policy(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertProjectSandboxPolicy>,
+) -> Result<ResponseJson<ApiResponse<ProjectSandboxPolicy>>, ApiError> {
+    if let Some(cpus) = payload.policy.cpus
+        && cpus <= 0.0
+    {
+        return Err(ApiError::BadRequest(
+            "CPU limit must be greater than zero".to_string(),
+        ));
+    }
+    if payload.policy.memory_mb == Some(0) {
+        return Err(ApiError::BadRequest(
+            "Memory limit must be greater than zero".to_string(),
+        ));
+    }
+
+    let policy = ProjectSandboxPolicy::upsert(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_sandbox_policy_updated",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "enabled": policy.enabled,
+                "backend": policy.policy.backend,
+                "network": policy.policy.network,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(policy)))
+}
+
+pub async fn get_project_ci_settings(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectCiSettings>>>, ApiError> {
+    let settings = ProjectCiSettings::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+pub async fn update_project_ci_settings(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertProjectCiSettings>,
+) -> Result<ResponseJson<ApiResponse<ProjectCiSettings>>, ApiError> {
+    if payload.max_auto_fixes < 0 {
+        return Err(ApiError::BadRequest(
+            "Maximum auto-fixes cannot be negative".to_string(),
+        ));
+    }
+
+    let settings = ProjectCiSettings::upsert(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_ci_settings_updated",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "auto_fix_failed_checks": settings.auto_fix_failed_checks,
+                "max_auto_fixes": settings.max_auto_fixes,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+pub async fn get_project_merge_queue_settings(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectMergeQueueSettings>>>, ApiError> {
+    let settings =
+        ProjectMergeQueueSettings::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+pub async fn update_project_merge_queue_settings(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertProjectMergeQueueSettings>,
+) -> Result<ResponseJson<ApiResponse<ProjectMergeQueueSettings>>, ApiError> {
+    if payload.verify_timeout_secs <= 0 {
+        return Err(ApiError::BadRequest(
+            "Verify timeout must be positive".to_string(),
+        ));
+    }
+
+    let settings =
+        ProjectMergeQueueSettings::upsert(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_merge_queue_settings_updated",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "has_verify_script": settings.verify_script.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+pub async fn get_project_merge_settings(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectMergeSettings>>>, ApiError> {
+    let settings =
+        ProjectMergeSettings::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+pub async fn update_project_merge_settings(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertProjectMergeSettings>,
+) -> Result<ResponseJson<ApiResponse<ProjectMergeSettings>>, ApiError> {
+    if payload
+        .commit_message_template
+        .as_deref()
+        .is_some_and(|template| template.trim().is_empty())
+    {
+        return Err(ApiError::BadRequest(
+            "Commit message template must not be empty".to_string(),
+        ));
+    }
+
+    let settings =
+        ProjectMergeSettings::upsert(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_merge_settings_updated",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "strategy": settings.strategy,
+                "has_commit_message_template": settings.commit_message_template.is_some(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(settings)))
+}
+
+pub async fn get_project_repositories(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<ProjectRepository>>>, ApiError> {
+    let repositories =
+        ProjectRepository::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(repositories)))
+}
+
+/// Add a repository to the project. Attempts created afterwards get a worktree of it
+/// next to the primary repository's.
+pub async fn create_project_repository(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateProjectRepository>,
+) -> Result<ResponseJson<ApiResponse<ProjectRepository>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let name = payload.name.trim();
+    if name.is_empty()
+        || name == "."
+        || name == ".."
+        || name.contains(['/', '\\'])
+        || name == project.repo_dir_name()
+    {
+        return Err(ApiError::BadRequest(format!(
+            "'{name}' cannot be used as a repository name"
+        )));
+    }
+
+    let path = std::path::absolute(expand_tilde(&payload.git_repo_path))?;
+    if !path.is_dir() || !path.join(".git").exists() {
+        return Err(ApiError::BadRequest(
+            "The specified directory is not a git repository".to_string(),
+        ));
+    }
+    let existing = ProjectRepository::find_by_project_id(pool, project.id).await?;
+    if path == project.git_repo_path
+        || existing
+            .iter()
+            .any(|repository| repository.name == name || repository.git_repo_path == path)
+    {
+        return Err(ApiError::Conflict(
+            "The project already has this repository".to_string(),
+        ));
+    }
+
+    // Recorded once, so later attempts do not depend on what happens to be checked out
+    let target_branch = match payload.target_branch.as_deref().map(str::trim) {
+        Some(branch) if !branch.is_empty() => branch.to_string(),
+        _ => deployment.git().get_current_branch(&path).map_err(|_| {
+            ApiError::BadRequest(
+                "The repository has no branch checked out; choose a target branch".to_string(),
+            )
+        })?,
+    };
+    if !deployment
+        .git()
+        .local_branch_exists(&path, &target_branch)?
+    {
+        return Err(ApiError::BadRequest(format!(
+            "The repository has no branch '{target_branch}'"
+        )));
+    }
+
+    let repository = ProjectRepository::create(
+        pool,
+        project.id,
+        name,
+        &path.to_string_lossy(),
+        &target_branch,
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_repository_added",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "repository_count": existing.len() + 1,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(repository)))
+}
+
+/// Remove a repository from the project. Existing attempts keep their worktree of it.
+pub async fn delete_project_repository(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Path((_, repository_id)): Path<(Uuid, Uuid)>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
+    match ProjectRepository::find_by_id(pool, repository_id).await? {
+        Some(repository) if repository.project_id == project.id => {
+            ProjectRepository::delete(pool, repository.id).await?;
+            Ok(ResponseJson(ApiResponse::success(())))
+        }
+        _ => Err(ApiError::BadRequest("Repository not found".to_string())),
+    }
+}
+
+pub async fn get_project_budget(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectBudget>>>, ApiError> {
+    let budget = ProjectBudget::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(budget)))
+}
+
+pub async fn update_project_budget(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<BudgetLimits>,
+) -> Result<ResponseJson<ApiResponse<ProjectBudget>>, ApiError> {
+    validate_budget_limits(&payload)?;
+    let budget = ProjectBudget::upsert(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_budget_updated",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "max_runtime_secs": budget.limits.max_runtime_secs,
+                "max_tool_calls": budget.limits.max_tool_calls,
+                "max_tokens": budget.limits.max_tokens,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(budget)))
+}
+
+/// Token usage summed over every execution process in the project
+pub async fn get_project_usage(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<UsageSummary>>, ApiError> {
+    let summary =
+        ExecutionProcessUsage::summary_for_project(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(summary)))
+}
+
+pub async fn get_project_env(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<ProjectEnv>>>, ApiError> {
+    let env = ProjectEnv::find_by_project_id(&deployment.db().pool, project.id).await?;
+    Ok(ResponseJson(ApiResponse::success(env)))
+}
+
+/// Replace the project's environment variables. Secret references must name stored
+/// secrets, so a typo fails here rather than when an agent starts.
+pub async fn update_project_env(
+    Extension(project): Extension<Project>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<UpsertProjectEnv>,
+) -> Result<ResponseJson<ApiResponse<ProjectEnv>>, ApiError> {
+    let secrets = deployment.container().secrets().values().await;
+    for (name, value) in &payload.vars {
+        if !is_valid_env_name(name) {
+            return Err(ApiError::BadRequest(format!(
+                "Invalid variable name '{name}'"
+            )));
+        }
+        expand_secrets(value, &secrets).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    }
+
+    let env = ProjectEnv::upsert(&deployment.db().pool, project.id, &payload).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "project_env_updated",
+            serde_json::json!({
+                "project_id": project.id.to_string(),
+                "variables": env.vars.len(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(env)))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let project_id_router = Router::new()
         .route(
@@ -660,6 +1013,36 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
             post(link_project_to_existing_remote).delete(unlink_project),
         )
         .route("/link/create", post(create_and_link_remote_project))
+        .route(
+            "/sandbox-policy",
+            get(get_project_sandbox_policy).put(update_project_sandbox_policy),
+        )
+        .route("/env", get(get_project_env).put(update_project_env))
+        .route(
+            "/ci-settings",
+            get(get_project_ci_settings).put(update_project_ci_settings),
+        )
+        .route(
+            "/merge-queue-settings",
+            get(get_project_merge_queue_settings).put(update_project_merge_queue_settings),
+        )
+        .route(
+            "/merge-settings",
+            get(get_project_merge_settings).put(update_project_merge_settings),
+        )
+        .route(
+            "/repositories",
+            get(get_project_repositories).post(create_project_repository),
+        )
+        .route(
+            "/repositories/{repository_id}",
+            delete(delete_project_repository),
+        )
+        .route("/usage", get(get_project_usage))
+        .route(
+            "/budget",
+            get(get_project_budget).put(update_project_budget),
+        )
         .layer(from_fn_with_state(
             deployment.clone(),
             load_project_middleware,
@@ -0,0 +1,60 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+    routing::{get, put},
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::container::ContainerService;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SetSecretRequest {
+    pub value: String,
+}
+
+/// Names of the stored secrets; their values are never returned
+pub async fn list_secrets(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<String>>>, ApiError> {
+    let names = deployment.container().secrets().names().await;
+    Ok(ResponseJson(ApiResponse::success(names)))
+}
+
+pub async fn set_secret(
+    State(deployment): State<DeploymentImpl>,
+    Path(name): Path<String>,
+    Json(payload): Json<SetSecretRequest>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    deployment
+        .container()
+        .secrets()
+        .set(&name, &payload.value)
+        .await?;
+    deployment
+        .track_if_analytics_allowed("secret_set", serde_json::json!({}))
+        .await;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub async fn delete_secret(
+    State(deployment): State<DeploymentImpl>,
+    Path(name): Path<String>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    if !deployment.container().secrets().delete(&name).await? {
+        return Err(ApiError::BadRequest(format!(
+            "Secret '{name}' does not exist"
+        )));
+    }
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/secrets", get(list_secrets))
+        .route("/secrets/{name}", put(set_secret).delete(delete_secret))
+}
@@ -1,6 +1,12 @@
 pub mod cursor_setup;
 pub mod drafts;
+pub mod fork;
 pub mod gh_cli_setup;
+pub mod pr_review;
+pub mod queue;
+pub mod race;
+pub mod repositories;
+pub mod stack;
 pub mod util;
 
 use axum::{
@@ -15,12 +21,17 @@ use axum::{
     routing::{get, post},
 };
 use db::models::{
+    conflict_resolution::ConflictResolution,
     draft::{Draft, DraftType},
     execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
     merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
+    merge_queue_entry::MergeQueueEntry,
+    pr_check_summary::{PrCheckSummary, PrChecks},
     project::{Project, ProjectError},
+    project_merge_settings::{MergeStrategy, ProjectMergeSettings},
     task::{Task, TaskRelationships, TaskStatus},
     task_attempt::{CreateTaskAttempt, TaskAttempt, TaskAttemptError},
+    task_attempt_stack::TaskAttemptStack,
 };
 use deployment::Deployment;
 use executors::{
@@ -35,14 +46,16 @@ use executors::{
 use git2::BranchType;
 use serde::{Deserialize, Serialize};
 use services::services::{
+    attempt_stack, conflict_resolution,
     container::ContainerService,
-    gh_cli::GhCli,
     git::{ConflictOp, WorktreeResetOptions},
+    git_host::{self, GitHostError, GitHostKind},
     github_service::{CreatePrRequest, GitHubService, GitHubServiceError},
+    merge_queue,
 };
 use sqlx::Error as SqlxError;
 use ts_rs::TS;
-use utils::response::ApiResponse;
+use utils::{diff::Diff, response::ApiResponse};
 use uuid::Uuid;
 
 use crate::{
@@ -139,6 +152,11 @@ pub struct CreateTaskAttemptBody {
     /// Executor profile specification
     pub executor_profile_id: ExecutorProfileId,
     pub base_branch: String,
+    /// Stack the attempt on another attempt of the same project. Its branch is used as
+    /// the base instead of `base_branch`, and the attempt is restacked when it changes.
+    #[serde(default)]
+    #[ts(optional)]
+    pub parent_attempt_id: Option<Uuid>,
 }
 
 impl CreateTaskAttemptBody {
@@ -162,10 +180,31 @@ pub async fn create_task_attempt(
     Json(payload): Json<CreateTaskAttemptBody>,
 ) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, ApiError> {
     let executor_profile_id = payload.get_executor_profile_id();
-    let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
+    let pool = &deployment.db().pool;
+    let task = Task::find_by_id(pool, payload.task_id)
         .await?
         .ok_or(SqlxError::RowNotFound)?;
 
+    // Stacked attempts start from the parent attempt's branch
+    let stack_parent = match payload.parent_attempt_id {
+        Some(parent_id) => {
+            let parent = stack::find_stack_parent(pool, parent_id, task.project_id).await?;
+            let project = Project::find_by_id(pool, task.project_id)
+                .await?
+                .ok_or(ApiError::Project(ProjectError::ProjectNotFound))?;
+            let base_commit = deployment
+                .git()
+                .get_branch_oid(&project.git_repo_path, &parent.branch)?;
+            Some((parent, base_commit))
+        }
+        None => None,
+    };
+    let base_branch = stack_parent
+        .as_ref()
+        .map_or(payload.base_branch.clone(), |(parent, _)| {
+            parent.branch.clone()
+        });
+
     let attempt_id = Uuid::new_v4();
     let git_branch_name = deployment
         .container()
@@ -173,16 +212,19 @@ pub async fn create_task_attempt(
         .await;
 
     let task_attempt = TaskAttempt::create(
-        &deployment.db().pool,
+        pool,
         &CreateTaskAttempt {
             executor: executor_profile_id.executor,
-            base_branch: payload.base_branch.clone(),
+            base_branch,
             branch: git_branch_name.clone(),
         },
         attempt_id,
         payload.task_id,
     )
     .await?;
+    if let Some((parent, base_commit)) = &stack_parent {
+        TaskAttemptStack::create(pool, task_attempt.id, parent.id, base_commit).await?;
+    }
 
     if let Err(err) = deployment
         .container()
@@ -200,6 +242,7 @@ pub async fn create_task_attempt(
                 "variant": &executor_profile_id.variant,
                 "executor": &executor_profile_id.executor,
                 "attempt_id": task_attempt.id.to_string(),
+                "stacked": stack_parent.is_some(),
             }),
         )
         .await;
@@ -238,7 +281,7 @@ pub async fn run_agent_setup(
     Ok(ResponseJson(ApiResponse::success(RunAgentSetupResponse {})))
 }
 
-#[derive(Debug, Deserialize, TS)]
+#[derive(Debug, Serialize, Deserialize, TS)]
 pub struct CreateFollowUpAttempt {
     pub prompt: String,
     pub variant: Option<String>,
@@ -524,6 +567,15 @@ pub async fn replace_process(
     })))
 }
 
+/// Changed files of an attempt with line counts, as the stats-only diff stream starts out
+pub async fn get_task_attempt_diff_stats(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<Diff>>>, ApiError> {
+    let diffs = deployment.container().diff_stats(&task_attempt).await?;
+    Ok(ResponseJson(ApiResponse::success(diffs)))
+}
+
 #[axum::debug_handler]
 pub async fn stream_task_attempt_diff_ws(
     ws: WebSocketUpgrade,
@@ -648,11 +700,17 @@ pub async fn compare_commit_to_head(
     })))
 }
 
-#[axum::debug_handler]
-pub async fn merge_task_attempt(
+#[derive(Debug, Serialize, TS)]
+pub struct MergePreview {
+    pub strategy: MergeStrategy,
+    /// Message of the commit the merge creates; rebase-and-fast-forward creates none
+    pub commit_message: Option<String>,
+}
+
+pub async fn get_merge_preview(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
-) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+) -> Result<ResponseJson<ApiResponse<MergePreview>>, ApiError> {
     let pool = &deployment.db().pool;
 
     let task = task_attempt
@@ -660,31 +718,70 @@ pub async fn merge_task_attempt(
         .await?
         .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
     let ctx = TaskAttempt::load_context(pool, task_attempt.id, task.id, task.project_id).await?;
+    let settings = ProjectMergeSettings::find_by_project_id(pool, ctx.project.id).await?;
+    let strategy = settings.as_ref().map(|s| s.strategy).unwrap_or_default();
 
-    let worktree_path_buf = ensure_worktree_path(&deployment, &task_attempt).await?;
-    let worktree_path = worktree_path_buf.as_path();
+    let commit_message = if strategy == MergeStrategy::RebaseFastForward {
+        None
+    } else {
+        Some(
+            merge_strategy::commit_message(
+                deployment.git(),
+                pool,
+                &ctx.project.git_repo_path,
+                &ctx.task,
+                &ctx.task_attempt,
+                &ctx.task_attempt.target_branch,
+                settings
+                    .as_ref()
+                    .and_then(|s| s.commit_message_template.as_deref()),
+            )
+            .await?,
+        )
+    };
 
-    let task_uuid_str = task.id.to_string();
-    let first_uuid_section = task_uuid_str.split('-').next().unwrap_or(&task_uuid_str);
+    Ok(ResponseJson(ApiResponse::success(MergePreview {
+        strategy,
+        commit_message,
+    })))
+}
 
-    // Create commit message with task title and description
-    let mut commit_message = format!("{} (vibe-kanban {})", ctx.task.title, first_uuid_section);
+#[axum::debug_handler]
+pub async fn merge_task_attempt(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let pool = &deployment.db().pool;
 
-    // Add description on next line if it exists
-    if let Some(description) = &ctx.task.description
-        && !description.trim().is_empty()
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    let ctx = TaskAttempt::load_context(pool, task_attempt.id, task.id, task.project_id).await?;
+    // The queue may be rebasing or merging the branch right now
+    if MergeQueueEntry::find_active_by_attempt_id(pool, task_attempt.id)
+        .await?
+        .is_some()
     {
-        commit_message.push_str("\n\n");
-        commit_message.push_str(description);
+        return Err(ApiError::Conflict(
+            "This attempt is in the merge queue; remove it from the queue to merge directly"
+                .to_string(),
+        ));
     }
 
-    let merge_commit_id = deployment.git().merge_changes(
+    let worktree_path_buf = ensure_worktree_path(&deployment, &task_attempt).await?;
+    let worktree_path = worktree_path_buf.as_path();
+
+    let merge_commit_id = merge_strategy::merge_attempt(
+        deployment.git(),
+        pool,
         &ctx.project.git_repo_path,
         worktree_path,
-        &ctx.task_attempt.branch,
+        &ctx.task,
+        &ctx.task_attempt,
         &ctx.task_attempt.target_branch,
-        &commit_message,
-    )?;
+    )
+    .await?;
 
     Merge::create_direct(
         pool,
@@ -695,6 +792,10 @@ pub async fn merge_task_attempt(
     .await?;
     Task::update_status(pool, ctx.task.id, TaskStatus::Done).await?;
 
+    // Attempts stacked on this one move onto the branch it was merged into
+    let hosts = deployment.config().read().await.git_hosts.clone();
+    attempt_stack::restack_descendants(deployment.container(), &hosts, task_attempt.id).await;
+
     // Try broadcast update to other users in organization
     if let Ok(publisher) = deployment.share_publisher() {
         if let Err(err) = publisher.update_shared_task_by_id(ctx.task.id).await {
@@ -729,11 +830,20 @@ pub async fn push_task_attempt_branch(
     Extension(task_attempt): Extension<TaskAttempt>,
     State(deployment): State<DeploymentImpl>,
 ) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
-    let github_service = GitHubService::new()?;
-    github_service.check_token().await?;
-
     let ws_path = ensure_worktree_path(&deployment, &task_attempt).await?;
 
+    // GitLab and Gitea remotes are pushed to with the user's own git credentials
+    let remote_url = deployment.git().get_remote_url(&ws_path)?;
+    let hosts = deployment.config().read().await.git_hosts.clone();
+    let secrets = deployment.container().secrets().values().await;
+    if !matches!(
+        git_host::kind_for_remote_url(&remote_url, &hosts),
+        Some(GitHostKind::GitLab | GitHostKind::Gitea)
+    ) {
+        let github_service = GitHubService::new()?;
+        github_service.check_token().await?;
+    }
+
     deployment
         .git()
         .push_to_github(&ws_path, &task_attempt.branch)?;
@@ -771,6 +881,23 @@ pub async fn create_github_pr(
 
     let workspace_path = ensure_worktree_path(&deployment, &task_attempt).await?;
 
+    // A PR against the parent of a stacked attempt needs the parent's branch on the remote
+    if let Some(stack_link) = TaskAttemptStack::find_by_attempt_id(pool, task_attempt.id).await?
+        && let Some(parent) = TaskAttempt::find_by_id(pool, stack_link.parent_attempt_id).await?
+        && parent.branch == target_branch
+    {
+        let parent_workspace_path = ensure_worktree_path(&deployment, &parent).await?;
+        if let Err(e) = deployment
+            .git()
+            .push_to_github(&parent_workspace_path, &parent.branch)
+        {
+            tracing::error!("Failed to push parent branch to GitHub: {}", e);
+            return Ok(ResponseJson(ApiResponse::error(
+                format!("Failed to push parent branch '{}': {}", parent.branch, e).as_str(),
+            )));
+        }
+    }
+
     // Push the branch to GitHub first
     if let Err(e) = deployment
         .git()
@@ -806,21 +933,51 @@ pub async fn create_github_pr(
     } else {
         target_branch
     };
+    // Additional repositories get their own PRs, listed in the primary one
+    let repository_prs = match repositories::open_repository_prs(
+        &deployment,
+        &task_attempt,
+        &request.title,
+        request.body.as_deref(),
+    )
+    .await
+    {
+        Ok(prs) => prs,
+        Err(message) => return Ok(ResponseJson(ApiResponse::error(&message))),
+    };
+    let body = if repository_prs.is_empty() {
+        request.body.clone()
+    } else {
+        let links = repository_prs
+            .iter()
+            .map(|(name, url)| format!("- {name}: {url}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(match request.body.as_deref() {
+            Some(body) if !body.trim().is_empty() => {
+                format!("{body}\n\nRelated pull requests:\n{links}")
+            }
+            _ => format!("Related pull requests:\n{links}"),
+        })
+    };
+
     // Create the PR using GitHub service
     let pr_request = CreatePrRequest {
         title: request.title.clone(),
-        body: request.body.clone(),
+        body,
         head_branch: task_attempt.branch.clone(),
         base_branch: norm_target_branch_name.clone(),
     };
-    // Use GitService to get the remote URL, then create GitHubRepoInfo
-    let repo_info = deployment
-        .git()
-        .get_github_repo_info(&project.git_repo_path)?;
+    // Resolve the git host (GitHub, GitLab or Gitea) from the project's remote
+    let remote_url = deployment.git().get_remote_url(&project.git_repo_path)?;
+    let hosts = deployment.config().read().await.git_hosts.clone();
+    let secrets = deployment.container().secrets().values().await;
+    let result = match git_host::from_remote_url(&remote_url, &hosts, &secrets) {
+        Ok(host) => host.create_pr(&pr_request).await,
+        Err(e) => Err(e),
+    };
 
-    // Use gh CLI to create the PR (uses native GitHub authentication)
-    let gh_cli = GhCli::new();
-    match gh_cli.create_pr(&pr_request, &repo_info) {
+    match result {
         Ok(pr_info) => {
             // Update the task attempt with PR information
             if let Err(e) = Merge::create_pr(
@@ -853,18 +1010,14 @@ pub async fn create_github_pr(
             Ok(ResponseJson(ApiResponse::success(pr_info.url)))
         }
         Err(e) => {
-            tracing::error!(
-                "Failed to create GitHub PR for attempt {}: {}",
-                task_attempt.id,
-                e
-            );
-            let gh_error = GitHubServiceError::from(e);
-            if gh_error.is_api_data() {
-                Ok(ResponseJson(ApiResponse::error_with_data(gh_error)))
-            } else {
-                Ok(ResponseJson(ApiResponse::error(
-                    format!("Failed to create PR: {}", gh_error).as_str(),
-                )))
+            tracing::error!("Failed to create PR for attempt {}: {}", task_attempt.id, e);
+            match e {
+                GitHostError::GitHub(gh_error) if gh_error.is_api_data() => {
+                    Ok(ResponseJson(ApiResponse::error_with_data(gh_error)))
+                }
+                e => Ok(ResponseJson(ApiResponse::error(
+                    format!("Failed to create PR: {}", e).as_str(),
+                ))),
             }
         }
     }
@@ -958,6 +1111,10 @@ pub struct BranchStatus {
     pub conflict_op: Option<ConflictOp>,
     /// List of files currently in conflicted (unmerged) state
     pub conflicted_files: Vec<String>,
+    /// Latest agent-assisted resolution, while it runs or its operation is still in progress
+    pub conflict_resolution: Option<ConflictResolution>,
+    /// CI checks of the latest PR, as last seen by the PR monitor
+    pub pr_checks: Option<PrChecks>,
 }
 
 pub async fn get_task_attempt_branch_status(
@@ -998,6 +1155,13 @@ pub async fn get_task_attempt_branch_status(
         };
         (in_rebase, conflicts, op)
     };
+    let conflict_resolution =
+        match ConflictResolution::find_latest_by_task_attempt_id(pool, task_attempt.id).await? {
+            Some(resolution) if resolution.completed_at.is_none() || conflict_op.is_some() => {
+                Some(resolution)
+            }
+            _ => None,
+        };
     let (uncommitted_count, untracked_count) = {
         let wt_buf = ensure_worktree_path(&deployment, &task_attempt).await?;
         let wt = wt_buf.as_path();
@@ -1048,6 +1212,12 @@ pub async fn get_task_attempt_branch_status(
     } else {
         (None, None)
     };
+    let pr_checks = match merges.first() {
+        Some(Merge::Pr(pr_merge)) => PrCheckSummary::find_by_merge_id(pool, pr_merge.id)
+            .await?
+            .map(|summary| summary.checks),
+        _ => None,
+    };
 
     let branch_status = BranchStatus {
         commits_ahead,
@@ -1063,6 +1233,8 @@ pub async fn get_task_attempt_branch_status(
         is_rebase_in_progress,
         conflict_op,
         conflicted_files,
+        conflict_resolution,
+        pr_checks,
     };
     Ok(ResponseJson(ApiResponse::success(branch_status)))
 }
@@ -1114,6 +1286,8 @@ pub async fn change_target_branch(
                 &new_target_branch,
             )
             .await?;
+            stack::unstack_if_retargeted(&deployment.db().pool, &task_attempt, &new_target_branch)
+                .await?;
         }
         false => {
             return Ok(ResponseJson(ApiResponse::error(
@@ -1219,13 +1393,21 @@ pub async fn rename_branch(
 
     TaskAttempt::update_branch_name(pool, task_attempt.id, new_branch_name).await?;
 
-    let updated_children_count = TaskAttempt::update_target_branch_for_children_of_attempt(
+    let mut updated_children_count = TaskAttempt::update_target_branch_for_children_of_attempt(
         pool,
         task_attempt.id,
         &old_branch,
         new_branch_name,
     )
     .await?;
+    for child in TaskAttemptStack::find_children(pool, task_attempt.id).await? {
+        if let Some(child_attempt) = TaskAttempt::find_by_id(pool, child.task_attempt_id).await?
+            && child_attempt.target_branch == old_branch
+        {
+            TaskAttempt::update_target_branch(pool, child_attempt.id, new_branch_name).await?;
+            updated_children_count += 1;
+        }
+    }
 
     if updated_children_count > 0 {
         tracing::info!(
@@ -1255,9 +1437,6 @@ pub async fn rebase_task_attempt(
     State(deployment): State<DeploymentImpl>,
     Json(payload): Json<RebaseTaskAttemptRequest>,
 ) -> Result<ResponseJson<ApiResponse<(), GitOperationError>>, ApiError> {
-    let old_base_branch = payload
-        .old_base_branch
-        .unwrap_or(task_attempt.target_branch.clone());
     let new_base_branch = payload
         .new_base_branch
         .unwrap_or(task_attempt.target_branch.clone());
@@ -1292,6 +1471,15 @@ pub async fn rebase_task_attempt(
         }
     }
 
+    stack::unstack_if_retargeted(pool, &task_attempt, &new_base_branch).await?;
+    // A stacked attempt sits on a parent commit the parent's branch may have moved past
+    let stack_link = TaskAttemptStack::find_by_attempt_id(pool, task_attempt.id).await?;
+    let old_base_branch = match (payload.old_base_branch, &stack_link) {
+        (Some(old_base_branch), _) => old_base_branch,
+        (None, Some(stack_link)) => stack_link.base_commit.clone(),
+        (None, None) => task_attempt.target_branch.clone(),
+    };
+
     let worktree_path_buf = ensure_worktree_path(&deployment, &task_attempt).await?;
     let worktree_path = worktree_path_buf.as_path();
 
@@ -1324,6 +1512,21 @@ pub async fn rebase_task_attempt(
         };
     }
 
+    if let Some(stack_link) = stack_link {
+        let base_commit = deployment
+            .git()
+            .get_branch_oid(&ctx.project.git_repo_path, &new_base_branch)?;
+        TaskAttemptStack::set_base(
+            pool,
+            task_attempt.id,
+            stack_link.parent_attempt_id,
+            &base_commit,
+        )
+        .await?;
+    }
+    let hosts = deployment.config().read().await.git_hosts.clone();
+    attempt_stack::restack_descendants(deployment.container(), &hosts, task_attempt.id).await;
+
     deployment
         .track_if_analytics_allowed(
             "task_attempt_rebased",
@@ -1352,6 +1555,27 @@ pub async fn abort_conflicts_task_attempt(
     Ok(ResponseJson(ApiResponse::success(())))
 }
 
+/// Ask the agent to resolve the conflicts of the in-progress rebase, merge, cherry-pick
+/// or revert; it is continued once the follow-up leaves the worktree conflict-free
+pub async fn resolve_conflicts_task_attempt(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<ExecutionProcess>>, ApiError> {
+    let execution_process =
+        conflict_resolution::start(deployment.container(), &task_attempt).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_conflicts_resolve_started",
+            serde_json::json!({
+                "attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(execution_process)))
+}
+
 #[derive(serde::Deserialize)]
 pub struct DeleteFileQuery {
     file_path: String,
@@ -1558,15 +1782,13 @@ pub async fn attach_existing_pr(
         return Err(ApiError::Project(ProjectError::ProjectNotFound));
     };
 
-    let github_service = GitHubService::new()?;
-    let repo_info = deployment
-        .git()
-        .get_github_repo_info(&project.git_repo_path)?;
+    let remote_url = deployment.git().get_remote_url(&project.git_repo_path)?;
+    let hosts = deployment.config().read().await.git_hosts.clone();
+    let secrets = deployment.container().secrets().values().await;
+    let host = git_host::from_remote_url(&remote_url, &hosts, &secrets)?;
 
     // List all PRs for branch (open, closed, and merged)
-    let prs = github_service
-        .list_all_prs_for_branch(&repo_info, &task_attempt.branch)
-        .await?;
+    let prs = host.list_prs_for_branch(&task_attempt.branch).await?;
 
     // Take the first PR (prefer open, but also accept merged/closed)
     if let Some(pr_info) = prs.into_iter().next() {
@@ -1683,13 +1905,32 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/commit-compare", get(compare_commit_to_head))
         .route("/start-dev-server", post(start_dev_server))
         .route("/branch-status", get(get_task_attempt_branch_status))
+        .route("/repositories", get(repositories::get_attempt_repositories))
+        .route("/diff/stats", get(get_task_attempt_diff_stats))
         .route("/diff/ws", get(stream_task_attempt_diff_ws))
         .route("/merge", post(merge_task_attempt))
+        .route("/merge/preview", get(get_merge_preview))
+        .route(
+            "/merge-queue",
+            get(queue::get_merge_queue_entry)
+                .post(queue::enqueue_task_attempt)
+                .delete(queue::dequeue_task_attempt),
+        )
         .route("/push", post(push_task_attempt_branch))
         .route("/rebase", post(rebase_task_attempt))
+        .route("/stack", get(stack::get_attempt_stack))
+        .route("/restack", post(stack::restack_task_attempt))
+        .route("/fork", post(fork::fork_task_attempt))
+        .route("/fork-tree", get(fork::get_fork_tree))
         .route("/conflicts/abort", post(abort_conflicts_task_attempt))
+        .route("/conflicts/resolve", post(resolve_conflicts_task_attempt))
         .route("/pr", post(create_github_pr))
         .route("/pr/attach", post(attach_existing_pr))
+        .route("/pr/review-threads", get(pr_review::get_pr_review_threads))
+        .route(
+            "/pr/review-threads/draft",
+            post(pr_review::create_review_draft),
+        )
         .route("/open-editor", post(open_task_attempt_in_editor))
         .route("/delete-file", post(delete_task_attempt_file))
         .route("/children", get(get_task_attempt_children))
@@ -1703,6 +1944,10 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
 
     let task_attempts_router = Router::new()
         .route("/", get(get_task_attempts).post(create_task_attempt))
+        .route("/race", post(race::create_race_attempts))
+        .route("/race/{race_id}", get(race::get_race))
+        .route("/race/{race_id}/compare", get(race::compare_race_attempts))
+        .route("/race/{race_id}/winner", post(race::pick_race_winner))
         .nest("/{id}", task_attempt_id_router);
 
     Router::new().nest("/task-attempts", task_attempts_router)
@@ -0,0 +1,52 @@
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::task_attempt::TaskAttempt;
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::attempt_fork::{self, AttemptForkTree};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct ForkTaskAttemptRequest {
+    /// Coding agent process of this attempt to fork after
+    pub execution_process_id: Uuid,
+}
+
+/// Fork the attempt after one of its coding agent processes into a new attempt
+pub async fn fork_task_attempt(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<ForkTaskAttemptRequest>,
+) -> Result<ResponseJson<ApiResponse<TaskAttempt>>, ApiError> {
+    let forked = attempt_fork::fork_attempt(
+        deployment.container(),
+        &task_attempt,
+        payload.execution_process_id,
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_forked",
+            serde_json::json!({
+                "task_id": forked.task_id.to_string(),
+                "attempt_id": forked.id.to_string(),
+                "parent_attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(forked)))
+}
+
+/// The tree of attempts forked from one another that this attempt belongs to
+pub async fn get_fork_tree(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<AttemptForkTree>>, ApiError> {
+    let tree = attempt_fork::fork_tree(&deployment.db().pool, &task_attempt).await?;
+    Ok(ResponseJson(ApiResponse::success(tree)))
+}
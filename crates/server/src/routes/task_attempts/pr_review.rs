@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+
+use axum::{Extension, Json, extract::State, response::Json as ResponseJson};
+use db::models::{
+    draft::{Draft, DraftType},
+    merge::Merge,
+    project::{Project, ProjectError},
+    task_attempt::{TaskAttempt, TaskAttemptError},
+};
+use deployment::Deployment;
+use serde::Deserialize;
+use services::services::{
+    drafts::{DraftResponse, UpdateFollowUpDraftRequest},
+    git_host::{self, GitHostKind},
+    github_service::{GitHubService, PrReviewThread},
+};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Lines of code shown above and below the commented lines
+const SNIPPET_CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateReviewDraftRequest {
+    /// Review threads to turn into a follow-up; resolved threads are skipped
+    pub thread_ids: Vec<String>,
+}
+
+/// Review threads of the GitHub pull request attached to the attempt
+async fn fetch_review_threads(
+    deployment: &DeploymentImpl,
+    task_attempt: &TaskAttempt,
+) -> Result<(Project, Vec<PrReviewThread>), ApiError> {
+    let pool = &deployment.db().pool;
+    let Some(Merge::Pr(pr_merge)) =
+        Merge::find_latest_by_task_attempt_id(pool, task_attempt.id).await?
+    else {
+        return Err(ApiError::BadRequest(
+            "No pull request is attached to this attempt".to_string(),
+        ));
+    };
+    let Some(task) = task_attempt.parent_task(pool).await? else {
+        return Err(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound));
+    };
+    let Some(project) = Project::find_by_id(pool, task.project_id).await? else {
+        return Err(ApiError::Project(ProjectError::ProjectNotFound));
+    };
+
+    let remote_url = deployment.git().get_remote_url(&project.git_repo_path)?;
+    let hosts = deployment.config().read().await.git_hosts.clone();
+    if git_host::kind_for_remote_url(&remote_url, &hosts) != Some(GitHostKind::GitHub) {
+        return Err(ApiError::BadRequest(
+            "Review comments can only be imported from GitHub pull requests".to_string(),
+        ));
+    }
+
+    let repo_info = deployment
+        .git()
+        .get_github_repo_info(&project.git_repo_path)?;
+    let threads = GitHubService::new()?
+        .list_review_threads(&repo_info, pr_merge.pr_info.number)
+        .await?;
+    Ok((project, threads))
+}
+
+pub async fn get_pr_review_threads(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<PrReviewThread>>>, ApiError> {
+    let (_, threads) = fetch_review_threads(&deployment, &task_attempt).await?;
+    Ok(ResponseJson(ApiResponse::success(threads)))
+}
+
+/// Append the selected unresolved review threads to the attempt's follow-up draft
+pub async fn create_review_draft(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateReviewDraftRequest>,
+) -> Result<ResponseJson<ApiResponse<DraftResponse>>, ApiError> {
+    let (project, threads) = fetch_review_threads(&deployment, &task_attempt).await?;
+    let selected: Vec<PrReviewThread> = threads
+        .into_iter()
+        .filter(|t| !t.is_resolved && payload.thread_ids.contains(&t.id))
+        .collect();
+    if selected.is_empty() {
+        return Err(ApiError::BadRequest(
+            "No unresolved review threads selected".to_string(),
+        ));
+    }
+
+    // Line numbers refer to the commit each thread was anchored to, which may no longer
+    // be the branch head
+    let mut contents = HashMap::new();
+    for thread in &selected {
+        let Some(commit_oid) = &thread.commit_oid else {
+            continue;
+        };
+        if let Some(content) =
+            deployment
+                .git()
+                .get_file_at_commit(&project.git_repo_path, commit_oid, &thread.path)?
+        {
+            contents.insert(thread.id.clone(), content);
+        }
+    }
+
+    let review_prompt = build_review_prompt(&selected, &contents);
+    let existing = Draft::find_by_task_attempt_and_type(
+        &deployment.db().pool,
+        task_attempt.id,
+        DraftType::FollowUp,
+    )
+    .await?
+    .map(|d| d.prompt)
+    .filter(|p| !p.trim().is_empty());
+    let prompt = match existing {
+        Some(existing) => format!("{}\n\n{review_prompt}", existing.trim_end()),
+        None => review_prompt,
+    };
+
+    let resp = deployment
+        .drafts()
+        .save_follow_up_draft(
+            &task_attempt,
+            &UpdateFollowUpDraftRequest {
+                prompt: Some(prompt),
+                variant: None,
+                image_ids: None,
+                version: None,
+            },
+        )
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(resp)))
+}
+
+/// `contents` maps thread ids to the commented file at the thread's commit
+fn build_review_prompt(threads: &[PrReviewThread], contents: &HashMap<String, String>) -> String {
+    let mut prompt = String::from("Address the following pull request review comments:\n");
+    for thread in threads {
+        let location = match (thread.start_line, thread.line) {
+            (Some(start), Some(end)) if start != end => format!("{}:{start}-{end}", thread.path),
+            (_, Some(line)) => format!("{}:{line}", thread.path),
+            _ => format!("{} (outdated)", thread.path),
+        };
+        prompt.push_str(&format!("\n### {location}\n"));
+
+        if let Some(end) = thread.line
+            && let Some(content) = contents.get(&thread.id)
+        {
+            let start = thread.start_line.unwrap_or(end);
+            if let Some(snippet) = snippet(content, start as usize, end as usize) {
+                prompt.push_str(&format!("```\n{snippet}```\n"));
+            }
+        }
+
+        for comment in &thread.comments {
+            let author = comment.author.as_deref().unwrap_or("unknown");
+            prompt.push_str(&format!("\n@{author}:\n{}\n", comment.body.trim()));
+        }
+    }
+    prompt
+}
+
+/// Numbered lines `start..=end` (1-based) with surrounding context, commented lines
+/// marked with `>`
+fn snippet(content: &str, start: usize, end: usize) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    if start == 0 || start > end || end > lines.len() {
+        return None;
+    }
+    let from = start.saturating_sub(SNIPPET_CONTEXT_LINES).max(1);
+    let to = (end + SNIPPET_CONTEXT_LINES).min(lines.len());
+    Some(
+        (from..=to)
+            .map(|n| {
+                let marker = if (start..=end).contains(&n) { '>' } else { ' ' };
+                format!("{marker}{n:>5} | {}\n", lines[n - 1])
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use services::services::github_service::PrReviewComment;
+
+    use super::*;
+
+    fn thread(id: &str, start_line: Option<i64>, line: Option<i64>) -> PrReviewThread {
+        PrReviewThread {
+            id: id.to_string(),
+            path: "src/lib.rs".to_string(),
+            line,
+            start_line,
+            is_resolved: false,
+            is_outdated: line.is_none(),
+            commit_oid: Some("abc123".to_string()),
+            comments: vec![PrReviewComment {
+                author: Some("alice".to_string()),
+                body: " Please rename this \n".to_string(),
+                url: "u".to_string(),
+                created_at: "2025-11-20T10:00:00Z".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn snippet_marks_commented_lines_with_context() {
+        let content: String = (1..=10).map(|n| format!("line {n}\n")).collect();
+        assert_eq!(
+            snippet(&content, 5, 6).unwrap(),
+            "     2 | line 2\n     3 | line 3\n     4 | line 4\n\
+             >    5 | line 5\n>    6 | line 6\n\
+             \x20    7 | line 7\n     8 | line 8\n     9 | line 9\n"
+        );
+        // Context is clipped at both ends of the file
+        assert_eq!(
+            snippet(&content, 1, 1).unwrap().lines().count(),
+            1 + SNIPPET_CONTEXT_LINES
+        );
+        assert_eq!(
+            snippet(&content, 10, 10).unwrap().lines().count(),
+            1 + SNIPPET_CONTEXT_LINES
+        );
+        assert_eq!(snippet(&content, 0, 1), None);
+        assert_eq!(snippet(&content, 4, 3), None);
+        assert_eq!(snippet(&content, 9, 11), None);
+    }
+
+    #[test]
+    fn review_prompt_lists_locations_code_and_comments() {
+        let contents = HashMap::from([(
+            "T1".to_string(),
+            "fn a() {}\nfn b() {}\nfn c() {}\n".to_string(),
+        )]);
+        let prompt = build_review_prompt(
+            &[
+                thread("T1", Some(1), Some(2)),
+                thread("T2", None, Some(7)),
+                thread("T3", None, None),
+            ],
+            &contents,
+        );
+
+        assert!(prompt.starts_with("Address the following pull request review comments:\n"));
+        assert!(prompt.contains(
+            "\n### src/lib.rs:1-2\n```\n>    1 | fn a() {}\n>    2 | fn b() {}\n     3 | fn c() {}\n```\n"
+        ));
+        // Without the file at the thread's commit there is no snippet
+        assert!(prompt.contains("\n### src/lib.rs:7\n\n@alice:\nPlease rename this\n"));
+        assert!(prompt.contains("\n### src/lib.rs (outdated)\n"));
+        assert_eq!(prompt.matches("@alice:").count(), 3);
+    }
+}
@@ -0,0 +1,66 @@
+use axum::{Extension, extract::State, response::Json as ResponseJson};
+use db::models::{
+    merge_queue_entry::MergeQueueEntry,
+    task_attempt::{TaskAttempt, TaskAttemptError},
+};
+use deployment::Deployment;
+use services::services::merge_queue;
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// The attempt's place in its project's merge queue, if it is queued
+pub async fn get_merge_queue_entry(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<MergeQueueEntry>>>, ApiError> {
+    let entry =
+        MergeQueueEntry::find_active_by_attempt_id(&deployment.db().pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(entry)))
+}
+
+/// Queue the attempt to be rebased, verified and merged into its target branch
+pub async fn enqueue_task_attempt(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<MergeQueueEntry>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let task = task_attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+
+    let entry = merge_queue::enqueue(pool, &task_attempt, task.project_id).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_merge_queued",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "project_id": task.project_id.to_string(),
+                "attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(entry)))
+}
+
+/// Take the attempt out of the merge queue
+pub async fn dequeue_task_attempt(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<MergeQueueEntry>>, ApiError> {
+    let entry = merge_queue::cancel(&deployment.db().pool, &task_attempt).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_merge_dequeued",
+            serde_json::json!({
+                "attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(entry)))
+}
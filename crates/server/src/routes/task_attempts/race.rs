@@ -0,0 +1,256 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::Json as ResponseJson,
+};
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    task::Task,
+    task_attempt::TaskAttempt,
+    task_attempt_race::TaskAttemptRace,
+};
+use deployment::Deployment;
+use executors::profile::ExecutorProfileId;
+use serde::{Deserialize, Serialize};
+use services::services::{
+    attempt_race,
+    git::{DiffTarget, GitService, GitServiceError},
+};
+use sqlx::Error as SqlxError;
+use ts_rs::TS;
+use utils::{diff::compute_line_change_counts, response::ApiResponse};
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateRaceAttemptsBody {
+    pub task_id: Uuid,
+    pub executor_profile_ids: Vec<ExecutorProfileId>,
+    pub base_branch: String,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct RaceAttemptsResponse {
+    pub race: TaskAttemptRace,
+    pub attempts: Vec<TaskAttempt>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct RaceAttemptComparison {
+    pub attempt: TaskAttempt,
+    pub archived: bool,
+    pub is_winner: bool,
+    pub files_changed: usize,
+    pub additions: usize,
+    pub deletions: usize,
+    /// Status of the latest coding agent run, if one has started
+    pub status: Option<ExecutionProcessStatus>,
+    pub exit_code: Option<i64>,
+    /// Wall-clock duration of the latest coding agent run in milliseconds
+    pub duration_ms: Option<i64>,
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct RaceComparisonResponse {
+    pub race: TaskAttemptRace,
+    pub attempts: Vec<RaceAttemptComparison>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct PickRaceWinnerRequest {
+    pub attempt_id: Uuid,
+}
+
+/// Start the same task on several executors at once, one worktree per executor.
+pub async fn create_race_attempts(
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateRaceAttemptsBody>,
+) -> Result<ResponseJson<ApiResponse<RaceAttemptsResponse>>, ApiError> {
+    let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+
+    let (race, attempts) = attempt_race::start_race(
+        deployment.container(),
+        &task,
+        &payload.executor_profile_ids,
+        &payload.base_branch,
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_race_started",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "race_id": race.id.to_string(),
+                "executors": payload
+                    .executor_profile_ids
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(RaceAttemptsResponse {
+        race,
+        attempts,
+    })))
+}
+
+pub async fn get_race(
+    State(deployment): State<DeploymentImpl>,
+    Path(race_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<RaceAttemptsResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let race = TaskAttemptRace::find_by_id(pool, race_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let attempts = race.attempts(pool).await?;
+    Ok(ResponseJson(ApiResponse::success(RaceAttemptsResponse {
+        race,
+        attempts,
+    })))
+}
+
+/// Side-by-side comparison of every attempt in a race: diff stats, exit status and duration.
+pub async fn compare_race_attempts(
+    State(deployment): State<DeploymentImpl>,
+    Path(race_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<RaceComparisonResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let race = TaskAttemptRace::find_by_id(pool, race_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let task = Task::find_by_id(pool, race.task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let project = task
+        .parent_project(pool)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let entries = race.entries(pool).await?;
+
+    let mut comparisons = Vec::new();
+    for attempt in race.attempts(pool).await? {
+        let archived = entries
+            .iter()
+            .any(|e| e.task_attempt_id == attempt.id && e.archived);
+        let (files_changed, additions, deletions) =
+            diff_stats(deployment.git(), &project.git_repo_path, &attempt).unwrap_or_else(|e| {
+                tracing::warn!(
+                    "Failed to compute diff for race attempt {}: {}",
+                    attempt.id,
+                    e
+                );
+                (0, 0, 0)
+            });
+
+        let latest_run = ExecutionProcess::find_latest_by_task_attempt_and_run_reason(
+            pool,
+            attempt.id,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+        let duration_ms = latest_run.as_ref().and_then(|p| {
+            p.completed_at
+                .map(|completed_at| (completed_at - p.started_at).num_milliseconds())
+        });
+
+        comparisons.push(RaceAttemptComparison {
+            is_winner: race.winner_attempt_id == Some(attempt.id),
+            archived,
+            files_changed,
+            additions,
+            deletions,
+            status: latest_run.as_ref().map(|p| p.status.clone()),
+            exit_code: latest_run.as_ref().and_then(|p| p.exit_code),
+            duration_ms,
+            attempt,
+        });
+    }
+
+    Ok(ResponseJson(ApiResponse::success(RaceComparisonResponse {
+        race,
+        attempts: comparisons,
+    })))
+}
+
+/// Keep one attempt and archive the rest, removing their worktrees and branches.
+pub async fn pick_race_winner(
+    State(deployment): State<DeploymentImpl>,
+    Path(race_id): Path<Uuid>,
+    Json(payload): Json<PickRaceWinnerRequest>,
+) -> Result<ResponseJson<ApiResponse<TaskAttemptRace>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let race = TaskAttemptRace::find_by_id(pool, race_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let race =
+        attempt_race::pick_race_winner(deployment.container(), &race, payload.attempt_id).await?;
+    let attempt_count = race.entries(pool).await?.len();
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_race_winner_picked",
+            serde_json::json!({
+                "task_id": race.task_id.to_string(),
+                "race_id": race.id.to_string(),
+                "attempt_id": payload.attempt_id.to_string(),
+                "attempt_count": attempt_count,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(race)))
+}
+
+/// Files changed, lines added and lines removed by the attempt relative to its target,
+/// including uncommitted work while its worktree exists
+fn diff_stats(
+    git: &GitService,
+    repo_path: &std::path::Path,
+    attempt: &TaskAttempt,
+) -> Result<(usize, usize, usize), GitServiceError> {
+    let worktree_path = attempt
+        .container_ref
+        .as_deref()
+        .map(std::path::Path::new)
+        .filter(|path| !attempt.worktree_deleted && path.exists());
+    let diffs = match worktree_path {
+        Some(worktree_path) => {
+            let base_commit =
+                git.get_base_commit(repo_path, &attempt.branch, &attempt.target_branch)?;
+            git.get_diffs(
+                DiffTarget::Worktree {
+                    worktree_path,
+                    base_commit: &base_commit,
+                },
+                None,
+            )?
+        }
+        None => git.get_diffs(
+            DiffTarget::Branch {
+                repo_path,
+                branch_name: &attempt.branch,
+                base_branch: &attempt.target_branch,
+            },
+            None,
+        )?,
+    };
+
+    let (additions, deletions) = diffs.iter().fold((0, 0), |(adds, dels), diff| {
+        let (a, d) = match (diff.additions, diff.deletions) {
+            (Some(a), Some(d)) => (a, d),
+            _ => compute_line_change_counts(
+                diff.old_content.as_deref().unwrap_or(""),
+                diff.new_content.as_deref().unwrap_or(""),
+            ),
+        };
+        (adds + a, dels + d)
+    });
+
+    Ok((diffs.len(), additions, deletions))
+}
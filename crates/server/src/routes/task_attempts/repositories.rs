@@ -0,0 +1,183 @@
+use axum::{Extension, extract::State, response::Json as ResponseJson};
+use db::models::{task_attempt::TaskAttempt, task_attempt_repository::TaskAttemptRepository};
+use deployment::Deployment;
+use serde::Serialize;
+use services::services::{
+    container::ContainerService, git::DiffTarget, git_host, github_service::CreatePrRequest,
+};
+use ts_rs::TS;
+use utils::{diff::Diff, response::ApiResponse};
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Changes of the attempt in one of its project's additional repositories
+#[derive(Debug, Serialize, TS)]
+pub struct AttemptRepositoryDiff {
+    pub repository: TaskAttemptRepository,
+    pub commits_ahead: usize,
+    pub commits_behind: usize,
+    pub diffs: Vec<Diff>,
+}
+
+/// The attempt's worktrees in the project's additional repositories, each with its diff
+/// against the repository's target branch. Empty for single-repository projects.
+pub async fn get_attempt_repositories(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<AttemptRepositoryDiff>>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let repositories =
+        TaskAttemptRepository::find_by_task_attempt_id(pool, task_attempt.id).await?;
+    if repositories.is_empty() {
+        return Ok(ResponseJson(ApiResponse::success(Vec::new())));
+    }
+    deployment
+        .container()
+        .ensure_container_exists(&task_attempt)
+        .await?;
+
+    let git = deployment.git();
+    let mut repository_diffs = Vec::with_capacity(repositories.len());
+    for repository in repositories {
+        let (commits_ahead, commits_behind) = git.get_branch_status(
+            &repository.git_repo_path,
+            &task_attempt.branch,
+            &repository.target_branch,
+        )?;
+        // Once merged, the changes are those of the merge commit
+        let diffs = match &repository.merge_commit {
+            Some(merge_commit)
+                if commits_ahead == 0 && git.is_worktree_clean(&repository.worktree_path)? =>
+            {
+                git.get_diffs(
+                    DiffTarget::Commit {
+                        repo_path: &repository.git_repo_path,
+                        commit_sha: merge_commit,
+                    },
+                    None,
+                )?
+            }
+            _ => {
+                let base_commit = git.get_base_commit(
+                    &repository.git_repo_path,
+                    &task_attempt.branch,
+                    &repository.target_branch,
+                )?;
+                git.get_diffs(
+                    DiffTarget::Worktree {
+                        worktree_path: &repository.worktree_path,
+                        base_commit: &base_commit,
+                    },
+                    None,
+                )?
+            }
+        };
+        repository_diffs.push(AttemptRepositoryDiff {
+            repository,
+            commits_ahead,
+            commits_behind,
+            diffs,
+        });
+    }
+
+    Ok(ResponseJson(ApiResponse::success(repository_diffs)))
+}
+
+/// Push the attempt's branch in each additional repository, then open a PR in each one
+/// that does not have one yet. Every repository is checked first (branch, remote, git
+/// host), so nothing is pushed or opened when one of them cannot get a PR. Should
+/// opening a PR still fail, the message lists the PRs that were opened; they are
+/// recorded and reused when the user tries again.
+/// Returns the PRs as `(repository name, url)`, or a message for the user.
+pub async fn open_repository_prs(
+    deployment: &DeploymentImpl,
+    task_attempt: &TaskAttempt,
+    title: &str,
+    body: Option<&str>,
+) -> Result<Vec<(String, String)>, String> {
+    let pool = &deployment.db().pool;
+    let repositories = TaskAttemptRepository::find_by_task_attempt_id(pool, task_attempt.id)
+        .await
+        .map_err(|e| format!("Failed to load the attempt's repositories: {e}"))?;
+    let git = deployment.git();
+    let hosts = deployment.config().read().await.git_hosts.clone();
+    let secrets = deployment.container().secrets().values().await;
+
+    let mut pending = Vec::with_capacity(repositories.len());
+    for repository in &repositories {
+        let has_branch = git
+            .local_branch_exists(&repository.git_repo_path, &task_attempt.branch)
+            .map_err(|e| format!("Failed to read '{}': {e}", repository.name))?;
+        if !has_branch {
+            return Err(format!(
+                "'{}' has no branch '{}', so no PR was created",
+                repository.name, task_attempt.branch
+            ));
+        }
+        if repository.pr_url.is_some() {
+            continue;
+        }
+        let remote_url = git
+            .get_remote_url(&repository.git_repo_path)
+            .map_err(|e| format!("No remote for '{}': {e}", repository.name))?;
+        let host = git_host::from_remote_url(&remote_url, &hosts, &secrets).map_err(|e| {
+            format!(
+                "Cannot open a PR in '{}', so no PR was created: {e}",
+                repository.name
+            )
+        })?;
+        pending.push((repository, host));
+    }
+
+    for repository in &repositories {
+        git.push_to_github(&repository.worktree_path, &task_attempt.branch)
+            .map_err(|e| {
+                format!(
+                    "Failed to push branch to '{}', so no PR was created: {e}",
+                    repository.name
+                )
+            })?;
+    }
+
+    let mut prs: Vec<(String, String)> = repositories
+        .iter()
+        .filter_map(|r| r.pr_url.clone().map(|url| (r.name.clone(), url)))
+        .collect();
+    for (repository, host) in pending {
+        let pr_request = CreatePrRequest {
+            title: title.to_string(),
+            body: body.map(str::to_string),
+            head_branch: task_attempt.branch.clone(),
+            base_branch: repository.target_branch.clone(),
+        };
+        let pr_info = match host.create_pr(&pr_request).await {
+            Ok(pr_info) => pr_info,
+            Err(e) => {
+                let mut message = format!("Failed to create PR in '{}': {e}", repository.name);
+                if !prs.is_empty() {
+                    let opened = prs
+                        .iter()
+                        .map(|(name, url)| format!("{name}: {url}"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    message.push_str(&format!(
+                        ". Already opened, and reused when you try again: {opened}"
+                    ));
+                }
+                return Err(message);
+            }
+        };
+        if let Err(e) =
+            TaskAttemptRepository::set_pr(pool, repository.id, pr_info.number, &pr_info.url).await
+        {
+            tracing::error!(
+                "Failed to record PR of repository '{}': {}",
+                repository.name,
+                e
+            );
+        }
+        prs.push((repository.name.clone(), pr_info.url));
+    }
+    prs.sort();
+    Ok(prs)
+}
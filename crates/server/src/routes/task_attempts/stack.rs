@@ -0,0 +1,113 @@
+use axum::{Extension, extract::State, response::Json as ResponseJson};
+use db::models::{
+    task_attempt::{TaskAttempt, TaskAttemptError},
+    task_attempt_stack::TaskAttemptStack,
+};
+use deployment::Deployment;
+use services::services::{
+    attempt_stack::{self, AttemptStackError},
+    git::{ConflictOp, GitServiceError},
+};
+use sqlx::SqlitePool;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{DeploymentImpl, error::ApiError, routes::task_attempts::GitOperationError};
+
+/// The attempt this attempt is stacked on, and the state of its last restack
+pub async fn get_attempt_stack(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Option<TaskAttemptStack>>>, ApiError> {
+    let stack =
+        TaskAttemptStack::find_by_attempt_id(&deployment.db().pool, task_attempt.id).await?;
+    Ok(ResponseJson(ApiResponse::success(stack)))
+}
+
+/// Restack the attempt onto its parent now, retrying a restack that failed before,
+/// then bring its own descendants along
+pub async fn restack_task_attempt(
+    Extension(task_attempt): Extension<TaskAttempt>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<(), GitOperationError>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let Some(stack) = TaskAttemptStack::find_by_attempt_id(pool, task_attempt.id).await? else {
+        return Err(ApiError::BadRequest(
+            "This attempt is not stacked on another attempt".to_string(),
+        ));
+    };
+
+    let hosts = deployment.config().read().await.git_hosts.clone();
+    match attempt_stack::restack(deployment.container(), &hosts, &stack, true).await {
+        Ok(_) => {}
+        Err(AttemptStackError::GitService(GitServiceError::MergeConflicts(message))) => {
+            return Ok(ResponseJson(ApiResponse::error_with_data(
+                GitOperationError::MergeConflicts {
+                    message,
+                    op: ConflictOp::Rebase,
+                },
+            )));
+        }
+        Err(AttemptStackError::GitService(GitServiceError::RebaseInProgress)) => {
+            return Ok(ResponseJson(ApiResponse::error_with_data(
+                GitOperationError::RebaseInProgress,
+            )));
+        }
+        Err(e) => return Err(e.into()),
+    }
+    attempt_stack::restack_descendants(deployment.container(), &hosts, task_attempt.id).await;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_restacked",
+            serde_json::json!({
+                "attempt_id": task_attempt.id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+/// The attempt a new attempt in `project_id` is to be stacked on
+pub async fn find_stack_parent(
+    pool: &SqlitePool,
+    parent_attempt_id: Uuid,
+    project_id: Uuid,
+) -> Result<TaskAttempt, ApiError> {
+    let parent = TaskAttempt::find_by_id(pool, parent_attempt_id)
+        .await?
+        .ok_or(ApiError::BadRequest("Parent attempt not found".to_string()))?;
+    let parent_task = parent
+        .parent_task(pool)
+        .await?
+        .ok_or(ApiError::TaskAttempt(TaskAttemptError::TaskNotFound))?;
+    if parent_task.project_id != project_id {
+        return Err(ApiError::BadRequest(
+            "Attempts can only be stacked on attempts of the same project".to_string(),
+        ));
+    }
+    Ok(parent)
+}
+
+/// Drop the stack link of an attempt that is moved onto something other than its
+/// parent's branch
+pub async fn unstack_if_retargeted(
+    pool: &SqlitePool,
+    task_attempt: &TaskAttempt,
+    new_target_branch: &str,
+) -> Result<(), ApiError> {
+    if let Some(stack) = TaskAttemptStack::find_by_attempt_id(pool, task_attempt.id).await?
+        && let Some(parent) = TaskAttempt::find_by_id(pool, stack.parent_attempt_id).await?
+        && parent.branch != new_target_branch
+    {
+        TaskAttemptStack::delete(pool, task_attempt.id).await?;
+        tracing::info!(
+            "Unstacked attempt {} from {} after retargeting it to '{}'",
+            task_attempt.id,
+            parent.id,
+            new_target_branch
+        );
+    }
+    Ok(())
+}
@@ -4,7 +4,7 @@ use anyhow;
 use axum::{
     Extension, Json, Router,
     extract::{
-        Query, State,
+        Path, Query, State,
         ws::{WebSocket, WebSocketUpgrade},
     },
     http::StatusCode,
@@ -16,6 +16,7 @@ use db::models::{
     image::TaskImage,
     task::{CreateTask, Task, TaskWithAttemptStatus, UpdateTask},
     task_attempt::{CreateTaskAttempt, TaskAttempt},
+    task_dependency::{TaskAutoStart, TaskDependencies, TaskDependency},
 };
 use deployment::Deployment;
 use executors::profile::ExecutorProfileId;
@@ -405,11 +406,144 @@ pub async fn share_task(
     })))
 }
 
+pub async fn get_task_dependencies(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<TaskDependencies>>, ApiError> {
+    let dependencies = TaskDependency::find_for_task(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(dependencies)))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct CreateTaskDependencyRequest {
+    pub depends_on_task_id: Uuid,
+}
+
+pub async fn add_task_dependency(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<CreateTaskDependencyRequest>,
+) -> Result<ResponseJson<ApiResponse<TaskDependency>>, ApiError> {
+    let pool = &deployment.db().pool;
+    let upstream = Task::find_by_id(pool, payload.depends_on_task_id)
+        .await?
+        .ok_or_else(|| ApiError::BadRequest("Upstream task not found".to_string()))?;
+
+    if upstream.project_id != task.project_id {
+        return Err(ApiError::BadRequest(
+            "Tasks can only depend on tasks in the same project".to_string(),
+        ));
+    }
+
+    if TaskDependency::would_create_cycle(pool, task.id, upstream.id).await? {
+        return Err(ApiError::Conflict(
+            "Adding this dependency would create a cycle".to_string(),
+        ));
+    }
+
+    let dependency = TaskDependency::create(pool, task.id, upstream.id).await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_dependency_added",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "depends_on_task_id": upstream.id.to_string(),
+                "project_id": task.project_id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(dependency)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RemoveTaskDependencyQuery {
+    pub depends_on_task_id: Uuid,
+}
+
+pub async fn remove_task_dependency(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<RemoveTaskDependencyQuery>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let rows_affected =
+        TaskDependency::delete(&deployment.db().pool, task.id, query.depends_on_task_id).await?;
+    if rows_affected == 0 {
+        return Err(ApiError::Database(SqlxError::RowNotFound));
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_dependency_removed",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "depends_on_task_id": query.depends_on_task_id.to_string(),
+                "project_id": task.project_id.to_string(),
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+#[derive(Debug, Deserialize, TS)]
+pub struct SetTaskAutoStartRequest {
+    pub executor_profile_id: ExecutorProfileId,
+    pub base_branch: String,
+}
+
+/// Schedule an attempt to start automatically once every upstream task is done or merged.
+pub async fn set_task_auto_start(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+    Json(payload): Json<SetTaskAutoStartRequest>,
+) -> Result<ResponseJson<ApiResponse<TaskAutoStart>>, ApiError> {
+    let auto_start = TaskAutoStart::upsert(
+        &deployment.db().pool,
+        task.id,
+        &payload.executor_profile_id,
+        &payload.base_branch,
+    )
+    .await?;
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_auto_start_scheduled",
+            serde_json::json!({
+                "task_id": task.id.to_string(),
+                "executor": &payload.executor_profile_id.executor,
+                "variant": &payload.executor_profile_id.variant,
+            }),
+        )
+        .await;
+
+    Ok(ResponseJson(ApiResponse::success(auto_start)))
+}
+
+pub async fn clear_task_auto_start(
+    Extension(task): Extension<Task>,
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    TaskAutoStart::delete(&deployment.db().pool, task.id).await?;
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
 pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
     let task_actions_router = Router::new()
         .route("/", put(update_task))
         .route("/", delete(delete_task))
-        .route("/share", post(share_task));
+        .route("/share", post(share_task))
+        .route(
+            "/dependencies",
+            get(get_task_dependencies)
+                .post(add_task_dependency)
+                .delete(remove_task_dependency),
+        )
+        .route(
+            "/auto-start",
+            put(set_task_auto_start).delete(clear_task_auto_start),
+        );
 
     let task_id_router = Router::new()
         .route("/", get(get_task))
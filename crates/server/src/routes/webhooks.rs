@@ -0,0 +1,108 @@
+use axum::{
+    Router,
+    body::Bytes,
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Json as ResponseJson,
+    routing::post,
+};
+use db::models::pr_webhook_delivery::PrWebhookDelivery;
+use deployment::Deployment;
+use services::services::{
+    git_host::GitHostKind,
+    pr_webhook::{self, PrWebhookError},
+};
+use utils::response::ApiResponse;
+
+use crate::{DeploymentImpl, error::ApiError};
+
+/// Receive a pull request webhook from GitHub, GitLab or Gitea. A verified delivery is
+/// acknowledged straight away and applied to the tracked PRs in the background; a
+/// delivery id that was already seen is acknowledged without being applied again.
+pub async fn receive_pr_webhook(
+    State(deployment): State<DeploymentImpl>,
+    Path(provider): Path<GitHostKind>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<ResponseJson<ApiResponse<()>>, ApiError> {
+    let Some(secret) = deployment
+        .config()
+        .read()
+        .await
+        .pr_webhook_secret
+        .clone()
+        .filter(|s| !s.is_empty())
+    else {
+        return Err(ApiError::Forbidden(
+            "Pull request webhooks are not enabled".to_string(),
+        ));
+    };
+
+    let (event_header, signature_header, delivery_header) = match provider {
+        GitHostKind::GitHub => ("X-GitHub-Event", "X-Hub-Signature-256", "X-GitHub-Delivery"),
+        GitHostKind::GitLab => ("X-Gitlab-Event", "X-Gitlab-Token", "X-Gitlab-Event-UUID"),
+        GitHostKind::Gitea => ("X-Gitea-Event", "X-Gitea-Signature", "X-Gitea-Delivery"),
+    };
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    if let Err(e) = pr_webhook::verify_signature(provider, header(signature_header), &secret, &body)
+    {
+        tracing::warn!("Rejected {} webhook: {}", provider.as_str(), e);
+        return Err(ApiError::Unauthorized);
+    }
+
+    let event_name = header(event_header)
+        .ok_or_else(|| ApiError::BadRequest(format!("Missing {event_header} header")))?;
+    let event = pr_webhook::parse_event(provider, event_name, &body)
+        .map_err(|e: PrWebhookError| ApiError::BadRequest(e.to_string()))?;
+
+    let delivery_id = header(delivery_header)
+        .ok_or_else(|| ApiError::BadRequest(format!("Missing {delivery_header} header")))?;
+    if !PrWebhookDelivery::claim_delivery_id(&deployment.db().pool, provider.as_str(), delivery_id)
+        .await?
+    {
+        tracing::debug!(
+            "Ignoring repeated {} webhook delivery {}",
+            provider.as_str(),
+            delivery_id
+        );
+        return Ok(ResponseJson(ApiResponse::success(())));
+    }
+
+    let pr_monitor = deployment.pr_monitor();
+    let pool = deployment.db().pool.clone();
+    let delivery_id = delivery_id.to_string();
+    tokio::spawn(async move {
+        match pr_monitor.handle_webhook(&event).await {
+            Ok(updated) => tracing::debug!(
+                "Applied {} {} webhook to {} tracked PRs",
+                event.provider.as_str(),
+                event.event,
+                updated
+            ),
+            Err(e) => {
+                tracing::error!(
+                    "Failed to apply {} {} webhook: {}",
+                    event.provider.as_str(),
+                    event.event,
+                    e
+                );
+                // Let the host's redelivery of this delivery be applied
+                if let Err(e) = PrWebhookDelivery::release_delivery_id(
+                    &pool,
+                    event.provider.as_str(),
+                    &delivery_id,
+                )
+                .await
+                {
+                    tracing::error!("Failed to release webhook delivery {}: {}", delivery_id, e);
+                }
+            }
+        }
+    });
+    Ok(ResponseJson(ApiResponse::success(())))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new().route("/webhooks/{provider}", post(receive_pr_webhook))
+}
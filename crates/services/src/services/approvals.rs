@@ -1,16 +1,18 @@
 pub mod executor_approvals;
+pub mod policy;
 
-use std::{collections::HashMap, sync::Arc, time::Duration as StdDuration};
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration as StdDuration};
 
 use dashmap::DashMap;
 use db::models::{
+    approval::{Approval, ApprovalDecider, ApprovalOutcome, CreateApproval, PolicyDecision},
     execution_process::ExecutionProcess,
     task::{Task, TaskStatus},
 };
 use executors::{
     approvals::ToolCallMetadata,
     logs::{
-        NormalizedEntry, NormalizedEntryType, ToolStatus,
+        ActionType, NormalizedEntry, NormalizedEntryType, ToolStatus,
         utils::patch::{ConversationPatch, extract_normalized_entry_from_patch},
     },
 };
@@ -25,6 +27,13 @@ use utils::{
 };
 use uuid::Uuid;
 
+use self::policy::ApprovalPolicy;
+use crate::services::events::{LifecycleEvent, lifecycle_patch};
+
+/// How long an approval request waits for the log normalizer to record its tool call
+const TOOL_USE_WAIT: StdDuration = StdDuration::from_secs(2);
+const TOOL_USE_POLL_INTERVAL: StdDuration = StdDuration::from_millis(20);
+
 #[derive(Debug)]
 struct PendingApproval {
     entry_index: usize,
@@ -42,11 +51,29 @@ pub struct ToolContext {
     pub execution_process_id: Uuid,
 }
 
+/// A project approval policy's verdict on a tool call, kept on its approval record
+#[derive(Debug, Clone, Copy)]
+pub struct PolicyVerdict {
+    pub decision: PolicyDecision,
+    /// Index of the matching rule, `None` when no rule matched
+    pub rule_index: Option<i64>,
+}
+
+/// What a project's approval policy made of a request
+#[derive(Debug)]
+pub enum PolicyOutcome {
+    /// The policy approved or denied the tool call
+    Decided(ApprovalStatus),
+    /// The policy left the tool call to a human
+    Escalated(PolicyVerdict),
+}
+
 #[derive(Clone)]
 pub struct Approvals {
     pending: Arc<DashMap<String, PendingApproval>>,
     completed: Arc<DashMap<String, ApprovalStatus>>,
     msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
+    events_msg_store: Arc<MsgStore>,
 }
 
 #[derive(Debug, Error)]
@@ -66,17 +93,25 @@ pub enum ApprovalError {
 }
 
 impl Approvals {
-    pub fn new(msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>) -> Self {
+    pub fn new(
+        msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
+        events_msg_store: Arc<MsgStore>,
+    ) -> Self {
         Self {
             pending: Arc::new(DashMap::new()),
             completed: Arc::new(DashMap::new()),
             msg_stores,
+            events_msg_store,
         }
     }
 
+    /// Ask a human to decide a request. `policy` is the verdict of a policy that
+    /// escalated the request, recorded with the human's decision.
     pub async fn create_with_waiter(
         &self,
+        pool: &SqlitePool,
         request: ApprovalRequest,
+        policy: Option<PolicyVerdict>,
     ) -> Result<(ApprovalRequest, ApprovalWaiter), ApprovalError> {
         let (tx, rx) = oneshot::channel();
         let waiter: ApprovalWaiter = rx
@@ -84,12 +119,14 @@ impl Approvals {
             .boxed()
             .shared();
         let req_id = request.id.clone();
+        let mut action_type = None;
 
         if let Some(store) = self.msg_store_by_id(&request.execution_process_id).await {
             // Find the matching tool use entry by name and input
             let matching_tool = find_matching_tool_use(store.clone(), &request.tool_call_id);
 
             if let Some((idx, matching_tool)) = matching_tool {
+                action_type = tool_action_type(&matching_tool).cloned();
                 let approval_entry = matching_tool
                     .with_tool_status(ToolStatus::PendingApproval {
                         approval_id: req_id.clone(),
@@ -129,10 +166,103 @@ impl Approvals {
             );
         }
 
-        self.spawn_timeout_watcher(req_id.clone(), request.timeout_at, waiter.clone());
+        self.events_msg_store.push_patch(lifecycle_patch::add(
+            &LifecycleEvent::ApprovalRequested {
+                execution_process_id: request.execution_process_id,
+                approval_id: req_id,
+                tool_name: request.tool_name.clone(),
+            },
+        ));
+
+        self.spawn_timeout_watcher(
+            pool.clone(),
+            request.clone(),
+            action_type,
+            policy,
+            waiter.clone(),
+        );
         Ok((request, waiter))
     }
 
+    /// Decide a request with the project's approval policy, recording the approval when
+    /// a rule approves or denies the tool call. Returns `None` when the project has no
+    /// policy.
+    pub async fn decide_by_policy(
+        &self,
+        pool: &SqlitePool,
+        request: &ApprovalRequest,
+    ) -> Result<Option<PolicyOutcome>, ApprovalError> {
+        let ctx = ExecutionProcess::load_context(pool, request.execution_process_id).await?;
+        let Some(project) = ctx.task.parent_project(pool).await? else {
+            return Ok(None);
+        };
+        let Some(policy) = ApprovalPolicy::load(&project.git_repo_path)
+            .await
+            .map_err(|e| ApprovalError::Custom(e.into()))?
+        else {
+            return Ok(None);
+        };
+
+        let store = self.msg_store_by_id(&request.execution_process_id).await;
+        let matching_tool = store
+            .clone()
+            .and_then(|store| find_matching_tool_use(store, &request.tool_call_id));
+        let action_type = matching_tool
+            .as_ref()
+            .and_then(|(_, entry)| tool_action_type(entry));
+
+        // Without the tool's log entry there is nothing to match rules against
+        let outcome = action_type.map(|action| {
+            policy.evaluate(
+                action,
+                ctx.task_attempt.container_ref.as_deref().map(Path::new),
+            )
+        });
+        let (decision, rule_index, reason) = match outcome {
+            Some(o) => (o.decision, o.rule_index, o.reason),
+            None => (PolicyDecision::Escalate, None, None),
+        };
+
+        let verdict = PolicyVerdict {
+            decision,
+            rule_index: rule_index.map(|i| i as i64),
+        };
+        let status = match decision {
+            PolicyDecision::Escalate => return Ok(Some(PolicyOutcome::Escalated(verdict))),
+            PolicyDecision::Approve => ApprovalStatus::Approved,
+            PolicyDecision::Deny => ApprovalStatus::Denied {
+                reason: Some(
+                    reason.unwrap_or_else(|| "Denied by the project approval policy".to_string()),
+                ),
+            },
+        };
+
+        record_approval(
+            pool,
+            request,
+            action_type,
+            ApprovalDecider::Policy,
+            Some(verdict),
+            &status,
+        )
+        .await;
+
+        if let (Some(store), Some((idx, entry))) = (store, matching_tool)
+            && let Some(tool_status) = ToolStatus::from_approval_status(&status)
+            && let Some(updated_entry) = entry.with_tool_status(tool_status)
+        {
+            store.push_patch(ConversationPatch::replace(idx, updated_entry));
+        }
+
+        tracing::debug!(
+            "Approval policy decided {:?} for tool '{}' (rule {:?})",
+            decision,
+            request.tool_name,
+            rule_index
+        );
+        Ok(Some(PolicyOutcome::Decided(status)))
+    }
+
     #[tracing::instrument(skip(self, id, req))]
     pub async fn respond(
         &self,
@@ -189,13 +319,18 @@ impl Approvals {
         }
     }
 
-    #[tracing::instrument(skip(self, id, timeout_at, waiter))]
+    /// Times the approval out if nobody answers, and records how it was resolved
+    #[tracing::instrument(skip(self, pool, request, action_type, policy, waiter))]
     fn spawn_timeout_watcher(
         &self,
-        id: String,
-        timeout_at: chrono::DateTime<chrono::Utc>,
+        pool: SqlitePool,
+        request: ApprovalRequest,
+        action_type: Option<ActionType>,
+        policy: Option<PolicyVerdict>,
         waiter: ApprovalWaiter,
     ) {
+        let id = request.id.clone();
+        let timeout_at = request.timeout_at;
         let pending = self.pending.clone();
         let completed = self.completed.clone();
         let msg_stores = self.msg_stores.clone();
@@ -217,6 +352,21 @@ impl Approvals {
             let is_timeout = matches!(&status, ApprovalStatus::TimedOut);
             completed.insert(id.clone(), status.clone());
 
+            let decided_by = if is_timeout {
+                ApprovalDecider::Timeout
+            } else {
+                ApprovalDecider::User
+            };
+            record_approval(
+                &pool,
+                &request,
+                action_type.as_ref(),
+                decided_by,
+                policy,
+                &status,
+            )
+            .await;
+
             if is_timeout && let Some((_, pending_approval)) = pending.remove(&id) {
                 if pending_approval.response_tx.send(status.clone()).is_err() {
                     tracing::debug!("approval '{}' timeout notification receiver dropped", id);
@@ -252,6 +402,24 @@ impl Approvals {
         });
     }
 
+    /// Wait until the tool call has a log entry the approval can attach to. Executors may
+    /// ask for approval before the normalizer has processed the tool call itself.
+    /// Returns false if the entry did not show up within [`TOOL_USE_WAIT`].
+    pub async fn wait_for_tool_use(&self, execution_process_id: &Uuid, tool_call_id: &str) -> bool {
+        let deadline = tokio::time::Instant::now() + TOOL_USE_WAIT;
+        loop {
+            if let Some(store) = self.msg_store_by_id(execution_process_id).await
+                && find_matching_tool_use(store, tool_call_id).is_some()
+            {
+                return true;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(TOOL_USE_POLL_INTERVAL).await;
+        }
+    }
+
     async fn msg_store_by_id(&self, execution_process_id: &Uuid) -> Option<Arc<MsgStore>> {
         let map = self.msg_stores.read().await;
         map.get(execution_process_id).cloned()
@@ -270,6 +438,50 @@ pub(crate) async fn ensure_task_in_review(pool: &SqlitePool, execution_process_i
     }
 }
 
+fn tool_action_type(entry: &NormalizedEntry) -> Option<&ActionType> {
+    match &entry.entry_type {
+        NormalizedEntryType::ToolUse { action_type, .. } => Some(action_type),
+        _ => None,
+    }
+}
+
+/// Persist a resolved approval to the approval history
+async fn record_approval(
+    pool: &SqlitePool,
+    request: &ApprovalRequest,
+    action_type: Option<&ActionType>,
+    decided_by: ApprovalDecider,
+    policy: Option<PolicyVerdict>,
+    status: &ApprovalStatus,
+) {
+    let (outcome, reason) = match status {
+        ApprovalStatus::Pending => return,
+        ApprovalStatus::Approved => (ApprovalOutcome::Approved, None),
+        ApprovalStatus::Denied { reason } => (ApprovalOutcome::Denied, reason.clone()),
+        ApprovalStatus::TimedOut => (ApprovalOutcome::TimedOut, None),
+    };
+    if let Err(e) = Approval::create(
+        pool,
+        &CreateApproval {
+            id: request.id.clone(),
+            execution_process_id: request.execution_process_id,
+            tool_name: request.tool_name.clone(),
+            tool_call_id: request.tool_call_id.clone(),
+            action_type: action_type.and_then(|a| serde_json::to_value(a).ok()),
+            decided_by,
+            status: outcome,
+            reason,
+            policy_decision: policy.map(|p| p.decision),
+            rule_index: policy.and_then(|p| p.rule_index),
+            requested_at: request.created_at,
+        },
+    )
+    .await
+    {
+        tracing::error!("Failed to record approval {}: {}", request.id, e);
+    }
+}
+
 /// Find a matching tool use entry that hasn't been assigned to an approval yet
 /// Matches by tool call id from tool metadata
 fn find_matching_tool_use(
@@ -398,4 +610,212 @@ mod tests {
             "Should not match different tool ids"
         );
     }
+
+    #[tokio::test]
+    async fn waits_for_tool_use_recorded_after_the_request() {
+        let store = Arc::new(MsgStore::new());
+        let execution_process_id = Uuid::new_v4();
+        let approvals = Approvals::new(
+            Arc::new(RwLock::new(HashMap::from([(
+                execution_process_id,
+                store.clone(),
+            )]))),
+            Arc::new(MsgStore::new()),
+        );
+
+        let late_entry = tokio::spawn(async move {
+            tokio::time::sleep(StdDuration::from_millis(100)).await;
+            let entry = create_tool_use_entry("Read", "late.rs", "late-id", ToolStatus::Created);
+            store.push_patch(
+                executors::logs::utils::patch::ConversationPatch::add_normalized_entry(0, entry),
+            );
+        });
+        assert!(
+            approvals
+                .wait_for_tool_use(&execution_process_id, "late-id")
+                .await
+        );
+        late_entry.await.unwrap();
+    }
+
+    async fn pool_with_execution_process() -> (SqlitePool, Uuid) {
+        use db::models::{
+            execution_process::{CreateExecutionProcess, ExecutionProcessRunReason},
+            project::{CreateProject, Project},
+            task::CreateTask,
+            task_attempt::{CreateTaskAttempt, TaskAttempt},
+        };
+        use executors::{
+            actions::{
+                ExecutorAction, ExecutorActionType,
+                script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+            },
+            executors::BaseCodingAgent,
+        };
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("../db/migrations").run(&pool).await.unwrap();
+
+        let (project_id, task_id, attempt_id, process_id) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        Project::create(
+            &pool,
+            &CreateProject {
+                name: "project".to_string(),
+                git_repo_path: format!("/tmp/{project_id}"),
+                use_existing_repo: true,
+                setup_script: None,
+                dev_script: None,
+                cleanup_script: None,
+                copy_files: None,
+            },
+            project_id,
+        )
+        .await
+        .unwrap();
+        Task::create(
+            &pool,
+            &CreateTask::from_title_description(project_id, "task".to_string(), None),
+            task_id,
+        )
+        .await
+        .unwrap();
+        TaskAttempt::create(
+            &pool,
+            &CreateTaskAttempt {
+                executor: BaseCodingAgent::ClaudeCode,
+                base_branch: "main".to_string(),
+                branch: "attempt".to_string(),
+            },
+            attempt_id,
+            task_id,
+        )
+        .await
+        .unwrap();
+        ExecutionProcess::create(
+            &pool,
+            &CreateExecutionProcess {
+                task_attempt_id: attempt_id,
+                executor_action: ExecutorAction::new(
+                    ExecutorActionType::ScriptRequest(ScriptRequest {
+                        script: "true".to_string(),
+                        language: ScriptRequestLanguage::Bash,
+                        context: ScriptContext::SetupScript,
+                    }),
+                    None,
+                ),
+                run_reason: ExecutionProcessRunReason::CodingAgent,
+            },
+            process_id,
+            None,
+        )
+        .await
+        .unwrap();
+        (pool, process_id)
+    }
+
+    #[tokio::test]
+    async fn record_approval_stores_resolved_approvals_once() {
+        use db::models::approval::ApprovalFilter;
+        use utils::approvals::CreateApprovalRequest;
+
+        let (pool, execution_process_id) = pool_with_execution_process().await;
+        let request = |tool_call_id: &str| {
+            ApprovalRequest::from_create(
+                CreateApprovalRequest {
+                    tool_name: "Bash".to_string(),
+                    tool_input: serde_json::json!({"command": "rm -rf target"}),
+                    tool_call_id: tool_call_id.to_string(),
+                },
+                execution_process_id,
+            )
+        };
+        let action = ActionType::CommandRun {
+            command: "rm -rf target".to_string(),
+            result: None,
+        };
+
+        // Pending requests are not resolved yet
+        let pending = request("pending");
+        record_approval(
+            &pool,
+            &pending,
+            Some(&action),
+            ApprovalDecider::User,
+            None,
+            &ApprovalStatus::Pending,
+        )
+        .await;
+
+        let denied = request("denied");
+        record_approval(
+            &pool,
+            &denied,
+            Some(&action),
+            ApprovalDecider::Policy,
+            Some(PolicyVerdict {
+                decision: PolicyDecision::Deny,
+                rule_index: Some(2),
+            }),
+            &ApprovalStatus::Denied {
+                reason: Some("No deleting build output".to_string()),
+            },
+        )
+        .await;
+
+        let timed_out = request("timed-out");
+        record_approval(
+            &pool,
+            &timed_out,
+            None,
+            ApprovalDecider::Timeout,
+            Some(PolicyVerdict {
+                decision: PolicyDecision::Escalate,
+                rule_index: None,
+            }),
+            &ApprovalStatus::TimedOut,
+        )
+        .await;
+        // The id is the primary key, so a second resolution is not recorded
+        record_approval(
+            &pool,
+            &timed_out,
+            None,
+            ApprovalDecider::User,
+            None,
+            &ApprovalStatus::Approved,
+        )
+        .await;
+
+        let approvals = Approval::find_filtered(&pool, &ApprovalFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(approvals.len(), 2);
+
+        let denied = approvals.iter().find(|a| a.id == denied.id).unwrap();
+        assert_eq!(denied.decided_by, ApprovalDecider::Policy);
+        assert_eq!(denied.status, ApprovalOutcome::Denied);
+        assert_eq!(denied.reason.as_deref(), Some("No deleting build output"));
+        assert_eq!(denied.policy_decision, Some(PolicyDecision::Deny));
+        assert_eq!(denied.rule_index, Some(2));
+        assert_eq!(
+            denied.action_type.as_ref().unwrap().0["command"],
+            serde_json::json!("rm -rf target")
+        );
+
+        let timed_out = approvals.iter().find(|a| a.id == timed_out.id).unwrap();
+        assert_eq!(timed_out.decided_by, ApprovalDecider::Timeout);
+        assert_eq!(timed_out.status, ApprovalOutcome::TimedOut);
+        assert_eq!(timed_out.policy_decision, Some(PolicyDecision::Escalate));
+        assert_eq!(timed_out.rule_index, None);
+        assert!(timed_out.action_type.is_none());
+    }
 }
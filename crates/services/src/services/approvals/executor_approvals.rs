@@ -7,20 +7,30 @@ use serde_json::Value;
 use utils::approvals::{ApprovalRequest, ApprovalStatus, CreateApprovalRequest};
 use uuid::Uuid;
 
-use crate::services::approvals::Approvals;
+use crate::services::approvals::{Approvals, PolicyOutcome};
 
 pub struct ExecutorApprovalBridge {
     approvals: Approvals,
     db: DBService,
     execution_process_id: Uuid,
+    /// Whether the executor may ask for approval before its tool call is logged
+    wait_for_tool_use: bool,
 }
 
 impl ExecutorApprovalBridge {
-    pub fn new(approvals: Approvals, db: DBService, execution_process_id: Uuid) -> Arc<Self> {
+    /// Set `wait_for_tool_use` for executors that ask for approval ahead of logging the
+    /// tool call (ACP agents), so the approval waits for the entry it attaches to
+    pub fn new(
+        approvals: Approvals,
+        db: DBService,
+        execution_process_id: Uuid,
+        wait_for_tool_use: bool,
+    ) -> Arc<Self> {
         Arc::new(Self {
             approvals,
             db,
             execution_process_id,
+            wait_for_tool_use,
         })
     }
 }
@@ -33,8 +43,6 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
         tool_input: Value,
         tool_call_id: &str,
     ) -> Result<ApprovalStatus, ExecutorApprovalError> {
-        super::ensure_task_in_review(&self.db.pool, self.execution_process_id).await;
-
         let request = ApprovalRequest::from_create(
             CreateApprovalRequest {
                 tool_name: tool_name.to_string(),
@@ -44,9 +52,41 @@ impl ExecutorApprovalService for ExecutorApprovalBridge {
             self.execution_process_id,
         );
 
+        if self.wait_for_tool_use
+            && !self
+                .approvals
+                .wait_for_tool_use(&self.execution_process_id, tool_call_id)
+                .await
+        {
+            tracing::warn!(
+                "No log entry for tool call '{}' of execution process {}",
+                tool_call_id,
+                self.execution_process_id
+            );
+        }
+
+        let policy = match self
+            .approvals
+            .decide_by_policy(&self.db.pool, &request)
+            .await
+        {
+            Ok(Some(PolicyOutcome::Decided(status))) => return Ok(status),
+            Ok(Some(PolicyOutcome::Escalated(verdict))) => Some(verdict),
+            Ok(None) => None,
+            Err(e) => {
+                tracing::warn!(
+                    "Approval policy could not be applied, asking a human instead: {}",
+                    e
+                );
+                None
+            }
+        };
+
+        super::ensure_task_in_review(&self.db.pool, self.execution_process_id).await;
+
         let (_, waiter) = self
             .approvals
-            .create_with_waiter(request)
+            .create_with_waiter(&self.db.pool, request, policy)
             .await
             .map_err(ExecutorApprovalError::request_failed)?;
 
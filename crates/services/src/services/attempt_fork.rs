@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    executor_session::ExecutorSession,
+    project::Project,
+    task_attempt::{CreateTaskAttempt, TaskAttempt, TaskAttemptError},
+    task_attempt_fork::TaskAttemptFork,
+    task_attempt_repository::TaskAttemptRepository,
+};
+use executors::{
+    actions::{
+        ExecutorAction, ExecutorActionType,
+        script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+    },
+    executors::BaseAgentCapability,
+    profile::ExecutorConfigs,
+};
+use serde::Serialize;
+use sqlx::{SqlitePool, error::Error as SqlxError};
+use thiserror::Error;
+use tracing::{info, warn};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::services::{
+    container::{ContainerError, ContainerService},
+    git::GitServiceError,
+};
+
+#[derive(Debug, Error)]
+pub enum AttemptForkError {
+    #[error("Execution process not found")]
+    ProcessNotFound,
+    #[error("Cannot fork from this process: {0}")]
+    NotForkable(String),
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+    #[error(transparent)]
+    Container(#[from] ContainerError),
+    #[error(transparent)]
+    TaskAttemptError(#[from] TaskAttemptError),
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+}
+
+/// An attempt with the attempts forked from it
+#[derive(Debug, Serialize, TS)]
+pub struct AttemptForkTree {
+    pub attempt: TaskAttempt,
+    /// How the attempt was forked from its parent; none for the root
+    pub fork: Option<TaskAttemptFork>,
+    pub children: Vec<AttemptForkTree>,
+}
+
+/// Fork `source` after one of its coding agent processes: the new attempt's branch starts
+/// at the HEAD the process left behind, and its first follow-up resumes the agent's
+/// session from that point when the agent can fork sessions.
+///
+/// Additional repositories have no per-process commits, so their branches start at the
+/// source attempt's current head.
+pub async fn fork_attempt<C>(
+    container: &C,
+    source: &TaskAttempt,
+    execution_process_id: Uuid,
+) -> Result<TaskAttempt, AttemptForkError>
+where
+    C: ContainerService + Sync,
+{
+    let pool = &container.db().pool;
+    let git = container.git();
+
+    let process = ExecutionProcess::find_by_id(pool, execution_process_id)
+        .await?
+        .filter(|p| p.task_attempt_id == source.id)
+        .ok_or(AttemptForkError::ProcessNotFound)?;
+    if process.run_reason != ExecutionProcessRunReason::CodingAgent {
+        return Err(AttemptForkError::NotForkable(
+            "only coding agent processes can be forked".to_string(),
+        ));
+    }
+    if process.status == ExecutionProcessStatus::Running {
+        return Err(AttemptForkError::NotForkable(
+            "the process is still running".to_string(),
+        ));
+    }
+    if process.dropped {
+        return Err(AttemptForkError::NotForkable(
+            "the process was dropped from the history".to_string(),
+        ));
+    }
+    let fork_commit = process.after_head_commit.clone().ok_or_else(|| {
+        AttemptForkError::NotForkable("the process did not record its HEAD".to_string())
+    })?;
+    let executor_profile_id = match process.executor_action().map(|action| &action.typ) {
+        Ok(ExecutorActionType::CodingAgentInitialRequest(request)) => {
+            request.executor_profile_id.clone()
+        }
+        Ok(ExecutorActionType::CodingAgentFollowUpRequest(request)) => {
+            request.executor_profile_id.clone()
+        }
+        _ => {
+            return Err(AttemptForkError::NotForkable(
+                "the process has no executor profile".to_string(),
+            ));
+        }
+    };
+
+    // Resuming a session that cannot be forked would rewrite the parent's conversation
+    let can_fork_session = ExecutorConfigs::get_cached()
+        .get_coding_agent_or_default(&executor_profile_id)
+        .capabilities()
+        .contains(&BaseAgentCapability::SessionFork);
+    let session_id = if can_fork_session {
+        ExecutorSession::find_by_execution_process_id(pool, process.id)
+            .await?
+            .and_then(|session| session.session_id)
+    } else {
+        None
+    };
+
+    let task = source
+        .parent_task(pool)
+        .await?
+        .ok_or(TaskAttemptError::TaskNotFound)?;
+    let project = Project::find_by_id(pool, task.project_id)
+        .await?
+        .ok_or(TaskAttemptError::ProjectNotFound)?;
+
+    let attempt_id = Uuid::new_v4();
+    let branch = container
+        .git_branch_from_task_attempt(&attempt_id, &task.title)
+        .await;
+    git.create_branch_at(&project.git_repo_path, &branch, &fork_commit)?;
+    for repository in TaskAttemptRepository::find_by_task_attempt_id(pool, source.id).await? {
+        let head = git.get_branch_oid(&repository.git_repo_path, &source.branch)?;
+        git.create_branch_at(&repository.git_repo_path, &branch, &head)?;
+    }
+
+    let attempt = TaskAttempt::create(
+        pool,
+        &CreateTaskAttempt {
+            executor: executor_profile_id.executor,
+            base_branch: source.target_branch.clone(),
+            branch,
+        },
+        attempt_id,
+        source.task_id,
+    )
+    .await?;
+    TaskAttemptFork::create(
+        pool,
+        attempt.id,
+        source.id,
+        process.id,
+        &fork_commit,
+        session_id.as_deref(),
+    )
+    .await?;
+    container.create(&attempt).await?;
+
+    if let Some(setup_script) = project.setup_script {
+        let executor_action = ExecutorAction::new(
+            ExecutorActionType::ScriptRequest(ScriptRequest {
+                script: setup_script,
+                language: ScriptRequestLanguage::Bash,
+                context: ScriptContext::SetupScript,
+            }),
+            None,
+        );
+        if let Err(e) = container
+            .start_execution(
+                &attempt,
+                &executor_action,
+                &ExecutionProcessRunReason::SetupScript,
+            )
+            .await
+        {
+            warn!(
+                "Failed to run setup script of forked attempt {}: {}",
+                attempt.id, e
+            );
+        }
+    }
+
+    info!(
+        "Forked attempt {} from attempt {} after process {}",
+        attempt.id, source.id, process.id
+    );
+    Ok(attempt)
+}
+
+/// The fork tree `attempt` belongs to, starting from the attempt everything was forked from
+pub async fn fork_tree(
+    pool: &SqlitePool,
+    attempt: &TaskAttempt,
+) -> Result<AttemptForkTree, AttemptForkError> {
+    let forks = TaskAttemptFork::find_by_task_id(pool, attempt.task_id).await?;
+    let attempts = TaskAttempt::fetch_all(pool, Some(attempt.task_id)).await?;
+    build_fork_tree(attempt.id, attempts, forks)
+        .ok_or(AttemptForkError::Sqlx(SqlxError::RowNotFound))
+}
+
+fn build_fork_tree(
+    attempt_id: Uuid,
+    attempts: Vec<TaskAttempt>,
+    forks: Vec<TaskAttemptFork>,
+) -> Option<AttemptForkTree> {
+    let parents: HashMap<Uuid, Uuid> = forks
+        .iter()
+        .map(|fork| (fork.task_attempt_id, fork.parent_attempt_id))
+        .collect();
+    let mut root_id = attempt_id;
+    while let Some(parent_id) = parents.get(&root_id) {
+        root_id = *parent_id;
+    }
+
+    let mut attempts: HashMap<Uuid, TaskAttempt> =
+        attempts.into_iter().map(|a| (a.id, a)).collect();
+    let mut forks: HashMap<Uuid, TaskAttemptFork> =
+        forks.into_iter().map(|f| (f.task_attempt_id, f)).collect();
+    let mut children: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for (child_id, parent_id) in &parents {
+        children.entry(*parent_id).or_default().push(*child_id);
+    }
+
+    fn build(
+        id: Uuid,
+        attempts: &mut HashMap<Uuid, TaskAttempt>,
+        forks: &mut HashMap<Uuid, TaskAttemptFork>,
+        children: &HashMap<Uuid, Vec<Uuid>>,
+    ) -> Option<AttemptForkTree> {
+        let attempt = attempts.remove(&id)?;
+        let mut subtrees: Vec<AttemptForkTree> = children
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(|child_id| build(*child_id, attempts, forks, children))
+            .collect();
+        subtrees.sort_by_key(|tree| tree.attempt.created_at);
+        Some(AttemptForkTree {
+            attempt,
+            fork: forks.remove(&id),
+            children: subtrees,
+        })
+    }
+    build(root_id, &mut attempts, &mut forks, &children)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::*;
+
+    fn attempt(task_id: Uuid, minutes: i64) -> TaskAttempt {
+        let created_at = Utc::now() + Duration::minutes(minutes);
+        TaskAttempt {
+            id: Uuid::new_v4(),
+            task_id,
+            container_ref: None,
+            branch: format!("attempt-{minutes}"),
+            target_branch: "main".to_string(),
+            executor: "CLAUDE_CODE".to_string(),
+            worktree_deleted: false,
+            setup_completed_at: None,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    fn fork(child: &TaskAttempt, parent: &TaskAttempt) -> TaskAttemptFork {
+        TaskAttemptFork {
+            task_attempt_id: child.id,
+            parent_attempt_id: parent.id,
+            execution_process_id: Uuid::new_v4(),
+            fork_commit: "abc123".to_string(),
+            session_id: Some("session".to_string()),
+            created_at: child.created_at,
+        }
+    }
+
+    #[test]
+    fn tree_starts_at_root_and_orders_children() {
+        let task_id = Uuid::new_v4();
+        let root = attempt(task_id, 0);
+        let first = attempt(task_id, 1);
+        let second = attempt(task_id, 2);
+        let grandchild = attempt(task_id, 3);
+        let unrelated = attempt(task_id, 4);
+        let forks = vec![
+            fork(&second, &root),
+            fork(&first, &root),
+            fork(&grandchild, &first),
+        ];
+        let ids = (root.id, first.id, second.id, grandchild.id);
+        let attempts = vec![unrelated, grandchild, second, first, root];
+
+        let tree = build_fork_tree(ids.3, attempts, forks).unwrap();
+        assert_eq!(tree.attempt.id, ids.0);
+        assert!(tree.fork.is_none());
+        let children: Vec<Uuid> = tree.children.iter().map(|c| c.attempt.id).collect();
+        assert_eq!(children, [ids.1, ids.2]);
+        let first_tree = &tree.children[0];
+        assert_eq!(
+            first_tree.fork.as_ref().map(|f| f.parent_attempt_id),
+            Some(ids.0)
+        );
+        assert_eq!(first_tree.children.len(), 1);
+        assert_eq!(first_tree.children[0].attempt.id, ids.3);
+        assert!(tree.children[1].children.is_empty());
+    }
+
+    #[test]
+    fn attempt_without_forks_is_its_own_tree() {
+        let task_id = Uuid::new_v4();
+        let root = attempt(task_id, 0);
+        let id = root.id;
+        let tree = build_fork_tree(id, vec![root, attempt(task_id, 1)], Vec::new()).unwrap();
+        assert_eq!(tree.attempt.id, id);
+        assert!(tree.children.is_empty());
+    }
+}
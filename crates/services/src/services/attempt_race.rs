@@ -0,0 +1,176 @@
+use db::models::{
+    task::Task,
+    task_attempt::{CreateTaskAttempt, TaskAttempt, TaskAttemptError},
+    task_attempt_race::TaskAttemptRace,
+};
+use executors::profile::ExecutorProfileId;
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::services::container::{ContainerError, ContainerService};
+
+#[derive(Debug, Error)]
+pub enum AttemptRaceError {
+    #[error("A race needs at least two executor profiles")]
+    TooFewExecutors,
+    #[error("A winner has already been picked for this race")]
+    WinnerAlreadyPicked,
+    #[error("Attempt is not part of this race")]
+    NotInRace,
+    #[error(transparent)]
+    Container(#[from] ContainerError),
+    #[error(transparent)]
+    TaskAttemptError(#[from] TaskAttemptError),
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+}
+
+/// Start the same task on several executors at once, one attempt per executor profile.
+///
+/// A race is started as a whole: if any attempt cannot be created or started, every
+/// attempt of the race is removed again, including its worktree and branch.
+pub async fn start_race<C>(
+    container: &C,
+    task: &Task,
+    executor_profile_ids: &[ExecutorProfileId],
+    base_branch: &str,
+) -> Result<(TaskAttemptRace, Vec<TaskAttempt>), AttemptRaceError>
+where
+    C: ContainerService + Sync,
+{
+    if executor_profile_ids.len() < 2 {
+        return Err(AttemptRaceError::TooFewExecutors);
+    }
+
+    let pool = &container.db().pool;
+    let mut attempts = Vec::with_capacity(executor_profile_ids.len());
+    for executor_profile_id in executor_profile_ids {
+        let attempt_id = Uuid::new_v4();
+        let git_branch_name = container
+            .git_branch_from_task_attempt(&attempt_id, &task.title)
+            .await;
+
+        let created = TaskAttempt::create(
+            pool,
+            &CreateTaskAttempt {
+                executor: executor_profile_id.executor,
+                base_branch: base_branch.to_string(),
+                branch: git_branch_name,
+            },
+            attempt_id,
+            task.id,
+        )
+        .await;
+        match created {
+            Ok(task_attempt) => attempts.push(task_attempt),
+            Err(err) => {
+                roll_back_race(container, None, &attempts).await;
+                return Err(err.into());
+            }
+        }
+    }
+
+    let attempt_ids: Vec<Uuid> = attempts.iter().map(|a| a.id).collect();
+    let race = match TaskAttemptRace::create(pool, Uuid::new_v4(), task.id, &attempt_ids).await {
+        Ok(race) => race,
+        Err(err) => {
+            roll_back_race(container, None, &attempts).await;
+            return Err(err.into());
+        }
+    };
+
+    for (task_attempt, executor_profile_id) in attempts.iter().zip(executor_profile_ids) {
+        if let Err(err) = container
+            .start_attempt(task_attempt, executor_profile_id.clone())
+            .await
+        {
+            error!(
+                "Failed to start race attempt {} ({}), rolling back race {}: {}",
+                task_attempt.id, executor_profile_id, race.id, err
+            );
+            roll_back_race(container, Some(&race), &attempts).await;
+            return Err(err.into());
+        }
+    }
+
+    info!(
+        "Started race {} with {} attempts for task {}",
+        race.id,
+        attempts.len(),
+        task.id
+    );
+    Ok((race, attempts))
+}
+
+/// Keep one attempt and archive the rest, stopping their processes and removing their
+/// worktrees and branches.
+pub async fn pick_race_winner<C>(
+    container: &C,
+    race: &TaskAttemptRace,
+    winner_attempt_id: Uuid,
+) -> Result<TaskAttemptRace, AttemptRaceError>
+where
+    C: ContainerService + Sync,
+{
+    if race.winner_attempt_id.is_some() {
+        return Err(AttemptRaceError::WinnerAlreadyPicked);
+    }
+
+    let pool = &container.db().pool;
+    let attempts = race.attempts(pool).await?;
+    if !attempts.iter().any(|a| a.id == winner_attempt_id) {
+        return Err(AttemptRaceError::NotInRace);
+    }
+
+    let race = TaskAttemptRace::set_winner(pool, race.id, winner_attempt_id).await?;
+
+    for loser in attempts.iter().filter(|a| a.id != winner_attempt_id) {
+        if let Err(e) = container.delete(loser).await {
+            warn!("Failed to clean up losing race attempt {}: {}", loser.id, e);
+            continue;
+        }
+        if let Err(e) = container.delete_branches(loser).await {
+            warn!(
+                "Failed to delete branch of losing race attempt {}: {}",
+                loser.id, e
+            );
+        }
+        TaskAttempt::mark_worktree_deleted(pool, loser.id).await?;
+    }
+
+    Ok(race)
+}
+
+/// Stop and remove every attempt of a race that could not be fully started
+async fn roll_back_race<C>(container: &C, race: Option<&TaskAttemptRace>, attempts: &[TaskAttempt])
+where
+    C: ContainerService + Sync,
+{
+    let pool = &container.db().pool;
+    for attempt in attempts {
+        // Starting sets the container ref, which cleanup needs to find the worktree
+        let attempt = match TaskAttempt::find_by_id(pool, attempt.id).await {
+            Ok(Some(attempt)) => attempt,
+            _ => attempt.clone(),
+        };
+        if let Err(e) = container.delete(&attempt).await {
+            warn!("Failed to clean up race attempt {}: {}", attempt.id, e);
+        }
+        if let Err(e) = container.delete_branches(&attempt).await {
+            warn!(
+                "Failed to delete branch of race attempt {}: {}",
+                attempt.id, e
+            );
+        }
+        if let Err(e) = TaskAttempt::delete(pool, attempt.id).await {
+            warn!("Failed to delete race attempt {}: {}", attempt.id, e);
+        }
+    }
+    if let Some(race) = race
+        && let Err(e) = TaskAttemptRace::delete(pool, race.id).await
+    {
+        warn!("Failed to delete race {}: {}", race.id, e);
+    }
+}
@@ -0,0 +1,290 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessStatus},
+    merge::{Merge, MergeStatus},
+    project::Project,
+    task_attempt::{TaskAttempt, TaskAttemptError},
+    task_attempt_stack::TaskAttemptStack,
+};
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::services::{
+    config::{Config, GitHostConfig},
+    container::{ContainerError, ContainerService},
+    git::GitServiceError,
+    git_host::{self, GitHostError},
+};
+
+// Restacks of the same attempt run one at a time, whether started by the service, a
+// merge or a request
+lazy_static::lazy_static! {
+    static ref RESTACK_LOCKS: Mutex<HashMap<Uuid, Arc<tokio::sync::Mutex<()>>>> =
+        Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Error)]
+pub enum AttemptStackError {
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+    #[error(transparent)]
+    Container(#[from] ContainerError),
+    #[error(transparent)]
+    GitHost(#[from] GitHostError),
+    #[error(transparent)]
+    TaskAttemptError(#[from] TaskAttemptError),
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+}
+
+/// Keeps stacked attempts based on their parent: when the parent's branch is rebased,
+/// amended or gets new commits, the attempt is rebased onto the new head; once the
+/// parent is merged, the attempt moves onto the parent's target branch.
+pub struct AttemptStackService<C> {
+    container: C,
+    config: Arc<RwLock<Config>>,
+    poll_interval: Duration,
+}
+
+impl<C> AttemptStackService<C>
+where
+    C: ContainerService + Send + Sync + 'static,
+{
+    pub async fn spawn(container: C, config: Arc<RwLock<Config>>) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            container,
+            config,
+            poll_interval: Duration::from_secs(30),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting attempt stack service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.restack_all().await {
+                error!("Error restacking attempts: {}", e);
+            }
+        }
+    }
+
+    async fn restack_all(&self) -> Result<(), AttemptStackError> {
+        let hosts = self.config.read().await.git_hosts.clone();
+        // Parents come first, so a whole stack is brought up to date in one pass
+        for stack in TaskAttemptStack::find_all(&self.container.db().pool).await? {
+            if let Err(e) = restack(&self.container, &hosts, &stack, false).await {
+                warn!("Failed to restack attempt {}: {}", stack.task_attempt_id, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Restack every attempt stacked on `parent_attempt_id`, and their descendants.
+/// Failures are recorded on each attempt's stack rather than returned.
+pub async fn restack_descendants<C>(container: &C, hosts: &[GitHostConfig], parent_attempt_id: Uuid)
+where
+    C: ContainerService + Sync,
+{
+    let mut parents = vec![parent_attempt_id];
+    while let Some(parent_id) = parents.pop() {
+        let children = match TaskAttemptStack::find_children(&container.db().pool, parent_id).await
+        {
+            Ok(children) => children,
+            Err(e) => {
+                error!("Failed to load attempts stacked on {}: {}", parent_id, e);
+                continue;
+            }
+        };
+        for child in children {
+            match restack(container, hosts, &child, false).await {
+                Ok(_) => parents.push(child.task_attempt_id),
+                Err(e) => warn!("Failed to restack attempt {}: {}", child.task_attempt_id, e),
+            }
+        }
+    }
+}
+
+/// Rebase a stacked attempt onto its parent's current head, or onto the parent's
+/// target branch once the parent is merged. Returns whether the attempt moved.
+///
+/// Attempts with a running process are left alone. Unless `force` is set, a parent
+/// commit that already failed to restack is not tried again. An open PR of an attempt
+/// whose parent was merged is retargeted at the parent's target branch; `hosts`
+/// resolves the git host for it.
+pub async fn restack<C>(
+    container: &C,
+    hosts: &[GitHostConfig],
+    stack: &TaskAttemptStack,
+    force: bool,
+) -> Result<bool, AttemptStackError>
+where
+    C: ContainerService + Sync,
+{
+    let lock = {
+        let mut locks = RESTACK_LOCKS.lock().unwrap();
+        locks
+            .entry(stack.task_attempt_id)
+            .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
+            .clone()
+    };
+    let _guard = lock.lock().await;
+
+    // Another restack may have moved the attempt while this one waited
+    let pool = &container.db().pool;
+    let Some(stack) = TaskAttemptStack::find_by_attempt_id(pool, stack.task_attempt_id).await?
+    else {
+        return Ok(false);
+    };
+    restack_locked(container, hosts, &stack, force).await
+}
+
+async fn restack_locked<C>(
+    container: &C,
+    hosts: &[GitHostConfig],
+    stack: &TaskAttemptStack,
+    force: bool,
+) -> Result<bool, AttemptStackError>
+where
+    C: ContainerService + Sync,
+{
+    let pool = &container.db().pool;
+    let git = container.git();
+    let Some(attempt) = TaskAttempt::find_by_id(pool, stack.task_attempt_id).await? else {
+        return Ok(false);
+    };
+    let Some(parent) = TaskAttempt::find_by_id(pool, stack.parent_attempt_id).await? else {
+        return Ok(false);
+    };
+    let task = attempt
+        .parent_task(pool)
+        .await?
+        .ok_or(TaskAttemptError::TaskNotFound)?;
+    let project = Project::find_by_id(pool, task.project_id)
+        .await?
+        .ok_or(TaskAttemptError::ProjectNotFound)?;
+    let repo_path = project.git_repo_path.as_path();
+
+    let processes = ExecutionProcess::find_by_task_attempt_id(pool, attempt.id, false).await?;
+    if processes
+        .iter()
+        .any(|p| p.status == ExecutionProcessStatus::Running)
+    {
+        return Ok(false);
+    }
+
+    let parent_merged = match Merge::find_latest_by_task_attempt_id(pool, parent.id).await? {
+        Some(Merge::Direct(_)) => true,
+        Some(Merge::Pr(pr_merge)) => matches!(pr_merge.pr_info.status, MergeStatus::Merged),
+        None => false,
+    };
+    let new_base = if parent_merged {
+        &parent.target_branch
+    } else {
+        &parent.branch
+    };
+    let new_base_commit = git.get_branch_oid(repo_path, new_base)?;
+    if !parent_merged && new_base_commit == stack.base_commit {
+        return Ok(false);
+    }
+    if !force && stack.failed_base_commit.as_deref() == Some(new_base_commit.as_str()) {
+        return Ok(false);
+    }
+
+    // Already on top of the new base, e.g. after conflicts were resolved by hand
+    let attempt_head = git.get_branch_oid(repo_path, &attempt.branch)?;
+    let (missing, _) =
+        git.ahead_behind_commits_by_oid(repo_path, &new_base_commit, &attempt_head)?;
+    if missing > 0 {
+        let worktree_path = PathBuf::from(container.ensure_container_exists(&attempt).await?);
+        if let Err(e) = git.rebase_branch(
+            repo_path,
+            &worktree_path,
+            new_base,
+            &stack.base_commit,
+            &attempt.branch,
+        ) {
+            TaskAttemptStack::set_error(pool, attempt.id, &new_base_commit, &e.to_string()).await?;
+            return Err(e.into());
+        }
+
+        // An open PR keeps showing the old commits until the rewritten branch is pushed
+        if let Some(Merge::Pr(pr_merge)) =
+            Merge::find_latest_by_task_attempt_id(pool, attempt.id).await?
+            && matches!(pr_merge.pr_info.status, MergeStatus::Open)
+            && let Err(e) = git.force_push_with_lease(&worktree_path, &attempt.branch)
+        {
+            warn!(
+                "Restacked attempt {} but failed to update its PR #{}: {}",
+                attempt.id, pr_merge.pr_info.number, e
+            );
+        }
+    }
+
+    if parent_merged {
+        // The PR still targets the parent's branch, which is done with
+        if let Some(Merge::Pr(pr_merge)) =
+            Merge::find_latest_by_task_attempt_id(pool, attempt.id).await?
+            && matches!(pr_merge.pr_info.status, MergeStatus::Open)
+        {
+            let secrets = container.secrets().values().await;
+            let retargeted = match git_host::from_remote_url(&pr_merge.pr_info.url, hosts, &secrets)
+            {
+                Ok(host) => {
+                    host.update_pr_base(pr_merge.pr_info.number, &parent.target_branch)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = retargeted {
+                TaskAttemptStack::set_error(pool, attempt.id, &new_base_commit, &e.to_string())
+                    .await?;
+                return Err(e.into());
+            }
+        }
+
+        // The attempt takes the merged parent's place in the stack
+        TaskAttempt::update_target_branch(pool, attempt.id, &parent.target_branch).await?;
+        match TaskAttemptStack::find_by_attempt_id(pool, parent.id).await? {
+            Some(parent_stack) => {
+                TaskAttemptStack::set_base(
+                    pool,
+                    attempt.id,
+                    parent_stack.parent_attempt_id,
+                    &new_base_commit,
+                )
+                .await?
+            }
+            None => TaskAttemptStack::delete(pool, attempt.id).await?,
+        }
+        info!(
+            "Moved attempt {} onto '{}' after its parent {} was merged",
+            attempt.id, parent.target_branch, parent.id
+        );
+    } else {
+        TaskAttemptStack::set_base(pool, attempt.id, parent.id, &new_base_commit).await?;
+        info!(
+            "Restacked attempt {} onto {} of parent {}",
+            attempt.id, new_base_commit, parent.id
+        );
+    }
+    Ok(true)
+}
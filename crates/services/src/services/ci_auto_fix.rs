@@ -0,0 +1,281 @@
+use std::{
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use db::{
+    DBService,
+    models::{
+        draft::{Draft, DraftType},
+        merge::{Merge, PrMerge},
+        pr_check_summary::{CheckRun, PrCheckSummary},
+        task_attempt::{TaskAttempt, TaskAttemptError},
+    },
+};
+use regex::Regex;
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tokio::{sync::RwLock, time::interval};
+use tracing::{error, info, warn};
+
+use crate::services::{
+    config::Config,
+    container::ContainerService,
+    drafts::{DraftsService, DraftsServiceError, SetQueueRequest, UpdateFollowUpDraftRequest},
+    git_host::{self, GitHostError},
+};
+
+/// Lines kept from the end of each failing job's log
+const LOG_EXCERPT_LINES: usize = 80;
+/// Upper bound on the size of each log excerpt, in bytes
+const LOG_EXCERPT_MAX_BYTES: usize = 8 * 1024;
+
+/// Terminal colour and cursor escape codes, which CI logs are full of
+static ANSI_ESCAPE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").expect("valid regex"));
+
+#[derive(Debug, Error)]
+enum CiAutoFixError {
+    #[error(transparent)]
+    TaskAttemptError(#[from] TaskAttemptError),
+    #[error(transparent)]
+    GitHost(#[from] GitHostError),
+    #[error(transparent)]
+    Drafts(#[from] DraftsServiceError),
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+}
+
+/// Service that queues a follow-up with the failing job logs when the checks of an
+/// attempt's PR fail, for projects that opted in. Each failing head commit is handed
+/// back to the agent once, up to the project's `max_auto_fixes` per PR.
+pub struct CiAutoFixService<C> {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    drafts: DraftsService,
+    container: C,
+    poll_interval: Duration,
+}
+
+impl<C> CiAutoFixService<C>
+where
+    C: ContainerService + Send + Sync + 'static,
+{
+    pub async fn spawn(
+        db: DBService,
+        config: Arc<RwLock<Config>>,
+        drafts: DraftsService,
+        container: C,
+    ) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            config,
+            drafts,
+            container,
+            poll_interval: Duration::from_secs(30),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting CI auto-fix service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.queue_pending_fixes().await {
+                error!("Error queueing CI fixes: {}", e);
+            }
+        }
+    }
+
+    async fn queue_pending_fixes(&self) -> Result<(), CiAutoFixError> {
+        let pending = PrCheckSummary::find_pending_auto_fixes(&self.db.pool).await?;
+        for summary in pending {
+            if let Err(e) = self.queue_fix(&summary).await {
+                error!(
+                    "Error queueing CI fix for attempt {}: {}",
+                    summary.task_attempt_id, e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn queue_fix(&self, summary: &PrCheckSummary) -> Result<(), CiAutoFixError> {
+        let pool = &self.db.pool;
+        let Some(head_sha) = summary.checks.head_sha.as_deref() else {
+            return Ok(());
+        };
+        let Some(task_attempt) = TaskAttempt::find_by_id(pool, summary.task_attempt_id).await?
+        else {
+            return Ok(());
+        };
+        let Some(Merge::Pr(pr_merge)) =
+            Merge::find_latest_by_task_attempt_id(pool, task_attempt.id).await?
+        else {
+            return Ok(());
+        };
+        if pr_merge.id != summary.merge_id {
+            return Ok(());
+        }
+
+        // Never touch a draft the user queued or that is being sent; retry next tick
+        let existing =
+            Draft::find_by_task_attempt_and_type(pool, task_attempt.id, DraftType::FollowUp)
+                .await?;
+        if existing.as_ref().is_some_and(|d| d.queued || d.sending) {
+            return Ok(());
+        }
+
+        // Claim first so a slow log download can never queue the same fix twice, and
+        // hand the claim back if the fix does not end up queued
+        if !PrCheckSummary::claim_auto_fix(pool, summary.merge_id, head_sha).await? {
+            return Ok(());
+        }
+        if let Err(e) = self
+            .queue_claimed_fix(summary, &task_attempt, &pr_merge, head_sha, existing)
+            .await
+        {
+            if let Err(release_err) =
+                PrCheckSummary::release_auto_fix(pool, summary.merge_id, head_sha).await
+            {
+                warn!(
+                    "Failed to release CI fix claim for PR #{}: {}",
+                    pr_merge.pr_info.number, release_err
+                );
+            }
+            return Err(e);
+        }
+
+        info!(
+            "Queued CI fix for PR #{} of attempt {} at {}",
+            pr_merge.pr_info.number, task_attempt.id, head_sha
+        );
+        Ok(())
+    }
+
+    /// Fetch the failing logs and queue the fix follow-up for a claimed head commit
+    async fn queue_claimed_fix(
+        &self,
+        summary: &PrCheckSummary,
+        task_attempt: &TaskAttempt,
+        pr_merge: &PrMerge,
+        head_sha: &str,
+        existing: Option<Draft>,
+    ) -> Result<(), CiAutoFixError> {
+        let hosts = self.config.read().await.git_hosts.clone();
+        let secrets = self.container.secrets().values().await;
+        let host = git_host::from_remote_url(&pr_merge.pr_info.url, &hosts, &secrets)?;
+        let mut failures = Vec::new();
+        for check in summary.checks.failed() {
+            let log = match host.failed_check_log(check).await {
+                Ok(log) => log.map(|log| log_excerpt(&log)),
+                Err(e) => {
+                    warn!("Failed to fetch log for check '{}': {}", check.name, e);
+                    None
+                }
+            };
+            failures.push((check, log));
+        }
+
+        let fix_prompt = build_fix_prompt(pr_merge, head_sha, &failures);
+        let prompt = match existing.map(|d| d.prompt) {
+            // Left behind by an earlier try that saved the draft but failed to queue it
+            Some(existing) if existing.contains(&fix_prompt) => existing,
+            Some(existing) if !existing.trim().is_empty() => {
+                format!("{}\n\n{fix_prompt}", existing.trim_end())
+            }
+            _ => fix_prompt,
+        };
+        self.drafts
+            .save_follow_up_draft(
+                task_attempt,
+                &UpdateFollowUpDraftRequest {
+                    prompt: Some(prompt),
+                    variant: None,
+                    image_ids: None,
+                    version: None,
+                },
+            )
+            .await?;
+        // Starts right away when the attempt is idle, otherwise after the running process
+        self.drafts
+            .set_follow_up_queue(
+                &self.container,
+                task_attempt,
+                &SetQueueRequest {
+                    queued: true,
+                    expected_queued: Some(false),
+                    expected_version: None,
+                },
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+fn build_fix_prompt(
+    pr_merge: &PrMerge,
+    head_sha: &str,
+    failures: &[(&CheckRun, Option<String>)],
+) -> String {
+    let short_sha = &head_sha[..head_sha.len().min(7)];
+    let mut prompt = format!(
+        "CI checks failed on pull request #{} at commit {short_sha}. \
+         Investigate the failures below, fix them and commit the changes.\n",
+        pr_merge.pr_info.number
+    );
+    for (check, log) in failures {
+        prompt.push_str(&format!("\n### {}\n", check.name));
+        if let Some(url) = &check.url {
+            prompt.push_str(&format!("{url}\n"));
+        }
+        match log {
+            Some(log) => prompt.push_str(&format!("```\n{log}\n```\n")),
+            None => prompt.push_str("No log is available for this check.\n"),
+        }
+    }
+    prompt
+}
+
+/// The tail of a CI log with terminal escape codes removed
+pub(crate) fn log_excerpt(log: &str) -> String {
+    let clean = ANSI_ESCAPE.replace_all(log, "").replace('\r', "");
+    let lines: Vec<&str> = clean.lines().collect();
+    let mut excerpt = lines[lines.len().saturating_sub(LOG_EXCERPT_LINES)..].join("\n");
+    if excerpt.len() > LOG_EXCERPT_MAX_BYTES {
+        let mut start = excerpt.len() - LOG_EXCERPT_MAX_BYTES;
+        while !excerpt.is_char_boundary(start) {
+            start += 1;
+        }
+        excerpt = excerpt.split_off(start);
+    }
+    excerpt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_excerpt_keeps_the_tail_without_escape_codes() {
+        let log: String = (1..=200)
+            .map(|n| format!("\x1b[31mline {n}\x1b[0m\r\n"))
+            .collect();
+        let excerpt = log_excerpt(&log);
+        let lines: Vec<&str> = excerpt.lines().collect();
+        assert_eq!(lines.len(), LOG_EXCERPT_LINES);
+        assert_eq!(lines.first(), Some(&"line 121"));
+        assert_eq!(lines.last(), Some(&"line 200"));
+
+        let long = "é".repeat(LOG_EXCERPT_MAX_BYTES);
+        assert!(log_excerpt(&long).len() <= LOG_EXCERPT_MAX_BYTES);
+    }
+}
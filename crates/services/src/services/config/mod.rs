@@ -21,6 +21,11 @@ pub type ThemeMode = versions::v8::ThemeMode;
 pub type SoundFile = versions::v8::SoundFile;
 pub type EditorType = versions::v8::EditorType;
 pub type GitHubConfig = versions::v8::GitHubConfig;
+pub type GitHostConfig = versions::v8::GitHostConfig;
+pub type NotifierConfig = versions::v8::NotifierConfig;
+pub type NotifierTarget = versions::v8::NotifierTarget;
+pub type NotificationEventKind = versions::v8::NotificationEventKind;
+pub type SmtpSecurity = versions::v8::SmtpSecurity;
 pub type UiLanguage = versions::v8::UiLanguage;
 pub type ShowcaseState = versions::v8::ShowcaseState;
 
@@ -2,17 +2,118 @@ use anyhow::Error;
 use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
+use uuid::Uuid;
 pub use v7::{
     EditorConfig, EditorType, GitHubConfig, NotificationConfig, ShowcaseState, SoundFile,
     ThemeMode, UiLanguage,
 };
 
-use crate::services::config::versions::v7;
+use crate::services::{config::versions::v7, git_host::GitHostKind};
 
 fn default_git_branch_prefix() -> String {
     "anyon".to_string()
 }
 
+/// A self-hosted git host, or credentials for a public one
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+pub struct GitHostConfig {
+    /// Hostname as it appears in remote URLs, with the port if it has one
+    pub host: String,
+    pub kind: GitHostKind,
+    /// API access token as a `{{secret:NAME}}` reference to the secret store. GitLab and
+    /// Gitea fall back to `GITLAB_TOKEN` / `GITEA_TOKEN`.
+    pub token: Option<String>,
+    /// API base URL, when it is not `https://<host>/api/v4` (GitLab) or
+    /// `https://<host>/api/v1` (Gitea)
+    pub api_url: Option<String>,
+}
+
+/// Task lifecycle events that can be sent to outbound notifiers
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEventKind {
+    AttemptCompleted,
+    AttemptFailed,
+    ApprovalRequested,
+    PrMerged,
+    TaskStatusChanged,
+}
+
+/// How an email notifier secures its SMTP connection
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    #[default]
+    StartTls,
+    /// Implicit TLS, usually on port 465
+    Tls,
+    /// Unencrypted, for local relays only
+    Plain,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotifierTarget {
+    /// POST each event as JSON
+    Webhook {
+        /// A `{{secret:NAME}}` reference to the secret store
+        url: String,
+    },
+    /// Slack-compatible incoming webhook
+    Slack {
+        /// A `{{secret:NAME}}` reference to the secret store
+        webhook_url: String,
+    },
+    Email {
+        smtp_host: String,
+        /// Defaults to the standard port for `security`
+        smtp_port: Option<u16>,
+        #[serde(default)]
+        security: SmtpSecurity,
+        username: Option<String>,
+        /// A `{{secret:NAME}}` reference to the secret store
+        password: Option<String>,
+        from: String,
+        to: Vec<String>,
+    },
+}
+
+impl NotifierTarget {
+    /// The fields holding credentials, by name, which config.json only refers to
+    pub fn credentials(&self) -> Vec<(&'static str, &str)> {
+        match self {
+            NotifierTarget::Webhook { url } => vec![("url", url)],
+            NotifierTarget::Slack { webhook_url } => vec![("webhook_url", webhook_url)],
+            NotifierTarget::Email { password, .. } => password
+                .iter()
+                .map(|password| ("password", password.as_str()))
+                .collect(),
+        }
+    }
+}
+
+/// An outbound notifier and the events it receives
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+pub struct NotifierConfig {
+    pub name: String,
+    pub enabled: bool,
+    pub target: NotifierTarget,
+    /// Events to send; empty sends all of them
+    #[serde(default)]
+    pub events: Vec<NotificationEventKind>,
+    /// Projects to send events for; empty sends events from every project
+    #[serde(default)]
+    pub project_ids: Vec<Uuid>,
+}
+
+impl NotifierConfig {
+    pub fn wants(&self, kind: NotificationEventKind, project_id: Uuid) -> bool {
+        self.enabled
+            && (self.events.is_empty() || self.events.contains(&kind))
+            && (self.project_ids.is_empty() || self.project_ids.contains(&project_id))
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
 pub struct Config {
     pub config_version: String,
@@ -33,6 +134,17 @@ pub struct Config {
     pub git_branch_prefix: String,
     #[serde(default)]
     pub showcases: ShowcaseState,
+    #[serde(default)]
+    pub git_hosts: Vec<GitHostConfig>,
+    /// Shared secret for inbound pull request webhooks; webhooks are rejected when unset
+    #[serde(default)]
+    pub pr_webhook_secret: Option<String>,
+    #[serde(default)]
+    pub notifiers: Vec<NotifierConfig>,
+    /// Regular expressions masked in process logs on top of the built-in secret formats;
+    /// a group named `secret` limits masking to that group
+    #[serde(default)]
+    pub redaction_patterns: Vec<String>,
 }
 
 impl Config {
@@ -56,6 +168,10 @@ impl Config {
             language: old_config.language,
             git_branch_prefix: old_config.git_branch_prefix,
             showcases: old_config.showcases,
+            git_hosts: Vec::new(),
+            pr_webhook_secret: None,
+            notifiers: Vec::new(),
+            redaction_patterns: Vec::new(),
         }
     }
 
@@ -104,6 +220,10 @@ impl Default for Config {
             language: UiLanguage::default(),
             git_branch_prefix: default_git_branch_prefix(),
             showcases: ShowcaseState::default(),
+            git_hosts: Vec::new(),
+            pr_webhook_secret: None,
+            notifiers: Vec::new(),
+            redaction_patterns: Vec::new(),
         }
     }
 }
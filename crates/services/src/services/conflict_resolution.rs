@@ -0,0 +1,304 @@
+use std::path::{Path, PathBuf};
+
+use db::models::{
+    conflict_resolution::ConflictResolution,
+    execution_process::{
+        ExecutionProcess, ExecutionProcessError, ExecutionProcessRunReason, ExecutionProcessStatus,
+    },
+    task_attempt::TaskAttempt,
+};
+use executors::actions::{
+    ExecutorAction, ExecutorActionType, coding_agent_follow_up::CodingAgentFollowUpRequest,
+    coding_agent_initial::CodingAgentInitialRequest,
+};
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::services::{
+    container::{ContainerError, ContainerService},
+    git::{ConflictOp, ConflictSides, GitServiceError},
+};
+
+/// Commit subjects listed per side of the conflict
+const MAX_COMMIT_SUBJECTS: usize = 20;
+/// Lines kept around each conflicting hunk
+const HUNK_CONTEXT_LINES: usize = 3;
+/// Upper bound on the excerpt of each conflicted file, in bytes
+const FILE_EXCERPT_MAX_BYTES: usize = 16 * 1024;
+
+#[derive(Debug, Error)]
+pub enum ConflictResolutionError {
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+    #[error(transparent)]
+    Container(#[from] ContainerError),
+    #[error(transparent)]
+    ExecutionProcess(#[from] ExecutionProcessError),
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+    #[error("No conflicted files to resolve")]
+    NoConflicts,
+    #[error("Stop the running process before resolving conflicts")]
+    ProcessRunning,
+}
+
+/// Start a follow-up asking the agent to resolve the conflicts of the attempt's
+/// in-progress rebase, merge, cherry-pick or revert. The operation itself is left
+/// untouched; `finish` continues it once the follow-up is done.
+pub async fn start<C>(
+    container: &C,
+    task_attempt: &TaskAttempt,
+) -> Result<ExecutionProcess, ConflictResolutionError>
+where
+    C: ContainerService + Sync,
+{
+    let pool = &container.db().pool;
+    let git = container.git();
+
+    let processes = ExecutionProcess::find_by_task_attempt_id(pool, task_attempt.id, false).await?;
+    if processes.iter().any(|p| {
+        p.status == ExecutionProcessStatus::Running
+            && p.run_reason != ExecutionProcessRunReason::DevServer
+    }) {
+        return Err(ConflictResolutionError::ProcessRunning);
+    }
+
+    let worktree_path = PathBuf::from(container.ensure_container_exists(task_attempt).await?);
+    let op = git
+        .detect_conflict_op(&worktree_path)?
+        .ok_or(ConflictResolutionError::NoConflicts)?;
+    let conflicted_files = git.get_conflicted_files(&worktree_path)?;
+    if conflicted_files.is_empty() {
+        return Err(ConflictResolutionError::NoConflicts);
+    }
+    let sides = git.get_conflict_sides(&worktree_path, &op, MAX_COMMIT_SUBJECTS)?;
+    let prompt = build_resolution_prompt(&worktree_path, &op, &conflicted_files, &sides);
+
+    let executor_profile_id =
+        ExecutionProcess::latest_executor_profile_for_attempt(pool, task_attempt.id).await?;
+    let latest_session_id =
+        ExecutionProcess::find_latest_session_id_by_task_attempt(pool, task_attempt.id).await?;
+    let action_type = match latest_session_id {
+        Some(session_id) => {
+            ExecutorActionType::CodingAgentFollowUpRequest(CodingAgentFollowUpRequest {
+                prompt,
+                session_id,
+                executor_profile_id,
+            })
+        }
+        None => ExecutorActionType::CodingAgentInitialRequest(CodingAgentInitialRequest {
+            prompt,
+            executor_profile_id,
+        }),
+    };
+    // No cleanup script: it would run against a half-applied operation
+    let action = ExecutorAction::new(action_type, None);
+
+    let execution_process = container
+        .start_execution(
+            task_attempt,
+            &action,
+            &ExecutionProcessRunReason::CodingAgent,
+        )
+        .await?;
+    ConflictResolution::create(
+        pool,
+        execution_process.id,
+        task_attempt.id,
+        &conflicted_files,
+    )
+    .await?;
+
+    Ok(execution_process)
+}
+
+/// Continue the operation a finished resolution follow-up was started for, if
+/// `execution_process` is one. When it cannot be continued the reason is recorded and
+/// the operation is left in progress; when continuing a rebase, cherry-pick or revert
+/// stops at the conflicts of its next commit, a new resolution follow-up is started.
+pub async fn finish<C>(
+    container: &C,
+    execution_process: &ExecutionProcess,
+    task_attempt: &TaskAttempt,
+) where
+    C: ContainerService + Sync,
+{
+    let pool = &container.db().pool;
+    let resolution =
+        match ConflictResolution::find_by_execution_process_id(pool, execution_process.id).await {
+            Ok(Some(resolution)) => resolution,
+            Ok(None) => return,
+            Err(e) => {
+                warn!(
+                    "Failed to load conflict resolution for process {}: {}",
+                    execution_process.id, e
+                );
+                return;
+            }
+        };
+
+    let error = if execution_process.status != ExecutionProcessStatus::Completed {
+        Some("The follow-up did not complete, so the operation was left in progress".to_string())
+    } else {
+        match continue_operation(container, task_attempt, &resolution).await {
+            Ok(()) => None,
+            Err(e) => Some(e.to_string()),
+        }
+    };
+    if let Err(e) = ConflictResolution::complete(pool, execution_process.id, error.as_deref()).await
+    {
+        warn!(
+            "Failed to record conflict resolution for process {}: {}",
+            execution_process.id, e
+        );
+    }
+}
+
+async fn continue_operation<C>(
+    container: &C,
+    task_attempt: &TaskAttempt,
+    resolution: &ConflictResolution,
+) -> Result<(), ConflictResolutionError>
+where
+    C: ContainerService + Sync,
+{
+    let git = container.git();
+    let worktree_path = PathBuf::from(container.ensure_container_exists(task_attempt).await?);
+    let head_before = git.get_head_info(&worktree_path)?.oid;
+
+    match git.continue_conflicts(&worktree_path, &resolution.conflicted_files) {
+        Ok(()) => {
+            info!(
+                "Continued the conflicted operation of attempt {} after the agent resolved it",
+                task_attempt.id
+            );
+            Ok(())
+        }
+        // HEAD only moves once the resolved commit was applied, so these are the next
+        // commit's conflicts rather than leftovers of this resolution
+        Err(GitServiceError::MergeConflicts(_))
+            if git.get_head_info(&worktree_path)?.oid != head_before =>
+        {
+            info!(
+                "Attempt {} stopped at further conflicts, asking the agent to resolve them",
+                task_attempt.id
+            );
+            start(container, task_attempt).await?;
+            Ok(())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn build_resolution_prompt(
+    worktree_path: &Path,
+    op: &ConflictOp,
+    conflicted_files: &[String],
+    sides: &ConflictSides,
+) -> String {
+    let (op_name, ours_label, theirs_label) = match op {
+        ConflictOp::Rebase => (
+            "rebase",
+            "the branch being rebased onto, with the commits replayed so far",
+            "the commit being replayed",
+        ),
+        ConflictOp::Merge => ("merge", "the current branch", "the branch being merged"),
+        ConflictOp::CherryPick => (
+            "cherry-pick",
+            "the current branch",
+            "the commit being cherry-picked",
+        ),
+        ConflictOp::Revert => ("revert", "the current branch", "the commit being reverted"),
+    };
+
+    let mut prompt = format!(
+        "A {op_name} in this worktree stopped at merge conflicts. Resolve them by editing \
+         the conflicted files below so that no conflict markers remain, keeping the intent \
+         of both sides. Do not stage or commit the changes, and do not continue or abort \
+         the {op_name}; it is continued automatically once the files are conflict-free.\n"
+    );
+    for (side, label, subjects) in [
+        ("Ours", ours_label, &sides.ours),
+        ("Theirs", theirs_label, &sides.theirs),
+    ] {
+        prompt.push_str(&format!("\n## {side}: {label}\n"));
+        if subjects.is_empty() {
+            prompt.push_str("No commits.\n");
+        }
+        for subject in subjects {
+            prompt.push_str(&format!("- {subject}\n"));
+        }
+    }
+    prompt.push_str("\n## Conflicted files\n");
+    for file in conflicted_files {
+        prompt.push_str(&format!("\n### {file}\n"));
+        match std::fs::read_to_string(worktree_path.join(file)) {
+            Ok(content) => prompt.push_str(&format!("```\n{}```\n", conflict_hunks(&content))),
+            Err(_) => prompt.push_str(
+                "Not shown: the file was deleted on one side or is not text. Decide whether \
+                 to keep it.\n",
+            ),
+        }
+    }
+    prompt
+}
+
+/// The conflicting hunks of `content` with a few lines of context, each introduced by
+/// its starting line number
+fn conflict_hunks(content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut start = None;
+    for (i, line) in lines.iter().enumerate() {
+        if line.starts_with("<<<<<<< ") {
+            start = Some(i);
+        } else if line.starts_with(">>>>>>> ")
+            && let Some(s) = start.take()
+        {
+            let from = s.saturating_sub(HUNK_CONTEXT_LINES);
+            let to = (i + HUNK_CONTEXT_LINES).min(lines.len() - 1);
+            match ranges.last_mut() {
+                Some(last) if from <= last.1 + 1 => last.1 = to,
+                _ => ranges.push((from, to)),
+            }
+        }
+    }
+
+    let mut excerpt = String::new();
+    for (from, to) in ranges {
+        let hunk = format!("@@ line {} @@\n{}\n", from + 1, lines[from..=to].join("\n"));
+        if excerpt.len() + hunk.len() > FILE_EXCERPT_MAX_BYTES {
+            excerpt.push_str("(further conflicts truncated)\n");
+            break;
+        }
+        excerpt.push_str(&hunk);
+    }
+    excerpt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflict_hunks_keep_markers_with_context() {
+        let content =
+            "a\nb\nc\nd\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> feature\ne\nf\ng\nh\n";
+        let hunks = conflict_hunks(content);
+        assert_eq!(
+            hunks,
+            "@@ line 2 @@\nb\nc\nd\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> feature\ne\nf\ng\n"
+        );
+    }
+
+    #[test]
+    fn conflict_hunks_merge_nearby_conflicts() {
+        let content =
+            "<<<<<<< HEAD\n1\n=======\n2\n>>>>>>> x\nmid\n<<<<<<< HEAD\n3\n=======\n4\n>>>>>>> x\n";
+        let hunks = conflict_hunks(content);
+        assert_eq!(hunks.matches("@@ line").count(), 1);
+        assert!(hunks.starts_with("@@ line 1 @@\n<<<<<<< HEAD"));
+        assert!(hunks.ends_with(">>>>>>> x\n"));
+    }
+}
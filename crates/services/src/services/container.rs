@@ -2,10 +2,12 @@ use std::{
     collections::HashMap,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::{Error as AnyhowError, anyhow};
 use async_trait::async_trait;
+use command_group::AsyncGroupChild;
 use db::{
     DBService,
     models::{
@@ -14,9 +16,13 @@ use db::{
             ExecutionProcessStatus,
         },
         execution_process_logs::ExecutionProcessLogs,
+        execution_process_normalization::ExecutionProcessNormalization,
+        execution_process_normalized_entry::ExecutionProcessNormalizedEntry,
+        execution_process_usage::ExecutionProcessUsage,
         executor_session::{CreateExecutorSession, ExecutorSession},
         task::{Task, TaskStatus},
         task_attempt::{TaskAttempt, TaskAttemptError},
+        task_attempt_repository::TaskAttemptRepository,
     },
 };
 use executors::{
@@ -27,14 +33,27 @@ use executors::{
         script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
     },
     executors::{ExecutorError, StandardCodingAgentExecutor},
-    logs::{NormalizedEntry, NormalizedEntryError, NormalizedEntryType, utils::ConversationPatch},
+    logs::{
+        NORMALIZER_VERSION, NormalizedEntry, NormalizedEntryError, NormalizedEntryType,
+        utils::{
+            ConversationPatch,
+            patch::{ConversationMirror, normalized_entry_changes},
+        },
+    },
     profile::{ExecutorConfigs, ExecutorProfileId, to_default_variant},
 };
 use futures::{StreamExt, future};
 use sqlx::Error as SqlxError;
 use thiserror::Error;
-use tokio::{sync::RwLock, task::JoinHandle};
+use tokio::{
+    sync::{
+        RwLock,
+        broadcast::error::{RecvError, TryRecvError},
+    },
+    task::JoinHandle,
+};
 use utils::{
+    diff::Diff,
     log_msg::LogMsg,
     msg_store::MsgStore,
     text::{git_branch_id, short_uuid},
@@ -44,11 +63,19 @@ use uuid::Uuid;
 use crate::services::{
     git::{GitService, GitServiceError},
     image::ImageService,
+    secrets::SecretStore,
     share::SharePublisher,
     worktree_manager::{WorktreeError, WorktreeManager},
 };
 pub type ContainerRef = String;
 
+/// How long to keep persisting normalized entries after a process finishes while its
+/// normalizers are still catching up
+const NORMALIZED_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a re-normalization may go without output before it is considered done
+const RENORMALIZE_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Data needed for background worktree cleanup (doesn't require DB access)
 #[derive(Debug, Clone)]
 pub struct WorktreeCleanupData {
@@ -83,6 +110,13 @@ pub async fn cleanup_worktrees_direct(data: &[WorktreeCleanupData]) -> Result<()
     Ok(())
 }
 
+/// A script spawned for an attempt outside of an execution process
+pub struct SpawnedScript {
+    pub child: AsyncGroupChild,
+    /// Podman container the script runs in, which keeps running when the child is killed
+    pub sandbox_container: Option<String>,
+}
+
 #[derive(Debug, Error)]
 pub enum ContainerError {
     #[error(transparent)]
@@ -113,6 +147,8 @@ pub trait ContainerService {
 
     fn share_publisher(&self) -> Option<&SharePublisher>;
 
+    fn secrets(&self) -> &Arc<SecretStore>;
+
     fn task_attempt_to_current_dir(&self, task_attempt: &TaskAttempt) -> PathBuf;
 
     async fn create(&self, task_attempt: &TaskAttempt) -> Result<ContainerRef, ContainerError>;
@@ -122,6 +158,32 @@ pub trait ContainerService {
         self.delete_inner(task_attempt).await
     }
 
+    /// Delete the attempt's branch in the project's repository and in its additional
+    /// repositories, for attempts that are thrown away. Call after [`Self::delete`] has
+    /// removed the worktrees that have the branch checked out.
+    async fn delete_branches(&self, task_attempt: &TaskAttempt) -> Result<(), ContainerError> {
+        let pool = &self.db().pool;
+        let task = task_attempt
+            .parent_task(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let project = task
+            .parent_project(pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let mut repo_paths = vec![project.git_repo_path];
+        repo_paths.extend(
+            TaskAttemptRepository::find_by_task_attempt_id(pool, task_attempt.id)
+                .await?
+                .into_iter()
+                .map(|repository| repository.git_repo_path),
+        );
+        for repo_path in repo_paths {
+            self.git().delete_branch(&repo_path, &task_attempt.branch)?;
+        }
+        Ok(())
+    }
+
     /// Check if a task has any running execution processes
     async fn has_running_processes(&self, task_id: Uuid) -> Result<bool, ContainerError> {
         let attempts = TaskAttempt::fetch_all(&self.db().pool, Some(task_id)).await?;
@@ -225,6 +287,19 @@ pub trait ContainerService {
         stats_only: bool,
     ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, ContainerError>;
 
+    /// Changed files of an attempt with their line counts but without contents, as the
+    /// diff stream starts out. Unlike the stream this returns right away.
+    async fn diff_stats(&self, task_attempt: &TaskAttempt) -> Result<Vec<Diff>, ContainerError>;
+
+    /// Spawn a script in `current_dir` the way the attempt's execution processes are
+    /// spawned: inside the project's sandbox, with its environment and secrets
+    async fn spawn_script(
+        &self,
+        task_attempt: &TaskAttempt,
+        current_dir: &Path,
+        request: &ScriptRequest,
+    ) -> Result<SpawnedScript, ContainerError>;
+
     /// Fetch the MsgStore for a given execution ID, panicking if missing.
     async fn get_msg_store_by_id(&self, uuid: &Uuid) -> Option<Arc<MsgStore>> {
         let map = self.msg_stores().read().await;
@@ -301,7 +376,7 @@ pub trait ContainerService {
     ) -> Option<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>> {
         // First try in-memory store (existing behavior)
         if let Some(store) = self.get_msg_store_by_id(id).await {
-            Some(
+            return Some(
                 store
                     .history_plus_stream() // BoxStream<Result<LogMsg, io::Error>>
                     .filter(|msg| future::ready(matches!(msg, Ok(LogMsg::JsonPatch(..)))))
@@ -309,119 +384,398 @@ pub trait ContainerService {
                         Ok::<_, std::io::Error>(LogMsg::Finished)
                     }))
                     .boxed(),
-            )
-        } else {
-            // Fallback: load from DB and normalize
-            let logs_record =
-                match ExecutionProcessLogs::find_by_execution_id(&self.db().pool, *id).await {
-                    Ok(Some(record)) => record,
-                    Ok(None) => return None, // No logs exist
-                    Err(e) => {
-                        tracing::error!("Failed to fetch logs for execution {}: {}", id, e);
-                        return None;
-                    }
-                };
+            );
+        }
 
-            let raw_messages = match logs_record.parse_logs() {
-                Ok(msgs) => msgs,
-                Err(e) => {
-                    tracing::error!("Failed to parse logs for execution {}: {}", id, e);
-                    return None;
-                }
-            };
+        // Then the stored conversation, once its normalizer has finished with it
+        match self.find_stored_conversation(id).await {
+            Ok(Some(entries)) => {
+                let patches = entries
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, entry)| {
+                        LogMsg::JsonPatch(ConversationPatch::add_normalized_entry(index, entry))
+                    })
+                    .chain(std::iter::once(LogMsg::Finished))
+                    .map(Ok::<_, std::io::Error>);
+                return Some(futures::stream::iter(patches).boxed());
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::error!(
+                    "Failed to fetch normalized entries for execution {}: {}",
+                    id,
+                    e
+                );
+            }
+        }
 
-            // Create temporary store and populate
-            // Include JsonPatch messages (already normalized) and Stdout/Stderr (need normalization)
-            let temp_store = Arc::new(MsgStore::new());
-            for msg in raw_messages {
-                if matches!(
-                    msg,
-                    LogMsg::Stdout(_) | LogMsg::Stderr(_) | LogMsg::JsonPatch(_)
-                ) {
-                    temp_store.push(msg);
-                }
+        // Fallback: load raw logs from DB and normalize
+        let process = match ExecutionProcess::find_by_id(&self.db().pool, *id).await {
+            Ok(Some(process)) => process,
+            Ok(None) => {
+                tracing::error!("No execution process found for ID: {}", id);
+                return None;
+            }
+            Err(e) => {
+                tracing::error!("Failed to fetch execution process {}: {}", id, e);
+                return None;
             }
-            temp_store.push_finished();
+        };
 
-            let process = match ExecutionProcess::find_by_id(&self.db().pool, *id).await {
-                Ok(Some(process)) => process,
-                Ok(None) => {
-                    tracing::error!("No execution process found for ID: {}", id);
-                    return None;
-                }
-                Err(e) => {
-                    tracing::error!("Failed to fetch execution process {}: {}", id, e);
-                    return None;
-                }
-            };
+        // Get the task attempt to determine correct directory
+        let task_attempt = match process.parent_task_attempt(&self.db().pool).await {
+            Ok(Some(task_attempt)) => task_attempt,
+            Ok(None) => {
+                tracing::error!("No task attempt found for ID: {}", process.task_attempt_id);
+                return None;
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Failed to fetch task attempt {}: {}",
+                    process.task_attempt_id,
+                    e
+                );
+                return None;
+            }
+        };
 
-            // Get the task attempt to determine correct directory
-            let task_attempt = match process.parent_task_attempt(&self.db().pool).await {
-                Ok(Some(task_attempt)) => task_attempt,
-                Ok(None) => {
-                    tracing::error!("No task attempt found for ID: {}", process.task_attempt_id);
-                    return None;
+        if let Err(err) = self.ensure_container_exists(&task_attempt).await {
+            tracing::warn!(
+                "Failed to recreate worktree before log normalization for task attempt {}: {}",
+                task_attempt.id,
+                err
+            );
+        }
+
+        let temp_store = self.normalize_stored_logs(&process, &task_attempt).await?;
+        Some(
+            temp_store
+                .history_plus_stream()
+                .filter(|msg| future::ready(matches!(msg, Ok(LogMsg::JsonPatch(..)))))
+                .chain(futures::stream::once(async {
+                    Ok::<_, std::io::Error>(LogMsg::Finished)
+                }))
+                .boxed(),
+        )
+    }
+
+    /// The conversation of a process as it stands now. For a running process this is
+    /// what has been normalized so far; the call does not wait for more.
+    async fn conversation_snapshot(&self, id: &Uuid) -> Option<Vec<NormalizedEntry>> {
+        let msgs = match self.get_msg_store_by_id(id).await {
+            Some(store) => store.get_history(),
+            // A process that is not running has a finite stream
+            None => {
+                self.stream_normalized_logs(id)
+                    .await?
+                    .filter_map(|msg| future::ready(msg.ok()))
+                    .collect()
+                    .await
+            }
+        };
+
+        let mut entries = Vec::new();
+        for msg in msgs {
+            if let LogMsg::JsonPatch(patch) = msg {
+                for change in normalized_entry_changes(&patch) {
+                    change.apply_to(&mut entries);
                 }
+            }
+        }
+        Some(entries)
+    }
+
+    /// The stored conversation of a process, or `None` when it was never completely stored
+    async fn find_stored_conversation(
+        &self,
+        id: &Uuid,
+    ) -> Result<Option<Vec<NormalizedEntry>>, SqlxError> {
+        if ExecutionProcessNormalization::find_by_execution_process_id(&self.db().pool, *id)
+            .await?
+            .is_none()
+        {
+            return Ok(None);
+        }
+        let entries =
+            ExecutionProcessNormalizedEntry::find_by_execution_process_id(&self.db().pool, *id)
+                .await?;
+        Ok(Some(entries.into_iter().map(|row| row.entry.0).collect()))
+    }
+
+    /// Replay a finished process's raw logs into a temporary store and run its
+    /// executor's normalizer over them
+    async fn normalize_stored_logs(
+        &self,
+        process: &ExecutionProcess,
+        task_attempt: &TaskAttempt,
+    ) -> Option<Arc<MsgStore>> {
+        let id = process.id;
+        let logs_record =
+            match ExecutionProcessLogs::find_by_execution_id(&self.db().pool, id).await {
+                Ok(Some(record)) => record,
+                Ok(None) => return None, // No logs exist
                 Err(e) => {
-                    tracing::error!(
-                        "Failed to fetch task attempt {}: {}",
-                        process.task_attempt_id,
-                        e
-                    );
+                    tracing::error!("Failed to fetch logs for execution {}: {}", id, e);
                     return None;
                 }
             };
 
-            if let Err(err) = self.ensure_container_exists(&task_attempt).await {
-                tracing::warn!(
-                    "Failed to recreate worktree before log normalization for task attempt {}: {}",
-                    task_attempt.id,
-                    err
-                );
+        let raw_messages = match logs_record.parse_logs() {
+            Ok(msgs) => msgs,
+            Err(e) => {
+                tracing::error!("Failed to parse logs for execution {}: {}", id, e);
+                return None;
             }
+        };
 
-            let current_dir = self.task_attempt_to_current_dir(&task_attempt);
+        // Create temporary store and populate
+        // Include JsonPatch messages (already normalized) and Stdout/Stderr (need normalization)
+        let temp_store = Arc::new(MsgStore::new());
+        for msg in raw_messages {
+            if matches!(
+                msg,
+                LogMsg::Stdout(_) | LogMsg::Stderr(_) | LogMsg::JsonPatch(_)
+            ) {
+                temp_store.push(msg);
+            }
+        }
+        temp_store.push_finished();
 
-            let executor_action = if let Ok(executor_action) = process.executor_action() {
-                executor_action
-            } else {
-                tracing::error!(
-                    "Failed to parse executor action: {:?}",
+        let current_dir = self.task_attempt_to_current_dir(task_attempt);
+
+        let executor_action = if let Ok(executor_action) = process.executor_action() {
+            executor_action
+        } else {
+            tracing::error!(
+                "Failed to parse executor action: {:?}",
+                process.executor_action()
+            );
+            return None;
+        };
+
+        // Spawn normalizer on populated store
+        match executor_action.typ() {
+            ExecutorActionType::CodingAgentInitialRequest(request) => {
+                let executor = ExecutorConfigs::get_cached()
+                    .get_coding_agent_or_default(&request.executor_profile_id);
+                executor.normalize_logs(temp_store.clone(), &current_dir);
+            }
+            ExecutorActionType::CodingAgentFollowUpRequest(request) => {
+                let executor = ExecutorConfigs::get_cached()
+                    .get_coding_agent_or_default(&request.executor_profile_id);
+                executor.normalize_logs(temp_store.clone(), &current_dir);
+            }
+            _ => {
+                tracing::debug!(
+                    "Executor action doesn't support log normalization: {:?}",
                     process.executor_action()
                 );
                 return None;
+            }
+        }
+        Some(temp_store)
+    }
+
+    /// Re-normalize a finished process's raw logs with the current normalizers and
+    /// replace its stored conversation. Returns the number of entries stored.
+    async fn renormalize_logs(&self, id: &Uuid) -> Result<usize, ContainerError> {
+        if self.get_msg_store_by_id(id).await.is_some() {
+            return Err(ContainerError::Other(anyhow!(
+                "Execution process {id} is still running"
+            )));
+        }
+
+        let process = ExecutionProcess::find_by_id(&self.db().pool, *id)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let task_attempt = process
+            .parent_task_attempt(&self.db().pool)
+            .await?
+            .ok_or(SqlxError::RowNotFound)?;
+        let store = self
+            .normalize_stored_logs(&process, &task_attempt)
+            .await
+            .ok_or_else(|| {
+                ContainerError::Other(anyhow!(
+                    "Execution process {id} has no logs that can be normalized"
+                ))
+            })?;
+
+        // The stream ends once the normalizers finish and drop their handles on the store
+        let mut stream = store.history_plus_stream();
+        drop(store);
+
+        let mut entries = Vec::new();
+        loop {
+            match tokio::time::timeout(RENORMALIZE_IDLE_TIMEOUT, stream.next()).await {
+                Ok(Some(Ok(LogMsg::JsonPatch(patch)))) => {
+                    for change in normalized_entry_changes(&patch) {
+                        change.apply_to(&mut entries);
+                    }
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => break,
+                Err(_) => {
+                    tracing::warn!(
+                        "Normalizer for execution {} went idle without finishing; storing {} entries",
+                        id,
+                        entries.len()
+                    );
+                    break;
+                }
+            }
+        }
+
+        ExecutionProcessNormalizedEntry::replace_all(
+            &self.db().pool,
+            *id,
+            &entries,
+            NORMALIZER_VERSION,
+        )
+        .await?;
+        Ok(entries.len())
+    }
+
+    /// Persist a running process's normalized conversation as its patches arrive. Each
+    /// batch of messages that is already available is written in one transaction, and
+    /// the conversation is marked complete once the normalizers finish.
+    fn spawn_stream_normalized_entries_to_db(&self, execution_id: &Uuid) -> JoinHandle<()> {
+        let execution_id = *execution_id;
+        let msg_stores = self.msg_stores().clone();
+        let db = self.db().clone();
+
+        tokio::spawn(async move {
+            let store = {
+                let map = msg_stores.read().await;
+                map.get(&execution_id).cloned()
+            };
+            let Some(store) = store else {
+                return;
             };
 
-            // Spawn normalizer on populated store
-            match executor_action.typ() {
-                ExecutorActionType::CodingAgentInitialRequest(request) => {
-                    let executor = ExecutorConfigs::get_cached()
-                        .get_coding_agent_or_default(&request.executor_profile_id);
-                    executor.normalize_logs(temp_store.clone(), &current_dir);
+            // Normalizers hold their own handles on the store and keep pushing patches
+            // after Finished, so read until the channel closes rather than holding it open
+            let (mut batch, mut receiver) = (store.get_history(), store.get_receiver());
+            drop(store);
+
+            let mut mirror = ConversationMirror::default();
+            let mut finished = false;
+            let complete = loop {
+                if batch.is_empty() {
+                    let next = if finished {
+                        match tokio::time::timeout(NORMALIZED_DRAIN_TIMEOUT, receiver.recv()).await
+                        {
+                            Ok(next) => next,
+                            Err(_) => {
+                                tracing::warn!(
+                                    "Normalizer for execution {} went idle without finishing",
+                                    execution_id
+                                );
+                                break false;
+                            }
+                        }
+                    } else {
+                        receiver.recv().await
+                    };
+                    match next {
+                        Ok(msg) => batch.push(msg),
+                        Err(RecvError::Closed) => break true,
+                        Err(RecvError::Lagged(skipped)) => {
+                            tracing::warn!(
+                                "Missed {} log messages while persisting normalized entries for execution {}",
+                                skipped,
+                                execution_id
+                            );
+                            break false;
+                        }
+                    }
                 }
-                ExecutorActionType::CodingAgentFollowUpRequest(request) => {
-                    let executor = ExecutorConfigs::get_cached()
-                        .get_coding_agent_or_default(&request.executor_profile_id);
-                    executor.normalize_logs(temp_store.clone(), &current_dir);
+
+                // Take whatever else is already waiting into the same batch
+                let (mut closed, mut lagged) = (false, false);
+                loop {
+                    match receiver.try_recv() {
+                        Ok(msg) => batch.push(msg),
+                        Err(TryRecvError::Empty) => break,
+                        Err(TryRecvError::Closed) => {
+                            closed = true;
+                            break;
+                        }
+                        Err(TryRecvError::Lagged(skipped)) => {
+                            tracing::warn!(
+                                "Missed {} log messages while persisting normalized entries for execution {}",
+                                skipped,
+                                execution_id
+                            );
+                            lagged = true;
+                            break;
+                        }
+                    }
+                }
+                if lagged {
+                    break false;
+                }
+
+                for msg in batch.drain(..) {
+                    match msg {
+                        LogMsg::JsonPatch(patch) => {
+                            for change in normalized_entry_changes(&patch) {
+                                mirror.apply(change);
+                            }
+                        }
+                        LogMsg::Finished => finished = true,
+                        _ => {}
+                    }
                 }
-                _ => {
-                    tracing::debug!(
-                        "Executor action doesn't support log normalization: {:?}",
-                        process.executor_action()
+                let flush = mirror.take_flush();
+                if !flush.is_empty()
+                    && let Err(e) = ExecutionProcessNormalizedEntry::write_flush(
+                        &db.pool,
+                        execution_id,
+                        &flush,
+                        NORMALIZER_VERSION,
+                    )
+                    .await
+                {
+                    tracing::error!(
+                        "Failed to persist normalized entries for execution {}: {}",
+                        execution_id,
+                        e
                     );
-                    return None;
+                    break false;
+                }
+                if closed {
+                    break true;
                 }
+            };
+
+            let result = if complete {
+                ExecutionProcessNormalization::mark_complete(
+                    &db.pool,
+                    execution_id,
+                    mirror.entries().len(),
+                    NORMALIZER_VERSION,
+                )
+                .await
+            } else {
+                // A partial conversation is worse than none: without rows the conversation
+                // is re-normalized from raw logs when requested
+                ExecutionProcessNormalizedEntry::delete_by_execution_process_id(
+                    &db.pool,
+                    execution_id,
+                )
+                .await
+                .map(|_| ())
+            };
+            if let Err(e) = result {
+                tracing::error!(
+                    "Failed to finish storing normalized entries for execution {}: {}",
+                    execution_id,
+                    e
+                );
             }
-            Some(
-                temp_store
-                    .history_plus_stream()
-                    .filter(|msg| future::ready(matches!(msg, Ok(LogMsg::JsonPatch(..)))))
-                    .chain(futures::stream::once(async {
-                        Ok::<_, std::io::Error>(LogMsg::Finished)
-                    }))
-                    .boxed(),
-            )
-        }
+        })
     }
 
     fn spawn_stream_raw_logs_to_db(&self, execution_id: &Uuid) -> JoinHandle<()> {
@@ -488,6 +842,17 @@ pub trait ContainerService {
                                 );
                             }
                         }
+                        LogMsg::Usage(usage) => {
+                            if let Err(e) =
+                                ExecutionProcessUsage::create(&db.pool, execution_id, usage).await
+                            {
+                                tracing::error!(
+                                    "Failed to record token usage for execution process {}: {}",
+                                    execution_id,
+                                    e
+                                );
+                            }
+                        }
                         LogMsg::Finished => {
                             break;
                         }
@@ -726,6 +1091,7 @@ pub trait ContainerService {
                 ExecutorConfigs::get_cached().get_coding_agent(executor_profile_id)
             {
                 executor.normalize_logs(msg_store, &self.task_attempt_to_current_dir(task_attempt));
+                self.spawn_stream_normalized_entries_to_db(&execution_process.id);
             } else {
                 tracing::error!(
                     "Failed to resolve profile '{:?}' for normalization",
@@ -5,6 +5,7 @@ use db::{
     models::{
         draft::{Draft, DraftType},
         execution_process::ExecutionProcess,
+        merge_queue_entry::MergeQueueEntry,
         shared_task::SharedTask as SharedDbTask,
         task::Task,
         task_attempt::TaskAttempt,
@@ -24,9 +25,10 @@ mod streams;
 pub mod types;
 
 pub use patches::{
-    draft_patch, execution_process_patch, shared_task_patch, task_attempt_patch, task_patch,
+    draft_patch, execution_process_patch, lifecycle_patch, merge_queue_patch, shared_task_patch,
+    task_attempt_patch, task_patch,
 };
-pub use types::{EventError, EventPatch, EventPatchInner, HookTables, RecordTypes};
+pub use types::{EventError, EventPatch, EventPatchInner, HookTables, LifecycleEvent, RecordTypes};
 
 #[derive(Clone)]
 pub struct EventService {
@@ -136,6 +138,14 @@ impl EventService {
                                     msg_store_for_preupdate.push_patch(patch);
                                 }
                             }
+                            "merge_queue_entries" => {
+                                if let Ok(value) = preupdate.get_old_column_value(0)
+                                    && let Ok(entry_id) = <Uuid as Decode<Sqlite>>::decode(value)
+                                {
+                                    let patch = merge_queue_patch::remove(entry_id);
+                                    msg_store_for_preupdate.push_patch(patch);
+                                }
+                            }
                             "drafts" => {
                                 let draft_type = preupdate
                                     .get_old_column_value(2)
@@ -180,7 +190,8 @@ impl EventService {
                                 | (HookTables::TaskAttempts, SqliteOperation::Delete)
                                 | (HookTables::ExecutionProcesses, SqliteOperation::Delete)
                                 | (HookTables::Drafts, SqliteOperation::Delete)
-                                | (HookTables::SharedTasks, SqliteOperation::Delete) => {
+                                | (HookTables::SharedTasks, SqliteOperation::Delete)
+                                | (HookTables::MergeQueueEntries, SqliteOperation::Delete) => {
                                     // Deletions handled in preupdate hook for reliable data capture
                                     return;
                                 }
@@ -200,6 +211,22 @@ impl EventService {
                                         }
                                     }
                                 }
+                                (HookTables::MergeQueueEntries, _) => {
+                                    match MergeQueueEntry::find_by_rowid(&db.pool, rowid).await {
+                                        Ok(Some(entry)) => RecordTypes::MergeQueueEntry(entry),
+                                        Ok(None) => RecordTypes::DeletedMergeQueueEntry {
+                                            rowid,
+                                            entry_id: None,
+                                        },
+                                        Err(e) => {
+                                            tracing::error!(
+                                                "Failed to fetch merge_queue_entry: {:?}",
+                                                e
+                                            );
+                                            return;
+                                        }
+                                    }
+                                }
                                 (HookTables::Tasks, _) => {
                                     match Task::find_by_rowid(&db.pool, rowid).await {
                                         Ok(Some(task)) => RecordTypes::Task(task),
@@ -319,6 +346,15 @@ impl EventService {
                                     msg_store_for_hook.push_patch(patch);
                                     return;
                                 }
+                                RecordTypes::MergeQueueEntry(entry) => {
+                                    let patch = match hook.operation {
+                                        SqliteOperation::Insert => merge_queue_patch::add(entry),
+                                        SqliteOperation::Update => merge_queue_patch::replace(entry),
+                                        _ => merge_queue_patch::replace(entry),
+                                    };
+                                    msg_store_for_hook.push_patch(patch);
+                                    return;
+                                }
                                 RecordTypes::DeletedDraft { draft_type, task_attempt_id: Some(id), .. } => {
                                     let patch = match draft_type {
                                         DraftType::FollowUp => draft_patch::follow_up_clear(*id),
@@ -343,6 +379,14 @@ impl EventService {
                                     msg_store_for_hook.push_patch(patch);
                                     return;
                                 }
+                                RecordTypes::DeletedMergeQueueEntry {
+                                    entry_id: Some(entry_id),
+                                    ..
+                                } => {
+                                    let patch = merge_queue_patch::remove(*entry_id);
+                                    msg_store_for_hook.push_patch(patch);
+                                    return;
+                                }
                                 RecordTypes::TaskAttempt(attempt) => {
                                     // Task attempts should update the parent task with fresh data
                                     if let Ok(Some(task)) =
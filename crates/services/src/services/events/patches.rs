@@ -1,6 +1,7 @@
 use db::models::{
     draft::{Draft, DraftType},
     execution_process::ExecutionProcess,
+    merge_queue_entry::MergeQueueEntry,
     shared_task::SharedTask as DbSharedTask,
     task::TaskWithAttemptStatus,
     task_attempt::TaskAttempt,
@@ -8,6 +9,8 @@ use db::models::{
 use json_patch::{AddOperation, Patch, PatchOperation, RemoveOperation, ReplaceOperation};
 use uuid::Uuid;
 
+use super::types::LifecycleEvent;
+
 // Shared helper to escape JSON Pointer segments
 fn escape_pointer_segment(s: &str) -> String {
     s.replace('~', "~0").replace('/', "~1")
@@ -241,3 +244,62 @@ pub mod task_attempt_patch {
         })])
     }
 }
+
+/// Helper functions for creating merge queue entry patches
+pub mod merge_queue_patch {
+    use super::*;
+
+    fn entry_path(entry_id: Uuid) -> String {
+        format!(
+            "/merge_queue/{}",
+            escape_pointer_segment(&entry_id.to_string())
+        )
+    }
+
+    /// Create patch for adding a new merge queue entry
+    pub fn add(entry: &MergeQueueEntry) -> Patch {
+        Patch(vec![PatchOperation::Add(AddOperation {
+            path: entry_path(entry.id)
+                .try_into()
+                .expect("Merge queue path should be valid"),
+            value: serde_json::to_value(entry)
+                .expect("Merge queue entry serialization should not fail"),
+        })])
+    }
+
+    /// Create patch for updating an existing merge queue entry
+    pub fn replace(entry: &MergeQueueEntry) -> Patch {
+        Patch(vec![PatchOperation::Replace(ReplaceOperation {
+            path: entry_path(entry.id)
+                .try_into()
+                .expect("Merge queue path should be valid"),
+            value: serde_json::to_value(entry)
+                .expect("Merge queue entry serialization should not fail"),
+        })])
+    }
+
+    /// Create patch for removing a merge queue entry
+    pub fn remove(entry_id: Uuid) -> Patch {
+        Patch(vec![PatchOperation::Remove(RemoveOperation {
+            path: entry_path(entry_id)
+                .try_into()
+                .expect("Merge queue path should be valid"),
+        })])
+    }
+}
+
+/// Helper functions for creating lifecycle event patches
+pub mod lifecycle_patch {
+    use super::*;
+
+    /// Create patch announcing a lifecycle event under a fresh id
+    pub fn add(event: &LifecycleEvent) -> Patch {
+        Patch(vec![PatchOperation::Add(AddOperation {
+            path: format!("/lifecycle/{}", Uuid::new_v4())
+                .try_into()
+                .expect("Lifecycle path should be valid"),
+            value: serde_json::to_value(event)
+                .expect("Lifecycle event serialization should not fail"),
+        })])
+    }
+}
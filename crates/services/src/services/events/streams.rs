@@ -1,6 +1,7 @@
 use db::models::{
     draft::{Draft, DraftType},
     execution_process::ExecutionProcess,
+    merge_queue_entry::MergeQueueEntry,
     project::Project,
     shared_task::SharedTask,
     task::{Task, TaskWithAttemptStatus},
@@ -446,4 +447,62 @@ impl EventService {
         let combined_stream = initial_stream.chain(filtered_stream).boxed();
         Ok(combined_stream)
     }
+
+    /// Stream the merge queue of a project with initial snapshot (raw LogMsg)
+    pub async fn stream_merge_queue_for_project_raw(
+        &self,
+        project_id: Uuid,
+    ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, EventError>
+    {
+        let entries = MergeQueueEntry::find_by_project_id(&self.db.pool, project_id).await?;
+        let entries_map: serde_json::Map<String, serde_json::Value> = entries
+            .into_iter()
+            .map(|entry| (entry.id.to_string(), serde_json::to_value(entry).unwrap()))
+            .collect();
+
+        let initial_patch = json!([
+            {
+                "op": "replace",
+                "path": "/merge_queue",
+                "value": entries_map
+            }
+        ]);
+        let initial_msg = LogMsg::JsonPatch(serde_json::from_value(initial_patch).unwrap());
+
+        // Live updates: entries carry their project, so no lookups are needed
+        let filtered_stream =
+            BroadcastStream::new(self.msg_store.get_receiver()).filter_map(move |msg_result| {
+                async move {
+                    match msg_result {
+                        Ok(LogMsg::JsonPatch(patch)) => {
+                            if let Some(patch_op) = patch.0.first()
+                                && patch_op.path().starts_with("/merge_queue/")
+                            {
+                                let value = match patch_op {
+                                    json_patch::PatchOperation::Add(op) => &op.value,
+                                    json_patch::PatchOperation::Replace(op) => &op.value,
+                                    // Forward removals; clients will ignore missing entries
+                                    json_patch::PatchOperation::Remove(_) => {
+                                        return Some(Ok(LogMsg::JsonPatch(patch)));
+                                    }
+                                    _ => return None,
+                                };
+                                if serde_json::from_value::<MergeQueueEntry>(value.clone())
+                                    .is_ok_and(|entry| entry.project_id == project_id)
+                                {
+                                    return Some(Ok(LogMsg::JsonPatch(patch)));
+                                }
+                            }
+                            None
+                        }
+                        Ok(other) => Some(Ok(other)),
+                        Err(_) => None,
+                    }
+                }
+            });
+
+        let initial_stream = futures::stream::once(async move { Ok(initial_msg) });
+        let combined_stream = initial_stream.chain(filtered_stream).boxed();
+        Ok(combined_stream)
+    }
 }
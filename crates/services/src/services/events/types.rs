@@ -2,6 +2,7 @@ use anyhow::Error as AnyhowError;
 use db::models::{
     draft::{Draft, DraftType},
     execution_process::ExecutionProcess,
+    merge_queue_entry::MergeQueueEntry,
     shared_task::SharedTask,
     task::Task,
     task_attempt::TaskAttempt,
@@ -35,6 +36,8 @@ pub enum HookTables {
     Drafts,
     #[strum(to_string = "shared_tasks")]
     SharedTasks,
+    #[strum(to_string = "merge_queue_entries")]
+    MergeQueueEntries,
 }
 
 #[derive(Serialize, Deserialize, TS)]
@@ -46,6 +49,7 @@ pub enum RecordTypes {
     Draft(Draft),
     RetryDraft(Draft),
     SharedTask(SharedTask),
+    MergeQueueEntry(MergeQueueEntry),
     DeletedTask {
         rowid: i64,
         project_id: Option<Uuid>,
@@ -69,6 +73,10 @@ pub enum RecordTypes {
         rowid: i64,
         task_id: Option<Uuid>,
     },
+    DeletedMergeQueueEntry {
+        rowid: i64,
+        entry_id: Option<Uuid>,
+    },
 }
 
 #[derive(Serialize, Deserialize, TS)]
@@ -83,3 +91,20 @@ pub struct EventPatch {
     pub(crate) path: String,
     pub(crate) value: EventPatchInner,
 }
+
+/// Lifecycle moments that are not visible from a single table change, published
+/// under `/lifecycle/<id>` for background listeners such as the notifier
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LifecycleEvent {
+    ApprovalRequested {
+        execution_process_id: Uuid,
+        approval_id: String,
+        tool_name: String,
+    },
+    PrMerged {
+        task_attempt_id: Uuid,
+        pr_number: i64,
+        pr_url: String,
+    },
+}
@@ -10,12 +10,17 @@ use std::{
 };
 
 use chrono::{DateTime, Utc};
-use db::models::merge::{MergeStatus, PullRequestInfo};
+use db::models::{
+    merge::{MergeStatus, PullRequestInfo},
+    pr_check_summary::{CheckRun, CheckState, PrChecks},
+};
 use serde_json::Value;
 use thiserror::Error;
 use utils::shell::resolve_executable_path_blocking;
 
-use crate::services::github_service::{CreatePrRequest, GitHubRepoInfo};
+use crate::services::github_service::{
+    CreatePrRequest, GitHubRepoInfo, PrReviewComment, PrReviewThread,
+};
 
 /// High-level errors originating from the GitHub CLI.
 #[derive(Debug, Error)]
@@ -30,6 +35,28 @@ pub enum GhCliError {
     UnexpectedOutput(String),
 }
 
+const REVIEW_THREADS_QUERY: &str = r#"
+query($owner: String!, $repo: String!, $number: Int!) {
+  repository(owner: $owner, name: $repo) {
+    pullRequest(number: $number) {
+      reviewThreads(first: 100) {
+        nodes {
+          id
+          path
+          line
+          startLine
+          isResolved
+          isOutdated
+          comments(first: 50) {
+            nodes { author { login } body url createdAt commit { oid } }
+          }
+        }
+      }
+    }
+  }
+}
+"#;
+
 /// Newtype wrapper for invoking the `gh` command.
 #[derive(Debug, Clone, Default)]
 pub struct GhCli;
@@ -162,6 +189,26 @@ impl GhCli {
         Self::parse_pr_view(&raw)
     }
 
+    /// Change the base branch of a pull request.
+    pub fn edit_pr_base(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+        base_branch: &str,
+    ) -> Result<(), GhCliError> {
+        self.run([
+            "pr",
+            "edit",
+            &pr_number.to_string(),
+            "--repo",
+            &format!("{owner}/{repo}"),
+            "--base",
+            base_branch,
+        ])?;
+        Ok(())
+    }
+
     /// List pull requests for a branch (includes closed/merged).
     pub fn list_prs_for_branch(
         &self,
@@ -183,6 +230,68 @@ impl GhCli {
         ])?;
         Self::parse_pr_list(&raw)
     }
+
+    /// Check runs and commit statuses of a pull request's head commit.
+    pub fn pr_checks(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+    ) -> Result<PrChecks, GhCliError> {
+        let raw = self.run([
+            "pr",
+            "view",
+            &pr_number.to_string(),
+            "--repo",
+            &format!("{owner}/{repo}"),
+            "--json",
+            "headRefOid,statusCheckRollup",
+        ])?;
+        Self::parse_pr_checks(&raw)
+    }
+
+    /// Log output of the failed steps of a GitHub Actions job.
+    pub fn failed_job_log(
+        &self,
+        owner: &str,
+        repo: &str,
+        job_id: i64,
+    ) -> Result<String, GhCliError> {
+        self.run([
+            "run",
+            "view",
+            "--job",
+            &job_id.to_string(),
+            "--repo",
+            &format!("{owner}/{repo}"),
+            "--log-failed",
+        ])
+    }
+
+    /// Fetch the review threads of a pull request through the GraphQL API.
+    ///
+    /// Only the first 100 threads and 50 comments per thread are returned.
+    pub fn list_review_threads(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: i64,
+    ) -> Result<Vec<PrReviewThread>, GhCliError> {
+        let raw = self.run([
+            "api",
+            "graphql",
+            "-f",
+            &format!("query={REVIEW_THREADS_QUERY}"),
+            // -f passes owner and repo verbatim; -F would turn a numeric name into an integer
+            "-f",
+            &format!("owner={owner}"),
+            "-f",
+            &format!("repo={repo}"),
+            "-F",
+            &format!("number={pr_number}"),
+        ])?;
+        Self::parse_review_threads(&raw)
+    }
 }
 
 impl GhCli {
@@ -259,6 +368,137 @@ impl GhCli {
             .collect()
     }
 
+    fn parse_pr_checks(raw: &str) -> Result<PrChecks, GhCliError> {
+        let value: Value = serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse gh pr view response: {err}; raw: {raw}"
+            ))
+        })?;
+        let head_sha = value
+            .get("headRefOid")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let checks = value
+            .get("statusCheckRollup")
+            .and_then(Value::as_array)
+            .map(|items| items.iter().filter_map(Self::extract_check).collect())
+            .unwrap_or_default();
+        Ok(PrChecks::new(head_sha, checks))
+    }
+
+    /// Map a `statusCheckRollup` item, either a check run or a legacy commit status
+    fn extract_check(value: &Value) -> Option<CheckRun> {
+        let str_field = |key: &str| value.get(key).and_then(Value::as_str);
+        if str_field("__typename") == Some("StatusContext") {
+            let state = match str_field("state")? {
+                "SUCCESS" => CheckState::Success,
+                "FAILURE" | "ERROR" => CheckState::Failure,
+                _ => CheckState::Pending,
+            };
+            return Some(CheckRun {
+                name: str_field("context")?.to_string(),
+                state,
+                url: str_field("targetUrl").map(str::to_string),
+            });
+        }
+
+        let state = if str_field("status") != Some("COMPLETED") {
+            CheckState::Pending
+        } else {
+            match str_field("conclusion").unwrap_or_default() {
+                "SUCCESS" => CheckState::Success,
+                "FAILURE" | "TIMED_OUT" | "STARTUP_FAILURE" | "ACTION_REQUIRED" => {
+                    CheckState::Failure
+                }
+                _ => CheckState::Neutral,
+            }
+        };
+        Some(CheckRun {
+            name: str_field("name")?.to_string(),
+            state,
+            url: str_field("detailsUrl").map(str::to_string),
+        })
+    }
+
+    fn parse_review_threads(raw: &str) -> Result<Vec<PrReviewThread>, GhCliError> {
+        let value: Value = serde_json::from_str(raw.trim()).map_err(|err| {
+            GhCliError::UnexpectedOutput(format!(
+                "Failed to parse gh api graphql response: {err}; raw: {raw}"
+            ))
+        })?;
+        let nodes = value
+            .pointer("/data/repository/pullRequest/reviewThreads/nodes")
+            .and_then(Value::as_array)
+            .ok_or_else(|| {
+                GhCliError::UnexpectedOutput(format!(
+                    "gh api graphql response missing review threads: {value:#?}"
+                ))
+            })?;
+        nodes
+            .iter()
+            .map(|node| {
+                Self::extract_review_thread(node).ok_or_else(|| {
+                    GhCliError::UnexpectedOutput(format!(
+                        "review thread missing required fields: {node:#?}"
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    fn extract_review_thread(value: &Value) -> Option<PrReviewThread> {
+        let comments = value
+            .pointer("/comments/nodes")
+            .and_then(Value::as_array)
+            .map(|nodes| {
+                nodes
+                    .iter()
+                    .map(|c| PrReviewComment {
+                        author: c
+                            .pointer("/author/login")
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                        body: c
+                            .get("body")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string(),
+                        url: c
+                            .get("url")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string(),
+                        created_at: c
+                            .get("createdAt")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(PrReviewThread {
+            // The thread's line numbers refer to the commit its comments are anchored to
+            commit_oid: value
+                .pointer("/comments/nodes/0/commit/oid")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            id: value.get("id")?.as_str()?.to_string(),
+            path: value.get("path")?.as_str()?.to_string(),
+            line: value.get("line").and_then(Value::as_i64),
+            start_line: value.get("startLine").and_then(Value::as_i64),
+            is_resolved: value
+                .get("isResolved")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            is_outdated: value
+                .get("isOutdated")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            comments,
+        })
+    }
+
     fn extract_pr_info(value: &Value) -> Option<PullRequestInfo> {
         let number = value.get("number")?.as_i64()?;
         let url = value.get("url")?.as_str()?.to_string();
@@ -291,3 +531,55 @@ impl GhCli {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_review_threads() {
+        let raw = r#"{"data":{"repository":{"pullRequest":{"reviewThreads":{"nodes":[
+            {
+                "id": "PRRT_1", "path": "src/lib.rs", "line": 12, "startLine": 10,
+                "isResolved": false, "isOutdated": false,
+                "comments": {"nodes": [
+                    {"author": {"login": "alice"}, "body": "Rename this", "url": "u1",
+                     "createdAt": "2025-11-20T10:00:00Z", "commit": {"oid": "abc123"}},
+                    {"author": null, "body": "Agreed", "url": "u2",
+                     "createdAt": "2025-11-20T11:00:00Z", "commit": {"oid": "def456"}}
+                ]}
+            },
+            {
+                "id": "PRRT_2", "path": "README.md", "line": null, "startLine": null,
+                "isResolved": true, "isOutdated": true, "comments": {"nodes": []}
+            }
+        ]}}}}}"#;
+
+        let threads = GhCli::parse_review_threads(raw).unwrap();
+        assert_eq!(threads.len(), 2);
+
+        let first = &threads[0];
+        assert_eq!(first.id, "PRRT_1");
+        assert_eq!((first.start_line, first.line), (Some(10), Some(12)));
+        assert_eq!(first.commit_oid.as_deref(), Some("abc123"));
+        assert_eq!(first.comments[0].author.as_deref(), Some("alice"));
+        assert_eq!(first.comments[1].author, None);
+        assert_eq!(first.comments[1].body, "Agreed");
+
+        let second = &threads[1];
+        assert!(second.is_resolved && second.is_outdated);
+        assert_eq!((second.line, second.commit_oid.as_deref()), (None, None));
+        assert!(second.comments.is_empty());
+
+        assert!(matches!(
+            GhCli::parse_review_threads(r#"{"data":{"repository":null}}"#),
+            Err(GhCliError::UnexpectedOutput(_))
+        ));
+        assert!(matches!(
+            GhCli::parse_review_threads(
+                r#"{"data":{"repository":{"pullRequest":{"reviewThreads":{"nodes":[{"path":"a"}]}}}}}"#
+            ),
+            Err(GhCliError::UnexpectedOutput(_))
+        ));
+    }
+}
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
 
 use chrono::{DateTime, Utc};
 use git2::{
@@ -54,6 +57,28 @@ pub enum ConflictOp {
     Revert,
 }
 
+impl ConflictOp {
+    /// The ref git keeps for the commit being applied by this operation
+    fn incoming_ref(&self) -> &'static str {
+        match self {
+            ConflictOp::Rebase => "REBASE_HEAD",
+            ConflictOp::Merge => "MERGE_HEAD",
+            ConflictOp::CherryPick => "CHERRY_PICK_HEAD",
+            ConflictOp::Revert => "REVERT_HEAD",
+        }
+    }
+}
+
+/// Commit subjects on both sides of an in-progress conflicting operation, newest first
+#[derive(Debug, Clone, Default)]
+pub struct ConflictSides {
+    /// Commits on HEAD since the merge base
+    pub ours: Vec<String>,
+    /// Commits being merged since the merge base, or the single commit being replayed,
+    /// cherry-picked or reverted
+    pub theirs: Vec<String>,
+}
+
 #[derive(Debug, Serialize, TS)]
 pub struct GitBranch {
     pub name: String,
@@ -858,6 +883,229 @@ impl GitService {
             }
         }
     }
+
+    /// Move the base branch forward to the task branch, keeping the task's commits.
+    /// Fails with `BranchesDiverged` unless the task branch contains the base branch.
+    pub fn fast_forward_changes(
+        &self,
+        base_worktree_path: &Path,
+        task_branch_name: &str,
+        base_branch_name: &str,
+    ) -> Result<String, GitServiceError> {
+        let (_, task_behind) =
+            self.get_branch_status(base_worktree_path, task_branch_name, base_branch_name)?;
+        if task_behind > 0 {
+            return Err(GitServiceError::BranchesDiverged(format!(
+                "Cannot fast-forward: base branch '{base_branch_name}' is {task_behind} commits ahead of task branch '{task_branch_name}'.",
+            )));
+        }
+
+        match self.find_checkout_path_for_branch(base_worktree_path, base_branch_name)? {
+            Some(base_checkout_path) => {
+                // The checkout's working tree has to move along with the branch
+                let git_cli = GitCli::new();
+                if git_cli
+                    .has_staged_changes(&base_checkout_path)
+                    .map_err(|e| {
+                        GitServiceError::InvalidRepository(format!("git diff --cached failed: {e}"))
+                    })?
+                {
+                    return Err(GitServiceError::WorktreeDirty(
+                        base_branch_name.to_string(),
+                        "staged changes present".to_string(),
+                    ));
+                }
+                git_cli
+                    .merge_ff_only(&base_checkout_path, base_branch_name, task_branch_name)
+                    .map_err(|e| {
+                        GitServiceError::InvalidRepository(format!("CLI fast-forward failed: {e}"))
+                    })
+            }
+            None => {
+                let repo = self.open_repo(base_worktree_path)?;
+                let task_commit = Self::find_branch(&repo, task_branch_name)?
+                    .get()
+                    .peel_to_commit()?;
+                let mut base_ref = Self::find_branch(&repo, base_branch_name)?.into_reference();
+                base_ref.set_target(
+                    task_commit.id(),
+                    &format!("Fast-forward to {task_branch_name}"),
+                )?;
+                Ok(task_commit.id().to_string())
+            }
+        }
+    }
+
+    /// Merge the task branch into the base branch with a merge commit, keeping the
+    /// task's commits. The branches may have diverged as long as they merge cleanly.
+    pub fn merge_commit_changes(
+        &self,
+        base_worktree_path: &Path,
+        task_branch_name: &str,
+        base_branch_name: &str,
+        commit_message: &str,
+    ) -> Result<String, GitServiceError> {
+        match self.find_checkout_path_for_branch(base_worktree_path, base_branch_name)? {
+            Some(base_checkout_path) => {
+                let git_cli = GitCli::new();
+                if git_cli
+                    .has_staged_changes(&base_checkout_path)
+                    .map_err(|e| {
+                        GitServiceError::InvalidRepository(format!("git diff --cached failed: {e}"))
+                    })?
+                {
+                    return Err(GitServiceError::WorktreeDirty(
+                        base_branch_name.to_string(),
+                        "staged changes present".to_string(),
+                    ));
+                }
+                self.ensure_cli_commit_identity(&base_checkout_path)?;
+                git_cli
+                    .merge_no_ff_commit(
+                        &base_checkout_path,
+                        base_branch_name,
+                        task_branch_name,
+                        commit_message,
+                    )
+                    .map_err(|e| {
+                        GitServiceError::InvalidRepository(format!("CLI merge failed: {e}"))
+                    })
+            }
+            None => {
+                let repo = self.open_repo(base_worktree_path)?;
+                let base_commit = Self::find_branch(&repo, base_branch_name)?
+                    .get()
+                    .peel_to_commit()?;
+                let task_commit = Self::find_branch(&repo, task_branch_name)?
+                    .get()
+                    .peel_to_commit()?;
+
+                let mut merge_opts = git2::MergeOptions::new();
+                merge_opts.find_renames(true);
+                merge_opts.fail_on_conflict(true);
+                let mut index =
+                    repo.merge_commits(&base_commit, &task_commit, Some(&merge_opts))?;
+                if index.has_conflicts() {
+                    return Err(GitServiceError::MergeConflicts(
+                        "Merge failed due to conflicts. Please resolve conflicts manually."
+                            .to_string(),
+                    ));
+                }
+                let tree = repo.find_tree(index.write_tree_to(&repo)?)?;
+                let signature = self.signature_with_fallback(&repo)?;
+                let merge_commit_id = repo.commit(
+                    None,
+                    &signature,
+                    &signature,
+                    commit_message,
+                    &tree,
+                    &[&base_commit, &task_commit],
+                )?;
+                repo.reference(
+                    &format!("refs/heads/{base_branch_name}"),
+                    merge_commit_id,
+                    true,
+                    "Merge commit",
+                )?;
+                Ok(merge_commit_id.to_string())
+            }
+        }
+    }
+
+    /// Fail with `MergeConflicts` if merging the task branch into the base branch would
+    /// conflict. Nothing is written to the repository.
+    pub fn check_merge_clean(
+        &self,
+        repo_path: &Path,
+        task_branch_name: &str,
+        base_branch_name: &str,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let base_commit = Self::find_branch(&repo, base_branch_name)?
+            .get()
+            .peel_to_commit()?;
+        let task_commit = Self::find_branch(&repo, task_branch_name)?
+            .get()
+            .peel_to_commit()?;
+
+        let mut merge_opts = git2::MergeOptions::new();
+        merge_opts.find_renames(true);
+        let index = repo.merge_commits(&base_commit, &task_commit, Some(&merge_opts))?;
+        if index.has_conflicts() {
+            return Err(GitServiceError::MergeConflicts(format!(
+                "'{task_branch_name}' conflicts with '{base_branch_name}'"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Point a branch back at `oid`, e.g. to undo a merge. A checkout of the branch moves
+    /// along, keeping its uncommitted changes.
+    pub fn reset_branch_to(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        oid: &str,
+    ) -> Result<(), GitServiceError> {
+        let git_cli = GitCli::new();
+        match self.find_checkout_path_for_branch(repo_path, branch_name)? {
+            Some(checkout_path) => {
+                git_cli.git(&checkout_path, ["reset", "--keep", oid])?;
+            }
+            None => {
+                git_cli.update_ref(repo_path, &format!("refs/heads/{branch_name}"), oid)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Distinct authors of the task branch's commits missing from the base branch, as
+    /// `Name <email>` in the order they first committed, leaving out the identity
+    /// merges are committed as
+    pub fn get_co_authors(
+        &self,
+        repo_path: &Path,
+        task_branch_name: &str,
+        base_branch_name: &str,
+    ) -> Result<Vec<String>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let committer_email = self
+            .signature_with_fallback(&repo)?
+            .email()
+            .map(|e| e.to_lowercase());
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+        revwalk.push(
+            Self::find_branch(&repo, task_branch_name)?
+                .get()
+                .peel_to_commit()?
+                .id(),
+        )?;
+        revwalk.hide(
+            Self::find_branch(&repo, base_branch_name)?
+                .get()
+                .peel_to_commit()?
+                .id(),
+        )?;
+
+        let mut seen = HashSet::new();
+        let mut authors = Vec::new();
+        for oid in revwalk {
+            let commit = repo.find_commit(oid?)?;
+            let author = commit.author();
+            let (Some(name), Some(email)) = (author.name(), author.email()) else {
+                continue;
+            };
+            let email_key = email.to_lowercase();
+            if committer_email.as_deref() == Some(email_key.as_str()) || !seen.insert(email_key) {
+                continue;
+            }
+            authors.push(format!("{name} <{email}>"));
+        }
+        Ok(authors)
+    }
+
     fn get_branch_status_inner(
         &self,
         repo: &Repository,
@@ -1062,6 +1310,30 @@ impl GitService {
         Ok(commit.summary().unwrap_or("(no subject)").to_string())
     }
 
+    /// Contents of the file at `path` in the given commit, `None` if the commit or file
+    /// is not in the repository or the file is not UTF-8 text
+    pub fn get_file_at_commit(
+        &self,
+        repo_path: &Path,
+        commit_sha: &str,
+        path: &str,
+    ) -> Result<Option<String>, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let Ok(oid) = git2::Oid::from_str(commit_sha) else {
+            return Ok(None);
+        };
+        let Ok(commit) = repo.find_commit(oid) else {
+            return Ok(None);
+        };
+        let Ok(entry) = commit.tree()?.get_path(Path::new(path)) else {
+            return Ok(None);
+        };
+        let Ok(blob) = entry.to_object(&repo)?.peel_to_blob() else {
+            return Ok(None);
+        };
+        Ok(std::str::from_utf8(blob.content()).ok().map(str::to_string))
+    }
+
     /// Compare two OIDs and return (ahead, behind) counts: how many commits
     /// `from_oid` is ahead of and behind `to_oid`.
     pub fn ahead_behind_commits_by_oid(
@@ -1200,6 +1472,43 @@ impl GitService {
         Ok(())
     }
 
+    /// Delete a local branch, e.g. of an attempt that was thrown away. A branch that does
+    /// not exist is not an error.
+    pub fn delete_branch(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        match repo.find_branch(branch_name, BranchType::Local) {
+            Ok(mut branch) => Ok(branch.delete()?),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Create a local branch at `oid`, e.g. to start an attempt from an earlier commit
+    pub fn create_branch_at(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+        oid: &str,
+    ) -> Result<(), GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        let commit = repo.find_commit(git2::Oid::from_str(oid)?)?;
+        repo.branch(branch_name, &commit, false)?;
+        Ok(())
+    }
+
+    pub fn local_branch_exists(
+        &self,
+        repo_path: &Path,
+        branch_name: &str,
+    ) -> Result<bool, GitServiceError> {
+        let repo = self.open_repo(repo_path)?;
+        Ok(repo.find_branch(branch_name, BranchType::Local).is_ok())
+    }
+
     /// Checkout a local branch in the given working tree
     pub fn checkout_branch(
         &self,
@@ -1562,6 +1871,84 @@ impl GitService {
         })
     }
 
+    /// Commit subjects on both sides of the in-progress `op`, at most `max` per side.
+    pub fn get_conflict_sides(
+        &self,
+        worktree_path: &Path,
+        op: &ConflictOp,
+        max: usize,
+    ) -> Result<ConflictSides, GitServiceError> {
+        let git = GitCli::new();
+        let incoming = op.incoming_ref();
+        let merge_base = git.merge_base(worktree_path, "HEAD", incoming)?;
+        let theirs = match op {
+            ConflictOp::Merge => {
+                git.log_subjects(worktree_path, &format!("{merge_base}..{incoming}"), max)?
+            }
+            ConflictOp::Rebase | ConflictOp::CherryPick | ConflictOp::Revert => {
+                git.log_subjects(worktree_path, incoming, 1)?
+            }
+        };
+        let ours = git.log_subjects(worktree_path, &format!("{merge_base}..HEAD"), max)?;
+        Ok(ConflictSides { ours, theirs })
+    }
+
+    /// Stage the resolution of an in-progress rebase, merge, cherry-pick or revert and
+    /// continue it (no-op if none). While any of `resolved_files` or the still conflicted
+    /// files contains conflict markers, nothing is staged and the operation is left as is.
+    /// Conflicts in a later step of the operation are returned as `MergeConflicts` with
+    /// the operation still in progress.
+    pub fn continue_conflicts(
+        &self,
+        worktree_path: &Path,
+        resolved_files: &[String],
+    ) -> Result<(), GitServiceError> {
+        let Some(op) = self.detect_conflict_op(worktree_path)? else {
+            return Ok(());
+        };
+
+        let mut files = self.get_conflicted_files(worktree_path)?;
+        for file in resolved_files {
+            if !files.contains(file) {
+                files.push(file.clone());
+            }
+        }
+        // Deleted and binary files have no markers to look for
+        let unresolved: Vec<String> = files
+            .into_iter()
+            .filter(|file| {
+                std::fs::read_to_string(worktree_path.join(file))
+                    .is_ok_and(|content| has_conflict_markers(&content))
+            })
+            .collect();
+        if !unresolved.is_empty() {
+            return Err(GitServiceError::MergeConflicts(format!(
+                "Conflict markers remain in: {}",
+                unresolved.join(", ")
+            )));
+        }
+
+        let git = GitCli::new();
+        git.add_all(worktree_path)
+            .map_err(|e| GitServiceError::InvalidRepository(format!("git add failed: {e}")))?;
+        self.ensure_cli_commit_identity(worktree_path)?;
+        match git.continue_conflict_op(worktree_path, &op) {
+            Ok(()) => Ok(()),
+            Err(GitCliError::CommandFailed(stderr))
+                if stderr.contains("CONFLICT") || stderr.contains("could not apply") =>
+            {
+                let conflicts = git.get_conflicted_files(worktree_path).unwrap_or_default();
+                Err(GitServiceError::MergeConflicts(format!(
+                    "Continuing stopped at new conflicts in: {}",
+                    conflicts.join(", ")
+                )))
+            }
+            Err(e) => Err(GitServiceError::InvalidRepository(format!(
+                "Continuing the conflicted operation failed: {e}"
+            ))),
+        }
+    }
+
     /// Abort an in-progress rebase in this worktree (no-op if none).
     pub fn abort_rebase(&self, worktree_path: &Path) -> Result<(), GitServiceError> {
         let git = GitCli::new();
@@ -1687,21 +2074,27 @@ impl GitService {
         }
     }
 
-    /// Extract GitHub owner and repo name from git repo path
-    pub fn get_github_repo_info(
-        &self,
-        repo_path: &Path,
-    ) -> Result<GitHubRepoInfo, GitServiceError> {
+    /// URL of the repository's default remote
+    pub fn get_remote_url(&self, repo_path: &Path) -> Result<String, GitServiceError> {
         let repo = self.open_repo(repo_path)?;
         let remote_name = self.default_remote_name(&repo);
         let remote = repo.find_remote(&remote_name).map_err(|_| {
             GitServiceError::InvalidRepository(format!("No '{remote_name}' remote found"))
         })?;
 
-        let url = remote
+        remote
             .url()
-            .ok_or_else(|| GitServiceError::InvalidRepository("Remote has no URL".to_string()))?;
-        GitHubRepoInfo::from_remote_url(url).map_err(|e| {
+            .map(str::to_string)
+            .ok_or_else(|| GitServiceError::InvalidRepository("Remote has no URL".to_string()))
+    }
+
+    /// Extract GitHub owner and repo name from git repo path
+    pub fn get_github_repo_info(
+        &self,
+        repo_path: &Path,
+    ) -> Result<GitHubRepoInfo, GitServiceError> {
+        let url = self.get_remote_url(repo_path)?;
+        GitHubRepoInfo::from_remote_url(&url).map_err(|e| {
             GitServiceError::InvalidRepository(format!("Failed to parse remote URL: {e}"))
         })
     }
@@ -1764,7 +2157,51 @@ impl GitService {
             return Err(e.into());
         }
 
-        let mut branch = Self::find_branch(&repo, branch_name)?;
+        Self::update_remote_tracking_branch(&repo, &remote_name, branch_name)
+    }
+
+    /// Force-push a branch whose history was rewritten (e.g. restacked onto a new
+    /// base). The push is refused if the remote branch moved since we last pushed
+    /// or fetched it, so commits pushed by someone else are never overwritten.
+    pub fn force_push_with_lease(
+        &self,
+        worktree_path: &Path,
+        branch_name: &str,
+    ) -> Result<(), GitServiceError> {
+        let repo = Repository::open(worktree_path)?;
+        self.check_worktree_clean(&repo)?;
+
+        let remote_name = self.default_remote_name(&repo);
+        let remote = repo.find_remote(&remote_name)?;
+        let remote_url = remote
+            .url()
+            .ok_or_else(|| GitServiceError::InvalidRepository("Remote has no URL".to_string()))?;
+
+        // Without a remote-tracking ref we have never seen the remote branch, so
+        // there is nothing to lease against and it must not exist yet
+        let expected_oid = repo
+            .find_reference(&format!("refs/remotes/{remote_name}/{branch_name}"))
+            .ok()
+            .and_then(|r| r.target())
+            .map(|oid| oid.to_string())
+            .unwrap_or_default();
+        let git_cli = GitCli::new();
+        if let Err(e) =
+            git_cli.force_push_with_lease(worktree_path, remote_url, branch_name, &expected_oid)
+        {
+            tracing::error!("Force push of {} failed: {}", branch_name, e);
+            return Err(e.into());
+        }
+
+        Self::update_remote_tracking_branch(&repo, &remote_name, branch_name)
+    }
+
+    fn update_remote_tracking_branch(
+        repo: &Repository,
+        remote_name: &str,
+        branch_name: &str,
+    ) -> Result<(), GitServiceError> {
+        let mut branch = Self::find_branch(repo, branch_name)?;
         if !branch.get().is_remote() {
             if let Some(branch_target) = branch.get().target() {
                 let remote_ref = format!("refs/remotes/{remote_name}/{branch_name}");
@@ -1962,6 +2399,13 @@ impl GitService {
     }
 }
 
+/// Whether `content` still contains the markers git writes around conflicting hunks
+fn has_conflict_markers(content: &str) -> bool {
+    content
+        .lines()
+        .any(|line| line.starts_with("<<<<<<< ") || line.starts_with(">>>>>>> "))
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use tempfile::TempDir;
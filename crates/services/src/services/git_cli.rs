@@ -24,7 +24,7 @@ use std::{
 use thiserror::Error;
 use utils::shell::resolve_executable_path_blocking; // TODO: make GitCli async
 
-use crate::services::git::Commit;
+use crate::services::git::{Commit, ConflictOp};
 
 #[derive(Debug, Error)]
 pub enum GitCliError {
@@ -325,15 +325,42 @@ impl GitCli {
         repo_path: &Path,
         remote_url: &str,
         branch: &str,
+    ) -> Result<(), GitCliError> {
+        self.push_branch(repo_path, remote_url, branch, None)
+    }
+
+    /// Force-push a rewritten branch, refusing if the remote branch is no longer at
+    /// `expected_oid` (someone else pushed to it in the meantime).
+    pub fn force_push_with_lease(
+        &self,
+        repo_path: &Path,
+        remote_url: &str,
+        branch: &str,
+        expected_oid: &str,
+    ) -> Result<(), GitCliError> {
+        self.push_branch(repo_path, remote_url, branch, Some(expected_oid))
+    }
+
+    fn push_branch(
+        &self,
+        repo_path: &Path,
+        remote_url: &str,
+        branch: &str,
+        lease: Option<&str>,
     ) -> Result<(), GitCliError> {
         let refspec = format!("refs/heads/{branch}:refs/heads/{branch}");
         let envs = vec![(OsString::from("GIT_TERMINAL_PROMPT"), OsString::from("0"))];
 
-        let args = [
-            OsString::from("push"),
-            OsString::from(remote_url),
-            OsString::from(refspec),
-        ];
+        let mut args = vec![OsString::from("push")];
+        // Pushing to a URL has no remote-tracking ref to compare against, so the
+        // expected value is always given explicitly
+        if let Some(expected_oid) = lease {
+            args.push(OsString::from(format!(
+                "--force-with-lease=refs/heads/{branch}:{expected_oid}"
+            )));
+        }
+        args.push(OsString::from(remote_url));
+        args.push(OsString::from(refspec));
 
         match self.git_with_env(repo_path, args, &envs) {
             Ok(_) => Ok(()),
@@ -390,7 +417,12 @@ impl GitCli {
 
     /// Return the merge base commit sha of two refs in the given worktree.
     /// If `git merge-base --fork-point` fails, falls back to regular `merge-base`.
-    fn merge_base(&self, worktree_path: &Path, a: &str, b: &str) -> Result<String, GitCliError> {
+    pub fn merge_base(
+        &self,
+        worktree_path: &Path,
+        a: &str,
+        b: &str,
+    ) -> Result<String, GitCliError> {
         let out = self
             .git(worktree_path, ["merge-base", "--fork-point", a, b])
             .unwrap_or(self.git(worktree_path, ["merge-base", a, b])?);
@@ -526,6 +558,44 @@ impl GitCli {
         Ok(sha)
     }
 
+    /// Checkout base branch and fast-forward it to from_branch. Returns new HEAD sha.
+    pub fn merge_ff_only(
+        &self,
+        repo_path: &Path,
+        base_branch: &str,
+        from_branch: &str,
+    ) -> Result<String, GitCliError> {
+        self.git(repo_path, ["checkout", base_branch]).map(|_| ())?;
+        self.git(repo_path, ["merge", "--ff-only", from_branch])
+            .map(|_| ())?;
+        let sha = self
+            .git(repo_path, ["rev-parse", "HEAD"])?
+            .trim()
+            .to_string();
+        Ok(sha)
+    }
+
+    /// Checkout base branch and merge from_branch with a merge commit. A merge that
+    /// stops at conflicts is aborted. Returns new HEAD sha.
+    pub fn merge_no_ff_commit(
+        &self,
+        repo_path: &Path,
+        base_branch: &str,
+        from_branch: &str,
+        message: &str,
+    ) -> Result<String, GitCliError> {
+        self.git(repo_path, ["checkout", base_branch]).map(|_| ())?;
+        if let Err(e) = self.git(repo_path, ["merge", "--no-ff", "-m", message, from_branch]) {
+            let _ = self.abort_merge(repo_path);
+            return Err(e);
+        }
+        let sha = self
+            .git(repo_path, ["rev-parse", "HEAD"])?
+            .trim()
+            .to_string();
+        Ok(sha)
+    }
+
     /// Update a ref to a specific sha in the repo.
     pub fn update_ref(
         &self,
@@ -572,6 +642,44 @@ impl GitCli {
         }
         Ok(files)
     }
+
+    /// Subjects of the commits in `range` (e.g. `base..HEAD`), newest first, at most `max`.
+    pub fn log_subjects(
+        &self,
+        worktree_path: &Path,
+        range: &str,
+        max: usize,
+    ) -> Result<Vec<String>, GitCliError> {
+        let max_count = format!("--max-count={max}");
+        let out = self.git(
+            worktree_path,
+            ["log", "--format=%s", max_count.as_str(), range],
+        )?;
+        Ok(out
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Continue the in-progress `op` once its conflicts are resolved and staged.
+    /// The editor is disabled so the commit message git prepared is used as is.
+    pub fn continue_conflict_op(
+        &self,
+        worktree_path: &Path,
+        op: &ConflictOp,
+    ) -> Result<(), GitCliError> {
+        let subcommand = match op {
+            ConflictOp::Rebase => "rebase",
+            ConflictOp::Merge => "merge",
+            ConflictOp::CherryPick => "cherry-pick",
+            ConflictOp::Revert => "revert",
+        };
+        let envs = vec![(OsString::from("GIT_EDITOR"), OsString::from("true"))];
+        self.git_with_env(worktree_path, [subcommand, "--continue"], &envs)
+            .map(|_| ())
+    }
 }
 
 // Private methods
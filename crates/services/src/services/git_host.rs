@@ -0,0 +1,321 @@
+//! Git hosting providers behind a single interface.
+//!
+//! PR creation, attaching existing PRs and the PR monitor talk to a [`GitHost`]
+//! resolved from the repository's remote URL. github.com goes through the GitHub
+//! CLI, GitLab and Gitea through their REST APIs. gitlab.com and codeberg.org are
+//! recognised by name; self-hosted instances only when listed in the `git_hosts` config,
+//! so `GITLAB_TOKEN` / `GITEA_TOKEN` are never sent to a host the user did not name.
+
+pub mod gitea;
+pub mod github;
+pub mod gitlab;
+
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+use backon::{ExponentialBuilder, Retryable};
+use db::models::{
+    merge::PullRequestInfo,
+    pr_check_summary::{CheckRun, PrChecks},
+};
+use executors::env::expand_secrets;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ts_rs::TS;
+
+use crate::services::{
+    config::GitHostConfig,
+    github_service::{CreatePrRequest, GitHubServiceError},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum GitHostKind {
+    #[serde(rename = "github")]
+    GitHub,
+    #[serde(rename = "gitlab")]
+    GitLab,
+    Gitea,
+}
+
+impl GitHostKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GitHostKind::GitHub => "github",
+            GitHostKind::GitLab => "gitlab",
+            GitHostKind::Gitea => "gitea",
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GitHostError {
+    #[error(transparent)]
+    GitHub(#[from] GitHubServiceError),
+    #[error("Unsupported git remote: {0}")]
+    UnsupportedRemote(String),
+    #[error("Token for git host {host} could not be resolved: {message}")]
+    Token { host: String, message: String },
+    #[error("{host} request failed: {source}")]
+    Request {
+        host: &'static str,
+        source: reqwest::Error,
+    },
+    #[error("{host} API returned {status}: {message}")]
+    Api {
+        host: &'static str,
+        status: u16,
+        message: String,
+    },
+}
+
+impl GitHostError {
+    pub fn should_retry(&self) -> bool {
+        match self {
+            GitHostError::GitHub(e) => e.should_retry(),
+            GitHostError::Request { .. } => true,
+            GitHostError::Api { status, .. } => *status >= 500,
+            GitHostError::UnsupportedRemote(_) | GitHostError::Token { .. } => false,
+        }
+    }
+}
+
+/// A repository on a git host, able to manage its pull/merge requests
+#[async_trait]
+pub trait GitHost: Send + Sync {
+    fn kind(&self) -> GitHostKind;
+
+    /// Open a pull request (merge request on GitLab)
+    async fn create_pr(&self, request: &CreatePrRequest) -> Result<PullRequestInfo, GitHostError>;
+
+    /// Fetch the current state of a pull request by its number
+    async fn pr_status(&self, number: i64) -> Result<PullRequestInfo, GitHostError>;
+
+    /// Point an open pull request at a different base (target) branch
+    async fn update_pr_base(&self, number: i64, base_branch: &str) -> Result<(), GitHostError>;
+
+    /// List all pull requests whose source is `branch`, including closed and merged
+    async fn list_prs_for_branch(&self, branch: &str)
+    -> Result<Vec<PullRequestInfo>, GitHostError>;
+
+    /// Fetch the CI check runs and commit statuses of a pull request's head commit
+    async fn pr_checks(&self, number: i64) -> Result<PrChecks, GitHostError>;
+
+    /// The log of a failed check, for hosts that expose CI job logs
+    async fn failed_check_log(&self, _check: &CheckRun) -> Result<Option<String>, GitHostError> {
+        Ok(None)
+    }
+}
+
+/// The parts of a remote or pull request URL needed to reach a repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteUrl {
+    /// `https` unless the remote itself is plain `http`
+    pub scheme: String,
+    /// Hostname, with the port for http(s) remotes on a non-default port
+    pub host: String,
+    /// Path segments after the host, without `.git`
+    pub segments: Vec<String>,
+}
+
+impl RemoteUrl {
+    /// Parse HTTPS, `ssh://` and scp-style (`git@host:owner/repo.git`) remotes as well
+    /// as web URLs of repositories and their pull requests
+    pub fn parse(remote_url: &str) -> Result<Self, GitHostError> {
+        let unsupported = || GitHostError::UnsupportedRemote(remote_url.to_string());
+        let remote_url = remote_url.trim();
+
+        let (scheme, host, path) = if let Ok(url) = url::Url::parse(remote_url)
+            && let Some(host) = url.host_str()
+        {
+            let scheme = if url.scheme() == "http" {
+                "http"
+            } else {
+                "https"
+            };
+            // SSH ports say nothing about where the API lives
+            let host = match url.port() {
+                Some(port) if matches!(url.scheme(), "http" | "https") => {
+                    format!("{host}:{port}")
+                }
+                _ => host.to_string(),
+            };
+            (scheme.to_string(), host, url.path().to_string())
+        } else {
+            // scp-like syntax: [user@]host:path
+            let (host, path) = remote_url.split_once(':').ok_or_else(unsupported)?;
+            let host = host.rsplit('@').next().unwrap_or(host);
+            ("https".to_string(), host.to_string(), path.to_string())
+        };
+
+        let mut segments: Vec<String> = path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+        if let Some(last) = segments.last_mut()
+            && let Some(stripped) = last.strip_suffix(".git")
+        {
+            *last = stripped.to_string();
+        }
+        if host.is_empty() || segments.len() < 2 {
+            return Err(unsupported());
+        }
+
+        Ok(Self {
+            scheme,
+            host: host.to_ascii_lowercase(),
+            segments,
+        })
+    }
+
+    /// The hostname without any port
+    pub fn hostname(&self) -> &str {
+        self.host.split(':').next().unwrap_or(&self.host)
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("{}://{}", self.scheme, self.host)
+    }
+
+    /// Host and repository path identifying the repository across its remote, web and
+    /// pull request URLs, e.g. `github.com/owner/repo`
+    pub fn repo_key(&self) -> String {
+        let path = self
+            .segments
+            .iter()
+            .take_while(|s| !matches!(s.as_str(), "-" | "pull" | "pulls" | "merge_requests"))
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("/");
+        format!("{}/{}", self.host, path).to_ascii_lowercase()
+    }
+}
+
+/// Which kind of host serves a remote or pull request URL, if it is a supported one
+pub fn kind_for_remote_url(remote_url: &str, hosts: &[GitHostConfig]) -> Option<GitHostKind> {
+    RemoteUrl::parse(remote_url)
+        .ok()
+        .and_then(|remote| detect_kind(&remote, hosts))
+}
+
+/// Which kind of host serves `remote`: configured hosts first, then the public ones
+fn detect_kind(remote: &RemoteUrl, hosts: &[GitHostConfig]) -> Option<GitHostKind> {
+    find_config(remote, hosts)
+        .map(|config| config.kind)
+        .or_else(|| public_kind(remote))
+}
+
+/// The public instances recognised without any configuration
+fn public_kind(remote: &RemoteUrl) -> Option<GitHostKind> {
+    match remote.hostname() {
+        "github.com" => Some(GitHostKind::GitHub),
+        "gitlab.com" => Some(GitHostKind::GitLab),
+        "codeberg.org" => Some(GitHostKind::Gitea),
+        _ => None,
+    }
+}
+
+fn find_config<'a>(remote: &RemoteUrl, hosts: &'a [GitHostConfig]) -> Option<&'a GitHostConfig> {
+    hosts
+        .iter()
+        .find(|h| h.host.eq_ignore_ascii_case(&remote.host))
+        .or_else(|| {
+            hosts
+                .iter()
+                .find(|h| h.host.eq_ignore_ascii_case(remote.hostname()))
+        })
+}
+
+/// Resolve the git host for a repository remote or pull request URL. Configured tokens
+/// are `{{secret:NAME}}` references resolved against `secrets`.
+pub fn from_remote_url(
+    remote_url: &str,
+    hosts: &[GitHostConfig],
+    secrets: &HashMap<String, String>,
+) -> Result<Box<dyn GitHost>, GitHostError> {
+    let remote = RemoteUrl::parse(remote_url)?;
+    let config = find_config(&remote, hosts);
+    let kind = detect_kind(&remote, hosts)
+        .ok_or_else(|| GitHostError::UnsupportedRemote(remote_url.to_string()))?;
+    let api_url = config.and_then(|c| c.api_url.clone());
+    let configured_token = config
+        .and_then(|c| c.token.as_deref())
+        .map(|token| {
+            expand_secrets(token, secrets).map_err(|e| GitHostError::Token {
+                host: remote.host.clone(),
+                message: e.to_string(),
+            })
+        })
+        .transpose()?;
+    // Only configured and public hosts get this far, so the environment token is safe to send
+    let token = |env_var: &str| {
+        configured_token
+            .clone()
+            .or_else(|| std::env::var(env_var).ok())
+            .filter(|t| !t.is_empty())
+    };
+
+    Ok(match kind {
+        GitHostKind::GitHub => Box::new(github::GitHubHost::from_remote_url(remote_url)?),
+        GitHostKind::GitLab => {
+            let api_url = api_url.unwrap_or_else(|| format!("{}/api/v4", remote.base_url()));
+            Box::new(gitlab::GitLabHost::new(
+                api_url,
+                gitlab::project_path(&remote.segments),
+                token("GITLAB_TOKEN"),
+            ))
+        }
+        GitHostKind::Gitea => {
+            let api_url = api_url.unwrap_or_else(|| format!("{}/api/v1", remote.base_url()));
+            Box::new(gitea::GiteaHost::new(
+                api_url,
+                remote.segments[0].clone(),
+                remote.segments[1].clone(),
+                token("GITEA_TOKEN"),
+            ))
+        }
+    })
+}
+
+/// Retry transient failures the same way `GitHubService` does
+pub(crate) async fn with_retry<T, F, Fut>(host: &'static str, f: F) -> Result<T, GitHostError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, GitHostError>>,
+{
+    f.retry(
+        &ExponentialBuilder::default()
+            .with_min_delay(Duration::from_secs(1))
+            .with_max_delay(Duration::from_secs(30))
+            .with_max_times(3)
+            .with_jitter(),
+    )
+    .when(|e: &GitHostError| e.should_retry())
+    .notify(|err: &GitHostError, dur: Duration| {
+        tracing::warn!(
+            "{} API call failed, retrying after {:.2}s: {}",
+            host,
+            dur.as_secs_f64(),
+            err
+        );
+    })
+    .await
+}
+
+/// Turn a non-success response into `GitHostError::Api`
+pub(crate) async fn check_response(
+    host: &'static str,
+    response: reqwest::Response,
+) -> Result<reqwest::Response, GitHostError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let message = response.text().await.unwrap_or_default();
+    Err(GitHostError::Api {
+        host,
+        status: status.as_u16(),
+        message,
+    })
+}
@@ -0,0 +1,253 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use db::models::{
+    merge::{MergeStatus, PullRequestInfo},
+    pr_check_summary::{CheckRun, CheckState, PrChecks},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{GitHost, GitHostError, GitHostKind, check_response, with_retry};
+use crate::services::github_service::CreatePrRequest;
+
+const HOST: &str = "Gitea";
+
+/// Page size used when scanning pull requests for a branch
+const PAGE_LIMIT: usize = 50;
+/// Gitea cannot filter pull requests by head branch, so stop scanning after this many pages
+const MAX_PAGES: usize = 10;
+
+#[derive(Debug, Deserialize)]
+struct PullRequestBranch {
+    #[serde(rename = "ref")]
+    ref_name: String,
+    sha: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequest {
+    number: i64,
+    html_url: String,
+    state: String,
+    #[serde(default)]
+    merged: bool,
+    merged_at: Option<DateTime<Utc>>,
+    merge_commit_sha: Option<String>,
+    head: PullRequestBranch,
+}
+
+impl From<PullRequest> for PullRequestInfo {
+    fn from(pr: PullRequest) -> Self {
+        let status = match (pr.merged, pr.state.as_str()) {
+            (true, _) => MergeStatus::Merged,
+            (false, "open") => MergeStatus::Open,
+            (false, "closed") => MergeStatus::Closed,
+            _ => MergeStatus::Unknown,
+        };
+        PullRequestInfo {
+            number: pr.number,
+            url: pr.html_url,
+            status,
+            merged_at: pr.merged_at,
+            merge_commit_sha: pr.merge_commit_sha,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitStatus {
+    context: String,
+    status: String,
+    target_url: Option<String>,
+}
+
+impl From<CommitStatus> for CheckRun {
+    fn from(status: CommitStatus) -> Self {
+        let state = match status.status.as_str() {
+            "success" => CheckState::Success,
+            "failure" | "error" => CheckState::Failure,
+            "pending" => CheckState::Pending,
+            _ => CheckState::Neutral,
+        };
+        CheckRun {
+            name: status.context,
+            state,
+            url: status.target_url.filter(|u| !u.is_empty()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CombinedStatus {
+    statuses: Option<Vec<CommitStatus>>,
+}
+
+/// A repository on Gitea (or Forgejo), through the REST API v1
+pub struct GiteaHost {
+    client: reqwest::Client,
+    api_url: String,
+    owner: String,
+    repo: String,
+    token: Option<String>,
+}
+
+impl GiteaHost {
+    pub fn new(api_url: String, owner: String, repo: String, token: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            owner,
+            repo,
+            token,
+        }
+    }
+
+    fn pulls_url(&self) -> String {
+        format!("{}/repos/{}/{}/pulls", self.api_url, self.owner, self.repo)
+    }
+
+    fn request(&self, method: reqwest::Method, url: String) -> reqwest::RequestBuilder {
+        let builder = self.client.request(method, url);
+        match &self.token {
+            Some(token) => builder.header("Authorization", format!("token {token}")),
+            None => builder,
+        }
+    }
+
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        builder: reqwest::RequestBuilder,
+    ) -> Result<T, GitHostError> {
+        let response = builder
+            .send()
+            .await
+            .map_err(|source| GitHostError::Request { host: HOST, source })?;
+        check_response(HOST, response)
+            .await?
+            .json::<T>()
+            .await
+            .map_err(|source| GitHostError::Request { host: HOST, source })
+    }
+}
+
+#[async_trait]
+impl GitHost for GiteaHost {
+    fn kind(&self) -> GitHostKind {
+        GitHostKind::Gitea
+    }
+
+    async fn create_pr(&self, request: &CreatePrRequest) -> Result<PullRequestInfo, GitHostError> {
+        let body = json!({
+            "head": request.head_branch,
+            "base": request.base_branch,
+            "title": request.title,
+            "body": request.body.clone().unwrap_or_default(),
+        });
+        let pr: PullRequest = with_retry(HOST, || async {
+            self.send(
+                self.request(reqwest::Method::POST, self.pulls_url())
+                    .json(&body),
+            )
+            .await
+        })
+        .await?;
+
+        tracing::info!(
+            "Created Gitea PR #{} for branch {} in {}/{}",
+            pr.number,
+            request.head_branch,
+            self.owner,
+            self.repo
+        );
+        Ok(pr.into())
+    }
+
+    async fn pr_status(&self, number: i64) -> Result<PullRequestInfo, GitHostError> {
+        let url = format!("{}/{number}", self.pulls_url());
+        let pr: PullRequest = with_retry(HOST, || async {
+            self.send(self.request(reqwest::Method::GET, url.clone()))
+                .await
+        })
+        .await?;
+        Ok(pr.into())
+    }
+
+    async fn update_pr_base(&self, number: i64, base_branch: &str) -> Result<(), GitHostError> {
+        let url = format!("{}/{number}", self.pulls_url());
+        let body = json!({ "base": base_branch });
+        let _: PullRequest = with_retry(HOST, || async {
+            self.send(
+                self.request(reqwest::Method::PATCH, url.clone())
+                    .json(&body),
+            )
+            .await
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn list_prs_for_branch(
+        &self,
+        branch: &str,
+    ) -> Result<Vec<PullRequestInfo>, GitHostError> {
+        let mut matching = Vec::new();
+        for page in 1..=MAX_PAGES {
+            let page_param = page.to_string();
+            let limit_param = PAGE_LIMIT.to_string();
+            let prs: Vec<PullRequest> = with_retry(HOST, || async {
+                self.send(
+                    self.request(reqwest::Method::GET, self.pulls_url())
+                        .query(&[
+                            ("state", "all"),
+                            ("page", page_param.as_str()),
+                            ("limit", limit_param.as_str()),
+                        ]),
+                )
+                .await
+            })
+            .await?;
+            let last_page = prs.len() < PAGE_LIMIT;
+            matching.extend(
+                prs.into_iter()
+                    .filter(|pr| pr.head.ref_name == branch)
+                    .map(PullRequestInfo::from),
+            );
+            if last_page {
+                break;
+            }
+        }
+        Ok(matching)
+    }
+
+    async fn pr_checks(&self, number: i64) -> Result<PrChecks, GitHostError> {
+        let url = format!("{}/{number}", self.pulls_url());
+        let pr: PullRequest = with_retry(HOST, || async {
+            self.send(self.request(reqwest::Method::GET, url.clone()))
+                .await
+        })
+        .await?;
+        let Some(sha) = pr.head.sha else {
+            return Ok(PrChecks::new(None, Vec::new()));
+        };
+
+        // Gitea Actions and external CI both report through commit statuses
+        let status_url = format!(
+            "{}/repos/{}/{}/commits/{sha}/status",
+            self.api_url, self.owner, self.repo
+        );
+        let combined: CombinedStatus = with_retry(HOST, || async {
+            self.send(self.request(reqwest::Method::GET, status_url.clone()))
+                .await
+        })
+        .await?;
+        Ok(PrChecks::new(
+            Some(sha),
+            combined
+                .statuses
+                .unwrap_or_default()
+                .into_iter()
+                .map(CheckRun::from)
+                .collect(),
+        ))
+    }
+}
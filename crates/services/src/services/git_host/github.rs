@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use db::models::{
+    merge::PullRequestInfo,
+    pr_check_summary::{CheckRun, PrChecks},
+};
+
+use super::{GitHost, GitHostError, GitHostKind};
+use crate::services::github_service::{CreatePrRequest, GitHubRepoInfo, GitHubService};
+
+/// github.com, through the GitHub CLI
+pub struct GitHubHost {
+    service: GitHubService,
+    repo_info: GitHubRepoInfo,
+}
+
+impl GitHubHost {
+    pub fn from_remote_url(remote_url: &str) -> Result<Self, GitHostError> {
+        Ok(Self {
+            service: GitHubService::new()?,
+            repo_info: GitHubRepoInfo::from_remote_url(remote_url)?,
+        })
+    }
+}
+
+#[async_trait]
+impl GitHost for GitHubHost {
+    fn kind(&self) -> GitHostKind {
+        GitHostKind::GitHub
+    }
+
+    async fn create_pr(&self, request: &CreatePrRequest) -> Result<PullRequestInfo, GitHostError> {
+        Ok(self.service.create_pr(&self.repo_info, request).await?)
+    }
+
+    async fn pr_status(&self, number: i64) -> Result<PullRequestInfo, GitHostError> {
+        Ok(self
+            .service
+            .update_pr_status(&self.repo_info, number)
+            .await?)
+    }
+
+    async fn update_pr_base(&self, number: i64, base_branch: &str) -> Result<(), GitHostError> {
+        Ok(self
+            .service
+            .update_pr_base(&self.repo_info, number, base_branch)
+            .await?)
+    }
+
+    async fn list_prs_for_branch(
+        &self,
+        branch: &str,
+    ) -> Result<Vec<PullRequestInfo>, GitHostError> {
+        Ok(self
+            .service
+            .list_all_prs_for_branch(&self.repo_info, branch)
+            .await?)
+    }
+
+    async fn pr_checks(&self, number: i64) -> Result<PrChecks, GitHostError> {
+        Ok(self.service.pr_checks(&self.repo_info, number).await?)
+    }
+
+    async fn failed_check_log(&self, check: &CheckRun) -> Result<Option<String>, GitHostError> {
+        // Only GitHub Actions jobs have logs the CLI can fetch
+        let Some(job_id) = check.url.as_deref().and_then(actions_job_id) else {
+            return Ok(None);
+        };
+        Ok(Some(
+            self.service.failed_job_log(&self.repo_info, job_id).await?,
+        ))
+    }
+}
+
+/// The job id in a GitHub Actions job URL, `.../actions/runs/<run>/job/<job>`
+fn actions_job_id(url: &str) -> Option<i64> {
+    let (_, rest) = url.split_once("/actions/runs/")?;
+    let (_, job) = rest.split_once("/job/")?;
+    job.split(['/', '?', '#']).next()?.parse().ok()
+}
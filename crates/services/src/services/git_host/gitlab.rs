@@ -0,0 +1,267 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use db::models::{
+    merge::{MergeStatus, PullRequestInfo},
+    pr_check_summary::{CheckRun, CheckState, PrChecks},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{GitHost, GitHostError, GitHostKind, check_response, with_retry};
+use crate::services::github_service::CreatePrRequest;
+
+const HOST: &str = "GitLab";
+
+/// The project path of a GitLab repository or merge request URL. Projects may sit in
+/// nested groups, and GitLab separates the project from its pages with `/-/`.
+pub fn project_path(segments: &[String]) -> String {
+    segments
+        .iter()
+        .take_while(|s| *s != "-")
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[derive(Debug, Deserialize)]
+struct MergeRequest {
+    iid: i64,
+    web_url: String,
+    state: String,
+    merged_at: Option<DateTime<Utc>>,
+    merge_commit_sha: Option<String>,
+    squash_commit_sha: Option<String>,
+}
+
+impl From<MergeRequest> for PullRequestInfo {
+    fn from(mr: MergeRequest) -> Self {
+        let status = match mr.state.as_str() {
+            "opened" => MergeStatus::Open,
+            "merged" => MergeStatus::Merged,
+            "closed" | "locked" => MergeStatus::Closed,
+            _ => MergeStatus::Unknown,
+        };
+        PullRequestInfo {
+            number: mr.iid,
+            url: mr.web_url,
+            status,
+            merged_at: mr.merged_at,
+            merge_commit_sha: mr.merge_commit_sha.or(mr.squash_commit_sha),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Pipeline {
+    id: i64,
+}
+
+/// The fields of a merge request needed to find its latest pipeline
+#[derive(Debug, Deserialize)]
+struct MergeRequestHead {
+    sha: Option<String>,
+    head_pipeline: Option<Pipeline>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Job {
+    name: String,
+    status: String,
+    #[serde(default)]
+    allow_failure: bool,
+    web_url: Option<String>,
+}
+
+impl From<Job> for CheckRun {
+    fn from(job: Job) -> Self {
+        let state = match job.status.as_str() {
+            "success" => CheckState::Success,
+            "failed" if job.allow_failure => CheckState::Neutral,
+            "failed" => CheckState::Failure,
+            "canceled" | "skipped" | "manual" => CheckState::Neutral,
+            _ => CheckState::Pending,
+        };
+        CheckRun {
+            name: job.name,
+            state,
+            url: job.web_url,
+        }
+    }
+}
+
+/// A project on gitlab.com or a self-hosted GitLab, through the REST API v4
+pub struct GitLabHost {
+    client: reqwest::Client,
+    api_url: String,
+    project_path: String,
+    token: Option<String>,
+}
+
+impl GitLabHost {
+    pub fn new(api_url: String, project_path: String, token: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            project_path,
+            token,
+        }
+    }
+
+    fn project_url(&self) -> String {
+        let project: String =
+            url::form_urlencoded::byte_serialize(self.project_path.as_bytes()).collect();
+        format!("{}/projects/{project}", self.api_url)
+    }
+
+    fn merge_requests_url(&self) -> String {
+        format!("{}/merge_requests", self.project_url())
+    }
+
+    fn request(&self, method: reqwest::Method, url: String) -> reqwest::RequestBuilder {
+        let builder = self.client.request(method, url);
+        match &self.token {
+            Some(token) => builder.header("PRIVATE-TOKEN", token),
+            None => builder,
+        }
+    }
+
+    async fn send_text(&self, builder: reqwest::RequestBuilder) -> Result<String, GitHostError> {
+        let response = builder
+            .send()
+            .await
+            .map_err(|source| GitHostError::Request { host: HOST, source })?;
+        check_response(HOST, response)
+            .await?
+            .text()
+            .await
+            .map_err(|source| GitHostError::Request { host: HOST, source })
+    }
+
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        builder: reqwest::RequestBuilder,
+    ) -> Result<T, GitHostError> {
+        let response = builder
+            .send()
+            .await
+            .map_err(|source| GitHostError::Request { host: HOST, source })?;
+        check_response(HOST, response)
+            .await?
+            .json::<T>()
+            .await
+            .map_err(|source| GitHostError::Request { host: HOST, source })
+    }
+}
+
+#[async_trait]
+impl GitHost for GitLabHost {
+    fn kind(&self) -> GitHostKind {
+        GitHostKind::GitLab
+    }
+
+    async fn create_pr(&self, request: &CreatePrRequest) -> Result<PullRequestInfo, GitHostError> {
+        let body = json!({
+            "source_branch": request.head_branch,
+            "target_branch": request.base_branch,
+            "title": request.title,
+            "description": request.body.clone().unwrap_or_default(),
+        });
+        let mr: MergeRequest = with_retry(HOST, || async {
+            self.send(
+                self.request(reqwest::Method::POST, self.merge_requests_url())
+                    .json(&body),
+            )
+            .await
+        })
+        .await?;
+
+        tracing::info!(
+            "Created GitLab MR !{} for branch {} in {}",
+            mr.iid,
+            request.head_branch,
+            self.project_path
+        );
+        Ok(mr.into())
+    }
+
+    async fn pr_status(&self, number: i64) -> Result<PullRequestInfo, GitHostError> {
+        let url = format!("{}/{number}", self.merge_requests_url());
+        let mr: MergeRequest = with_retry(HOST, || async {
+            self.send(self.request(reqwest::Method::GET, url.clone()))
+                .await
+        })
+        .await?;
+        Ok(mr.into())
+    }
+
+    async fn update_pr_base(&self, number: i64, base_branch: &str) -> Result<(), GitHostError> {
+        let url = format!("{}/{number}", self.merge_requests_url());
+        let body = json!({ "target_branch": base_branch });
+        let _: MergeRequest = with_retry(HOST, || async {
+            self.send(self.request(reqwest::Method::PUT, url.clone()).json(&body))
+                .await
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn list_prs_for_branch(
+        &self,
+        branch: &str,
+    ) -> Result<Vec<PullRequestInfo>, GitHostError> {
+        let mrs: Vec<MergeRequest> = with_retry(HOST, || async {
+            self.send(
+                self.request(reqwest::Method::GET, self.merge_requests_url())
+                    .query(&[("source_branch", branch), ("state", "all")]),
+            )
+            .await
+        })
+        .await?;
+        Ok(mrs.into_iter().map(Into::into).collect())
+    }
+
+    async fn pr_checks(&self, number: i64) -> Result<PrChecks, GitHostError> {
+        let url = format!("{}/{number}", self.merge_requests_url());
+        let mr: MergeRequestHead = with_retry(HOST, || async {
+            self.send(self.request(reqwest::Method::GET, url.clone()))
+                .await
+        })
+        .await?;
+        let Some(pipeline) = mr.head_pipeline else {
+            return Ok(PrChecks::new(mr.sha, Vec::new()));
+        };
+
+        let jobs_url = format!("{}/pipelines/{}/jobs", self.project_url(), pipeline.id);
+        let jobs: Vec<Job> = with_retry(HOST, || async {
+            self.send(
+                self.request(reqwest::Method::GET, jobs_url.clone())
+                    .query(&[("per_page", "100")]),
+            )
+            .await
+        })
+        .await?;
+        Ok(PrChecks::new(
+            mr.sha,
+            jobs.into_iter().map(CheckRun::from).collect(),
+        ))
+    }
+
+    async fn failed_check_log(&self, check: &CheckRun) -> Result<Option<String>, GitHostError> {
+        let Some(job_id) = check.url.as_deref().and_then(job_id) else {
+            return Ok(None);
+        };
+        let url = format!("{}/jobs/{job_id}/trace", self.project_url());
+        let log = with_retry(HOST, || async {
+            self.send_text(self.request(reqwest::Method::GET, url.clone()))
+                .await
+        })
+        .await?;
+        Ok(Some(log))
+    }
+}
+
+/// The job id in a GitLab job URL, `.../-/jobs/<id>`
+fn job_id(url: &str) -> Option<i64> {
+    let (_, rest) = url.split_once("/-/jobs/")?;
+    rest.split(['/', '?', '#']).next()?.parse().ok()
+}
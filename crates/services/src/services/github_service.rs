@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use backon::{ExponentialBuilder, Retryable};
-use db::models::merge::PullRequestInfo;
+use db::models::{merge::PullRequestInfo, pr_check_summary::PrChecks};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -166,6 +166,31 @@ pub struct RepositoryInfo {
     pub private: bool,
 }
 
+/// A review thread on a pull request, anchored to a line of the PR diff
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct PrReviewThread {
+    pub id: String,
+    pub path: String,
+    /// Line in the new version of the file; `None` once the thread is outdated
+    pub line: Option<i64>,
+    /// First line of a multi-line comment
+    pub start_line: Option<i64>,
+    pub is_resolved: bool,
+    pub is_outdated: bool,
+    /// Commit the thread's lines refer to
+    pub commit_oid: Option<String>,
+    pub comments: Vec<PrReviewComment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct PrReviewComment {
+    /// Login of the author, missing for deleted accounts
+    pub author: Option<String>,
+    pub body: String,
+    pub url: String,
+    pub created_at: String,
+}
+
 impl GitHubService {
     /// Create a new GitHub service with authentication
     pub fn new() -> Result<Self, GitHubServiceError> {
@@ -307,6 +332,45 @@ impl GitHubService {
         .await
     }
 
+    /// Point a pull request at a different base branch
+    pub async fn update_pr_base(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+        base_branch: &str,
+    ) -> Result<(), GitHubServiceError> {
+        (|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            let base_branch = base_branch.to_string();
+            let cli = self.gh_cli.clone();
+            task::spawn_blocking(move || cli.edit_pr_base(&owner, &repo, pr_number, &base_branch))
+                .await
+                .map_err(|err| {
+                    GitHubServiceError::PullRequest(format!(
+                        "Failed to execute GitHub CLI for editing PR #{pr_number}: {err}"
+                    ))
+                })?
+                .map_err(GitHubServiceError::from)
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|e: &GitHubServiceError| e.should_retry())
+        .notify(|err: &GitHubServiceError, dur: Duration| {
+            tracing::warn!(
+                "GitHub API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+
     /// List all pull requests for a branch (including closed/merged)
     pub async fn list_all_prs_for_branch(
         &self,
@@ -351,6 +415,103 @@ impl GitHubService {
         .await
     }
 
+    /// List the review threads of a pull request, resolved ones included
+    pub async fn list_review_threads(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+    ) -> Result<Vec<PrReviewThread>, GitHubServiceError> {
+        (|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            let cli = self.gh_cli.clone();
+            let threads = task::spawn_blocking(move || {
+                cli.list_review_threads(&owner, &repo, pr_number)
+            })
+            .await
+            .map_err(|err| {
+                GitHubServiceError::PullRequest(format!(
+                    "Failed to execute GitHub CLI for review threads on PR #{pr_number}: {err}"
+                ))
+            })?;
+            let threads = threads.map_err(GitHubServiceError::from)?;
+            Ok(threads)
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|e: &GitHubServiceError| e.should_retry())
+        .notify(|err: &GitHubServiceError, dur: Duration| {
+            tracing::warn!(
+                "GitHub API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+
+    /// Fetch the CI checks of a pull request's head commit
+    pub async fn pr_checks(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        pr_number: i64,
+    ) -> Result<PrChecks, GitHubServiceError> {
+        (|| async {
+            let owner = repo_info.owner.clone();
+            let repo = repo_info.repo_name.clone();
+            let cli = self.gh_cli.clone();
+            let checks = task::spawn_blocking(move || cli.pr_checks(&owner, &repo, pr_number))
+                .await
+                .map_err(|err| {
+                    GitHubServiceError::PullRequest(format!(
+                        "Failed to execute GitHub CLI for checks on PR #{pr_number}: {err}"
+                    ))
+                })?;
+            let checks = checks.map_err(GitHubServiceError::from)?;
+            Ok(checks)
+        })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(3)
+                .with_jitter(),
+        )
+        .when(|e: &GitHubServiceError| e.should_retry())
+        .notify(|err: &GitHubServiceError, dur: Duration| {
+            tracing::warn!(
+                "GitHub API call failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+    }
+
+    /// Fetch the log of the failed steps of a GitHub Actions job
+    pub async fn failed_job_log(
+        &self,
+        repo_info: &GitHubRepoInfo,
+        job_id: i64,
+    ) -> Result<String, GitHubServiceError> {
+        let owner = repo_info.owner.clone();
+        let repo = repo_info.repo_name.clone();
+        let cli = self.gh_cli.clone();
+        task::spawn_blocking(move || cli.failed_job_log(&owner, &repo, job_id))
+            .await
+            .map_err(|err| {
+                GitHubServiceError::Repository(format!(
+                    "Failed to execute GitHub CLI for job {job_id} logs: {err}"
+                ))
+            })?
+            .map_err(GitHubServiceError::from)
+    }
+
     #[cfg(feature = "cloud")]
     pub async fn list_repositories(
         &self,
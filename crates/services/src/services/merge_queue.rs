@@ -0,0 +1,597 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use db::models::{
+    draft::{Draft, DraftType},
+    execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+    merge::Merge,
+    merge_queue_entry::{MergeQueueEntry, MergeQueueStatus},
+    project::Project,
+    project_merge_queue_settings::ProjectMergeQueueSettings,
+    task::{Task, TaskStatus},
+    task_attempt::{TaskAttempt, TaskAttemptError},
+};
+use executors::{
+    actions::script::{ScriptContext, ScriptRequest, ScriptRequestLanguage},
+    sandbox::Sandbox,
+};
+use sqlx::{SqlitePool, error::Error as SqlxError};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    sync::RwLock,
+    time::interval,
+};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::services::{
+    attempt_stack,
+    ci_auto_fix::log_excerpt,
+    config::Config,
+    container::{ContainerError, ContainerService, SpawnedScript},
+    drafts::{DraftsService, DraftsServiceError, UpdateFollowUpDraftRequest},
+    git::GitServiceError,
+    merge_strategy::{self, MergeStrategyError},
+};
+
+/// Used when a project has not configured its merge queue
+const DEFAULT_VERIFY_TIMEOUT_SECS: u64 = 30 * 60;
+/// Times an attempt is rebased and verified again when its target branch moves before
+/// it could be merged
+const MAX_MERGE_ROUNDS: usize = 3;
+
+#[derive(Debug, Error)]
+pub enum MergeQueueError {
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+    #[error(transparent)]
+    Container(#[from] ContainerError),
+    #[error(transparent)]
+    TaskAttemptError(#[from] TaskAttemptError),
+    #[error(transparent)]
+    Drafts(#[from] DraftsServiceError),
+    #[error(transparent)]
+    MergeStrategy(#[from] MergeStrategyError),
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+    #[error("This attempt is already in the merge queue")]
+    AlreadyQueued,
+    #[error("This attempt is not in the merge queue")]
+    NotQueued,
+}
+
+/// How processing an entry ended
+enum Outcome {
+    Merged(String),
+    /// The entry was cancelled while it was processed
+    Cancelled,
+    Rejected {
+        error: String,
+        verify_log: Option<String>,
+    },
+}
+
+/// Queue an attempt to be merged into its current target branch
+pub async fn enqueue(
+    pool: &SqlitePool,
+    task_attempt: &TaskAttempt,
+    project_id: Uuid,
+) -> Result<MergeQueueEntry, MergeQueueError> {
+    if MergeQueueEntry::find_active_by_attempt_id(pool, task_attempt.id)
+        .await?
+        .is_some()
+    {
+        return Err(MergeQueueError::AlreadyQueued);
+    }
+    Ok(MergeQueueEntry::create(
+        pool,
+        project_id,
+        task_attempt.id,
+        &task_attempt.target_branch,
+    )
+    .await?)
+}
+
+/// Take an attempt out of the merge queue. An entry that is being verified stops once
+/// its verify script finishes, without merging.
+pub async fn cancel(
+    pool: &SqlitePool,
+    task_attempt: &TaskAttempt,
+) -> Result<MergeQueueEntry, MergeQueueError> {
+    let entry = MergeQueueEntry::find_active_by_attempt_id(pool, task_attempt.id)
+        .await?
+        .ok_or(MergeQueueError::NotQueued)?;
+    if !MergeQueueEntry::cancel(pool, entry.id).await? {
+        return Err(MergeQueueError::NotQueued);
+    }
+    MergeQueueEntry::find_by_id(pool, entry.id)
+        .await?
+        .ok_or(MergeQueueError::NotQueued)
+}
+
+/// Service that merges queued attempts into their target branch one at a time per
+/// branch: each is rebased onto the branch's current tip, verified with the project's
+/// verify script in its worktree and merged only if that passes. Attempts that cannot
+/// be merged get a follow-up draft explaining why.
+pub struct MergeQueueService<C> {
+    drafts: DraftsService,
+    container: C,
+    config: Arc<RwLock<Config>>,
+    poll_interval: Duration,
+    /// Target branches (per project) with an entry being processed
+    in_flight: Mutex<HashSet<(Uuid, String)>>,
+}
+
+impl<C> MergeQueueService<C>
+where
+    C: ContainerService + Send + Sync + 'static,
+{
+    pub async fn spawn(
+        drafts: DraftsService,
+        container: C,
+        config: Arc<RwLock<Config>>,
+    ) -> tokio::task::JoinHandle<()> {
+        let service = Arc::new(Self {
+            drafts,
+            container,
+            config,
+            poll_interval: Duration::from_secs(10),
+            in_flight: Mutex::new(HashSet::new()),
+        });
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(self: Arc<Self>) {
+        info!(
+            "Starting merge queue service with interval {:?}",
+            self.poll_interval
+        );
+
+        match MergeQueueEntry::fail_interrupted(&self.container.db().pool).await {
+            Ok(0) => {}
+            Ok(n) => warn!("Failed {} merge queue entries interrupted by a restart", n),
+            Err(e) => error!("Error failing interrupted merge queue entries: {}", e),
+        }
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.process_queues().await {
+                error!("Error processing merge queues: {}", e);
+            }
+        }
+    }
+
+    /// Start processing the oldest ready entry of every target branch that has none in
+    /// flight. Each entry runs in its own task, so a slow verify script only holds up
+    /// its own branch.
+    async fn process_queues(self: &Arc<Self>) -> Result<(), MergeQueueError> {
+        let pool = &self.container.db().pool;
+        let mut branches = self.in_flight.lock().unwrap().clone();
+        let mut ready = Vec::new();
+        for entry in MergeQueueEntry::find_queued(pool).await? {
+            if branches.contains(&(entry.project_id, entry.target_branch.clone())) {
+                continue;
+            }
+            // An attempt the agent is still working on waits without holding up others
+            let processes =
+                ExecutionProcess::find_by_task_attempt_id(pool, entry.task_attempt_id, false)
+                    .await?;
+            if processes.iter().any(|p| {
+                p.status == ExecutionProcessStatus::Running
+                    && p.run_reason != ExecutionProcessRunReason::DevServer
+            }) {
+                continue;
+            }
+            branches.insert((entry.project_id, entry.target_branch.clone()));
+            ready.push(entry);
+        }
+
+        for entry in ready {
+            let branch = (entry.project_id, entry.target_branch.clone());
+            self.in_flight.lock().unwrap().insert(branch.clone());
+            let service = Arc::clone(self);
+            tokio::spawn(async move {
+                service.process(&entry).await;
+                service.in_flight.lock().unwrap().remove(&branch);
+            });
+        }
+        Ok(())
+    }
+
+    async fn process(&self, entry: &MergeQueueEntry) {
+        let pool = &self.container.db().pool;
+        let outcome = match self.merge_entry(entry).await {
+            Ok(outcome) => outcome,
+            Err(e) => Outcome::Rejected {
+                error: e.to_string(),
+                verify_log: None,
+            },
+        };
+
+        let result = match outcome {
+            Outcome::Merged(merge_commit) => {
+                info!(
+                    "Merge queue merged attempt {} into '{}' at {}",
+                    entry.task_attempt_id, entry.target_branch, merge_commit
+                );
+                MergeQueueEntry::mark_merged(pool, entry.id, &merge_commit).await
+            }
+            Outcome::Cancelled => return,
+            Outcome::Rejected { error, verify_log } => {
+                info!(
+                    "Merge queue rejected attempt {}: {}",
+                    entry.task_attempt_id, error
+                );
+                if let Err(e) = self.kick_back(entry, &error, verify_log.as_deref()).await {
+                    warn!(
+                        "Failed to save merge queue failure for attempt {}: {}",
+                        entry.task_attempt_id, e
+                    );
+                }
+                MergeQueueEntry::mark_failed(pool, entry.id, &error, verify_log.as_deref()).await
+            }
+        };
+        if let Err(e) = result {
+            error!("Failed to record merge queue entry {}: {}", entry.id, e);
+        }
+    }
+
+    async fn merge_entry(&self, entry: &MergeQueueEntry) -> Result<Outcome, MergeQueueError> {
+        let pool = &self.container.db().pool;
+        let git = self.container.git();
+        let Some(task_attempt) = TaskAttempt::find_by_id(pool, entry.task_attempt_id).await? else {
+            return Ok(Outcome::Cancelled);
+        };
+        let task = task_attempt
+            .parent_task(pool)
+            .await?
+            .ok_or(TaskAttemptError::TaskNotFound)?;
+        let project = Project::find_by_id(pool, task.project_id)
+            .await?
+            .ok_or(TaskAttemptError::ProjectNotFound)?;
+        let settings = ProjectMergeQueueSettings::find_by_project_id(pool, project.id).await?;
+        let (verify_script, verify_timeout) = match &settings {
+            Some(s) => (
+                s.verify_script.as_deref().filter(|s| !s.trim().is_empty()),
+                Duration::from_secs(s.verify_timeout_secs.max(1) as u64),
+            ),
+            None => (None, Duration::from_secs(DEFAULT_VERIFY_TIMEOUT_SECS)),
+        };
+        let repo_path = project.git_repo_path.as_path();
+        let target_branch = entry.target_branch.as_str();
+        let worktree_path = PathBuf::from(
+            self.container
+                .ensure_container_exists(&task_attempt)
+                .await?,
+        );
+
+        for _ in 0..MAX_MERGE_ROUNDS {
+            if !MergeQueueEntry::set_status(pool, entry.id, MergeQueueStatus::Rebasing).await? {
+                return Ok(Outcome::Cancelled);
+            }
+            let (_, behind) =
+                git.get_branch_status(repo_path, &task_attempt.branch, target_branch)?;
+            if behind > 0 {
+                // Conflicts leave the rebase in progress for the agent or user to resolve
+                if let Err(e) = git.rebase_branch(
+                    repo_path,
+                    &worktree_path,
+                    target_branch,
+                    target_branch,
+                    &task_attempt.branch,
+                ) {
+                    return Ok(Outcome::Rejected {
+                        error: format!("Could not rebase onto '{target_branch}': {e}"),
+                        verify_log: None,
+                    });
+                }
+            }
+
+            if !MergeQueueEntry::set_status(pool, entry.id, MergeQueueStatus::Verifying).await? {
+                return Ok(Outcome::Cancelled);
+            }
+            if let Some(script) = verify_script
+                && let Some(rejection) = verify(
+                    &self.container,
+                    &task_attempt,
+                    &worktree_path,
+                    script,
+                    verify_timeout,
+                )
+                .await
+            {
+                return Ok(rejection);
+            }
+
+            if !MergeQueueEntry::set_status(pool, entry.id, MergeQueueStatus::Merging).await? {
+                return Ok(Outcome::Cancelled);
+            }
+            // The branch moved while the attempt was verified; verify it again on top
+            let (_, behind) =
+                git.get_branch_status(repo_path, &task_attempt.branch, target_branch)?;
+            if behind > 0 {
+                continue;
+            }
+            let merge_commit = match merge_strategy::merge_attempt(
+                git,
+                pool,
+                repo_path,
+                &worktree_path,
+                &task,
+                &task_attempt,
+                target_branch,
+            )
+            .await
+            {
+                Ok(merge_commit) => merge_commit,
+                Err(MergeStrategyError::GitService(GitServiceError::BranchesDiverged(_))) => {
+                    continue;
+                }
+                Err(
+                    e @ (MergeStrategyError::RepositoryConflicts(_)
+                    | MergeStrategyError::RepositoryFailed { .. }),
+                ) => {
+                    return Ok(Outcome::Rejected {
+                        error: e.to_string(),
+                        verify_log: None,
+                    });
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            Merge::create_direct(pool, task_attempt.id, target_branch, &merge_commit).await?;
+            Task::update_status(pool, task.id, TaskStatus::Done).await?;
+            let hosts = self.config.read().await.git_hosts.clone();
+            attempt_stack::restack_descendants(&self.container, &hosts, task_attempt.id).await;
+            if let Some(publisher) = self.container.share_publisher()
+                && let Err(err) = publisher.update_shared_task_by_id(task.id).await
+            {
+                warn!(
+                    ?err,
+                    "Failed to propagate shared task update for {}", task.id
+                );
+            }
+            return Ok(Outcome::Merged(merge_commit));
+        }
+
+        Ok(Outcome::Rejected {
+            error: format!(
+                "'{target_branch}' kept moving while the attempt was verified; queue it again"
+            ),
+            verify_log: None,
+        })
+    }
+
+    /// Leave the failure in the attempt's follow-up draft, unless the user already
+    /// queued that draft
+    async fn kick_back(
+        &self,
+        entry: &MergeQueueEntry,
+        error: &str,
+        verify_log: Option<&str>,
+    ) -> Result<(), MergeQueueError> {
+        let pool = &self.container.db().pool;
+        let Some(task_attempt) = TaskAttempt::find_by_id(pool, entry.task_attempt_id).await? else {
+            return Ok(());
+        };
+        let existing =
+            Draft::find_by_task_attempt_and_type(pool, task_attempt.id, DraftType::FollowUp)
+                .await?;
+        if existing.as_ref().is_some_and(|d| d.queued || d.sending) {
+            return Ok(());
+        }
+
+        let failure_prompt = build_failure_prompt(&entry.target_branch, error, verify_log);
+        let prompt = match existing.map(|d| d.prompt) {
+            Some(existing) if !existing.trim().is_empty() => {
+                format!("{}\n\n{failure_prompt}", existing.trim_end())
+            }
+            _ => failure_prompt,
+        };
+        self.drafts
+            .save_follow_up_draft(
+                &task_attempt,
+                &UpdateFollowUpDraftRequest {
+                    prompt: Some(prompt),
+                    variant: None,
+                    image_ids: None,
+                    version: None,
+                },
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+fn verify_request(script: &str) -> ScriptRequest {
+    ScriptRequest {
+        script: script.to_string(),
+        language: ScriptRequestLanguage::Bash,
+        context: ScriptContext::VerifyScript,
+    }
+}
+
+/// Run the verify script in the worktree like the attempt's own scripts, sandboxed and
+/// with the project's environment, returning why the attempt is rejected if it does not
+/// exit successfully within `timeout`
+async fn verify<C>(
+    container: &C,
+    task_attempt: &TaskAttempt,
+    worktree_path: &Path,
+    script: &str,
+    timeout: Duration,
+) -> Option<Outcome>
+where
+    C: ContainerService + Sync,
+{
+    match container
+        .spawn_script(task_attempt, worktree_path, &verify_request(script))
+        .await
+    {
+        Ok(spawned) => wait_for_verify(spawned, timeout).await,
+        Err(e) => Some(Outcome::Rejected {
+            error: format!("Failed to run the verify script: {e}"),
+            verify_log: None,
+        }),
+    }
+}
+
+/// Collect the output of a spawned verify script. The script runs in its own process
+/// group, so a timeout also stops whatever it started (test runners, dev servers).
+async fn wait_for_verify(spawned: SpawnedScript, timeout: Duration) -> Option<Outcome> {
+    let SpawnedScript {
+        mut child,
+        sandbox_container,
+    } = spawned;
+    let stdout = child.inner().stdout.take();
+    let stderr = child.inner().stderr.take();
+    let run = async {
+        let (stdout, stderr, status) =
+            tokio::join!(read_pipe(stdout), read_pipe(stderr), child.wait());
+        status.map(|status| (status, stdout, stderr))
+    };
+
+    let (status, stdout, stderr) = match tokio::time::timeout(timeout, run).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            return Some(Outcome::Rejected {
+                error: format!("Failed to run the verify script: {e}"),
+                verify_log: None,
+            });
+        }
+        Err(_) => {
+            if let Err(e) = child.kill().await {
+                warn!("Failed to kill timed out verify script: {}", e);
+            }
+            let _ = child.wait().await;
+            if let Some(name) = &sandbox_container
+                && let Err(e) = Sandbox::stop_container(name).await
+            {
+                warn!("Failed to stop sandbox container {}: {}", name, e);
+            }
+            return Some(Outcome::Rejected {
+                error: format!(
+                    "The verify script did not finish within {} seconds",
+                    timeout.as_secs()
+                ),
+                verify_log: None,
+            });
+        }
+    };
+    if status.success() {
+        return None;
+    }
+
+    let log = format!(
+        "{}{}",
+        String::from_utf8_lossy(&stdout),
+        String::from_utf8_lossy(&stderr)
+    );
+    let status = status
+        .code()
+        .map(|code| format!("exit code {code}"))
+        .unwrap_or_else(|| "a signal".to_string());
+    Some(Outcome::Rejected {
+        error: format!("The verify script failed with {status}"),
+        verify_log: Some(log_excerpt(&log)),
+    })
+}
+
+async fn read_pipe(pipe: Option<impl AsyncRead + Unpin>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    if let Some(mut pipe) = pipe {
+        let _ = pipe.read_to_end(&mut buf).await;
+    }
+    buf
+}
+
+fn build_failure_prompt(target_branch: &str, error: &str, verify_log: Option<&str>) -> String {
+    let mut prompt = format!(
+        "The merge queue could not merge this attempt into '{target_branch}': {error}\n\
+         Fix the problem, commit the changes and queue the attempt again.\n"
+    );
+    if let Some(log) = verify_log {
+        prompt.push_str(&format!("\n### Verify script output\n```\n{log}\n```\n"));
+    }
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use executors::{actions::Executable, approvals::NoopExecutorApprovalService};
+
+    use super::*;
+
+    /// Run a verify script on the host, as `verify` does for an unsandboxed project
+    async fn run_verify(dir: &Path, script: &str, timeout: Duration) -> Option<Outcome> {
+        let spawned = verify_request(script)
+            .spawn(dir, Arc::new(NoopExecutorApprovalService {}))
+            .await
+            .unwrap();
+        let spawned = SpawnedScript {
+            child: spawned.child,
+            sandbox_container: None,
+        };
+        wait_for_verify(spawned, timeout).await
+    }
+
+    #[tokio::test]
+    async fn verify_rejects_failing_script_with_its_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let rejection = run_verify(
+            dir.path(),
+            "echo checking && echo broken >&2 && exit 3",
+            Duration::from_secs(30),
+        )
+        .await;
+        let Some(Outcome::Rejected { error, verify_log }) = rejection else {
+            panic!("failing script should reject the attempt");
+        };
+        assert_eq!(error, "The verify script failed with exit code 3");
+        assert_eq!(verify_log.as_deref(), Some("checking\nbroken"));
+
+        assert!(
+            run_verify(dir.path(), "true", Duration::from_secs(30))
+                .await
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_rejects_script_that_times_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let rejection = run_verify(dir.path(), "sleep 5", Duration::from_millis(100)).await;
+        assert!(matches!(
+            rejection,
+            Some(Outcome::Rejected {
+                verify_log: None,
+                ..
+            })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn verify_timeout_kills_processes_started_by_the_script() {
+        let dir = tempfile::tempdir().unwrap();
+        let rejection = run_verify(
+            dir.path(),
+            "(sleep 1 && touch late) & wait",
+            Duration::from_millis(100),
+        )
+        .await;
+        assert!(matches!(rejection, Some(Outcome::Rejected { .. })));
+
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(!dir.path().join("late").exists());
+    }
+}
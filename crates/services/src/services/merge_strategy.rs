@@ -0,0 +1,436 @@
+use std::path::Path;
+
+use db::models::{
+    executor_session::ExecutorSession,
+    project_merge_settings::{MergeStrategy, ProjectMergeSettings},
+    task::Task,
+    task_attempt::TaskAttempt,
+    task_attempt_repository::TaskAttemptRepository,
+};
+use sqlx::{SqlitePool, error::Error as SqlxError};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::services::git::{GitService, GitServiceError};
+
+/// Message of squash and merge commits for projects without a template
+pub const DEFAULT_COMMIT_MESSAGE_TEMPLATE: &str =
+    "{task_title} (vibe-kanban {task_id_short})\n\n{task_description}";
+/// Lines of the agent's summary kept for `{agent_summary}`
+const AGENT_SUMMARY_MAX_LINES: usize = 40;
+
+#[derive(Debug, Error)]
+pub enum MergeStrategyError {
+    #[error(transparent)]
+    GitService(#[from] GitServiceError),
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+    #[error("Merge conflicts in {}; nothing was merged", .0.join(", "))]
+    RepositoryConflicts(Vec<String>),
+    #[error("Merging into '{repository}' failed, so nothing was merged: {source}")]
+    RepositoryFailed {
+        repository: String,
+        source: Box<MergeStrategyError>,
+    },
+}
+
+/// Values substituted into a commit message template
+struct CommitMessageValues<'a> {
+    task_title: &'a str,
+    task_description: &'a str,
+    task_id: Uuid,
+    attempt_id: Uuid,
+    executor: &'a str,
+    co_authors: &'a [String],
+    agent_summary: &'a str,
+}
+
+/// Merge the attempt's branch into `target_branch` the way the project is configured
+/// to. Returns the commit the target branch points to afterwards.
+///
+/// Rebasing onto the target branch may stop at conflicts, which are left in progress
+/// in the worktree like any other rebase.
+///
+/// Attempts with additional repositories are merged into every repository or none:
+/// nothing is merged while any of them conflicts, and repositories merged before a
+/// later one fails are reset.
+pub async fn merge_attempt(
+    git: &GitService,
+    pool: &SqlitePool,
+    repo_path: &Path,
+    worktree_path: &Path,
+    task: &Task,
+    task_attempt: &TaskAttempt,
+    target_branch: &str,
+) -> Result<String, MergeStrategyError> {
+    let settings = ProjectMergeSettings::find_by_project_id(pool, task.project_id).await?;
+    let strategy = settings.as_ref().map(|s| s.strategy).unwrap_or_default();
+    let template = settings.and_then(|s| s.commit_message_template);
+
+    let repositories =
+        TaskAttemptRepository::find_by_task_attempt_id(pool, task_attempt.id).await?;
+    let primary = MergeTarget {
+        name: repo_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| repo_path.display().to_string()),
+        repo_path,
+        worktree_path,
+        target_branch,
+    };
+    if repositories.is_empty() {
+        return merge_repository(
+            git,
+            pool,
+            strategy,
+            template.as_deref(),
+            &primary,
+            task,
+            task_attempt,
+        )
+        .await;
+    }
+
+    // A squash merge of the primary repository refuses a target branch that moved on
+    // (the merge queue rebases and verifies again), so check that before merging anything
+    if strategy == MergeStrategy::Squash {
+        let (_, behind) = git.get_branch_status(repo_path, &task_attempt.branch, target_branch)?;
+        if behind > 0 {
+            return Err(GitServiceError::BranchesDiverged(format!(
+                "Cannot merge: base branch '{target_branch}' is {behind} commits ahead of task branch '{}'.",
+                task_attempt.branch
+            ))
+            .into());
+        }
+    }
+
+    let targets: Vec<MergeTarget> = std::iter::once(primary)
+        .chain(repositories.iter().map(|repository| MergeTarget {
+            name: repository.name.clone(),
+            repo_path: &repository.git_repo_path,
+            worktree_path: &repository.worktree_path,
+            target_branch: &repository.target_branch,
+        }))
+        .collect();
+
+    let mut conflicted = Vec::new();
+    for target in &targets {
+        match git.check_merge_clean(target.repo_path, &task_attempt.branch, target.target_branch) {
+            Ok(()) => {}
+            Err(GitServiceError::MergeConflicts(_)) => conflicted.push(target.name.clone()),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    if !conflicted.is_empty() {
+        return Err(MergeStrategyError::RepositoryConflicts(conflicted));
+    }
+
+    // Branch heads before merging each repository, to undo it if a later one fails
+    let mut merged: Vec<(&MergeTarget, String, String)> = Vec::new();
+    let mut merge_commits = Vec::new();
+    for (index, target) in targets.iter().enumerate() {
+        let target_head = git.get_branch_oid(target.repo_path, target.target_branch)?;
+        let task_head = git.get_branch_oid(target.repo_path, &task_attempt.branch)?;
+        // Only the primary repository's branch status is shown, so the others are
+        // brought up to date here rather than refused
+        let rebased = if index > 0 && strategy == MergeStrategy::Squash {
+            rebase_if_behind(git, target, &task_attempt.branch)
+        } else {
+            Ok(())
+        };
+        let result = match rebased {
+            Ok(()) => {
+                merge_repository(
+                    git,
+                    pool,
+                    strategy,
+                    template.as_deref(),
+                    target,
+                    task,
+                    task_attempt,
+                )
+                .await
+            }
+            Err(e) => Err(e.into()),
+        };
+        match result {
+            Ok(merge_commit) => {
+                merged.push((target, target_head, task_head));
+                merge_commits.push(merge_commit);
+            }
+            Err(e) => {
+                for (target, target_head, task_head) in merged.iter().rev() {
+                    if let Err(reset_err) = git
+                        .reset_branch_to(target.repo_path, target.target_branch, target_head)
+                        .and_then(|_| {
+                            git.reset_branch_to(target.repo_path, &task_attempt.branch, task_head)
+                        })
+                    {
+                        tracing::error!(
+                            "Failed to undo the merge of attempt {} into '{}': {}",
+                            task_attempt.id,
+                            target.name,
+                            reset_err
+                        );
+                    }
+                }
+                return Err(MergeStrategyError::RepositoryFailed {
+                    repository: target.name.clone(),
+                    source: Box::new(e),
+                });
+            }
+        }
+    }
+
+    for (repository, merge_commit) in repositories.iter().zip(merge_commits.iter().skip(1)) {
+        TaskAttemptRepository::set_merge_commit(pool, repository.id, merge_commit).await?;
+    }
+    Ok(merge_commits.swap_remove(0))
+}
+
+/// A repository the attempt's branch is merged into
+struct MergeTarget<'a> {
+    name: String,
+    repo_path: &'a Path,
+    worktree_path: &'a Path,
+    target_branch: &'a str,
+}
+
+async fn merge_repository(
+    git: &GitService,
+    pool: &SqlitePool,
+    strategy: MergeStrategy,
+    template: Option<&str>,
+    target: &MergeTarget<'_>,
+    task: &Task,
+    task_attempt: &TaskAttempt,
+) -> Result<String, MergeStrategyError> {
+    let MergeTarget {
+        repo_path,
+        worktree_path,
+        target_branch,
+        ..
+    } = *target;
+
+    if strategy == MergeStrategy::RebaseFastForward {
+        rebase_if_behind(git, target, &task_attempt.branch)?;
+        // The attempt's commits land as they are, so there is no message to render
+        return Ok(git.fast_forward_changes(repo_path, &task_attempt.branch, target_branch)?);
+    }
+
+    let message = commit_message(
+        git,
+        pool,
+        repo_path,
+        task,
+        task_attempt,
+        target_branch,
+        template,
+    )
+    .await?;
+    let merge_commit = if strategy == MergeStrategy::MergeCommit {
+        git.merge_commit_changes(repo_path, &task_attempt.branch, target_branch, &message)?
+    } else {
+        git.merge_changes(
+            repo_path,
+            worktree_path,
+            &task_attempt.branch,
+            target_branch,
+            &message,
+        )?
+    };
+    Ok(merge_commit)
+}
+
+/// Rebase the attempt's branch onto the target branch if the target moved on
+fn rebase_if_behind(
+    git: &GitService,
+    target: &MergeTarget<'_>,
+    task_branch: &str,
+) -> Result<(), GitServiceError> {
+    let (_, behind) = git.get_branch_status(target.repo_path, task_branch, target.target_branch)?;
+    if behind > 0 {
+        git.rebase_branch(
+            target.repo_path,
+            target.worktree_path,
+            target.target_branch,
+            target.target_branch,
+            task_branch,
+        )?;
+    }
+    Ok(())
+}
+
+/// The commit message for merging the attempt into `target_branch`, from the project's
+/// template or the default one
+pub async fn commit_message(
+    git: &GitService,
+    pool: &SqlitePool,
+    repo_path: &Path,
+    task: &Task,
+    task_attempt: &TaskAttempt,
+    target_branch: &str,
+    template: Option<&str>,
+) -> Result<String, MergeStrategyError> {
+    let template = template.unwrap_or(DEFAULT_COMMIT_MESSAGE_TEMPLATE);
+
+    // Only look up what the template uses
+    let co_authors = if template.contains("{co_authored_by}") {
+        git.get_co_authors(repo_path, &task_attempt.branch, target_branch)?
+    } else {
+        Vec::new()
+    };
+    let agent_summary = if template.contains("{agent_summary}") {
+        latest_agent_summary(pool, task_attempt.id).await?
+    } else {
+        None
+    };
+
+    Ok(render_commit_message(
+        template,
+        &CommitMessageValues {
+            task_title: &task.title,
+            task_description: task.description.as_deref().unwrap_or_default(),
+            task_id: task.id,
+            attempt_id: task_attempt.id,
+            executor: &task_attempt.executor,
+            co_authors: &co_authors,
+            agent_summary: agent_summary.as_deref().unwrap_or_default(),
+        },
+    ))
+}
+
+/// The final message of the attempt's latest coding agent run that left one
+async fn latest_agent_summary(
+    pool: &SqlitePool,
+    task_attempt_id: Uuid,
+) -> Result<Option<String>, SqlxError> {
+    let sessions = ExecutorSession::find_by_task_attempt_id(pool, task_attempt_id).await?;
+    Ok(sessions
+        .into_iter()
+        .rev()
+        .filter_map(|session| session.summary)
+        .find(|summary| !summary.trim().is_empty())
+        .map(|summary| {
+            summary
+                .trim()
+                .lines()
+                .take(AGENT_SUMMARY_MAX_LINES)
+                .collect::<Vec<_>>()
+                .join("\n")
+        }))
+}
+
+/// Substitute the known `{placeholders}` of `template`, leaving unknown ones as they
+/// are, then drop the blank lines that empty values leave behind
+fn render_commit_message(template: &str, values: &CommitMessageValues) -> String {
+    let task_id = values.task_id.to_string();
+    let task_id_short = task_id.split('-').next().unwrap_or(&task_id).to_string();
+    let co_authored_by = values
+        .co_authors
+        .iter()
+        .map(|author| format!("Co-authored-by: {author}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let value = |name: &str| -> Option<String> {
+        Some(match name {
+            "task_title" => values.task_title.to_string(),
+            "task_description" => values.task_description.trim().to_string(),
+            "task_id" => task_id.clone(),
+            "task_id_short" => task_id_short.clone(),
+            "attempt_id" => values.attempt_id.to_string(),
+            "executor" => values.executor.to_string(),
+            "co_authored_by" => co_authored_by.clone(),
+            "agent_summary" => values.agent_summary.to_string(),
+            _ => return None,
+        })
+    };
+
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after
+            .find('}')
+            .and_then(|end| value(&after[..end]).map(|v| (end, v)))
+        {
+            Some((end, substituted)) => {
+                rendered.push_str(&substituted);
+                rest = &after[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+
+    let mut message = String::new();
+    let mut blank = false;
+    for line in rendered.lines().map(str::trim_end) {
+        if line.is_empty() {
+            blank = !message.is_empty();
+            continue;
+        }
+        if blank {
+            message.push('\n');
+            blank = false;
+        }
+        message.push_str(line);
+        message.push('\n');
+    }
+    message.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values<'a>(description: &'a str, co_authors: &'a [String]) -> CommitMessageValues<'a> {
+        CommitMessageValues {
+            task_title: "Add login page",
+            task_description: description,
+            task_id: Uuid::parse_str("1b2c3d4e-0000-0000-0000-000000000000").unwrap(),
+            attempt_id: Uuid::parse_str("5f6a7b8c-0000-0000-0000-000000000000").unwrap(),
+            executor: "CLAUDE_CODE",
+            co_authors,
+            agent_summary: "",
+        }
+    }
+
+    #[test]
+    fn default_template_matches_the_previous_message() {
+        let message = render_commit_message(
+            DEFAULT_COMMIT_MESSAGE_TEMPLATE,
+            &values("Use the new form\ncomponents.", &[]),
+        );
+        assert_eq!(
+            message,
+            "Add login page (vibe-kanban 1b2c3d4e)\n\nUse the new form\ncomponents."
+        );
+
+        let message = render_commit_message(DEFAULT_COMMIT_MESSAGE_TEMPLATE, &values("  ", &[]));
+        assert_eq!(message, "Add login page (vibe-kanban 1b2c3d4e)");
+    }
+
+    #[test]
+    fn template_renders_trailers_and_drops_empty_paragraphs() {
+        let co_authors = vec![
+            "Ada <ada@example.com>".to_string(),
+            "Lin <lin@example.com>".to_string(),
+        ];
+        let message = render_commit_message(
+            "feat: {task_title} {unknown}\n\n{agent_summary}\n\nAttempt {attempt_id} by {executor}\n\n{co_authored_by}\n",
+            &values("", &co_authors),
+        );
+        assert_eq!(
+            message,
+            "feat: Add login page {unknown}\n\n\
+             Attempt 5f6a7b8c-0000-0000-0000-000000000000 by CLAUDE_CODE\n\n\
+             Co-authored-by: Ada <ada@example.com>\n\
+             Co-authored-by: Lin <lin@example.com>"
+        );
+    }
+}
@@ -1,7 +1,12 @@
 pub mod analytics;
 pub mod approvals;
+pub mod attempt_fork;
+pub mod attempt_race;
+pub mod attempt_stack;
 pub mod auth;
+pub mod ci_auto_fix;
 pub mod config;
+pub mod conflict_resolution;
 pub mod container;
 pub mod diff_stream;
 pub mod drafts;
@@ -13,11 +18,18 @@ pub mod filesystem_watcher;
 pub mod gh_cli;
 pub mod git;
 pub mod git_cli;
+pub mod git_host;
 pub mod github_service;
 pub mod image;
+pub mod merge_queue;
+pub mod merge_strategy;
 pub mod notification;
+pub mod notifier;
 pub mod oauth_credentials;
 pub mod pr_monitor;
+pub mod pr_webhook;
 pub mod remote_client;
+pub mod secrets;
 pub mod share;
+pub mod task_scheduler;
 pub mod worktree_manager;
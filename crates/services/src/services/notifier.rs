@@ -0,0 +1,657 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use backon::{ExponentialBuilder, Retryable};
+use chrono::{DateTime, Utc};
+use db::{
+    DBService,
+    models::{
+        execution_process::{ExecutionProcess, ExecutionProcessRunReason, ExecutionProcessStatus},
+        project::Project,
+        task::{Task, TaskStatus},
+        task_attempt::TaskAttempt,
+    },
+};
+use executors::env::expand_secrets;
+use json_patch::PatchOperation;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::header::ContentType,
+    transport::smtp::{AsyncSmtpTransportBuilder, authentication::Credentials},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tokio::sync::{RwLock, broadcast::error::RecvError};
+use tracing::{error, info, warn};
+use utils::{log_msg::LogMsg, msg_store::MsgStore};
+use uuid::Uuid;
+
+use crate::services::{
+    config::{Config, NotificationEventKind, NotifierTarget, SmtpSecurity},
+    events::LifecycleEvent,
+    secrets::SecretStore,
+};
+
+#[derive(Debug, Error)]
+pub enum NotifierError {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error("notifier endpoint returned {status}: {body}")]
+    Status { status: u16, body: String },
+    #[error("invalid email: {0}")]
+    InvalidEmail(String),
+    #[error(transparent)]
+    Smtp(#[from] lettre::transport::smtp::Error),
+    #[error("notifier secret could not be resolved: {0}")]
+    Secret(String),
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+}
+
+impl NotifierError {
+    fn should_retry(&self) -> bool {
+        match self {
+            NotifierError::Http(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            NotifierError::Status { status, .. } => *status == 429 || *status >= 500,
+            NotifierError::Smtp(e) => !e.is_permanent(),
+            NotifierError::InvalidEmail(_) | NotifierError::Secret(_) | NotifierError::Sqlx(_) => {
+                false
+            }
+        }
+    }
+}
+
+/// A task lifecycle event as delivered to notifiers; webhooks receive it as JSON
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationEvent {
+    pub kind: NotificationEventKind,
+    pub project_id: Uuid,
+    pub project_name: String,
+    pub task_id: Uuid,
+    pub task_title: String,
+    pub task_attempt_id: Option<Uuid>,
+    /// One line describing what happened
+    pub summary: String,
+    /// Link to the pull request, for PR events
+    pub url: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Something in the event stream worth notifying about, before it is enriched with
+/// task and project details
+#[derive(Debug, Clone, PartialEq)]
+enum Trigger {
+    ProcessFinished {
+        process_id: Uuid,
+        status: ExecutionProcessStatus,
+    },
+    TaskStatusChanged {
+        task_id: Uuid,
+        from: TaskStatus,
+        to: TaskStatus,
+    },
+    Lifecycle(LifecycleEvent),
+}
+
+#[derive(Deserialize)]
+struct ProcessSnapshot {
+    id: Uuid,
+    run_reason: ExecutionProcessRunReason,
+    status: ExecutionProcessStatus,
+}
+
+#[derive(Deserialize)]
+struct TaskSnapshot {
+    id: Uuid,
+    status: TaskStatus,
+}
+
+/// Turns the event stream's patches into triggers. Processes are only reported when
+/// they were seen running, so re-saving an old process never notifies twice, and
+/// task changes only when the status differs from the last one seen.
+#[derive(Default)]
+struct TriggerTracker {
+    running: HashSet<Uuid>,
+    task_statuses: HashMap<Uuid, TaskStatus>,
+}
+
+impl TriggerTracker {
+    fn observe(&mut self, op: &PatchOperation) -> Option<Trigger> {
+        let path = op.path();
+        let value = match op {
+            PatchOperation::Add(op) => &op.value,
+            PatchOperation::Replace(op) => &op.value,
+            PatchOperation::Remove(_) => {
+                if let Some(task_id) = path
+                    .strip_prefix("/tasks/")
+                    .and_then(|id| Uuid::parse_str(id).ok())
+                {
+                    self.task_statuses.remove(&task_id);
+                }
+                return None;
+            }
+            _ => return None,
+        };
+
+        if path.starts_with("/execution_processes/") {
+            let process: ProcessSnapshot = serde_json::from_value(value.clone()).ok()?;
+            if process.run_reason != ExecutionProcessRunReason::CodingAgent {
+                return None;
+            }
+            match process.status {
+                ExecutionProcessStatus::Running => {
+                    self.running.insert(process.id);
+                    None
+                }
+                ExecutionProcessStatus::Completed | ExecutionProcessStatus::Failed
+                    if self.running.remove(&process.id) =>
+                {
+                    Some(Trigger::ProcessFinished {
+                        process_id: process.id,
+                        status: process.status,
+                    })
+                }
+                _ => {
+                    self.running.remove(&process.id);
+                    None
+                }
+            }
+        } else if path.starts_with("/tasks/") {
+            let task: TaskSnapshot = serde_json::from_value(value.clone()).ok()?;
+            let previous = self.task_statuses.insert(task.id, task.status.clone())?;
+            (previous != task.status).then_some(Trigger::TaskStatusChanged {
+                task_id: task.id,
+                from: previous,
+                to: task.status,
+            })
+        } else if path.starts_with("/lifecycle/") {
+            serde_json::from_value(value.clone())
+                .ok()
+                .map(Trigger::Lifecycle)
+        } else {
+            None
+        }
+    }
+}
+
+/// Service that sends task lifecycle events from the `EventService` stream to the
+/// webhook, Slack and email notifiers in the config
+pub struct NotifierService {
+    db: DBService,
+    config: Arc<RwLock<Config>>,
+    secrets: Arc<SecretStore>,
+    http: reqwest::Client,
+}
+
+impl NotifierService {
+    pub async fn spawn(
+        db: DBService,
+        config: Arc<RwLock<Config>>,
+        secrets: Arc<SecretStore>,
+        events_msg_store: Arc<MsgStore>,
+    ) -> tokio::task::JoinHandle<()> {
+        // Subscribe before spawning so nothing published in between is missed
+        let receiver = events_msg_store.get_receiver();
+        let service = Self {
+            db,
+            config,
+            secrets,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+        };
+        tokio::spawn(async move {
+            service.start(receiver).await;
+        })
+    }
+
+    async fn start(&self, mut receiver: tokio::sync::broadcast::Receiver<LogMsg>) {
+        info!("Starting notifier service");
+
+        let mut tracker = TriggerTracker::default();
+        match Task::find_all_statuses(&self.db.pool).await {
+            Ok(statuses) => tracker.task_statuses.extend(statuses),
+            Err(e) => error!("Failed to load task statuses for notifications: {}", e),
+        }
+
+        loop {
+            let patch = match receiver.recv().await {
+                Ok(LogMsg::JsonPatch(patch)) => patch,
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Notifier fell behind the event stream, {skipped} events skipped");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            for op in &patch.0 {
+                let Some(trigger) = tracker.observe(op) else {
+                    continue;
+                };
+                match self.resolve(trigger).await {
+                    Ok(Some(event)) => self.dispatch(event).await,
+                    Ok(None) => {}
+                    Err(e) => error!("Failed to build notification: {}", e),
+                }
+            }
+        }
+    }
+
+    /// Add task and project details to a trigger
+    async fn resolve(&self, trigger: Trigger) -> Result<Option<NotificationEvent>, NotifierError> {
+        let pool = &self.db.pool;
+        let (kind, task, task_attempt_id, summary, url) = match trigger {
+            Trigger::ProcessFinished { process_id, status } => {
+                let ctx = ExecutionProcess::load_context(pool, process_id).await?;
+                let (kind, outcome) = match status {
+                    ExecutionProcessStatus::Failed => {
+                        (NotificationEventKind::AttemptFailed, "failed")
+                    }
+                    _ => (NotificationEventKind::AttemptCompleted, "completed"),
+                };
+                let summary = format!(
+                    "Attempt on '{}' {outcome} ({})",
+                    ctx.task.title, ctx.task_attempt.executor
+                );
+                (kind, ctx.task, Some(ctx.task_attempt.id), summary, None)
+            }
+            Trigger::TaskStatusChanged { task_id, from, to } => {
+                let Some(task) = Task::find_by_id(pool, task_id).await? else {
+                    return Ok(None);
+                };
+                let summary = format!(
+                    "'{}' moved from {} to {}",
+                    task.title,
+                    status_label(&from),
+                    status_label(&to)
+                );
+                (
+                    NotificationEventKind::TaskStatusChanged,
+                    task,
+                    None,
+                    summary,
+                    None,
+                )
+            }
+            Trigger::Lifecycle(LifecycleEvent::ApprovalRequested {
+                execution_process_id,
+                tool_name,
+                ..
+            }) => {
+                let ctx = ExecutionProcess::load_context(pool, execution_process_id).await?;
+                let summary = format!(
+                    "'{}' is waiting for approval to use {tool_name}",
+                    ctx.task.title
+                );
+                (
+                    NotificationEventKind::ApprovalRequested,
+                    ctx.task,
+                    Some(ctx.task_attempt.id),
+                    summary,
+                    None,
+                )
+            }
+            Trigger::Lifecycle(LifecycleEvent::PrMerged {
+                task_attempt_id,
+                pr_number,
+                pr_url,
+            }) => {
+                let Some(task_attempt) = TaskAttempt::find_by_id(pool, task_attempt_id).await?
+                else {
+                    return Ok(None);
+                };
+                let Some(task) = task_attempt.parent_task(pool).await? else {
+                    return Ok(None);
+                };
+                let summary = format!("Pull request #{pr_number} for '{}' was merged", task.title);
+                (
+                    NotificationEventKind::PrMerged,
+                    task,
+                    Some(task_attempt_id),
+                    summary,
+                    Some(pr_url),
+                )
+            }
+        };
+
+        let Some(project) = Project::find_by_id(pool, task.project_id).await? else {
+            return Ok(None);
+        };
+        Ok(Some(NotificationEvent {
+            kind,
+            project_id: project.id,
+            project_name: project.name,
+            task_id: task.id,
+            task_title: task.title,
+            task_attempt_id,
+            summary,
+            url,
+            occurred_at: Utc::now(),
+        }))
+    }
+
+    /// Send an event to every notifier routed to it, each in the background so a
+    /// slow or retrying endpoint never holds up the others
+    async fn dispatch(&self, event: NotificationEvent) {
+        let notifiers: Vec<_> = self
+            .config
+            .read()
+            .await
+            .notifiers
+            .iter()
+            .filter(|n| n.wants(event.kind, event.project_id))
+            .cloned()
+            .collect();
+        if notifiers.is_empty() {
+            return;
+        }
+        let secrets = self.secrets.values().await;
+        for notifier in notifiers {
+            let target = match resolve_secrets(&notifier.target, &secrets) {
+                Ok(target) => target,
+                Err(e) => {
+                    error!("Notifier '{}' is misconfigured: {}", notifier.name, e);
+                    continue;
+                }
+            };
+            let http = self.http.clone();
+            let event = event.clone();
+            tokio::spawn(async move {
+                if let Err(e) = deliver(&http, &target, &event).await {
+                    error!(
+                        "Notifier '{}' failed to send {:?}: {}",
+                        notifier.name, event.kind, e
+                    );
+                }
+            });
+        }
+    }
+}
+
+/// `target` with the `{{secret:NAME}}` references in its URL or password replaced by
+/// the secrets' values
+pub fn resolve_secrets(
+    target: &NotifierTarget,
+    secrets: &HashMap<String, String>,
+) -> Result<NotifierTarget, NotifierError> {
+    let expand = |value: &str| {
+        expand_secrets(value, secrets).map_err(|e| NotifierError::Secret(e.to_string()))
+    };
+    let mut target = target.clone();
+    match &mut target {
+        NotifierTarget::Webhook { url } => *url = expand(url)?,
+        NotifierTarget::Slack { webhook_url } => *webhook_url = expand(webhook_url)?,
+        NotifierTarget::Email {
+            password: Some(password),
+            ..
+        } => *password = expand(password)?,
+        NotifierTarget::Email { password: None, .. } => {}
+    }
+    Ok(target)
+}
+
+/// Send `event` to `target`, retrying transient failures with exponential backoff
+pub async fn deliver(
+    http: &reqwest::Client,
+    target: &NotifierTarget,
+    event: &NotificationEvent,
+) -> Result<(), NotifierError> {
+    (|| async { send(http, target, event).await })
+        .retry(
+            &ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_delay(Duration::from_secs(60))
+                .with_max_times(5)
+                .with_jitter(),
+        )
+        .when(|e: &NotifierError| e.should_retry())
+        .notify(|err: &NotifierError, dur: Duration| {
+            warn!(
+                "Notification failed, retrying after {:.2}s: {}",
+                dur.as_secs_f64(),
+                err
+            );
+        })
+        .await
+}
+
+async fn send(
+    http: &reqwest::Client,
+    target: &NotifierTarget,
+    event: &NotificationEvent,
+) -> Result<(), NotifierError> {
+    match target {
+        NotifierTarget::Webhook { url } => post_json(http, url, &json!(event)).await,
+        NotifierTarget::Slack { webhook_url } => {
+            post_json(http, webhook_url, &json!({ "text": slack_text(event) })).await
+        }
+        NotifierTarget::Email {
+            smtp_host,
+            smtp_port,
+            security,
+            username,
+            password,
+            from,
+            to,
+        } => {
+            let message = email_message(from, to, event)?;
+            let mut transport = smtp_transport(smtp_host, *security)?;
+            if let Some(port) = smtp_port {
+                transport = transport.port(*port);
+            }
+            if let (Some(username), Some(password)) = (username, password) {
+                transport =
+                    transport.credentials(Credentials::new(username.clone(), password.clone()));
+            }
+            transport.build().send(message).await?;
+            Ok(())
+        }
+    }
+}
+
+async fn post_json(
+    http: &reqwest::Client,
+    url: &str,
+    body: &serde_json::Value,
+) -> Result<(), NotifierError> {
+    let response = http.post(url).json(body).send().await?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(NotifierError::Status {
+        status: status.as_u16(),
+        body,
+    })
+}
+
+fn email_message(
+    from: &str,
+    to: &[String],
+    event: &NotificationEvent,
+) -> Result<Message, NotifierError> {
+    let invalid = |e: &dyn std::fmt::Display| NotifierError::InvalidEmail(e.to_string());
+    let mut builder = Message::builder()
+        .from(from.parse().map_err(|e| invalid(&e))?)
+        .subject(format!("[{}] {}", event.project_name, event.summary));
+    for recipient in to {
+        builder = builder.to(recipient.parse().map_err(|e| invalid(&e))?);
+    }
+    builder
+        .header(ContentType::TEXT_PLAIN)
+        .body(plain_text(event))
+        .map_err(|e| invalid(&e))
+}
+
+/// Each constructor defaults to the standard port for its kind of connection
+fn smtp_transport(
+    host: &str,
+    security: SmtpSecurity,
+) -> Result<AsyncSmtpTransportBuilder, NotifierError> {
+    Ok(match security {
+        SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+        SmtpSecurity::Plain => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+    })
+}
+
+fn status_label(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Todo => "To Do",
+        TaskStatus::InProgress => "In Progress",
+        TaskStatus::InReview => "In Review",
+        TaskStatus::Done => "Done",
+        TaskStatus::Cancelled => "Cancelled",
+    }
+}
+
+fn plain_text(event: &NotificationEvent) -> String {
+    let mut text = format!(
+        "{}\n\nProject: {}\nTask: {}\n",
+        event.summary, event.project_name, event.task_title
+    );
+    if let Some(url) = &event.url {
+        text.push_str(&format!("{url}\n"));
+    }
+    text
+}
+
+/// Slack mrkdwn message; `&`, `<` and `>` have to be escaped outside of links
+fn slack_text(event: &NotificationEvent) -> String {
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let mut text = format!(
+        "*{}* {}",
+        escape(&event.project_name),
+        escape(&event.summary)
+    );
+    if let Some(url) = &event.url {
+        text.push_str(&format!(" <{url}|View pull request>"));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use json_patch::Patch;
+
+    use super::*;
+    use crate::services::events::{lifecycle_patch, task_patch};
+
+    fn process_op(id: Uuid, run_reason: &str, status: &str) -> PatchOperation {
+        let patch: Patch = serde_json::from_value(json!([{
+            "op": "replace",
+            "path": format!("/execution_processes/{id}"),
+            "value": { "id": id, "run_reason": run_reason, "status": status },
+        }]))
+        .unwrap();
+        patch.0.into_iter().next().unwrap()
+    }
+
+    fn task_op(id: Uuid, status: &str) -> PatchOperation {
+        let patch: Patch = serde_json::from_value(json!([{
+            "op": "replace",
+            "path": format!("/tasks/{id}"),
+            "value": { "id": id, "title": "Task", "status": status },
+        }]))
+        .unwrap();
+        patch.0.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn coding_agent_processes_trigger_once_after_running() {
+        let mut tracker = TriggerTracker::default();
+        let id = Uuid::new_v4();
+
+        // Finished processes that were never seen running are not reported
+        assert_eq!(
+            tracker.observe(&process_op(Uuid::new_v4(), "codingagent", "completed")),
+            None
+        );
+        assert_eq!(
+            tracker.observe(&process_op(Uuid::new_v4(), "setupscript", "running")),
+            None
+        );
+
+        assert_eq!(
+            tracker.observe(&process_op(id, "codingagent", "running")),
+            None
+        );
+        assert_eq!(
+            tracker.observe(&process_op(id, "codingagent", "failed")),
+            Some(Trigger::ProcessFinished {
+                process_id: id,
+                status: ExecutionProcessStatus::Failed,
+            })
+        );
+        assert_eq!(
+            tracker.observe(&process_op(id, "codingagent", "failed")),
+            None
+        );
+    }
+
+    #[test]
+    fn task_status_changes_trigger_only_on_a_new_status() {
+        let mut tracker = TriggerTracker::default();
+        let id = Uuid::new_v4();
+
+        assert_eq!(tracker.observe(&task_op(id, "todo")), None);
+        assert_eq!(tracker.observe(&task_op(id, "todo")), None);
+        assert_eq!(
+            tracker.observe(&task_op(id, "inprogress")),
+            Some(Trigger::TaskStatusChanged {
+                task_id: id,
+                from: TaskStatus::Todo,
+                to: TaskStatus::InProgress,
+            })
+        );
+
+        tracker.observe(&task_patch::remove(id).0[0]);
+        assert_eq!(tracker.observe(&task_op(id, "done")), None);
+    }
+
+    #[test]
+    fn lifecycle_patches_are_passed_through() {
+        let mut tracker = TriggerTracker::default();
+        let event = LifecycleEvent::PrMerged {
+            task_attempt_id: Uuid::new_v4(),
+            pr_number: 7,
+            pr_url: "https://github.com/owner/repo/pull/7".to_string(),
+        };
+        assert_eq!(
+            tracker.observe(&lifecycle_patch::add(&event).0[0]),
+            Some(Trigger::Lifecycle(event))
+        );
+    }
+
+    #[test]
+    fn secret_references_in_targets_are_resolved() {
+        let secrets = HashMap::from([("SLACK_HOOK".to_string(), "https://hooks/x".to_string())]);
+
+        let slack = NotifierTarget::Slack {
+            webhook_url: "{{secret:SLACK_HOOK}}".to_string(),
+        };
+        assert!(matches!(
+            resolve_secrets(&slack, &secrets),
+            Ok(NotifierTarget::Slack { webhook_url }) if webhook_url == "https://hooks/x"
+        ));
+
+        let webhook = NotifierTarget::Webhook {
+            url: "{{secret:MISSING}}".to_string(),
+        };
+        assert!(matches!(
+            resolve_secrets(&webhook, &secrets),
+            Err(NotifierError::Secret(_))
+        ));
+    }
+}
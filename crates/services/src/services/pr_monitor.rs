@@ -1,9 +1,11 @@
-use std::time::Duration;
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
 use db::{
     DBService,
     models::{
-        merge::{Merge, MergeStatus, PrMerge},
+        merge::{Merge, MergeStatus, PrMerge, PullRequestInfo},
+        pr_check_summary::PrCheckSummary,
+        pr_webhook_delivery::PrWebhookDelivery,
         task::{Task, TaskStatus},
         task_attempt::{TaskAttempt, TaskAttemptError},
     },
@@ -11,47 +13,78 @@ use db::{
 use serde_json::json;
 use sqlx::error::Error as SqlxError;
 use thiserror::Error;
-use tokio::time::interval;
+use tokio::{
+    sync::RwLock,
+    time::{Instant, interval},
+};
 use tracing::{debug, error, info};
+use utils::msg_store::MsgStore;
 
 use crate::services::{
     analytics::AnalyticsContext,
-    github_service::{GitHubRepoInfo, GitHubService, GitHubServiceError},
+    config::Config,
+    events::{LifecycleEvent, lifecycle_patch},
+    git_host::{self, GitHost, GitHostError, RemoteUrl},
+    pr_webhook::{PrWebhookAction, PrWebhookEvent},
+    secrets::SecretStore,
     share::SharePublisher,
 };
 
+/// Repositories that delivered a status webhook within this window are polled only on
+/// the slow fallback schedule
+const WEBHOOK_FRESHNESS: chrono::Duration = chrono::Duration::hours(6);
+/// How often PRs of webhook-served repositories are still polled, in case a delivery
+/// was lost
+const WEBHOOK_FALLBACK_POLL_INTERVAL: Duration = Duration::from_secs(30 * 60);
+/// How long delivery ids are kept to recognise redelivered or replayed webhooks
+const DELIVERY_ID_RETENTION: chrono::Duration = chrono::Duration::days(30);
+
 #[derive(Debug, Error)]
-enum PrMonitorError {
+pub enum PrMonitorError {
     #[error(transparent)]
-    GitHubServiceError(#[from] GitHubServiceError),
+    GitHost(#[from] GitHostError),
     #[error(transparent)]
     TaskAttemptError(#[from] TaskAttemptError),
     #[error(transparent)]
     Sqlx(#[from] SqlxError),
 }
 
-/// Service to monitor GitHub PRs and update task status when they are merged
+/// Service to monitor PRs on GitHub, GitLab and Gitea, update task status when they
+/// are merged and record the CI checks of open ones. Updates arrive through webhooks
+/// where a repository has one configured; polling covers the rest.
 pub struct PrMonitorService {
     db: DBService,
+    config: Arc<RwLock<Config>>,
+    secrets: Arc<SecretStore>,
     poll_interval: Duration,
     analytics: Option<AnalyticsContext>,
     publisher: Option<SharePublisher>,
+    events_msg_store: Arc<MsgStore>,
 }
 
 impl PrMonitorService {
-    pub async fn spawn(
+    pub fn new(
         db: DBService,
+        config: Arc<RwLock<Config>>,
+        secrets: Arc<SecretStore>,
         analytics: Option<AnalyticsContext>,
         publisher: Option<SharePublisher>,
-    ) -> tokio::task::JoinHandle<()> {
-        let service = Self {
+        events_msg_store: Arc<MsgStore>,
+    ) -> Self {
+        Self {
             db,
+            config,
+            secrets,
             poll_interval: Duration::from_secs(60), // Check every minute
             analytics,
             publisher,
-        };
+            events_msg_store,
+        }
+    }
+
+    pub async fn spawn(self) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
-            service.start().await;
+            self.start().await;
         })
     }
 
@@ -62,17 +95,32 @@ impl PrMonitorService {
         );
 
         let mut interval = interval(self.poll_interval);
+        let mut last_fallback_poll: Option<Instant> = None;
 
         loop {
             interval.tick().await;
-            if let Err(e) = self.check_all_open_prs().await {
+            let fallback =
+                last_fallback_poll.is_none_or(|at| at.elapsed() >= WEBHOOK_FALLBACK_POLL_INTERVAL);
+            if fallback {
+                last_fallback_poll = Some(Instant::now());
+                if let Err(e) = PrWebhookDelivery::delete_delivery_ids_before(
+                    &self.db.pool,
+                    chrono::Utc::now() - DELIVERY_ID_RETENTION,
+                )
+                .await
+                {
+                    error!("Error pruning webhook delivery ids: {}", e);
+                }
+            }
+            if let Err(e) = self.check_all_open_prs(fallback).await {
                 error!("Error checking open PRs: {}", e);
             }
         }
     }
 
-    /// Check all open PRs for updates with the provided GitHub token
-    async fn check_all_open_prs(&self) -> Result<(), PrMonitorError> {
+    /// Check all open PRs for updates. Repositories served by webhooks are skipped
+    /// unless `include_webhook_repos` is set.
+    async fn check_all_open_prs(&self, include_webhook_repos: bool) -> Result<(), PrMonitorError> {
         let open_prs = Merge::get_open_prs(&self.db.pool).await?;
 
         if open_prs.is_empty() {
@@ -80,9 +128,30 @@ impl PrMonitorService {
             return Ok(());
         }
 
-        info!("Checking {} open PRs", open_prs.len());
+        let webhook_repos: HashSet<String> = if include_webhook_repos {
+            HashSet::new()
+        } else {
+            PrWebhookDelivery::find_repo_keys_delivered_since(
+                &self.db.pool,
+                chrono::Utc::now() - WEBHOOK_FRESHNESS,
+            )
+            .await?
+            .into_iter()
+            .collect()
+        };
+        let polled: Vec<_> = open_prs
+            .into_iter()
+            .filter(|pr_merge| repo_key(pr_merge).is_none_or(|key| !webhook_repos.contains(&key)))
+            .collect();
+
+        if polled.is_empty() {
+            debug!("All open PRs are covered by webhooks");
+            return Ok(());
+        }
+
+        info!("Checking {} open PRs", polled.len());
 
-        for pr_merge in open_prs {
+        for pr_merge in polled {
             if let Err(e) = self.check_pr_status(&pr_merge).await {
                 error!(
                     "Error checking PR #{} for attempt {}: {}",
@@ -93,16 +162,106 @@ impl PrMonitorService {
         Ok(())
     }
 
+    /// Apply a verified webhook delivery. Returns the number of tracked PRs it touched.
+    pub async fn handle_webhook(&self, event: &PrWebhookEvent) -> Result<usize, PrMonitorError> {
+        let Some(key) = event.repo_key.as_deref() else {
+            return Ok(0);
+        };
+
+        let numbers = match &event.action {
+            PrWebhookAction::StatusChanged { number, .. } => {
+                // Only status deliveries show the repository's PRs no longer need polling
+                PrWebhookDelivery::record(
+                    &self.db.pool,
+                    key,
+                    event.provider.as_str(),
+                    &event.event,
+                )
+                .await?;
+                vec![*number]
+            }
+            PrWebhookAction::ChecksCompleted { numbers } => numbers.clone(),
+            PrWebhookAction::Ignored => return Ok(0),
+        };
+        let tracked: Vec<PrMerge> = Merge::get_open_prs(&self.db.pool)
+            .await?
+            .into_iter()
+            .filter(|pr_merge| {
+                numbers.contains(&pr_merge.pr_info.number)
+                    && repo_key(pr_merge).as_deref() == Some(key)
+            })
+            .collect();
+
+        for pr_merge in &tracked {
+            match &event.action {
+                PrWebhookAction::StatusChanged {
+                    status,
+                    merge_commit_sha,
+                    ..
+                } => {
+                    let pr_status = PullRequestInfo {
+                        number: pr_merge.pr_info.number,
+                        url: pr_merge.pr_info.url.clone(),
+                        status: status.clone(),
+                        merged_at: None,
+                        merge_commit_sha: merge_commit_sha.clone(),
+                    };
+                    self.apply_pr_status(pr_merge, &pr_status).await?;
+                    // A push to an open PR starts a new round of checks
+                    if *status == MergeStatus::Open {
+                        self.refresh_checks(pr_merge).await?;
+                    }
+                }
+                _ => self.check_pr_status(pr_merge).await?,
+            }
+        }
+        Ok(tracked.len())
+    }
+
+    async fn git_host(&self, pr_url: &str) -> Result<Box<dyn GitHost>, GitHostError> {
+        let hosts = self.config.read().await.git_hosts.clone();
+        git_host::from_remote_url(pr_url, &hosts, &self.secrets.values().await)
+    }
+
     /// Check the status of a specific PR
     async fn check_pr_status(&self, pr_merge: &PrMerge) -> Result<(), PrMonitorError> {
-        // GitHubService now uses gh CLI, no token needed
-        let github_service = GitHubService::new()?;
-        let repo_info = GitHubRepoInfo::from_remote_url(&pr_merge.pr_info.url)?;
+        let host = self.git_host(&pr_merge.pr_info.url).await?;
 
-        let pr_status = github_service
-            .update_pr_status(&repo_info, pr_merge.pr_info.number)
-            .await?;
+        let pr_status = host.pr_status(pr_merge.pr_info.number).await?;
+        self.apply_pr_status(pr_merge, &pr_status).await?;
+        if matches!(pr_status.status, MergeStatus::Open) {
+            self.refresh_checks(pr_merge).await?;
+        }
+        Ok(())
+    }
+
+    /// Record the current CI checks of an open PR
+    async fn refresh_checks(&self, pr_merge: &PrMerge) -> Result<(), PrMonitorError> {
+        let host = self.git_host(&pr_merge.pr_info.url).await?;
+
+        let checks = host.pr_checks(pr_merge.pr_info.number).await?;
+        debug!(
+            "PR #{} checks: {:?} ({} reported)",
+            pr_merge.pr_info.number,
+            checks.state,
+            checks.checks.len()
+        );
+        PrCheckSummary::upsert(
+            &self.db.pool,
+            pr_merge.id,
+            pr_merge.task_attempt_id,
+            &checks,
+        )
+        .await?;
+        Ok(())
+    }
 
+    /// Record a tracked PR's latest status, marking its task done once merged
+    async fn apply_pr_status(
+        &self,
+        pr_merge: &PrMerge,
+        pr_status: &PullRequestInfo,
+    ) -> Result<(), PrMonitorError> {
         debug!(
             "PR #{} status: {:?} (was open)",
             pr_merge.pr_info.number, pr_status.status
@@ -110,12 +269,12 @@ impl PrMonitorService {
 
         // Update the PR status in the database
         if !matches!(&pr_status.status, MergeStatus::Open) {
-            // Update merge status with the latest information from GitHub
+            // Update merge status with the latest information from the git host
             Merge::update_status(
                 &self.db.pool,
                 pr_merge.id,
                 pr_status.status.clone(),
-                pr_status.merge_commit_sha,
+                pr_status.merge_commit_sha.clone(),
             )
             .await?;
 
@@ -129,6 +288,12 @@ impl PrMonitorService {
                     pr_merge.pr_info.number, task_attempt.task_id
                 );
                 Task::update_status(&self.db.pool, task_attempt.task_id, TaskStatus::Done).await?;
+                self.events_msg_store
+                    .push_patch(lifecycle_patch::add(&LifecycleEvent::PrMerged {
+                        task_attempt_id: task_attempt.id,
+                        pr_number: pr_merge.pr_info.number,
+                        pr_url: pr_merge.pr_info.url.clone(),
+                    }));
 
                 // Track analytics event
                 if let Some(analytics) = &self.analytics
@@ -163,3 +328,9 @@ impl PrMonitorService {
         Ok(())
     }
 }
+
+fn repo_key(pr_merge: &PrMerge) -> Option<String> {
+    RemoteUrl::parse(&pr_merge.pr_info.url)
+        .ok()
+        .map(|url| url.repo_key())
+}
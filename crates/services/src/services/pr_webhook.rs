@@ -0,0 +1,392 @@
+//! Inbound pull request webhooks.
+//!
+//! GitHub and Gitea sign deliveries with an HMAC-SHA256 of the body keyed by the
+//! shared secret (`X-Hub-Signature-256` / `X-Gitea-Signature`). GitLab does not sign
+//! payloads and sends the secret itself in `X-Gitlab-Token`.
+
+use db::models::merge::MergeStatus;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::services::git_host::{GitHostKind, RemoteUrl};
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Error)]
+pub enum PrWebhookError {
+    #[error("Missing webhook signature")]
+    MissingSignature,
+    #[error("Invalid webhook signature")]
+    InvalidSignature,
+    #[error("Invalid webhook payload: {0}")]
+    Payload(#[from] serde_json::Error),
+}
+
+/// What a verified webhook delivery asks the PR monitor to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrWebhookAction {
+    /// A pull request changed state; apply it without asking the host
+    StatusChanged {
+        number: i64,
+        status: MergeStatus,
+        merge_commit_sha: Option<String>,
+    },
+    /// Checks finished for these pull requests; refresh them from the host
+    ChecksCompleted { numbers: Vec<i64> },
+    /// Nothing to update, e.g. a ping or an unrelated event
+    Ignored,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrWebhookEvent {
+    pub provider: GitHostKind,
+    /// Event name from the provider's event header
+    pub event: String,
+    /// The repository the event came from, see [`RemoteUrl::repo_key`]
+    pub repo_key: Option<String>,
+    pub action: PrWebhookAction,
+}
+
+/// Verify a delivery's signature header against the shared secret
+pub fn verify_signature(
+    provider: GitHostKind,
+    signature: Option<&str>,
+    secret: &str,
+    body: &[u8],
+) -> Result<(), PrWebhookError> {
+    let signature = signature
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or(PrWebhookError::MissingSignature)?;
+
+    let valid = match provider {
+        GitHostKind::GitLab => constant_time_eq(signature.as_bytes(), secret.as_bytes()),
+        GitHostKind::GitHub | GitHostKind::Gitea => {
+            let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+            let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+                .expect("HMAC accepts keys of any length");
+            mac.update(body);
+            let expected: String = mac
+                .finalize()
+                .into_bytes()
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect();
+            constant_time_eq(
+                signature.to_ascii_lowercase().as_bytes(),
+                expected.as_bytes(),
+            )
+        }
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(PrWebhookError::InvalidSignature)
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[derive(Deserialize)]
+struct Repository {
+    html_url: String,
+}
+
+#[derive(Deserialize)]
+struct PullRequest {
+    number: i64,
+    state: String,
+    #[serde(default)]
+    merged: bool,
+    merge_commit_sha: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PullRequestPayload {
+    pull_request: PullRequest,
+    repository: Option<Repository>,
+}
+
+#[derive(Deserialize)]
+struct CheckSuitePullRequest {
+    number: i64,
+}
+
+#[derive(Deserialize)]
+struct CheckSuite {
+    #[serde(default)]
+    pull_requests: Vec<CheckSuitePullRequest>,
+}
+
+#[derive(Deserialize)]
+struct CheckSuitePayload {
+    action: String,
+    check_suite: CheckSuite,
+    repository: Option<Repository>,
+}
+
+#[derive(Deserialize)]
+struct RepositoryPayload {
+    repository: Option<Repository>,
+    project: Option<GitLabProject>,
+}
+
+#[derive(Deserialize)]
+struct GitLabProject {
+    web_url: String,
+}
+
+#[derive(Deserialize)]
+struct GitLabMergeRequest {
+    iid: i64,
+    state: String,
+    merge_commit_sha: Option<String>,
+    squash_commit_sha: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitLabMergeRequestPayload {
+    object_attributes: GitLabMergeRequest,
+    project: GitLabProject,
+}
+
+#[derive(Deserialize)]
+struct GitLabPipeline {
+    status: String,
+}
+
+#[derive(Deserialize)]
+struct GitLabPipelineMergeRequest {
+    iid: i64,
+}
+
+#[derive(Deserialize)]
+struct GitLabPipelinePayload {
+    object_attributes: GitLabPipeline,
+    merge_request: Option<GitLabPipelineMergeRequest>,
+    project: GitLabProject,
+}
+
+fn repo_key(web_url: &str) -> Option<String> {
+    RemoteUrl::parse(web_url).ok().map(|url| url.repo_key())
+}
+
+/// Parse a verified delivery. `event` is the value of the provider's event header.
+pub fn parse_event(
+    provider: GitHostKind,
+    event: &str,
+    body: &[u8],
+) -> Result<PrWebhookEvent, PrWebhookError> {
+    let (repo_key, action) = match (provider, event) {
+        (GitHostKind::GitHub | GitHostKind::Gitea, "pull_request") => {
+            let payload: PullRequestPayload = serde_json::from_slice(body)?;
+            let pr = payload.pull_request;
+            let status = match (pr.merged, pr.state.as_str()) {
+                (true, _) => MergeStatus::Merged,
+                (false, "open") => MergeStatus::Open,
+                (false, "closed") => MergeStatus::Closed,
+                _ => MergeStatus::Unknown,
+            };
+            (
+                payload.repository.and_then(|r| repo_key(&r.html_url)),
+                PrWebhookAction::StatusChanged {
+                    number: pr.number,
+                    // Open PRs carry a test merge commit that was never merged
+                    merge_commit_sha: pr.merge_commit_sha.filter(|_| pr.merged),
+                    status,
+                },
+            )
+        }
+        (GitHostKind::GitHub, "check_suite") => {
+            let payload: CheckSuitePayload = serde_json::from_slice(body)?;
+            let action = if payload.action == "completed" {
+                PrWebhookAction::ChecksCompleted {
+                    numbers: payload
+                        .check_suite
+                        .pull_requests
+                        .iter()
+                        .map(|pr| pr.number)
+                        .collect(),
+                }
+            } else {
+                PrWebhookAction::Ignored
+            };
+            (
+                payload.repository.and_then(|r| repo_key(&r.html_url)),
+                action,
+            )
+        }
+        (GitHostKind::GitLab, "Merge Request Hook") => {
+            let payload: GitLabMergeRequestPayload = serde_json::from_slice(body)?;
+            let mr = payload.object_attributes;
+            let status = match mr.state.as_str() {
+                "opened" => MergeStatus::Open,
+                "merged" => MergeStatus::Merged,
+                "closed" | "locked" => MergeStatus::Closed,
+                _ => MergeStatus::Unknown,
+            };
+            (
+                repo_key(&payload.project.web_url),
+                PrWebhookAction::StatusChanged {
+                    number: mr.iid,
+                    status,
+                    merge_commit_sha: mr.merge_commit_sha.or(mr.squash_commit_sha),
+                },
+            )
+        }
+        (GitHostKind::GitLab, "Pipeline Hook") => {
+            let payload: GitLabPipelinePayload = serde_json::from_slice(body)?;
+            let finished = matches!(
+                payload.object_attributes.status.as_str(),
+                "success" | "failed" | "canceled" | "skipped"
+            );
+            let action = match payload.merge_request {
+                Some(mr) if finished => PrWebhookAction::ChecksCompleted {
+                    numbers: vec![mr.iid],
+                },
+                _ => PrWebhookAction::Ignored,
+            };
+            (repo_key(&payload.project.web_url), action)
+        }
+        _ => {
+            // Still note which repository is delivering, e.g. for ping events
+            let payload: RepositoryPayload = serde_json::from_slice(body)?;
+            let web_url = payload
+                .repository
+                .map(|r| r.html_url)
+                .or(payload.project.map(|p| p.web_url));
+            (
+                web_url.as_deref().and_then(repo_key),
+                PrWebhookAction::Ignored,
+            )
+        }
+    };
+
+    Ok(PrWebhookEvent {
+        provider,
+        event: event.to_string(),
+        repo_key,
+        action,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        let hex: String = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        format!("sha256={hex}")
+    }
+
+    #[test]
+    fn verifies_hmac_and_token_signatures() {
+        let body = br#"{"zen":"hi"}"#;
+        let signature = sign("s3cret", body);
+
+        assert!(verify_signature(GitHostKind::GitHub, Some(&signature), "s3cret", body).is_ok());
+        assert!(matches!(
+            verify_signature(GitHostKind::GitHub, Some(&signature), "other", body),
+            Err(PrWebhookError::InvalidSignature)
+        ));
+        assert!(matches!(
+            verify_signature(GitHostKind::GitHub, None, "s3cret", body),
+            Err(PrWebhookError::MissingSignature)
+        ));
+        // Gitea sends the bare hex digest
+        let bare = signature.trim_start_matches("sha256=");
+        assert!(verify_signature(GitHostKind::Gitea, Some(bare), "s3cret", body).is_ok());
+        assert!(verify_signature(GitHostKind::GitLab, Some("s3cret"), "s3cret", body).is_ok());
+        assert!(verify_signature(GitHostKind::GitLab, Some("s3cre"), "s3cret", body).is_err());
+    }
+
+    #[test]
+    fn parses_pull_request_events() {
+        let body = json!({
+            "action": "closed",
+            "pull_request": { "number": 7, "state": "closed", "merged": true, "merge_commit_sha": "abc" },
+            "repository": { "html_url": "https://github.com/Owner/Repo" },
+        })
+        .to_string();
+        let event = parse_event(GitHostKind::GitHub, "pull_request", body.as_bytes()).unwrap();
+        assert_eq!(event.repo_key.as_deref(), Some("github.com/owner/repo"));
+        assert_eq!(
+            event.action,
+            PrWebhookAction::StatusChanged {
+                number: 7,
+                status: MergeStatus::Merged,
+                merge_commit_sha: Some("abc".to_string()),
+            }
+        );
+
+        let body = json!({
+            "object_attributes": { "iid": 3, "state": "closed" },
+            "project": { "web_url": "https://gitlab.example.com/group/sub/repo" },
+        })
+        .to_string();
+        let event =
+            parse_event(GitHostKind::GitLab, "Merge Request Hook", body.as_bytes()).unwrap();
+        assert_eq!(
+            event.repo_key.as_deref(),
+            Some("gitlab.example.com/group/sub/repo")
+        );
+        assert!(matches!(
+            event.action,
+            PrWebhookAction::StatusChanged {
+                number: 3,
+                status: MergeStatus::Closed,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parses_check_suites_and_ignores_other_events() {
+        let body = json!({
+            "action": "completed",
+            "check_suite": { "pull_requests": [{ "number": 4 }, { "number": 9 }] },
+            "repository": { "html_url": "https://github.com/owner/repo" },
+        })
+        .to_string();
+        let event = parse_event(GitHostKind::GitHub, "check_suite", body.as_bytes()).unwrap();
+        assert_eq!(
+            event.action,
+            PrWebhookAction::ChecksCompleted {
+                numbers: vec![4, 9]
+            }
+        );
+
+        let body = json!({
+            "object_attributes": { "status": "failed" },
+            "merge_request": { "iid": 5 },
+            "project": { "web_url": "https://gitlab.com/group/repo" },
+        })
+        .to_string();
+        let event = parse_event(GitHostKind::GitLab, "Pipeline Hook", body.as_bytes()).unwrap();
+        assert_eq!(
+            event.action,
+            PrWebhookAction::ChecksCompleted { numbers: vec![5] }
+        );
+
+        let ping =
+            json!({ "zen": "hi", "repository": { "html_url": "https://github.com/owner/repo" } })
+                .to_string();
+        let event = parse_event(GitHostKind::GitHub, "ping", ping.as_bytes()).unwrap();
+        assert_eq!(event.action, PrWebhookAction::Ignored);
+        assert_eq!(event.repo_key.as_deref(), Some("github.com/owner/repo"));
+    }
+}
@@ -0,0 +1,311 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::Utc;
+use ring::{
+    aead::{Aad, CHACHA20_POLY1305, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+const KEY_LEN: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum SecretStoreError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(
+        "Invalid secret name '{0}': use letters, digits and underscores, not starting with a digit"
+    )]
+    InvalidName(String),
+    #[error("Secret store could not be read with its key")]
+    Unreadable,
+    #[error("Secrets could not be sealed")]
+    Seal,
+    #[error("Secret store key could not be generated")]
+    KeyGeneration,
+}
+
+/// What is written to disk: the secrets as JSON, sealed with ChaCha20-Poly1305 under
+/// the key from the neighbouring key file so they cannot be read at a glance
+#[derive(Serialize, Deserialize)]
+struct SealedSecrets {
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Secret values used in executor environment variables.
+///
+/// The store does not protect secrets from anyone who can read the data directory: its
+/// key is kept next to it, readable by the same user. Sealing only keeps values from
+/// showing up in plain text when the file is viewed or searched. Both files are
+/// readable by the current user alone.
+pub struct SecretStore {
+    path: PathBuf,
+    key: [u8; KEY_LEN],
+    secrets: RwLock<BTreeMap<String, String>>,
+}
+
+impl SecretStore {
+    /// Open the store at `path`, creating its key at `key_path` on first use
+    pub fn open(path: PathBuf, key_path: &Path) -> Result<Self, SecretStoreError> {
+        let key = load_or_create_key(key_path)?;
+        let secrets = if path.exists() {
+            let sealed: SealedSecrets = serde_json::from_slice(&std::fs::read(&path)?)?;
+            open_sealed(&key, &sealed)?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self {
+            path,
+            key,
+            secrets: RwLock::new(secrets),
+        })
+    }
+
+    /// Open the store like [`SecretStore::open`], but start over with an empty store
+    /// when the existing one cannot be read, e.g. because its key was lost. The
+    /// unreadable store and key are moved aside, not deleted.
+    pub fn open_or_reset(path: PathBuf, key_path: &Path) -> Result<Self, SecretStoreError> {
+        match Self::open(path.clone(), key_path) {
+            Err(e @ (SecretStoreError::Unreadable | SecretStoreError::Json(_))) => {
+                let suffix = format!("unreadable-{}", Utc::now().format("%Y%m%d%H%M%S"));
+                for file in [path.as_path(), key_path] {
+                    if file.exists() {
+                        std::fs::rename(file, aside_path(file, &suffix))?;
+                    }
+                }
+                tracing::error!(
+                    "Secret store {} could not be read ({}); moved it aside with the suffix '{}' and starting with an empty store",
+                    path.display(),
+                    e,
+                    suffix
+                );
+                Self::open(path, key_path)
+            }
+            result => result,
+        }
+    }
+
+    /// Names of the stored secrets; values are never listed
+    pub async fn names(&self) -> Vec<String> {
+        self.secrets.read().await.keys().cloned().collect()
+    }
+
+    pub async fn values(&self) -> HashMap<String, String> {
+        self.secrets
+            .read()
+            .await
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    pub async fn set(&self, name: &str, value: &str) -> Result<(), SecretStoreError> {
+        if !is_valid_env_name(name) {
+            return Err(SecretStoreError::InvalidName(name.to_string()));
+        }
+        let mut secrets = self.secrets.write().await;
+        let mut updated = secrets.clone();
+        updated.insert(name.to_string(), value.to_string());
+        self.persist(&updated)?;
+        *secrets = updated;
+        Ok(())
+    }
+
+    /// Remove a secret. Returns whether it existed.
+    pub async fn delete(&self, name: &str) -> Result<bool, SecretStoreError> {
+        let mut secrets = self.secrets.write().await;
+        if !secrets.contains_key(name) {
+            return Ok(false);
+        }
+        let mut updated = secrets.clone();
+        updated.remove(name);
+        self.persist(&updated)?;
+        *secrets = updated;
+        Ok(true)
+    }
+
+    fn persist(&self, secrets: &BTreeMap<String, String>) -> Result<(), SecretStoreError> {
+        let sealed = seal(&self.key, secrets)?;
+        write_private(&self.path, &serde_json::to_vec_pretty(&sealed)?)?;
+        Ok(())
+    }
+}
+
+/// Secret and variable names follow environment variable naming
+pub fn is_valid_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn aside_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{suffix}"));
+    PathBuf::from(name)
+}
+
+fn load_or_create_key(key_path: &Path) -> Result<[u8; KEY_LEN], SecretStoreError> {
+    if key_path.exists() {
+        return std::fs::read(key_path)?
+            .try_into()
+            .map_err(|_| SecretStoreError::Unreadable);
+    }
+    let mut key = [0u8; KEY_LEN];
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|_| SecretStoreError::KeyGeneration)?;
+    write_private(key_path, &key)?;
+    Ok(key)
+}
+
+fn cipher(key: &[u8; KEY_LEN]) -> LessSafeKey {
+    LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, key).expect("key has the cipher's length"))
+}
+
+fn seal(
+    key: &[u8; KEY_LEN],
+    secrets: &BTreeMap<String, String>,
+) -> Result<SealedSecrets, SecretStoreError> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| SecretStoreError::KeyGeneration)?;
+    let mut data = serde_json::to_vec(secrets)?;
+    cipher(key)
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+        .map_err(|_| SecretStoreError::Seal)?;
+    Ok(SealedSecrets {
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(data),
+    })
+}
+
+fn open_sealed(
+    key: &[u8; KEY_LEN],
+    sealed: &SealedSecrets,
+) -> Result<BTreeMap<String, String>, SecretStoreError> {
+    let nonce: [u8; NONCE_LEN] = STANDARD
+        .decode(&sealed.nonce)
+        .ok()
+        .and_then(|n| n.try_into().ok())
+        .ok_or(SecretStoreError::Unreadable)?;
+    let mut data = STANDARD
+        .decode(&sealed.ciphertext)
+        .map_err(|_| SecretStoreError::Unreadable)?;
+    let plaintext = cipher(key)
+        .open_in_place(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+        .map_err(|_| SecretStoreError::Unreadable)?;
+    Ok(serde_json::from_slice(plaintext)?)
+}
+
+/// Write `bytes` to `path` through a temporary file readable only by the current user
+fn write_private(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    let mut file = {
+        let mut opts = std::fs::OpenOptions::new();
+        opts.create(true).truncate(true).write(true);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            opts.mode(0o600);
+        }
+
+        opts.open(&tmp)?
+    };
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn secrets_are_sealed_on_disk_and_survive_reopening() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("secrets.json");
+        let key_path = dir.path().join("secrets.key");
+
+        let store = SecretStore::open(path.clone(), &key_path).unwrap();
+        store.set("OPENAI_API_KEY", "sk-very-secret").await.unwrap();
+        store.set("PROXY_TOKEN", "tok-123456").await.unwrap();
+        assert!(store.delete("PROXY_TOKEN").await.unwrap());
+        assert!(!store.delete("PROXY_TOKEN").await.unwrap());
+
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("sk-very-secret"));
+        assert!(!on_disk.contains("OPENAI_API_KEY"));
+
+        let reopened = SecretStore::open(path.clone(), &key_path).unwrap();
+        assert_eq!(reopened.names().await, ["OPENAI_API_KEY"]);
+        assert_eq!(
+            reopened
+                .values()
+                .await
+                .get("OPENAI_API_KEY")
+                .map(String::as_str),
+            Some("sk-very-secret")
+        );
+
+        // Another key cannot read the store
+        let other_key = dir.path().join("other.key");
+        assert!(matches!(
+            SecretStore::open(path, &other_key),
+            Err(SecretStoreError::Unreadable)
+        ));
+    }
+
+    #[tokio::test]
+    async fn store_without_its_key_is_moved_aside() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("secrets.json");
+        let key_path = dir.path().join("secrets.key");
+        let store = SecretStore::open(path.clone(), &key_path).unwrap();
+        store.set("OPENAI_API_KEY", "sk-very-secret").await.unwrap();
+        std::fs::remove_file(&key_path).unwrap();
+
+        let reset = SecretStore::open_or_reset(path.clone(), &key_path).unwrap();
+        assert!(reset.names().await.is_empty());
+        let aside: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with("secrets.json.unreadable-"))
+            .collect();
+        assert_eq!(aside.len(), 1);
+
+        reset.set("PROXY_TOKEN", "tok-123456").await.unwrap();
+        let reopened = SecretStore::open(path, &key_path).unwrap();
+        assert_eq!(reopened.names().await, ["PROXY_TOKEN"]);
+    }
+
+    #[tokio::test]
+    async fn names_must_be_environment_variable_names() {
+        let dir = TempDir::new().unwrap();
+        let store =
+            SecretStore::open(dir.path().join("secrets.json"), &dir.path().join("key")).unwrap();
+        for name in ["", "1TOKEN", "MY-TOKEN", "MY TOKEN"] {
+            assert!(matches!(
+                store.set(name, "value").await,
+                Err(SecretStoreError::InvalidName(_))
+            ));
+        }
+        store.set("_TOKEN_2", "value").await.unwrap();
+    }
+}
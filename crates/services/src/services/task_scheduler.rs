@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use db::{
+    DBService,
+    models::{
+        task::Task,
+        task_attempt::{CreateTaskAttempt, TaskAttempt, TaskAttemptError},
+        task_dependency::TaskAutoStart,
+    },
+};
+use sqlx::error::Error as SqlxError;
+use thiserror::Error;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::services::container::{ContainerError, ContainerService};
+
+#[derive(Debug, Error)]
+enum TaskSchedulerError {
+    #[error(transparent)]
+    TaskAttemptError(#[from] TaskAttemptError),
+    #[error(transparent)]
+    Container(#[from] ContainerError),
+    #[error(transparent)]
+    Sqlx(#[from] SqlxError),
+}
+
+/// Service that starts attempts for blocked tasks once every task they depend
+/// on is done or has a merge.
+pub struct TaskSchedulerService<C> {
+    db: DBService,
+    container: C,
+    poll_interval: Duration,
+}
+
+impl<C> TaskSchedulerService<C>
+where
+    C: ContainerService + Send + Sync + 'static,
+{
+    pub async fn spawn(db: DBService, container: C) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            container,
+            poll_interval: Duration::from_secs(10),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting task scheduler service with interval {:?}",
+            self.poll_interval
+        );
+
+        let mut interval = interval(self.poll_interval);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.start_ready_tasks().await {
+                error!("Error starting unblocked tasks: {}", e);
+            }
+        }
+    }
+
+    async fn start_ready_tasks(&self) -> Result<(), TaskSchedulerError> {
+        let stale = TaskAutoStart::delete_stale(&self.db.pool).await?;
+        if stale > 0 {
+            debug!("Discarded {} stale task auto starts", stale);
+        }
+
+        let ready = TaskAutoStart::find_ready(&self.db.pool).await?;
+        if ready.is_empty() {
+            return Ok(());
+        }
+
+        info!("Starting {} unblocked tasks", ready.len());
+
+        for auto_start in ready {
+            if let Err(e) = self.start_task(&auto_start).await {
+                error!("Error auto-starting task {}: {}", auto_start.task_id, e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn start_task(&self, auto_start: &TaskAutoStart) -> Result<(), TaskSchedulerError> {
+        // Claim the start first so a slow start can never be triggered twice
+        if !TaskAutoStart::delete(&self.db.pool, auto_start.task_id).await? {
+            return Ok(());
+        }
+
+        let Some(task) = Task::find_by_id(&self.db.pool, auto_start.task_id).await? else {
+            return Ok(());
+        };
+
+        let attempt_id = Uuid::new_v4();
+        let git_branch_name = self
+            .container
+            .git_branch_from_task_attempt(&attempt_id, &task.title)
+            .await;
+
+        let task_attempt = match TaskAttempt::create(
+            &self.db.pool,
+            &CreateTaskAttempt {
+                executor: auto_start.executor_profile_id.executor,
+                base_branch: auto_start.base_branch.clone(),
+                branch: git_branch_name,
+            },
+            attempt_id,
+            task.id,
+        )
+        .await
+        {
+            Ok(task_attempt) => task_attempt,
+            Err(e) => {
+                self.release(auto_start).await;
+                return Err(e.into());
+            }
+        };
+
+        if let Err(e) = self
+            .container
+            .start_attempt(&task_attempt, auto_start.executor_profile_id.clone())
+            .await
+        {
+            // Leave no half-started attempt behind: the task only counts as ready again
+            // while it has no attempts
+            if let Err(e) = self.container.delete(&task_attempt).await {
+                warn!(
+                    "Failed to clean up auto-started attempt {}: {}",
+                    task_attempt.id, e
+                );
+            }
+            if let Err(e) = self.container.delete_branches(&task_attempt).await {
+                warn!(
+                    "Failed to delete branch of auto-started attempt {}: {}",
+                    task_attempt.id, e
+                );
+            }
+            if let Err(e) = TaskAttempt::delete(&self.db.pool, task_attempt.id).await {
+                error!(
+                    "Failed to delete auto-started attempt {}: {}",
+                    task_attempt.id, e
+                );
+            }
+            self.release(auto_start).await;
+            return Err(e.into());
+        }
+
+        info!(
+            "Auto-started attempt {} for unblocked task {}",
+            task_attempt.id, task.id
+        );
+        Ok(())
+    }
+
+    /// Put back a claimed auto start whose attempt could not be started, so it is
+    /// retried on a later tick
+    async fn release(&self, auto_start: &TaskAutoStart) {
+        if let Err(e) = TaskAutoStart::upsert(
+            &self.db.pool,
+            auto_start.task_id,
+            &auto_start.executor_profile_id,
+            &auto_start.base_branch,
+        )
+        .await
+        {
+            error!(
+                "Failed to restore auto start of task {}: {}",
+                auto_start.task_id, e
+            );
+        }
+    }
+}
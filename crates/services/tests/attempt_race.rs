@@ -0,0 +1,362 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use db::{
+    DBService,
+    models::{
+        execution_process::{ExecutionContext, ExecutionProcess, ExecutionProcessStatus},
+        project::{CreateProject, Project},
+        task::{CreateTask, Task},
+        task_attempt::TaskAttempt,
+        task_attempt_race::TaskAttemptRace,
+    },
+};
+use executors::{
+    actions::{ExecutorAction, ExecutorActionType, script::ScriptRequest},
+    executors::BaseCodingAgent,
+    profile::ExecutorProfileId,
+};
+use services::services::{
+    attempt_race::{self, AttemptRaceError},
+    container::{ContainerError, ContainerRef, ContainerService, SpawnedScript},
+    git::GitService,
+    secrets::SecretStore,
+    share::SharePublisher,
+    worktree_manager::WorktreeManager,
+};
+use sqlx::sqlite::SqlitePoolOptions;
+use tempfile::TempDir;
+use tokio::sync::RwLock;
+use utils::{diff::Diff, log_msg::LogMsg, msg_store::MsgStore};
+use uuid::Uuid;
+
+/// Container that only manages worktrees: executions are recorded but never run, and
+/// starting `failing_executor` fails.
+struct WorktreeContainer {
+    db: DBService,
+    git: GitService,
+    msg_stores: Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>>,
+    secrets: Arc<SecretStore>,
+    worktrees_dir: PathBuf,
+    failing_executor: Option<BaseCodingAgent>,
+}
+
+impl WorktreeContainer {
+    async fn project(&self, task_attempt: &TaskAttempt) -> Project {
+        let task = task_attempt
+            .parent_task(&self.db.pool)
+            .await
+            .unwrap()
+            .unwrap();
+        task.parent_project(&self.db.pool).await.unwrap().unwrap()
+    }
+}
+
+#[async_trait]
+impl ContainerService for WorktreeContainer {
+    fn msg_stores(&self) -> &Arc<RwLock<HashMap<Uuid, Arc<MsgStore>>>> {
+        &self.msg_stores
+    }
+
+    fn db(&self) -> &DBService {
+        &self.db
+    }
+
+    fn git(&self) -> &GitService {
+        &self.git
+    }
+
+    fn share_publisher(&self) -> Option<&SharePublisher> {
+        None
+    }
+
+    fn secrets(&self) -> &Arc<SecretStore> {
+        &self.secrets
+    }
+
+    fn task_attempt_to_current_dir(&self, task_attempt: &TaskAttempt) -> PathBuf {
+        PathBuf::from(task_attempt.container_ref.clone().unwrap_or_default())
+    }
+
+    async fn create(&self, task_attempt: &TaskAttempt) -> Result<ContainerRef, ContainerError> {
+        let project = self.project(task_attempt).await;
+        let worktree_path = self.worktrees_dir.join(task_attempt.id.to_string());
+        WorktreeManager::create_worktree(
+            &project.git_repo_path,
+            &task_attempt.branch,
+            &worktree_path,
+            &task_attempt.target_branch,
+            true,
+        )
+        .await?;
+        let container_ref = worktree_path.to_string_lossy().to_string();
+        TaskAttempt::update_container_ref(&self.db.pool, task_attempt.id, &container_ref).await?;
+        Ok(container_ref)
+    }
+
+    async fn delete_inner(&self, task_attempt: &TaskAttempt) -> Result<(), ContainerError> {
+        if let Some(container_ref) = &task_attempt.container_ref {
+            let project = self.project(task_attempt).await;
+            WorktreeManager::cleanup_worktree(
+                Path::new(container_ref),
+                Some(&project.git_repo_path),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn ensure_container_exists(
+        &self,
+        task_attempt: &TaskAttempt,
+    ) -> Result<ContainerRef, ContainerError> {
+        Ok(task_attempt.container_ref.clone().unwrap_or_default())
+    }
+
+    async fn is_container_clean(&self, _: &TaskAttempt) -> Result<bool, ContainerError> {
+        Ok(true)
+    }
+
+    async fn start_execution_inner(
+        &self,
+        _: &TaskAttempt,
+        _: &ExecutionProcess,
+        executor_action: &ExecutorAction,
+    ) -> Result<(), ContainerError> {
+        if let ExecutorActionType::CodingAgentInitialRequest(request) = executor_action.typ()
+            && Some(request.executor_profile_id.executor) == self.failing_executor
+        {
+            return Err(ContainerError::Other(anyhow!("executor failed to start")));
+        }
+        Ok(())
+    }
+
+    async fn stop_execution(
+        &self,
+        execution_process: &ExecutionProcess,
+        status: ExecutionProcessStatus,
+    ) -> Result<(), ContainerError> {
+        ExecutionProcess::update_completion(&self.db.pool, execution_process.id, status, None)
+            .await?;
+        Ok(())
+    }
+
+    async fn try_commit_changes(&self, _: &ExecutionContext) -> Result<bool, ContainerError> {
+        Ok(false)
+    }
+
+    async fn copy_project_files(&self, _: &Path, _: &Path, _: &str) -> Result<(), ContainerError> {
+        Ok(())
+    }
+
+    async fn stream_diff(
+        &self,
+        _: &TaskAttempt,
+        _: bool,
+    ) -> Result<futures::stream::BoxStream<'static, Result<LogMsg, std::io::Error>>, ContainerError>
+    {
+        unimplemented!("diffs are not streamed in these tests")
+    }
+
+    async fn diff_stats(&self, _: &TaskAttempt) -> Result<Vec<Diff>, ContainerError> {
+        unimplemented!("diffs are not read in these tests")
+    }
+
+    async fn spawn_script(
+        &self,
+        _: &TaskAttempt,
+        _: &Path,
+        _: &ScriptRequest,
+    ) -> Result<SpawnedScript, ContainerError> {
+        unimplemented!("scripts are not run in these tests")
+    }
+
+    async fn git_branch_prefix(&self) -> String {
+        "race".to_string()
+    }
+}
+
+struct Fixture {
+    _root: TempDir,
+    repo_path: PathBuf,
+    task: Task,
+}
+
+async fn setup(failing_executor: Option<BaseCodingAgent>) -> (WorktreeContainer, Fixture) {
+    let root = TempDir::new().unwrap();
+    let repo_path = root.path().join("repo");
+    let git = GitService::new();
+    git.initialize_repo_with_main_branch(&repo_path).unwrap();
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::migrate!("../db/migrations").run(&pool).await.unwrap();
+
+    let project_id = Uuid::new_v4();
+    Project::create(
+        &pool,
+        &CreateProject {
+            name: "project".to_string(),
+            git_repo_path: repo_path.to_string_lossy().to_string(),
+            use_existing_repo: true,
+            setup_script: None,
+            dev_script: None,
+            cleanup_script: None,
+            copy_files: None,
+        },
+        project_id,
+    )
+    .await
+    .unwrap();
+    let task = Task::create(
+        &pool,
+        &CreateTask::from_title_description(project_id, "Add checkout".to_string(), None),
+        Uuid::new_v4(),
+    )
+    .await
+    .unwrap();
+
+    let secrets = SecretStore::open(
+        root.path().join("secrets.json"),
+        &root.path().join("secrets.key"),
+    )
+    .unwrap();
+    let container = WorktreeContainer {
+        db: DBService { pool },
+        git,
+        msg_stores: Arc::default(),
+        secrets: Arc::new(secrets),
+        worktrees_dir: root.path().join("worktrees"),
+        failing_executor,
+    };
+    let fixture = Fixture {
+        _root: root,
+        repo_path,
+        task,
+    };
+    (container, fixture)
+}
+
+fn executors() -> Vec<ExecutorProfileId> {
+    vec![
+        ExecutorProfileId::new(BaseCodingAgent::ClaudeCode),
+        ExecutorProfileId::new(BaseCodingAgent::Codex),
+    ]
+}
+
+fn branch_exists(container: &WorktreeContainer, fixture: &Fixture, attempt: &TaskAttempt) -> bool {
+    container
+        .git
+        .local_branch_exists(&fixture.repo_path, &attempt.branch)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn start_race_starts_one_attempt_per_executor() {
+    let (container, fixture) = setup(None).await;
+
+    let (race, attempts) =
+        attempt_race::start_race(&container, &fixture.task, &executors(), "main")
+            .await
+            .unwrap();
+
+    assert_eq!(attempts.len(), 2);
+    assert_eq!(race.attempts(&container.db.pool).await.unwrap().len(), 2);
+    for attempt in &attempts {
+        assert!(branch_exists(&container, &fixture, attempt));
+        let processes =
+            ExecutionProcess::find_by_task_attempt_id(&container.db.pool, attempt.id, false)
+                .await
+                .unwrap();
+        assert_eq!(processes.len(), 1);
+    }
+
+    let too_few =
+        attempt_race::start_race(&container, &fixture.task, &executors()[..1], "main").await;
+    assert!(matches!(too_few, Err(AttemptRaceError::TooFewExecutors)));
+}
+
+#[tokio::test]
+async fn start_race_rolls_back_every_attempt_when_one_fails_to_start() {
+    let (container, fixture) = setup(Some(BaseCodingAgent::Codex)).await;
+
+    let result = attempt_race::start_race(&container, &fixture.task, &executors(), "main").await;
+    assert!(matches!(result, Err(AttemptRaceError::Container(_))));
+
+    let pool = &container.db.pool;
+    assert!(
+        TaskAttempt::fetch_all(pool, Some(fixture.task.id))
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(
+        TaskAttemptRace::find_by_task_id(pool, fixture.task.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let branches = container.git.get_all_branches(&fixture.repo_path).unwrap();
+    assert!(
+        branches
+            .iter()
+            .all(|branch| !branch.name.starts_with("race/"))
+    );
+    assert_eq!(
+        std::fs::read_dir(&container.worktrees_dir).unwrap().count(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn picking_a_winner_removes_the_losers_worktrees_and_branches() {
+    let (container, fixture) = setup(None).await;
+    let (race, attempts) =
+        attempt_race::start_race(&container, &fixture.task, &executors(), "main")
+            .await
+            .unwrap();
+    let (winner, loser) = (&attempts[0], &attempts[1]);
+
+    let not_in_race = attempt_race::pick_race_winner(&container, &race, Uuid::new_v4()).await;
+    assert!(matches!(not_in_race, Err(AttemptRaceError::NotInRace)));
+
+    let race = attempt_race::pick_race_winner(&container, &race, winner.id)
+        .await
+        .unwrap();
+    assert_eq!(race.winner_attempt_id, Some(winner.id));
+
+    let pool = &container.db.pool;
+    let entries = race.entries(pool).await.unwrap();
+    assert!(
+        entries
+            .iter()
+            .all(|e| e.archived == (e.task_attempt_id == loser.id))
+    );
+
+    let loser = TaskAttempt::find_by_id(pool, loser.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(loser.worktree_deleted);
+    assert!(!Path::new(loser.container_ref.as_deref().unwrap()).exists());
+    assert!(!branch_exists(&container, &fixture, &loser));
+
+    let winner = TaskAttempt::find_by_id(pool, winner.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!winner.worktree_deleted);
+    assert!(Path::new(winner.container_ref.as_deref().unwrap()).exists());
+    assert!(branch_exists(&container, &fixture, &winner));
+
+    let again = attempt_race::pick_race_winner(&container, &race, winner.id).await;
+    assert!(matches!(again, Err(AttemptRaceError::WinnerAlreadyPicked)));
+}
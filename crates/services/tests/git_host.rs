@@ -0,0 +1,410 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, patch, put},
+};
+use db::models::{
+    merge::MergeStatus,
+    pr_check_summary::{CheckRun, CheckState},
+};
+use serde_json::{Value, json};
+use services::services::{
+    config::GitHostConfig,
+    git_host::{
+        GitHost, GitHostError, GitHostKind, RemoteUrl, from_remote_url, gitea::GiteaHost,
+        gitlab::GitLabHost, kind_for_remote_url,
+    },
+    github_service::CreatePrRequest,
+};
+
+/// Serve `router` on a random local port and return its base URL
+async fn serve(router: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{addr}")
+}
+
+fn pr_request() -> CreatePrRequest {
+    CreatePrRequest {
+        title: "Add feature".to_string(),
+        body: Some("Details".to_string()),
+        head_branch: "vk/feature".to_string(),
+        base_branch: "main".to_string(),
+    }
+}
+
+#[test]
+fn parses_remotes_and_detects_hosts() {
+    let remote = RemoteUrl::parse("git@gitlab.example.com:group/sub/repo.git").unwrap();
+    assert_eq!(remote.host, "gitlab.example.com");
+    assert_eq!(remote.segments, ["group", "sub", "repo"]);
+
+    let remote = RemoteUrl::parse("https://git.internal:3000/owner/repo/pulls/7").unwrap();
+    assert_eq!(remote.base_url(), "https://git.internal:3000");
+    assert_eq!(remote.segments[..2], ["owner", "repo"]);
+
+    let ssh = RemoteUrl::parse("ssh://git@git.internal:2222/owner/repo.git").unwrap();
+    assert_eq!(ssh.host, "git.internal");
+
+    assert_eq!(
+        kind_for_remote_url("https://github.com/owner/repo.git", &[]),
+        Some(GitHostKind::GitHub)
+    );
+    assert_eq!(
+        kind_for_remote_url("https://gitlab.com/group/repo/-/merge_requests/3", &[]),
+        Some(GitHostKind::GitLab)
+    );
+    assert_eq!(
+        kind_for_remote_url("git@git.internal:owner/repo.git", &[]),
+        None
+    );
+    // A hostname that merely mentions a product is not that product
+    assert_eq!(
+        kind_for_remote_url("https://gitlab.attacker.example/owner/repo.git", &[]),
+        None
+    );
+    assert_eq!(
+        kind_for_remote_url("git@my-gitea.example:owner/repo.git", &[]),
+        None
+    );
+
+    let hosts = [GitHostConfig {
+        host: "git.internal".to_string(),
+        kind: GitHostKind::Gitea,
+        token: None,
+        api_url: None,
+    }];
+    assert_eq!(
+        kind_for_remote_url("git@git.internal:owner/repo.git", &hosts),
+        Some(GitHostKind::Gitea)
+    );
+}
+
+#[tokio::test]
+async fn gitlab_creates_polls_and_lists_merge_requests() {
+    let seen = Arc::new(Mutex::new(Vec::<String>::new()));
+
+    async fn create(
+        State(seen): State<Arc<Mutex<Vec<String>>>>,
+        Path(project): Path<String>,
+        headers: HeaderMap,
+        Json(body): Json<Value>,
+    ) -> Json<Value> {
+        seen.lock().unwrap().push(format!(
+            "{project} {} {}",
+            headers["PRIVATE-TOKEN"].to_str().unwrap(),
+            body["source_branch"].as_str().unwrap()
+        ));
+        Json(json!({
+            "iid": 12,
+            "web_url": "https://gitlab.example.com/group/sub/repo/-/merge_requests/12",
+            "state": "opened",
+            "merged_at": null,
+            "merge_commit_sha": null,
+        }))
+    }
+
+    async fn show(Path((_project, iid)): Path<(String, i64)>) -> Json<Value> {
+        Json(json!({
+            "iid": iid,
+            "web_url": format!("https://gitlab.example.com/group/sub/repo/-/merge_requests/{iid}"),
+            "state": "merged",
+            "merged_at": "2025-11-20T10:00:00Z",
+            "merge_commit_sha": null,
+            "squash_commit_sha": "abc123",
+        }))
+    }
+
+    async fn list(Query(query): Query<Vec<(String, String)>>) -> Json<Value> {
+        assert!(query.contains(&("source_branch".to_string(), "vk/feature".to_string())));
+        Json(json!([
+            { "iid": 13, "web_url": "u13", "state": "opened" },
+            { "iid": 9, "web_url": "u9", "state": "closed" },
+        ]))
+    }
+
+    let base = serve(
+        Router::new()
+            .route(
+                "/api/v4/projects/{project}/merge_requests",
+                get(list).post(create),
+            )
+            .route("/api/v4/projects/{project}/merge_requests/{iid}", get(show))
+            .with_state(seen.clone()),
+    )
+    .await;
+    let host = GitLabHost::new(
+        format!("{base}/api/v4"),
+        "group/sub/repo".to_string(),
+        Some("secret".to_string()),
+    );
+
+    let created = host.create_pr(&pr_request()).await.unwrap();
+    assert_eq!(created.number, 12);
+    assert!(matches!(created.status, MergeStatus::Open));
+    assert_eq!(
+        seen.lock().unwrap().as_slice(),
+        ["group/sub/repo secret vk/feature"]
+    );
+
+    let merged = host.pr_status(12).await.unwrap();
+    assert!(matches!(merged.status, MergeStatus::Merged));
+    assert_eq!(merged.merge_commit_sha.as_deref(), Some("abc123"));
+    assert!(merged.merged_at.is_some());
+
+    let listed = host.list_prs_for_branch("vk/feature").await.unwrap();
+    assert_eq!(listed.len(), 2);
+    assert!(matches!(listed[1].status, MergeStatus::Closed));
+}
+
+#[tokio::test]
+async fn configured_tokens_are_read_from_the_secret_store() {
+    async fn show(headers: HeaderMap, Path((_project, iid)): Path<(String, i64)>) -> Json<Value> {
+        assert_eq!(headers["PRIVATE-TOKEN"].to_str().unwrap(), "glpat-stored");
+        Json(json!({
+            "iid": iid,
+            "web_url": format!("https://gitlab.internal/group/repo/-/merge_requests/{iid}"),
+            "state": "opened",
+        }))
+    }
+
+    let base =
+        serve(Router::new().route("/api/v4/projects/{project}/merge_requests/{iid}", get(show)))
+            .await;
+    let hosts = [GitHostConfig {
+        host: "gitlab.internal".to_string(),
+        kind: GitHostKind::GitLab,
+        token: Some("{{secret:INTERNAL_GITLAB_TOKEN}}".to_string()),
+        api_url: Some(format!("{base}/api/v4")),
+    }];
+    let pr_url = "https://gitlab.internal/group/repo/-/merge_requests/5";
+
+    let secrets = HashMap::from([(
+        "INTERNAL_GITLAB_TOKEN".to_string(),
+        "glpat-stored".to_string(),
+    )]);
+    let host = from_remote_url(pr_url, &hosts, &secrets).unwrap();
+    assert_eq!(host.pr_status(5).await.unwrap().number, 5);
+
+    assert!(matches!(
+        from_remote_url(pr_url, &hosts, &HashMap::new()),
+        Err(GitHostError::Token { .. })
+    ));
+}
+
+#[tokio::test]
+async fn gitea_filters_pull_requests_by_head_branch_across_pages() {
+    async fn list(Query(query): Query<Vec<(String, String)>>) -> Json<Value> {
+        let page = query
+            .iter()
+            .find(|(k, _)| k == "page")
+            .map(|(_, v)| v.parse::<usize>().unwrap())
+            .unwrap();
+        let pr = |number: usize, head: &str| {
+            json!({
+                "number": number,
+                "html_url": format!("https://gitea.example.com/owner/repo/pulls/{number}"),
+                "state": "closed",
+                "merged": number == 60,
+                "head": { "ref": head },
+            })
+        };
+        // A full first page forces a second request
+        let prs: Vec<Value> = match page {
+            1 => (1..=50).map(|n| pr(n, "other")).collect(),
+            2 => vec![pr(60, "vk/feature"), pr(61, "other")],
+            _ => vec![],
+        };
+        Json(Value::Array(prs))
+    }
+
+    async fn show(headers: HeaderMap) -> Result<Json<Value>, StatusCode> {
+        if headers.get("Authorization").and_then(|v| v.to_str().ok()) != Some("token secret") {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        Ok(Json(json!({
+            "number": 5,
+            "html_url": "https://gitea.example.com/owner/repo/pulls/5",
+            "state": "open",
+            "merged": false,
+            "head": { "ref": "vk/feature" },
+        })))
+    }
+
+    let base = serve(
+        Router::new()
+            .route("/api/v1/repos/owner/repo/pulls", get(list))
+            .route("/api/v1/repos/owner/repo/pulls/5", get(show)),
+    )
+    .await;
+
+    let host = GiteaHost::new(
+        format!("{base}/api/v1"),
+        "owner".to_string(),
+        "repo".to_string(),
+        Some("secret".to_string()),
+    );
+    let listed = host.list_prs_for_branch("vk/feature").await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].number, 60);
+    assert!(matches!(listed[0].status, MergeStatus::Merged));
+
+    let open = host.pr_status(5).await.unwrap();
+    assert!(matches!(open.status, MergeStatus::Open));
+
+    let anonymous = GiteaHost::new(
+        format!("{base}/api/v1"),
+        "owner".to_string(),
+        "repo".to_string(),
+        None,
+    );
+    let err = anonymous.pr_status(5).await.unwrap_err();
+    assert!(matches!(err, GitHostError::Api { status: 401, .. }));
+}
+
+#[tokio::test]
+async fn gitlab_and_gitea_aggregate_pr_checks() {
+    async fn merge_request() -> Json<Value> {
+        Json(json!({ "sha": "abc123", "head_pipeline": { "id": 77 } }))
+    }
+
+    async fn jobs(Path((_project, pipeline)): Path<(String, i64)>) -> Json<Value> {
+        assert_eq!(pipeline, 77);
+        Json(json!([
+            { "name": "lint", "status": "success", "web_url": "https://gitlab.example.com/g/r/-/jobs/1" },
+            { "name": "flaky", "status": "failed", "allow_failure": true, "web_url": "https://gitlab.example.com/g/r/-/jobs/2" },
+            { "name": "test", "status": "failed", "web_url": "https://gitlab.example.com/g/r/-/jobs/3" },
+        ]))
+    }
+
+    async fn trace(Path((_project, job)): Path<(String, i64)>) -> String {
+        format!("job {job} failed")
+    }
+
+    async fn pull() -> Json<Value> {
+        Json(json!({
+            "number": 5,
+            "html_url": "https://gitea.example.com/owner/repo/pulls/5",
+            "state": "open",
+            "head": { "ref": "vk/feature", "sha": "def456" },
+        }))
+    }
+
+    async fn status() -> Json<Value> {
+        Json(json!({
+            "statuses": [
+                { "context": "ci/build", "status": "success", "target_url": "" },
+                { "context": "ci/test", "status": "pending", "target_url": "https://ci.example.com/2" },
+            ],
+        }))
+    }
+
+    let base = serve(
+        Router::new()
+            .route(
+                "/api/v4/projects/{project}/merge_requests/12",
+                get(merge_request),
+            )
+            .route(
+                "/api/v4/projects/{project}/pipelines/{pipeline}/jobs",
+                get(jobs),
+            )
+            .route("/api/v4/projects/{project}/jobs/{job}/trace", get(trace))
+            .route("/api/v1/repos/owner/repo/pulls/5", get(pull))
+            .route(
+                "/api/v1/repos/owner/repo/commits/def456/status",
+                get(status),
+            ),
+    )
+    .await;
+
+    let gitlab = GitLabHost::new(format!("{base}/api/v4"), "g/r".to_string(), None);
+    let checks = gitlab.pr_checks(12).await.unwrap();
+    assert_eq!(checks.head_sha.as_deref(), Some("abc123"));
+    assert_eq!(checks.state, CheckState::Failure);
+    let failed: Vec<&CheckRun> = checks.failed().collect();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].name, "test");
+    assert_eq!(
+        gitlab.failed_check_log(failed[0]).await.unwrap().as_deref(),
+        Some("job 3 failed")
+    );
+
+    let gitea = GiteaHost::new(
+        format!("{base}/api/v1"),
+        "owner".to_string(),
+        "repo".to_string(),
+        None,
+    );
+    let checks = gitea.pr_checks(5).await.unwrap();
+    assert_eq!(checks.head_sha.as_deref(), Some("def456"));
+    assert_eq!(checks.state, CheckState::Pending);
+    assert_eq!(checks.checks[0].url, None);
+    assert_eq!(
+        gitea.failed_check_log(&checks.checks[1]).await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn gitlab_and_gitea_retarget_pull_requests() {
+    let bases = Arc::new(Mutex::new(Vec::<String>::new()));
+
+    async fn update_mr(
+        State(bases): State<Arc<Mutex<Vec<String>>>>,
+        Path((_project, iid)): Path<(String, i64)>,
+        Json(body): Json<Value>,
+    ) -> Json<Value> {
+        bases
+            .lock()
+            .unwrap()
+            .push(body["target_branch"].as_str().unwrap().to_string());
+        Json(json!({ "iid": iid, "web_url": "u", "state": "opened" }))
+    }
+
+    async fn update_pull(
+        State(bases): State<Arc<Mutex<Vec<String>>>>,
+        Json(body): Json<Value>,
+    ) -> Json<Value> {
+        bases
+            .lock()
+            .unwrap()
+            .push(body["base"].as_str().unwrap().to_string());
+        Json(json!({
+            "number": 5,
+            "html_url": "u",
+            "state": "open",
+            "head": { "ref": "vk/child" },
+        }))
+    }
+
+    let base = serve(
+        Router::new()
+            .route(
+                "/api/v4/projects/{project}/merge_requests/{iid}",
+                put(update_mr),
+            )
+            .route("/api/v1/repos/owner/repo/pulls/5", patch(update_pull))
+            .with_state(bases.clone()),
+    )
+    .await;
+
+    let gitlab = GitLabHost::new(format!("{base}/api/v4"), "g/r".to_string(), None);
+    gitlab.update_pr_base(12, "main").await.unwrap();
+    let gitea = GiteaHost::new(
+        format!("{base}/api/v1"),
+        "owner".to_string(),
+        "repo".to_string(),
+        None,
+    );
+    gitea.update_pr_base(5, "develop").await.unwrap();
+
+    assert_eq!(bases.lock().unwrap().as_slice(), ["main", "develop"]);
+}
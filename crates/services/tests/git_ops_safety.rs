@@ -6,7 +6,7 @@ use std::{
 
 use git2::{PushOptions, Repository, build::CheckoutBuilder};
 use services::services::{
-    git::GitService,
+    git::{ConflictOp, GitService, GitServiceError},
     git_cli::{GitCli, GitCliError},
 };
 use tempfile::TempDir;
@@ -708,6 +708,87 @@ fn rebase_refuses_to_abort_existing_rebase() {
     // Note: We do not auto-abort; user should resolve or abort explicitly
 }
 
+#[test]
+fn continue_conflicts_keeps_rebase_while_markers_remain() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_conflict_repo_with_worktree(&td);
+
+    let svc = GitService::new();
+    let _ = svc
+        .rebase_branch(
+            &repo_path,
+            &worktree_path,
+            "new-base",
+            "old-base",
+            "feature",
+        )
+        .expect_err("rebase should stop at conflicts");
+
+    let sides = svc
+        .get_conflict_sides(&worktree_path, &ConflictOp::Rebase, 10)
+        .expect("conflict sides");
+    assert_eq!(sides.ours, vec!["new-base change".to_string()]);
+    assert_eq!(sides.theirs, vec!["feature conflicting change".to_string()]);
+
+    // The conflicted file still holds its markers, so nothing may be continued
+    let res = svc.continue_conflicts(&worktree_path, &[]);
+    assert!(matches!(res, Err(GitServiceError::MergeConflicts(_))));
+    assert!(svc.is_rebase_in_progress(&worktree_path).unwrap());
+    assert_eq!(
+        svc.get_conflicted_files(&worktree_path).unwrap(),
+        vec!["conflict.txt".to_string()]
+    );
+
+    // Staging the file with its markers does not let it through either
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    let mut index = wt_repo.index().unwrap();
+    index.add_path(Path::new("conflict.txt")).unwrap();
+    index.write().unwrap();
+    assert!(svc.get_conflicted_files(&worktree_path).unwrap().is_empty());
+    let res = svc.continue_conflicts(&worktree_path, &["conflict.txt".to_string()]);
+    assert!(res.is_err());
+    assert!(svc.is_rebase_in_progress(&worktree_path).unwrap());
+}
+
+#[test]
+fn continue_conflicts_completes_resolved_rebase() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_conflict_repo_with_worktree(&td);
+
+    let svc = GitService::new();
+    let _ = svc
+        .rebase_branch(
+            &repo_path,
+            &worktree_path,
+            "new-base",
+            "old-base",
+            "feature",
+        )
+        .expect_err("rebase should stop at conflicts");
+
+    write_file(&worktree_path, "conflict.txt", "resolved version\n");
+    svc.continue_conflicts(&worktree_path, &["conflict.txt".to_string()])
+        .expect("continue resolved rebase");
+
+    assert!(!svc.is_rebase_in_progress(&worktree_path).unwrap());
+    assert_eq!(svc.detect_conflict_op(&worktree_path).unwrap(), None);
+    let content = fs::read_to_string(worktree_path.join("conflict.txt")).unwrap();
+    assert_eq!(content, "resolved version\n");
+
+    let repo = Repository::open(&worktree_path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.summary(), Some("feature conflicting change"));
+    let base = Repository::open(&repo_path)
+        .unwrap()
+        .find_branch("new-base", git2::BranchType::Local)
+        .unwrap()
+        .get()
+        .peel_to_commit()
+        .unwrap()
+        .id();
+    assert_eq!(head.parent_id(0).unwrap(), base);
+}
+
 #[test]
 fn rebase_fast_forwards_when_no_unique_commits() {
     let td = TempDir::new().unwrap();
@@ -940,6 +1021,168 @@ fn merge_refreshes_main_worktree_when_on_base() {
     assert_eq!(oid, merge_sha);
 }
 
+#[test]
+fn fast_forward_moves_base_to_task_head_keeping_commits() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let s = GitService::new();
+    let feature_head = s.get_branch_oid(&worktree_path, "feature").unwrap();
+
+    // main is not checked out anywhere, so the ref is moved directly
+    let sha = s
+        .fast_forward_changes(&repo_path, "feature", "main")
+        .expect("fast-forward should succeed");
+    assert_eq!(sha, feature_head);
+    assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), feature_head);
+    // The task branch is left as it is
+    assert_eq!(
+        s.get_branch_oid(&repo_path, "feature").unwrap(),
+        feature_head
+    );
+}
+
+#[test]
+fn fast_forward_refreshes_main_worktree_when_on_base() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let repo = Repository::open(&repo_path).unwrap();
+    checkout_branch(&repo, "main");
+
+    let s = GitService::new();
+    let sha = s
+        .fast_forward_changes(&repo_path, "feature", "main")
+        .expect("fast-forward should succeed");
+    assert_eq!(sha, s.get_branch_oid(&worktree_path, "feature").unwrap());
+    let content = std::fs::read_to_string(repo_path.join("feat.txt")).unwrap();
+    assert_eq!(content, "feat change\n");
+}
+
+#[test]
+fn fast_forward_refuses_when_base_ahead() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, _worktree_path) = setup_repo_with_worktree(&td);
+    let repo = Repository::open(&repo_path).unwrap();
+    checkout_branch(&repo, "main");
+    write_file(&repo_path, "base_ahead.txt", "base ahead\n");
+    commit_all(&repo, "base ahead commit");
+
+    let s = GitService::new();
+    let before_main = s.get_branch_oid(&repo_path, "main").unwrap();
+    let res = s.fast_forward_changes(&repo_path, "feature", "main");
+    assert!(matches!(res, Err(GitServiceError::BranchesDiverged(_))));
+    assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), before_main);
+}
+
+#[test]
+fn merge_commit_keeps_task_commits_with_two_parents() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, _worktree_path) = setup_repo_with_worktree(&td);
+    let repo = Repository::open(&repo_path).unwrap();
+    // Base moved on, so the branches have diverged
+    checkout_branch(&repo, "main");
+    write_file(&repo_path, "base_ahead.txt", "base ahead\n");
+    commit_all(&repo, "base ahead commit");
+
+    let s = GitService::new();
+    let before_main = s.get_branch_oid(&repo_path, "main").unwrap();
+    let feature_oid = s.get_branch_oid(&repo_path, "feature").unwrap();
+    let merge_oid = s
+        .merge_commit_changes(&repo_path, "feature", "main", "Merge feature")
+        .unwrap();
+
+    assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), merge_oid);
+    let merge_commit = repo
+        .find_commit(git2::Oid::from_str(&merge_oid).unwrap())
+        .unwrap();
+    let parents: Vec<String> = merge_commit.parent_ids().map(|id| id.to_string()).collect();
+    assert_eq!(parents, vec![before_main, feature_oid]);
+    assert_eq!(merge_commit.message().unwrap().trim(), "Merge feature");
+    // main is checked out, so its worktree shows the merged files
+    assert!(repo_path.join("feat.txt").exists());
+    assert!(repo_path.join("base_ahead.txt").exists());
+}
+
+#[test]
+fn merge_commit_updates_base_that_is_not_checked_out() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, _worktree_path) = setup_repo_with_worktree(&td);
+    let repo = Repository::open(&repo_path).unwrap();
+    checkout_branch(&repo, "old-base");
+
+    let s = GitService::new();
+    let before_main = s.get_branch_oid(&repo_path, "main").unwrap();
+    let merge_oid = s
+        .merge_commit_changes(&repo_path, "feature", "main", "Merge feature")
+        .unwrap();
+
+    assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), merge_oid);
+    let merge_commit = repo
+        .find_commit(git2::Oid::from_str(&merge_oid).unwrap())
+        .unwrap();
+    assert_eq!(merge_commit.parent_count(), 2);
+    assert_eq!(merge_commit.parent_id(0).unwrap().to_string(), before_main);
+    assert!(merge_commit.tree().unwrap().get_name("feat.txt").is_some());
+}
+
+#[test]
+fn co_authors_lists_other_authors_of_task_commits_once() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    let mut cfg = wt_repo.config().unwrap();
+    cfg.set_str("user.name", "Ada").unwrap();
+    cfg.set_str("user.email", "ada@example.com").unwrap();
+    for n in 0..2 {
+        write_file(&worktree_path, "ada.txt", &format!("change {n}\n"));
+        commit_all(&wt_repo, "ada commit");
+    }
+    // The repository's own user is the committer, not a co-author
+    let mut cfg = wt_repo.config().unwrap();
+    cfg.set_str("user.name", "Test User").unwrap();
+    cfg.set_str("user.email", "test@example.com").unwrap();
+
+    let s = GitService::new();
+    let co_authors = s.get_co_authors(&repo_path, "feature", "old-base").unwrap();
+    assert_eq!(co_authors, vec!["Ada <ada@example.com>".to_string()]);
+}
+
+#[test]
+fn check_merge_clean_reports_conflicts_without_writing() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, _worktree_path) = setup_conflict_repo_with_worktree(&td);
+
+    let s = GitService::new();
+    let before_new_base = s.get_branch_oid(&repo_path, "new-base").unwrap();
+    let res = s.check_merge_clean(&repo_path, "feature", "new-base");
+    assert!(matches!(res, Err(GitServiceError::MergeConflicts(_))));
+    assert_eq!(
+        s.get_branch_oid(&repo_path, "new-base").unwrap(),
+        before_new_base
+    );
+    assert!(
+        s.check_merge_clean(&repo_path, "feature", "old-base")
+            .is_ok()
+    );
+}
+
+#[test]
+fn reset_branch_to_undoes_a_merge_into_checked_out_base() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, _worktree_path) = setup_repo_with_worktree(&td);
+    let repo = Repository::open(&repo_path).unwrap();
+    checkout_branch(&repo, "main");
+
+    let s = GitService::new();
+    let before_main = s.get_branch_oid(&repo_path, "main").unwrap();
+    s.merge_commit_changes(&repo_path, "feature", "main", "Merge feature")
+        .unwrap();
+    assert!(repo_path.join("feat.txt").exists());
+
+    s.reset_branch_to(&repo_path, "main", &before_main).unwrap();
+    assert_eq!(s.get_branch_oid(&repo_path, "main").unwrap(), before_main);
+    assert!(!repo_path.join("feat.txt").exists());
+}
+
 #[test]
 fn sparse_checkout_respected_in_worktree_diffs_and_commit() {
     let td = TempDir::new().unwrap();
@@ -1400,3 +1643,46 @@ fn merge_base_ahead_of_task_should_error() {
         "Merge should error when base branch is ahead of task branch"
     );
 }
+
+#[test]
+fn branch_created_at_earlier_commit_backs_a_worktree() {
+    let td = TempDir::new().unwrap();
+    let (repo_path, worktree_path) = setup_repo_with_worktree(&td);
+    let service = GitService::new();
+
+    // Fork the feature branch from before its own commit
+    let fork_commit = service.get_branch_oid(&repo_path, "old-base").unwrap();
+    write_file(&worktree_path, "later.txt", "later change\n");
+    let wt_repo = Repository::open(&worktree_path).unwrap();
+    commit_all(&wt_repo, "later feature commit");
+
+    assert!(!service.local_branch_exists(&repo_path, "fork").unwrap());
+    service
+        .create_branch_at(&repo_path, "fork", &fork_commit)
+        .expect("create fork branch");
+    assert!(service.local_branch_exists(&repo_path, "fork").unwrap());
+    assert_eq!(
+        service.get_branch_oid(&repo_path, "fork").unwrap(),
+        fork_commit
+    );
+
+    // An existing branch is never moved
+    let feature_head = service.get_branch_oid(&repo_path, "feature").unwrap();
+    assert!(
+        service
+            .create_branch_at(&repo_path, "feature", &fork_commit)
+            .is_err()
+    );
+    assert_eq!(
+        service.get_branch_oid(&repo_path, "feature").unwrap(),
+        feature_head
+    );
+
+    let fork_path = td.path().join("wt-fork");
+    service
+        .add_worktree(&repo_path, &fork_path, "fork", false)
+        .expect("create fork worktree");
+    assert!(fork_path.join("base.txt").exists());
+    assert!(!fork_path.join("feat.txt").exists());
+    assert!(!fork_path.join("later.txt").exists());
+}
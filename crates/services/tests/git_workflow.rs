@@ -618,3 +618,98 @@ fn squash_merge_libgit2_sets_author_without_user() {
         assert_eq!(email.as_deref(), Some("noreply@vibekanban.com"));
     }
 }
+
+#[test]
+fn restack_onto_rewritten_parent_and_force_push_with_lease() {
+    use git2::Repository;
+    use services::services::{git::GitServiceError, git_cli::GitCliError};
+
+    let td = TempDir::new().unwrap();
+    let repo_path = init_repo_main(&td);
+    let child_wt = td.path().join("wt_child");
+    let remote_path = td.path().join("remote.git");
+    let s = GitService::new();
+
+    write_file(&repo_path, "base.txt", "base\n");
+    s.commit(&repo_path, "base").unwrap();
+    let main_oid = s.get_branch_oid(&repo_path, "main").unwrap();
+
+    // parent: one commit on main; child: stacked on parent with its own commit
+    s.create_branch(&repo_path, "parent").unwrap();
+    s.checkout_branch(&repo_path, "parent").unwrap();
+    write_file(&repo_path, "parent.txt", "p1\n");
+    s.commit(&repo_path, "p1").unwrap();
+    let old_parent_oid = s.get_branch_oid(&repo_path, "parent").unwrap();
+
+    s.create_branch(&repo_path, "child").unwrap();
+    s.add_worktree(&repo_path, &child_wt, "child", false)
+        .unwrap();
+    write_file(&child_wt, "child.txt", "c1\n");
+    s.commit(&child_wt, "c1").unwrap();
+
+    // The child has an open PR, i.e. it was pushed before the restack
+    Repository::init_bare(&remote_path).unwrap();
+    s.set_remote(&repo_path, "origin", remote_path.to_str().unwrap())
+        .unwrap();
+    s.push_to_github(&child_wt, "child").unwrap();
+    let pushed_child_oid = s.get_branch_oid(&repo_path, "child").unwrap();
+
+    // Amend the parent: its old commit is replaced by a conflicting one
+    s.reset_branch_to(&repo_path, "parent", &main_oid).unwrap();
+    write_file(&repo_path, "parent.txt", "p1 amended\n");
+    s.commit(&repo_path, "p1 amended").unwrap();
+    let new_parent_oid = s.get_branch_oid(&repo_path, "parent").unwrap();
+
+    // Only the child's own commit is replayed; replaying the old parent commit
+    // would conflict with the amended one
+    s.rebase_branch(&repo_path, &child_wt, "parent", &old_parent_oid, "child")
+        .unwrap();
+    let child_oid = s.get_branch_oid(&repo_path, "child").unwrap();
+    assert_eq!(
+        s.ahead_behind_commits_by_oid(&repo_path, &child_oid, &new_parent_oid)
+            .unwrap(),
+        (1, 0)
+    );
+    assert_eq!(
+        fs::read_to_string(child_wt.join("parent.txt")).unwrap(),
+        "p1 amended\n"
+    );
+    assert_eq!(
+        fs::read_to_string(child_wt.join("child.txt")).unwrap(),
+        "c1\n"
+    );
+
+    // The rewritten branch replaces the pushed one
+    s.force_push_with_lease(&child_wt, "child").unwrap();
+    let remote = Repository::open_bare(&remote_path).unwrap();
+    let remote_child = |remote: &Repository| {
+        remote
+            .find_reference("refs/heads/child")
+            .unwrap()
+            .target()
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(remote_child(&remote), child_oid);
+
+    // Someone else moved the remote branch since: the lease refuses to overwrite it
+    remote
+        .reference(
+            "refs/heads/child",
+            git2::Oid::from_str(&pushed_child_oid).unwrap(),
+            true,
+            "push from elsewhere",
+        )
+        .unwrap();
+    s.reset_branch_to(&repo_path, "child", &new_parent_oid)
+        .unwrap();
+    let res = s.force_push_with_lease(&child_wt, "child");
+    assert!(
+        matches!(
+            res,
+            Err(GitServiceError::GitCLI(GitCliError::PushRejected(_)))
+        ),
+        "{res:?}"
+    );
+    assert_eq!(remote_child(&remote), pushed_child_oid);
+}
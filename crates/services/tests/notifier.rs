@@ -0,0 +1,123 @@
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use axum::{Json, Router, extract::State, http::StatusCode, routing::post};
+use serde_json::Value;
+use services::services::{
+    config::{NotificationEventKind, NotifierTarget},
+    notifier::{NotificationEvent, deliver},
+};
+use uuid::Uuid;
+
+/// Serve `router` on a random local port and return its base URL
+async fn serve(router: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{addr}")
+}
+
+fn pr_merged_event() -> NotificationEvent {
+    NotificationEvent {
+        kind: NotificationEventKind::PrMerged,
+        project_id: Uuid::new_v4(),
+        project_name: "Shop <api>".to_string(),
+        task_id: Uuid::new_v4(),
+        task_title: "Add checkout".to_string(),
+        task_attempt_id: Some(Uuid::new_v4()),
+        summary: "Pull request #7 for 'Add checkout' was merged".to_string(),
+        url: Some("https://github.com/owner/repo/pull/7".to_string()),
+        occurred_at: chrono::Utc::now(),
+    }
+}
+
+#[derive(Clone, Default)]
+struct Received {
+    bodies: Arc<Mutex<Vec<Value>>>,
+    calls: Arc<AtomicUsize>,
+}
+
+/// Fails the first request with a 503, then accepts everything
+async fn flaky(State(received): State<Received>, Json(body): Json<Value>) -> StatusCode {
+    if received.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+        return StatusCode::SERVICE_UNAVAILABLE;
+    }
+    received.bodies.lock().unwrap().push(body);
+    StatusCode::OK
+}
+
+#[tokio::test]
+async fn webhook_and_slack_notifiers_retry_and_deliver() {
+    let received = Received::default();
+    let base = serve(
+        Router::new()
+            .route("/hook", post(flaky))
+            .route("/slack", post(flaky))
+            .with_state(received.clone()),
+    )
+    .await;
+    let http = reqwest::Client::new();
+    let event = pr_merged_event();
+
+    deliver(
+        &http,
+        &NotifierTarget::Webhook {
+            url: format!("{base}/hook"),
+        },
+        &event,
+    )
+    .await
+    .unwrap();
+    deliver(
+        &http,
+        &NotifierTarget::Slack {
+            webhook_url: format!("{base}/slack"),
+        },
+        &event,
+    )
+    .await
+    .unwrap();
+
+    // The 503 is retried; later requests go through first time
+    assert_eq!(received.calls.load(Ordering::SeqCst), 3);
+    let bodies = received.bodies.lock().unwrap();
+    assert_eq!(bodies[0]["kind"], "pr_merged");
+    assert_eq!(bodies[0]["project_id"], event.project_id.to_string());
+    assert_eq!(bodies[0]["url"], "https://github.com/owner/repo/pull/7");
+    assert_eq!(
+        bodies[1]["text"],
+        "*Shop &lt;api&gt;* Pull request #7 for 'Add checkout' was merged \
+         <https://github.com/owner/repo/pull/7|View pull request>"
+    );
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let base = serve(
+        Router::new()
+            .route(
+                "/hook",
+                post(|State(calls): State<Arc<AtomicUsize>>| async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    (StatusCode::NOT_FOUND, "no such hook")
+                }),
+            )
+            .with_state(calls.clone()),
+    )
+    .await;
+
+    let err = deliver(
+        &reqwest::Client::new(),
+        &NotifierTarget::Webhook {
+            url: format!("{base}/hook"),
+        },
+        &pr_merged_event(),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("404"));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
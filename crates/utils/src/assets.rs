@@ -36,6 +36,14 @@ pub fn credentials_path() -> std::path::PathBuf {
     asset_dir().join("credentials.json")
 }
 
+pub fn secrets_path() -> std::path::PathBuf {
+    asset_dir().join("secrets.json")
+}
+
+pub fn secrets_key_path() -> std::path::PathBuf {
+    asset_dir().join("secrets.key")
+}
+
 #[derive(RustEmbed)]
 #[folder = "../../assets/sounds"]
 pub struct SoundAssets;
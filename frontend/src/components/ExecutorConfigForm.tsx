@@ -17,6 +17,8 @@ type ExecutorType =
   | 'CODEX'
   | 'CURSOR_AGENT'
   | 'COPILOT'
+  | 'CUSTOM'
+  | 'ACP_AGENT'
   | 'OPENCODE'
   | 'QWEN_CODE';
 
@@ -18,6 +18,105 @@ export type SearchResult = { path: string, is_file: boolean, match_type: SearchM
 
 export type SearchMatchType = "FileName" | "DirectoryName" | "FullPath";
 
+export type ProjectSandboxPolicy = { project_id: string, enabled: boolean, policy: SandboxPolicy, created_at: string, updated_at: string, };
+
+export type UpsertProjectSandboxPolicy = { enabled: boolean, policy: SandboxPolicy, };
+
+export type ProjectEnv = { project_id: string, 
+/**
+ * Values may refer to secrets with `{{secret:NAME}}`
+ */
+vars: { [key in string]?: string }, created_at: string, updated_at: string, };
+
+export type UpsertProjectEnv = { vars: { [key in string]?: string }, };
+
+export type SetSecretRequest = { value: string, };
+
+export type ProjectCiSettings = { project_id: string, 
+/**
+ * Queue a follow-up with the failing job logs when a PR's checks fail
+ */
+auto_fix_failed_checks: boolean, 
+/**
+ * Stop queueing fixes for a PR after this many
+ */
+max_auto_fixes: bigint, created_at: string, updated_at: string, };
+
+export type UpsertProjectCiSettings = { auto_fix_failed_checks: boolean, max_auto_fixes: bigint, };
+
+export type ProjectMergeQueueSettings = { project_id: string, 
+/**
+ * Run in the attempt's worktree after it is rebased; it is merged only if this exits 0
+ */
+verify_script: string | null, 
+/**
+ * The verify script is stopped and the attempt fails after this many seconds
+ */
+verify_timeout_secs: bigint, created_at: string, updated_at: string, };
+
+export type UpsertProjectMergeQueueSettings = { verify_script: string | null, verify_timeout_secs: bigint, };
+
+export type ProjectMergeSettings = { project_id: string, strategy: MergeStrategy, 
+/**
+ * Message of squash and merge commits. Supports `{task_title}`, `{task_description}`,
+ * `{task_id}`, `{task_id_short}`, `{attempt_id}`, `{executor}`, `{agent_summary}`
+ * and `{co_authored_by}`; the default message is used when unset.
+ */
+commit_message_template: string | null, created_at: string, updated_at: string, };
+
+export type UpsertProjectMergeSettings = { strategy: MergeStrategy, commit_message_template: string | null, };
+
+export type MergeStrategy = "squash" | "merge_commit" | "rebase_fast_forward";
+
+export type ProjectRepository = { id: string, project_id: string, 
+/**
+ * Directory of the repository's worktree inside an attempt's directory
+ */
+name: string, git_repo_path: string, 
+/**
+ * Branch attempts are merged into. Unset for repositories added before it was
+ * recorded, which use the attempt's target branch.
+ */
+target_branch: string | null, created_at: string, updated_at: string, };
+
+export type CreateProjectRepository = { name: string, git_repo_path: string, 
+/**
+ * Defaults to the branch checked out in the repository when it is added
+ */
+target_branch?: string | null, };
+
+export type TaskAttemptRepository = { id: string, task_attempt_id: string, name: string, git_repo_path: string, worktree_path: string, 
+/**
+ * Branch of this repository the attempt's branch is merged into
+ */
+target_branch: string, merge_commit: string | null, pr_number: bigint | null, pr_url: string | null, created_at: string, updated_at: string, };
+
+export type SandboxPolicy = { backend: SandboxBackend, 
+/**
+ * OCI image to run processes in (podman backend only)
+ */
+image: string | null, 
+/**
+ * Maximum number of CPUs, e.g. 1.5
+ */
+cpus: number | null, 
+/**
+ * Memory limit in megabytes
+ */
+memory_mb: number | null, network: SandboxNetwork, 
+/**
+ * Additional host paths mounted read-only (e.g. agent install directories)
+ */
+read_only_paths: Array<string>, 
+/**
+ * Additional host paths mounted read-write (e.g. agent credential directories)
+ */
+writable_paths: Array<string>, };
+
+export type SandboxBackend = "bubblewrap" | "podman";
+
+export type SandboxNetwork = "none" | "host";
+
 export type CreateRemoteProjectRequest = { organization_id: string, name: string, };
 
 export type LinkToExistingRequest = { remote_project_id: string, };
@@ -28,15 +127,15 @@ export type McpConfig = { servers: { [key in string]?: JsonValue }, servers_path
 
 export type ExecutorActionType = { "type": "CodingAgentInitialRequest" } & CodingAgentInitialRequest | { "type": "CodingAgentFollowUpRequest" } & CodingAgentFollowUpRequest | { "type": "ScriptRequest" } & ScriptRequest;
 
-export type ScriptContext = "SetupScript" | "CleanupScript" | "DevServer" | "GithubCliSetupScript";
+export type ScriptContext = "SetupScript" | "CleanupScript" | "DevServer" | "GithubCliSetupScript" | "VerifyScript";
 
 export type ScriptRequest = { script: string, language: ScriptRequestLanguage, context: ScriptContext, };
 
 export type ScriptRequestLanguage = "Bash";
 
-export enum BaseCodingAgent { CLAUDE_CODE = "CLAUDE_CODE", AMP = "AMP", GEMINI = "GEMINI", CODEX = "CODEX", OPENCODE = "OPENCODE", CURSOR_AGENT = "CURSOR_AGENT", QWEN_CODE = "QWEN_CODE", COPILOT = "COPILOT" }
+export enum BaseCodingAgent { CLAUDE_CODE = "CLAUDE_CODE", AMP = "AMP", GEMINI = "GEMINI", CODEX = "CODEX", OPENCODE = "OPENCODE", CURSOR_AGENT = "CURSOR_AGENT", QWEN_CODE = "QWEN_CODE", COPILOT = "COPILOT", CUSTOM = "CUSTOM", ACP_AGENT = "ACP_AGENT" }
 
-export type CodingAgent = { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot };
+export type CodingAgent = { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "CUSTOM": Custom } | { "ACP_AGENT": AcpAgent };
 
 export type Tag = { id: string, tag_name: string, content: string, created_at: string, updated_at: string, };
 
@@ -58,6 +157,12 @@ export type CreateTask = { project_id: string, title: string, description: strin
 
 export type UpdateTask = { title: string | null, description: string | null, status: TaskStatus | null, parent_task_attempt: string | null, image_ids: Array<string> | null, };
 
+export type TaskDependency = { task_id: string, depends_on_task_id: string, created_at: string, };
+
+export type TaskAutoStart = { task_id: string, executor_profile_id: ExecutorProfileId, base_branch: string, created_at: string, };
+
+export type TaskDependencies = { depends_on: Array<Task>, dependents: Array<Task>, auto_start: TaskAutoStart | null, is_blocked: boolean, };
+
 export type SharedTask = { id: string, remote_project_id: string, title: string, description: string | null, status: TaskStatus, assignee_user_id: string | null, assignee_first_name: string | null, assignee_last_name: string | null, assignee_username: string | null, version: bigint, last_event_seq: bigint | null, created_at: Date, updated_at: Date, };
 
 export type Image = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
@@ -160,13 +265,26 @@ export type ShareTaskResponse = { shared_task_id: string, };
 
 export type CreateAndStartTaskRequest = { task: CreateTask, executor_profile_id: ExecutorProfileId, base_branch: string, };
 
+export type CreateTaskDependencyRequest = { depends_on_task_id: string, };
+
+export type SetTaskAutoStartRequest = { executor_profile_id: ExecutorProfileId, base_branch: string, };
+
 export type CreateGitHubPrRequest = { title: string, body: string | null, target_branch: string | null, };
 
 export type ImageResponse = { id: string, file_path: string, original_name: string, mime_type: string | null, size_bytes: bigint, hash: string, created_at: string, updated_at: string, };
 
 export enum GitHubServiceError { TOKEN_INVALID = "TOKEN_INVALID", INSUFFICIENT_PERMISSIONS = "INSUFFICIENT_PERMISSIONS", REPO_NOT_FOUND_OR_NO_ACCESS = "REPO_NOT_FOUND_OR_NO_ACCESS", GH_CLI_NOT_INSTALLED = "GH_CLI_NOT_INSTALLED" }
 
-export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, };
+export type Config = { config_version: string, theme: ThemeMode, executor_profile: ExecutorProfileId, disclaimer_acknowledged: boolean, onboarding_acknowledged: boolean, notifications: NotificationConfig, editor: EditorConfig, github: GitHubConfig, analytics_enabled: boolean, workspace_dir: string | null, last_app_version: string | null, show_release_notes: boolean, language: UiLanguage, git_branch_prefix: string, showcases: ShowcaseState, git_hosts: Array<GitHostConfig>, 
+/**
+ * Shared secret for inbound pull request webhooks; webhooks are rejected when unset
+ */
+pr_webhook_secret: string | null, notifiers: Array<NotifierConfig>, 
+/**
+ * Regular expressions masked in process logs on top of the built-in secret formats;
+ * a group named `secret` limits masking to that group
+ */
+redaction_patterns: Array<string>, };
 
 export type NotificationConfig = { sound_enabled: boolean, push_enabled: boolean, sound_file: SoundFile, };
 
@@ -178,6 +296,56 @@ export enum EditorType { VS_CODE = "VS_CODE", CURSOR = "CURSOR", WINDSURF = "WIN
 
 export type GitHubConfig = { pat: string | null, oauth_token: string | null, username: string | null, primary_email: string | null, default_pr_base: string | null, };
 
+export type GitHostConfig = { 
+/**
+ * Hostname as it appears in remote URLs, with the port if it has one
+ */
+host: string, kind: GitHostKind, 
+/**
+ * API access token as a `{{secret:NAME}}` reference to the secret store. GitLab and
+ * Gitea fall back to `GITLAB_TOKEN` / `GITEA_TOKEN`.
+ */
+token: string | null, 
+/**
+ * API base URL, when it is not `https://<host>/api/v4` (GitLab) or
+ * `https://<host>/api/v1` (Gitea)
+ */
+api_url: string | null, };
+
+export type GitHostKind = "github" | "gitlab" | "gitea";
+
+export type NotifierConfig = { name: string, enabled: boolean, target: NotifierTarget, 
+/**
+ * Events to send; empty sends all of them
+ */
+events: Array<NotificationEventKind>, 
+/**
+ * Projects to send events for; empty sends events from every project
+ */
+project_ids: Array<string>, };
+
+export type NotifierTarget = { "type": "webhook", 
+/**
+ * A `{{secret:NAME}}` reference to the secret store
+ */
+url: string, } | { "type": "slack", 
+/**
+ * A `{{secret:NAME}}` reference to the secret store
+ */
+webhook_url: string, } | { "type": "email", smtp_host: string, 
+/**
+ * Defaults to the standard port for `security`
+ */
+smtp_port: number | null, security: SmtpSecurity, username: string | null, 
+/**
+ * A `{{secret:NAME}}` reference to the secret store
+ */
+password: string | null, from: string, to: Array<string>, };
+
+export type NotificationEventKind = "attempt_completed" | "attempt_failed" | "approval_requested" | "pr_merged" | "task_status_changed";
+
+export type SmtpSecurity = "start_tls" | "tls" | "plain";
+
 export enum SoundFile { ABSTRACT_SOUND1 = "ABSTRACT_SOUND1", ABSTRACT_SOUND2 = "ABSTRACT_SOUND2", ABSTRACT_SOUND3 = "ABSTRACT_SOUND3", ABSTRACT_SOUND4 = "ABSTRACT_SOUND4", COW_MOOING = "COW_MOOING", PHONE_VIBRATION = "PHONE_VIBRATION", ROOSTER = "ROOSTER" }
 
 export type UiLanguage = "BROWSER" | "EN" | "KO";
@@ -200,6 +368,26 @@ export type DiffChangeKind = "added" | "deleted" | "modified" | "renamed" | "cop
 
 export type RepositoryInfo = { id: bigint, name: string, full_name: string, owner: string, description: string | null, clone_url: string, ssh_url: string, default_branch: string, private: boolean, };
 
+export type PrReviewThread = { id: string, path: string, 
+/**
+ * Line in the new version of the file; `None` once the thread is outdated
+ */
+line: bigint | null, 
+/**
+ * First line of a multi-line comment
+ */
+start_line: bigint | null, is_resolved: boolean, is_outdated: boolean, 
+/**
+ * Commit the thread's lines refer to
+ */
+commit_oid: string | null, comments: Array<PrReviewComment>, };
+
+export type PrReviewComment = { 
+/**
+ * Login of the author, missing for deleted accounts
+ */
+author: string | null, body: string, url: string, created_at: string, };
+
 export type CommandBuilder = { 
 /**
  * Base executable command (e.g., "npx -y @anthropic-ai/claude-code@latest")
@@ -208,7 +396,11 @@ base: string,
 /**
  * Optional parameters to append to the base command
  */
-params: Array<string> | null, };
+params: Array<string> | null, 
+/**
+ * Environment variables set for the command, possibly referring to secrets
+ */
+env: { [key in string]?: string } | null, };
 
 export type ExecutorProfileId = { 
 /**
@@ -220,19 +412,19 @@ executor: BaseCodingAgent,
  */
 variant: string | null, };
 
-export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } };
+export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } | { "AMP": Amp } | { "GEMINI": Gemini } | { "CODEX": Codex } | { "OPENCODE": Opencode } | { "CURSOR_AGENT": CursorAgent } | { "QWEN_CODE": QwenCode } | { "COPILOT": Copilot } | { "CUSTOM": Custom } | { "ACP_AGENT": AcpAgent } };
 
 export enum BaseAgentCapability { SESSION_FORK = "SESSION_FORK", SETUP_HELPER = "SETUP_HELPER" }
 
-export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
-export type Gemini = { append_prompt: AppendPrompt, model: GeminiModel, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Gemini = { append_prompt: AppendPrompt, model: GeminiModel, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
 export type GeminiModel = "default" | "flash";
 
-export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Amp = { append_prompt: AppendPrompt, dangerously_allow_all?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
-export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, ask_for_approval?: AskForApproval | null, oss?: boolean | null, model?: string | null, model_reasoning_effort?: ReasoningEffort | null, model_reasoning_summary?: ReasoningSummary | null, model_reasoning_summary_format?: ReasoningSummaryFormat | null, profile?: string | null, base_instructions?: string | null, include_plan_tool?: boolean | null, include_apply_patch_tool?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Codex = { append_prompt: AppendPrompt, sandbox?: SandboxMode | null, ask_for_approval?: AskForApproval | null, oss?: boolean | null, model?: string | null, model_reasoning_effort?: ReasoningEffort | null, model_reasoning_summary?: ReasoningSummary | null, model_reasoning_summary_format?: ReasoningSummaryFormat | null, profile?: string | null, base_instructions?: string | null, include_plan_tool?: boolean | null, include_apply_patch_tool?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
 export type SandboxMode = "auto" | "read-only" | "workspace-write" | "danger-full-access";
 
@@ -244,13 +436,82 @@ export type ReasoningSummary = "auto" | "concise" | "detailed" | "none";
 
 export type ReasoningSummaryFormat = "none" | "experimental";
 
-export type CursorAgent = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type CursorAgent = { append_prompt: AppendPrompt, force?: boolean | null, model?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+
+export type Copilot = { append_prompt: AppendPrompt, model?: string | null, allow_all_tools?: boolean | null, allow_tool?: string | null, deny_tool?: string | null, add_dir?: Array<string> | null, disable_mcp_server?: Array<string> | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+
+/**
+ * Executor for agents that have no dedicated integration. The adapter spec describes how to
+ * invoke the agent and how to turn its output into conversation entries.
+ */
+export type Custom = { append_prompt: AppendPrompt, adapter: AdapterSpec, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+
+export type AdapterSpec = { 
+/**
+ * Base command, e.g. "my-agent --headless"
+ */
+command: string, 
+/**
+ * Parameters of the initial run. `{prompt}` is replaced with the prompt; without it the
+ * prompt is written to stdin.
+ */
+params: Array<string>, 
+/**
+ * Parameters of follow-up runs, used in place of `params`. `{session_id}` is replaced with
+ * the session to resume. Follow-ups are not supported when unset.
+ */
+follow_up_params?: Array<string> | null, 
+/**
+ * Where the session id appears in stdout
+ */
+session_id?: SessionIdSpec | null, output: OutputSpec, };
+
+export type SessionIdSpec = { matches: LineMatcher, 
+/**
+ * Template of the session id, e.g. "{1}" or "{/session_id}"
+ */
+value: string, };
+
+export type OutputSpec = { format: OutputFormat, 
+/**
+ * Rules tried in order on each stdout line. Lines no rule matches are shown as assistant
+ * messages, except JSON lines in `json_lines` format, which are dropped.
+ */
+rules: Array<OutputRule>, };
+
+export type OutputFormat = "plain_text" | "json_lines";
+
+export type OutputRule = { matches: LineMatcher, entry: EntryTemplate, };
+
+/**
+ * Matches a stdout line. Templates of a regex match can use `{1}` or `{name}` for its
+ * groups, those of a JSON match `{/json/pointer}`. `{line}` is the whole line.
+ */
+export type LineMatcher = { "type": "regex", pattern: string, } | { "type": "json", pointer: string, equals?: string | null, };
+
+export type EntryTemplate = { "type": "assistant_message", content: string, } | { "type": "thinking", content: string, } | { "type": "system_message", content: string, } | { "type": "error_message", content: string, } | { "type": "tool_use", tool_name: string, action_type: ActionTemplate, content: string, } | { "type": "ignore" };
 
-export type Copilot = { append_prompt: AppendPrompt, model?: string | null, allow_all_tools?: boolean | null, allow_tool?: string | null, deny_tool?: string | null, add_dir?: Array<string> | null, disable_mcp_server?: Array<string> | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+/**
+ * Action of a tool use, shaped like `ActionType` with the fields that can be templated
+ */
+export type ActionTemplate = { "action": "file_read", path: string, } | { "action": "file_edit", path: string, } | { "action": "command_run", command: string, } | { "action": "search", query: string, } | { "action": "web_fetch", url: string, } | { "action": "tool" } | { "action": "other", description: string, };
 
-export type Opencode = { append_prompt: AppendPrompt, model?: string | null, agent?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+/**
+ * Any agent that speaks the Agent Client Protocol over stdio
+ */
+export type AcpAgent = { append_prompt: AppendPrompt, 
+/**
+ * Command that starts the agent in ACP mode, e.g. "my-agent --acp"
+ */
+command: string, 
+/**
+ * Ask the user to decide the agent's permission requests instead of approving them
+ */
+approvals?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
-export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, };
+export type Opencode = { append_prompt: AppendPrompt, model?: string | null, agent?: string | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
+
+export type QwenCode = { append_prompt: AppendPrompt, yolo?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
 
 export type AppendPrompt = string | null;
 
@@ -270,7 +531,12 @@ export type CreateTaskAttemptBody = { task_id: string,
 /**
  * Executor profile specification
  */
-executor_profile_id: ExecutorProfileId, base_branch: string, };
+executor_profile_id: ExecutorProfileId, base_branch: string, 
+/**
+ * Stack the attempt on another attempt of the same project. Its branch is used as
+ * the base instead of `base_branch`, and the attempt is restacked when it changes.
+ */
+parent_attempt_id?: string, };
 
 export type RunAgentSetupRequest = { executor_profile_id: ExecutorProfileId, };
 
@@ -318,12 +584,119 @@ conflict_op: ConflictOp | null,
 /**
  * List of files currently in conflicted (unmerged) state
  */
-conflicted_files: Array<string>, };
+conflicted_files: Array<string>, 
+/**
+ * Latest agent-assisted resolution, while it runs or its operation is still in progress
+ */
+conflict_resolution: ConflictResolution | null, 
+/**
+ * CI checks of the latest PR, as last seen by the PR monitor
+ */
+pr_checks: PrChecks | null, };
+
+export type MergePreview = { strategy: MergeStrategy, 
+/**
+ * Message of the commit the merge creates; rebase-and-fast-forward creates none
+ */
+commit_message: string | null, };
+
+export type AttemptRepositoryDiff = { repository: TaskAttemptRepository, commits_ahead: number, commits_behind: number, diffs: Array<Diff>, };
+
+export type ForkTaskAttemptRequest = { 
+/**
+ * Coding agent process of this attempt to fork after
+ */
+execution_process_id: string, };
+
+export type AttemptForkTree = { attempt: TaskAttempt, 
+/**
+ * How the attempt was forked from its parent; none for the root
+ */
+fork: TaskAttemptFork | null, children: Array<AttemptForkTree>, };
 
 export type ConflictOp = "rebase" | "merge" | "cherry_pick" | "revert";
 
+export type ConflictResolution = { execution_process_id: string, task_attempt_id: string, 
+/**
+ * Files that were conflicted when the follow-up started
+ */
+conflicted_files: Array<string>, 
+/**
+ * Why the operation could not be continued; it is left in progress
+ */
+error: string | null, created_at: string, completed_at: string | null, };
+
 export type TaskAttempt = { id: string, task_id: string, container_ref: string | null, branch: string, target_branch: string, executor: string, worktree_deleted: boolean, setup_completed_at: string | null, created_at: string, updated_at: string, };
 
+export type TaskAttemptStack = { task_attempt_id: string, parent_attempt_id: string, 
+/**
+ * Commit of the parent's branch the attempt is currently based on
+ */
+base_commit: string, 
+/**
+ * Why the last restack failed, e.g. merge conflicts
+ */
+restack_error: string | null, 
+/**
+ * Parent commit the last restack failed for; it is not retried automatically
+ */
+failed_base_commit: string | null, created_at: string, updated_at: string, };
+
+export type TaskAttemptFork = { task_attempt_id: string, parent_attempt_id: string, 
+/**
+ * Process of the parent attempt the fork starts after
+ */
+execution_process_id: string, 
+/**
+ * HEAD the process left behind, which the fork's branch starts at
+ */
+fork_commit: string, 
+/**
+ * Agent session the fork's first follow-up resumes; none when the agent cannot fork
+ * its sessions
+ */
+session_id: string | null, created_at: string, };
+
+export type MergeQueueEntry = { id: string, project_id: string, task_attempt_id: string, target_branch: string, status: MergeQueueStatus, 
+/**
+ * Why the attempt was not merged
+ */
+error: string | null, 
+/**
+ * Tail of the verify script's output when it failed
+ */
+verify_log: string | null, merge_commit: string | null, created_at: string, updated_at: string, };
+
+export type MergeQueueStatus = "queued" | "rebasing" | "verifying" | "merging" | "merged" | "failed" | "cancelled";
+
+export type TaskAttemptRace = { id: string, task_id: string, winner_attempt_id: string | null, created_at: string, decided_at: string | null, };
+
+export type TaskAttemptRaceEntry = { race_id: string, task_attempt_id: string, archived: boolean, };
+
+export type CreateRaceAttemptsBody = { task_id: string, executor_profile_ids: Array<ExecutorProfileId>, base_branch: string, };
+
+export type RaceAttemptsResponse = { race: TaskAttemptRace, attempts: Array<TaskAttempt>, };
+
+export type RaceAttemptComparison = { attempt: TaskAttempt, archived: boolean, is_winner: boolean, files_changed: number, additions: number, deletions: number, 
+/**
+ * Status of the latest coding agent run, if one has started
+ */
+status: ExecutionProcessStatus | null, exit_code: bigint | null, 
+/**
+ * Wall-clock duration of the latest coding agent run in milliseconds
+ */
+duration_ms: bigint | null, };
+
+export type RaceComparisonResponse = { race: TaskAttemptRace, attempts: Array<RaceAttemptComparison>, };
+
+export type PickRaceWinnerRequest = { attempt_id: string, };
+
+export type CreateReviewDraftRequest = { 
+/**
+ * Review threads to turn into a follow-up; resolved threads are skipped
+ */
+thread_ids: Array<string>, };
+
 export type ExecutionProcess = { id: string, task_attempt_id: string, run_reason: ExecutionProcessRunReason, executor_action: ExecutorAction, 
 /**
  * Git HEAD commit OID captured before the process starts
@@ -332,7 +705,7 @@ before_head_commit: string | null,
 /**
  * Git HEAD commit OID captured after the process ends
  */
-after_head_commit: string | null, status: ExecutionProcessStatus, exit_code: bigint | null, 
+after_head_commit: string | null, status: ExecutionProcessStatus, exit_code: bigint | null, status_reason: ExecutionProcessStatusReason | null, 
 /**
  * dropped: true if this process is excluded from the current
  * history view (due to restore/trimming). Hidden from logs/timeline;
@@ -344,6 +717,109 @@ export enum ExecutionProcessStatus { running = "running", completed = "completed
 
 export type ExecutionProcessRunReason = "setupscript" | "cleanupscript" | "codingagent" | "devserver";
 
+export type ExecutionProcessStatusReason = "budget_exceeded";
+
+export type BudgetLimits = { 
+/**
+ * Maximum wall-clock runtime in seconds
+ */
+max_runtime_secs: bigint | null, 
+/**
+ * Maximum number of tool calls made by the agent
+ */
+max_tool_calls: bigint | null, 
+/**
+ * Maximum input + output tokens, for agents that report usage. ACP agents (Gemini,
+ * Qwen) report none, so only their runtime and tool calls are limited.
+ */
+max_tokens: bigint | null, };
+
+export type ProjectBudget = { project_id: string, created_at: string, updated_at: string, 
+/**
+ * Maximum wall-clock runtime in seconds
+ */
+max_runtime_secs: bigint | null, 
+/**
+ * Maximum number of tool calls made by the agent
+ */
+max_tool_calls: bigint | null, 
+/**
+ * Maximum input + output tokens, for agents that report usage. ACP agents (Gemini,
+ * Qwen) report none, so only their runtime and tool calls are limited.
+ */
+max_tokens: bigint | null, };
+
+export type ExecutorProfileBudget = { 
+/**
+ * "EXECUTOR" or "EXECUTOR:VARIANT"
+ */
+profile_key: string, created_at: string, updated_at: string, 
+/**
+ * Maximum wall-clock runtime in seconds
+ */
+max_runtime_secs: bigint | null, 
+/**
+ * Maximum number of tool calls made by the agent
+ */
+max_tool_calls: bigint | null, 
+/**
+ * Maximum input + output tokens, for agents that report usage. ACP agents (Gemini,
+ * Qwen) report none, so only their runtime and tool calls are limited.
+ */
+max_tokens: bigint | null, };
+
+export type UpdateExecutorProfileBudget = { executor_profile_id: ExecutorProfileId, 
+/**
+ * Maximum wall-clock runtime in seconds
+ */
+max_runtime_secs: bigint | null, 
+/**
+ * Maximum number of tool calls made by the agent
+ */
+max_tool_calls: bigint | null, 
+/**
+ * Maximum input + output tokens, for agents that report usage. ACP agents (Gemini,
+ * Qwen) report none, so only their runtime and tool calls are limited.
+ */
+max_tokens: bigint | null, };
+
+export type ExecutionProcessUsage = { id: string, execution_process_id: string, model: string | null, input_tokens: bigint, output_tokens: bigint, cache_read_tokens: bigint, cache_write_tokens: bigint, created_at: string, };
+
+export type ModelUsage = { model: string | null, turns: bigint, input_tokens: bigint, output_tokens: bigint, cache_read_tokens: bigint, cache_write_tokens: bigint, };
+
+export type UsageSummary = { turns: bigint, input_tokens: bigint, output_tokens: bigint, cache_read_tokens: bigint, cache_write_tokens: bigint, by_model: Array<ModelUsage>, };
+
+export type PolicyDecision = "approve" | "deny" | "escalate";
+
+export type ApprovalDecider = "user" | "policy" | "timeout";
+
+export type ApprovalOutcome = "approved" | "denied" | "timed_out";
+
+export type Approval = { id: string, execution_process_id: string, tool_name: string, tool_call_id: string, 
+/**
+ * The tool call's `ActionType`, as JSON, when its log entry was found
+ */
+action_type: unknown | null, decided_by: ApprovalDecider, status: ApprovalOutcome, reason: string | null, 
+/**
+ * The project policy's verdict, `None` when no policy applied. `Escalate` means
+ * the policy left the decision to `decided_by`.
+ */
+policy_decision: PolicyDecision | null, 
+/**
+ * Index of the policy rule that matched, `None` when no rule matched
+ */
+rule_index: bigint | null, requested_at: string, decided_at: string, latency_ms: bigint, };
+
+export type ApprovalFilter = { project_id: string | null, task_id: string | null, task_attempt_id: string | null, execution_process_id: string | null, tool_name: string | null, decided_by: ApprovalDecider | null, status: ApprovalOutcome | null, policy_decision: PolicyDecision | null, 
+/**
+ * Only approvals decided at or after this time
+ */
+since: string | null, 
+/**
+ * Only approvals decided before this time
+ */
+until: string | null, limit: bigint | null, };
+
 export type Merge = { "type": "direct" } & DirectMerge | { "type": "pr" } & PrMerge;
 
 export type DirectMerge = { id: string, task_attempt_id: string, merge_commit: string, target_branch_name: string, created_at: string, };
@@ -354,6 +830,16 @@ export type MergeStatus = "open" | "merged" | "closed" | "unknown";
 
 export type PullRequestInfo = { number: bigint, url: string, status: MergeStatus, merged_at: string | null, merge_commit_sha: string | null, };
 
+export type CheckState = "pending" | "success" | "failure" | "neutral";
+
+export type CheckRun = { name: string, state: CheckState, 
+/**
+ * Link to the check's details, e.g. the CI job page
+ */
+url: string | null, };
+
+export type PrChecks = { head_sha: string | null, state: CheckState, checks: Array<CheckRun>, };
+
 export type Draft = { id: string, task_attempt_id: string, draft_type: DraftType, retry_process_id: string | null, prompt: string, queued: boolean, sending: boolean, variant: string | null, image_ids: Array<string> | null, created_at: string, updated_at: string, version: bigint, };
 
 export type DraftType = "follow_up" | "retry";